@@ -0,0 +1,42 @@
+#ifndef MERKLE_TREE_H
+#define MERKLE_TREE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define MERKLE_OK 0
+#define MERKLE_ERR_NULL_POINTER -1
+#define MERKLE_ERR_UNKNOWN_ALGORITHM -2
+#define MERKLE_ERR_HASH_SIZE -3
+#define MERKLE_ERR_TREE_EMPTY -4
+#define MERKLE_ERR_MALFORMED_PROOF -5
+#define MERKLE_ERR_BUFFER_TOO_SMALL -6
+#define MERKLE_ERR_INDEX_OUT_OF_BOUNDS -7
+#define MERKLE_ERR_LEAF_NOT_FOUND -8
+#define MERKLE_ERR_INTERNAL -99
+
+#define MERKLE_ALGORITHM_KECCAK256 0
+#define MERKLE_ALGORITHM_SHA3_256 1
+#define MERKLE_ALGORITHM_SHA3_512 2
+
+typedef struct MerkleTreeHandle MerkleTreeHandle;
+
+int32_t merkle_tree_new(uint32_t algorithm, MerkleTreeHandle **out_tree);
+void merkle_tree_free(MerkleTreeHandle *tree);
+int32_t merkle_tree_append(MerkleTreeHandle *tree, const uint8_t *leaf, size_t leaf_len);
+int32_t merkle_tree_root(MerkleTreeHandle *tree, uint8_t *out, size_t out_cap, size_t *out_len);
+int32_t merkle_tree_proof(MerkleTreeHandle *tree, const uint8_t *leaf, size_t leaf_len,
+                          uint8_t *out, size_t out_cap, size_t *out_len);
+int32_t merkle_proof_verify(uint32_t algorithm, const uint8_t *root, size_t root_len,
+                            const uint8_t *leaf, size_t leaf_len, const uint8_t *proof,
+                            size_t proof_len, int32_t *out_valid);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,153 @@
+//! Python bindings for `merkle_tree`, built with maturin
+//!
+//! Hashes and proofs cross the boundary as `bytes`, proofs in the format of
+//! [`MerkleProof::to_bytes`], so a proof generated in Python verifies in Rust and back.
+//! Algorithms are selected by name: `"keccak256"`, `"sha3-256"` or `"sha3-512"`. Trees take
+//! the pair ordering (`"sorted"`, `"positional"`, `"ascending"`) and odd leaf policy
+//! (`"promote"`, `"duplicate-last"`, `"pad-with-zero-hash"`) of a [`TreePolicy`], and
+//! dispatch through [`DynMerkleTree`] like the wasm and C bindings.
+//!
+//! ```python
+//! from merkle_tree_py import MerkleProof, MerkleTree, hash_leaf
+//!
+//! tree = MerkleTree("sha3-256", pair_ordering="positional")
+//! for data in [b"alice", b"bob", b"carol"]:
+//!     tree.append(hash_leaf("sha3-256", data))
+//!
+//! proof = MerkleProof.from_bytes("sha3-256", tree.proof_at(1))
+//! assert proof.validate_data(tree.root_hash(), b"bob")
+//! ```
+use merkle_tree::error::tree_error::TreeError;
+use merkle_tree::{DynMerkleTree, HashAlgorithm, OddLeafPolicy, PairOrdering, TreePolicy};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::borrow::Cow;
+
+/// Byte-oriented tree whose hash algorithm and policy are picked by name
+#[pyclass(name = "MerkleTree")]
+struct PyMerkleTree {
+    tree: DynMerkleTree,
+}
+
+#[pymethods]
+impl PyMerkleTree {
+    #[new]
+    #[pyo3(signature = (algorithm, pair_ordering = "sorted", odd_leaf = "promote"))]
+    fn new(algorithm: &str, pair_ordering: &str, odd_leaf: &str) -> PyResult<Self> {
+        let policy = TreePolicy {
+            pair_ordering: parse_pair_ordering(pair_ordering)?,
+            odd_leaf: parse_odd_leaf(odd_leaf)?,
+            ..TreePolicy::default()
+        };
+
+        Ok(Self {
+            tree: DynMerkleTree::with_policy(parse_algorithm(algorithm)?, policy),
+        })
+    }
+
+    fn append(&mut self, leaf: &[u8]) -> PyResult<()> {
+        self.tree.append(leaf).map_err(to_py_error)
+    }
+
+    fn __len__(&self) -> usize {
+        self.tree.leaf_count()
+    }
+
+    fn root_hash(&self) -> PyResult<Cow<'static, [u8]>> {
+        Ok(self.tree.root_hash().map_err(to_py_error)?.into())
+    }
+
+    /// Proof for the first occurrence of the leaf, serialized with `MerkleProof::to_bytes`
+    fn proof(&self, leaf: &[u8]) -> PyResult<Cow<'static, [u8]>> {
+        Ok(self.tree.get_proof(leaf).map_err(to_py_error)?.into())
+    }
+
+    /// Proof for the leaf at `index`, serialized with `MerkleProof::to_bytes`
+    fn proof_at(&self, index: usize) -> PyResult<Cow<'static, [u8]>> {
+        Ok(self.tree.get_proof_at(index).map_err(to_py_error)?.into())
+    }
+}
+
+/// Serialized proof, validated against roots given as `bytes`
+#[pyclass(name = "MerkleProof")]
+struct PyMerkleProof {
+    algorithm: HashAlgorithm,
+    bytes: Vec<u8>,
+}
+
+#[pymethods]
+impl PyMerkleProof {
+    #[staticmethod]
+    fn from_bytes(algorithm: &str, bytes: &[u8]) -> PyResult<Self> {
+        let algorithm = parse_algorithm(algorithm)?;
+        algorithm.check_proof(bytes).map_err(to_py_error)?;
+
+        Ok(Self {
+            algorithm,
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    fn to_bytes(&self) -> Cow<'static, [u8]> {
+        self.bytes.clone().into()
+    }
+
+    /// Validates the proof for an already hashed leaf
+    fn validate(&self, root: &[u8], leaf: &[u8]) -> PyResult<bool> {
+        self.algorithm
+            .validate(root, leaf, &self.bytes)
+            .map_err(to_py_error)
+    }
+
+    /// Validates the proof for raw leaf data, hashed with the tree's algorithm
+    fn validate_data(&self, root: &[u8], data: &[u8]) -> PyResult<bool> {
+        self.algorithm
+            .validate_data(root, data, &self.bytes)
+            .map_err(to_py_error)
+    }
+}
+
+/// Hashes raw data with the named algorithm, e.g. to derive leaves
+#[pyfunction]
+fn hash_leaf(algorithm: &str, data: &[u8]) -> PyResult<Cow<'static, [u8]>> {
+    let algorithm = parse_algorithm(algorithm)?;
+    Ok(algorithm.hash(data).map_err(to_py_error)?.into())
+}
+
+fn parse_algorithm(name: &str) -> PyResult<HashAlgorithm> {
+    name.parse().map_err(to_py_error)
+}
+
+fn parse_pair_ordering(name: &str) -> PyResult<PairOrdering> {
+    match name {
+        "sorted" => Ok(PairOrdering::Sorted),
+        "positional" => Ok(PairOrdering::Positional),
+        "ascending" => Ok(PairOrdering::Ascending),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown pair ordering {name:?}"
+        ))),
+    }
+}
+
+fn parse_odd_leaf(name: &str) -> PyResult<OddLeafPolicy> {
+    match name {
+        "promote" => Ok(OddLeafPolicy::Promote),
+        "duplicate-last" => Ok(OddLeafPolicy::DuplicateLast),
+        "pad-with-zero-hash" => Ok(OddLeafPolicy::PadWithZeroHash),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown odd leaf policy {name:?}"
+        ))),
+    }
+}
+
+fn to_py_error(error: TreeError) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+#[pymodule]
+fn merkle_tree_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyMerkleTree>()?;
+    module.add_class::<PyMerkleProof>()?;
+    module.add_function(wrap_pyfunction!(hash_leaf, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::Position;
+use crate::merkle_tree::{path_from_levels, MerkleTree, TreeResult};
+use crate::tree_policy::PairOrdering;
+use crate::utils::hex;
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Proof entry for one leaf of an [`AirdropExport`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirdropClaim {
+    pub index: usize,
+    pub proof: Vec<String>,
+    /// Side of every sibling, only exported for positional trees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub positions: Option<Vec<Position>>,
+}
+
+/// Root and per-leaf proofs of a tree, in the shape consumed by claim UIs
+///
+/// ```json
+/// {
+///   "algorithm": "keccak256",
+///   "pair_ordering": "ascending",
+///   "root": "0x…",
+///   "claims": {
+///     "0x…": { "index": 0, "proof": ["0x…", "0x…"] }
+///   }
+/// }
+/// ```
+///
+/// Claims are keyed by the `0x`-prefixed hex leaf. If a leaf occurs more than once only its
+/// first index is exported. `pair_ordering` tells consumers how to fold the proofs: `sorted`
+/// puts the greater hash first, `ascending` the lesser one as OpenZeppelin's `MerkleProof`
+/// does, and `positional` claims carry a `positions` array of `"left"`/`"right"` entries,
+/// one per sibling.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirdropExport {
+    pub algorithm: String,
+    #[serde(default = "sorted")]
+    pub pair_ordering: String,
+    pub root: String,
+    pub claims: BTreeMap<String, AirdropClaim>,
+}
+
+#[derive(Serialize)]
+struct AirdropHeader<'a> {
+    algorithm: &'a str,
+    pair_ordering: &'a str,
+    root: &'a str,
+}
+
+#[derive(Serialize)]
+struct AirdropLine<'a> {
+    leaf: &'a str,
+    #[serde(flatten)]
+    claim: &'a AirdropClaim,
+}
+
+impl AirdropExport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("airdrop exports always serialize")
+    }
+
+    /// Writes the export as NDJSON: a `{ algorithm, pair_ordering, root }` header line
+    /// followed by one `{ leaf, index, proof }` line per claim, with `positions` for
+    /// positional trees
+    pub fn write_ndjson(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        let header = AirdropHeader {
+            algorithm: &self.algorithm,
+            pair_ordering: &self.pair_ordering,
+            root: &self.root,
+        };
+        writeln!(writer, "{}", serde_json::to_string(&header)?)?;
+        for (leaf, claim) in &self.claims {
+            let line = AirdropLine { leaf, claim };
+            writeln!(writer, "{}", serde_json::to_string(&line)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Exports the root together with a proof for every leaf, building the tree only once
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Keccak256, Keccak256Tree, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..4).map(|i| Keccak256::hash(&[i])).collect();
+    ///     let tree = Keccak256Tree::from_leaves(&leaves);
+    ///
+    ///     let export = tree.airdrop_export()?;
+    ///     assert_eq!(export.claims.len(), 4);
+    ///     assert_eq!(export.claims.values().map(|claim| claim.proof.len()).max(), Some(2));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn airdrop_export(&self) -> TreeResult<AirdropExport> {
+        let levels = self.cached_levels()?;
+        let root = levels[levels.len() - 1][0];
+
+        let mut claims = BTreeMap::new();
+        let ordering = self.policy().pair_ordering;
+        for (index, leaf) in levels[0].iter().enumerate() {
+            if let Entry::Vacant(entry) = claims.entry(encode_hash(leaf.as_ref())) {
+                let path = path_from_levels::<T>(levels, index, &self.policy())?;
+                let (proof, positions): (Vec<_>, Vec<_>) = path
+                    .iter()
+                    .map(|(sibling, position)| (encode_hash(sibling.as_ref()), *position))
+                    .unzip();
+                entry.insert(AirdropClaim {
+                    index,
+                    proof,
+                    positions: (ordering == PairOrdering::Positional).then_some(positions),
+                });
+            }
+        }
+
+        Ok(AirdropExport {
+            algorithm: T::ALGORITHM.to_owned(),
+            pair_ordering: ordering_name(ordering).to_owned(),
+            root: encode_hash(root.as_ref()),
+            claims,
+        })
+    }
+}
+
+fn ordering_name(ordering: PairOrdering) -> &'static str {
+    match ordering {
+        PairOrdering::Sorted => "sorted",
+        PairOrdering::Positional => "positional",
+        PairOrdering::Ascending => "ascending",
+    }
+}
+
+fn sorted() -> String {
+    ordering_name(PairOrdering::Sorted).to_owned()
+}
+
+fn encode_hash(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Keccak256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{Keccak256Proof, Keccak256Tree, ProofResponse, TreeBuilder};
+
+    #[test]
+    fn test_airdrop_export_proofs_validate() {
+        let leaves = raw_leaves_to_hashed_leaves::<Keccak256>(&["0", "1", "2", "3", "4"]);
+        let tree = Keccak256Tree::from_leaves(&leaves);
+        let export = tree.airdrop_export().unwrap();
+        assert_eq!(export.root, encode_hash(&tree.root_hash().unwrap()));
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let claim = &export.claims[&encode_hash(leaf)];
+            assert_eq!(claim.index, index);
+
+            let proof = Keccak256Proof::new(tree.get_proof_at(index).unwrap());
+            let response = ProofResponse::new(tree.root_hash().unwrap(), *leaf, index, &proof);
+            assert_eq!(claim.proof, response.proof);
+        }
+    }
+
+    #[test]
+    fn test_airdrop_exports_follow_the_pair_ordering() {
+        let leaves = raw_leaves_to_hashed_leaves::<Keccak256>(&["0", "1", "2", "3", "4"]);
+        let build = |pair_ordering| {
+            TreeBuilder::<Keccak256>::new()
+                .without_domain_separation()
+                .leaf_hashes(leaves.iter().copied())
+                .pair_ordering(pair_ordering)
+                .build()
+                .unwrap()
+        };
+        let decode =
+            |hash: &str| -> [u8; 32] { hex::decode(&hash[2..]).unwrap().try_into().unwrap() };
+
+        let positional = build(PairOrdering::Positional);
+        let export = positional.airdrop_export().unwrap();
+        assert_eq!(export.pair_ordering, "positional");
+        for leaf in &leaves {
+            let claim = &export.claims[&encode_hash(leaf)];
+            let proof = Keccak256Proof::with_positions(
+                claim.proof.iter().map(|hash| decode(hash)).collect(),
+                claim.positions.clone().unwrap(),
+            )
+            .unwrap();
+            assert!(proof.validate(positional.root_hash().unwrap(), *leaf));
+        }
+
+        // Ascending claims fold as OpenZeppelin's `MerkleProof.processProof` does
+        let ascending = build(PairOrdering::Ascending);
+        let export = ascending.airdrop_export().unwrap();
+        assert_eq!(export.pair_ordering, "ascending");
+        for leaf in &leaves {
+            let claim = &export.claims[&encode_hash(leaf)];
+            assert_eq!(claim.positions, None);
+            let root = claim.proof.iter().fold(*leaf, |node, sibling| {
+                let sibling = decode(sibling);
+                Keccak256::hash_node(node.min(sibling), node.max(sibling))
+            });
+            assert_eq!(root, ascending.root_hash().unwrap());
+        }
+
+        let json = build(PairOrdering::Sorted)
+            .airdrop_export()
+            .unwrap()
+            .to_json();
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value.as_object_mut().unwrap().remove("pair_ordering");
+        let export: AirdropExport = serde_json::from_value(value).unwrap();
+        assert_eq!(export.pair_ordering, "sorted");
+    }
+
+    #[test]
+    fn test_airdrop_export_ndjson() {
+        let leaves = raw_leaves_to_hashed_leaves::<Keccak256>(&["0", "1", "2"]);
+        let export = Keccak256Tree::from_leaves(&leaves)
+            .airdrop_export()
+            .unwrap();
+
+        let mut ndjson = Vec::new();
+        export.write_ndjson(&mut ndjson).unwrap();
+        let lines: Vec<serde_json::Value> = String::from_utf8(ndjson)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["root"], export.root.as_str());
+        assert!(lines[1..].iter().all(|line| line["proof"].is_array()));
+    }
+}
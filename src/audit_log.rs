@@ -0,0 +1,223 @@
+use crate::checkpoint::{Checkpoint, CheckpointSigner, SignedCheckpoint};
+use crate::consistency_proof::ConsistencyProof;
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Tamper-evident, append-only key-value log
+///
+/// Every `(key, value)` entry becomes a leaf of an append-only tree. The log keeps an index
+/// from keys to the sequence numbers of their entries, produces inclusion proofs for
+/// entries, consistency proofs between sizes and signed checkpoints of its current state.
+pub struct AuditLog<K, V, H: ToHash> {
+    tree: MerkleTree<H>,
+    entries: Vec<(K, V)>,
+    index: HashMap<K, Vec<usize>>,
+}
+
+/// Proof that an entry is included in the log at a given size
+pub struct EntryProof<H: ToHash> {
+    pub sequence: usize,
+    pub tree_size: usize,
+    pub proof: MerkleProof<H>,
+}
+
+impl<K, V, H> AuditLog<K, V, H>
+where
+    K: AsRef<[u8]> + Eq + Hash + Clone,
+    V: AsRef<[u8]>,
+    H: ToHash,
+{
+    pub fn new() -> Self {
+        Self {
+            tree: MerkleTree::new(),
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// Leaf committed for an entry: `hash(key length (u64, BE) || key || value)`
+    pub fn entry_hash(key: &K, value: &V) -> TreeResult<H::Hash> {
+        let key = key.as_ref();
+        Ok(H::try_hash(
+            &[&(key.len() as u64).to_be_bytes(), key, value.as_ref()].concat(),
+        )?)
+    }
+
+    /// Appends an entry, returning its sequence number
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{AuditLog, Sha256};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut log = AuditLog::<String, String, Sha256>::new();
+    ///     log.append("alice".to_owned(), "admin".to_owned())?;
+    ///     log.append("bob".to_owned(), "viewer".to_owned())?;
+    ///     let checkpoint = log.checkpoint()?;
+    ///
+    ///     let proof = log.prove_latest(&"alice".to_owned())?;
+    ///     assert!(AuditLog::<String, String, Sha256>::verify_entry(
+    ///         &checkpoint,
+    ///         &"alice".to_owned(),
+    ///         &"admin".to_owned(),
+    ///         &proof,
+    ///     ));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn append(&mut self, key: K, value: V) -> TreeResult<usize> {
+        let sequence = self.entries.len();
+        self.tree.try_append(Self::entry_hash(&key, &value)?)?;
+        self.index.entry(key.clone()).or_default().push(sequence);
+        self.entries.push((key, value));
+
+        Ok(sequence)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entry(&self, sequence: usize) -> Option<(&K, &V)> {
+        self.entries.get(sequence).map(|(key, value)| (key, value))
+    }
+
+    /// Latest value recorded for the key
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let sequence = *self.index.get(key)?.last()?;
+        Some(&self.entries[sequence].1)
+    }
+
+    /// Sequence numbers of every entry recorded for the key, oldest first
+    pub fn history(&self, key: &K) -> &[usize] {
+        self.index.get(key).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn root_hash(&self) -> TreeResult<H::Hash> {
+        self.tree.root_hash()
+    }
+
+    pub fn checkpoint(&self) -> TreeResult<Checkpoint<H>> {
+        Ok(Checkpoint::new(self.len() as u64, self.root_hash()?))
+    }
+
+    pub fn signed_checkpoint(
+        &self,
+        signer: &impl CheckpointSigner,
+    ) -> TreeResult<SignedCheckpoint<H>> {
+        self.checkpoint()?.sign(signer)
+    }
+
+    /// Inclusion proof for the entry with the given sequence number
+    pub fn prove(&self, sequence: usize) -> TreeResult<EntryProof<H>> {
+        Ok(EntryProof {
+            sequence,
+            tree_size: self.len(),
+            proof: MerkleProof::new(self.tree.get_proof_at(sequence)?),
+        })
+    }
+
+    /// Inclusion proof for the latest entry recorded for the key
+    pub fn prove_latest(&self, key: &K) -> TreeResult<EntryProof<H>> {
+        let sequence = self
+            .history(key)
+            .last()
+            .copied()
+            .ok_or_else(TreeError::leaf_not_found)?;
+
+        self.prove(sequence)
+    }
+
+    /// Proof that the log at `old_size` entries is a prefix of the current log
+    pub fn prove_consistency(&self, old_size: usize) -> TreeResult<ConsistencyProof<H>> {
+        Ok(ConsistencyProof::new(
+            self.tree.consistency_proof(old_size)?,
+        ))
+    }
+
+    /// Checks that the entry is included in the log described by the checkpoint
+    pub fn verify_entry(
+        checkpoint: &Checkpoint<H>,
+        key: &K,
+        value: &V,
+        proof: &EntryProof<H>,
+    ) -> bool {
+        if proof.tree_size as u64 != checkpoint.tree_size || proof.sequence >= proof.tree_size {
+            return false;
+        }
+
+        match Self::entry_hash(key, value) {
+            Ok(leaf) => proof.proof.validate(checkpoint.root_hash, leaf),
+            Err(_) => false,
+        }
+    }
+}
+
+impl<K, V, H> Default for AuditLog<K, V, H>
+where
+    K: AsRef<[u8]> + Eq + Hash + Clone,
+    V: AsRef<[u8]>,
+    H: ToHash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::tests::KeyedHashSigner;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+
+    type Log = AuditLog<&'static str, &'static str, Sha256>;
+
+    #[test]
+    fn test_audit_log_lookup_and_inclusion() {
+        let mut log = Log::new();
+        log.append("alice", "admin").unwrap();
+        log.append("bob", "viewer").unwrap();
+        log.append("alice", "viewer").unwrap();
+
+        assert_eq!(log.get(&"alice"), Some(&"viewer"));
+        assert_eq!(log.history(&"alice"), &[0, 2]);
+        assert_eq!(
+            log.prove_latest(&"carol").err().unwrap().kind(),
+            &TreeErrorKind::LeafNotFound
+        );
+
+        let checkpoint = log.checkpoint().unwrap();
+        let proof = log.prove(0).unwrap();
+        assert!(Log::verify_entry(&checkpoint, &"alice", &"admin", &proof));
+        assert!(!Log::verify_entry(&checkpoint, &"alice", &"viewer", &proof));
+    }
+
+    #[test]
+    fn test_audit_log_consistency_and_checkpoints() {
+        let mut log = Log::new();
+        log.append("alice", "admin").unwrap();
+        log.append("bob", "viewer").unwrap();
+        let old = log.signed_checkpoint(&KeyedHashSigner([7; 4])).unwrap();
+
+        log.append("carol", "admin").unwrap();
+        let new = log.signed_checkpoint(&KeyedHashSigner([7; 4])).unwrap();
+
+        assert!(old.verify(&KeyedHashSigner([7; 4])));
+        assert!(new.verify(&KeyedHashSigner([7; 4])));
+        assert!(log.prove_consistency(2).unwrap().validate(
+            2,
+            3,
+            old.checkpoint().root_hash,
+            new.checkpoint().root_hash
+        ));
+    }
+}
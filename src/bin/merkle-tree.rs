@@ -0,0 +1,144 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use merkle_tree::{
+    ByteOrder, Keccak256, MerkleProof, MerkleTree, ProofResponse, Sha256, Sha512, ToHash,
+};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Builds merkle trees, exports inclusion proofs and verifies them
+#[derive(Parser)]
+#[command(name = "merkle-tree", version)]
+struct Cli {
+    /// Hash algorithm used for the tree
+    #[arg(long, value_enum, default_value_t = Hash::Sha3_256, global = true)]
+    hash: Hash,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Hash {
+    #[value(name = "sha3-256")]
+    Sha3_256,
+    #[value(name = "keccak256")]
+    Keccak256,
+    #[value(name = "sha3-512")]
+    Sha3_512,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the root hash of the tree
+    Root(Source),
+    /// Prints the proofs for the leaves at the given indexes as JSON
+    Prove {
+        #[command(flatten)]
+        source: Source,
+        /// Index of a leaf to prove, may be repeated
+        #[arg(long = "index", required = true)]
+        indexes: Vec<usize>,
+    },
+    /// Verifies a JSON proof, optionally against an expected root
+    Verify {
+        /// File holding a proof as printed by `prove`
+        proof: PathBuf,
+        /// Expected root hash as hex
+        #[arg(long)]
+        root: Option<String>,
+    },
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct Source {
+    /// File with one hex encoded leaf hash per line
+    #[arg(long)]
+    leaves: Option<PathBuf>,
+    /// Directory whose files, ordered by name, are hashed into leaves
+    #[arg(long)]
+    dir: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.hash {
+        Hash::Sha3_256 => run::<Sha256>(cli.command),
+        Hash::Keccak256 => run::<Keccak256>(cli.command),
+        Hash::Sha3_512 => run::<Sha512>(cli.command),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run<T: ToHash>(command: Command) -> Result<bool, Box<dyn Error>> {
+    match command {
+        Command::Root(source) => {
+            let tree = MerkleTree::<T>::from_leaves(&read_leaves::<T>(&source)?);
+            println!("{}", ByteOrder::Natural.to_hex::<T>(tree.root_hash()?));
+        }
+        Command::Prove { source, indexes } => {
+            let leaves = read_leaves::<T>(&source)?;
+            let tree = MerkleTree::<T>::from_leaves(&leaves);
+            let root = tree.root_hash()?;
+            for index in indexes {
+                let leaf = *leaves.get(index).ok_or("leaf index out of bounds")?;
+                let proof = MerkleProof::<T>::new(tree.get_proof_at(index)?);
+                println!(
+                    "{}",
+                    ProofResponse::new(root, leaf, index, &proof).to_json()
+                );
+            }
+        }
+        Command::Verify { proof, root } => {
+            let response = ProofResponse::from_json(&std::fs::read_to_string(proof)?)?;
+            let (proof_root, _, _) = response.decode::<T>()?;
+            if let Some(root) = root {
+                if ByteOrder::Natural.from_hex::<T>(&root)? != proof_root {
+                    println!("invalid: proof is for a different root");
+                    return Ok(false);
+                }
+            }
+
+            let valid = response.validate::<T>()?;
+            println!("{}", if valid { "valid" } else { "invalid" });
+            return Ok(valid);
+        }
+    }
+
+    Ok(true)
+}
+
+fn read_leaves<T: ToHash>(source: &Source) -> Result<Vec<T::Hash>, Box<dyn Error>> {
+    match (&source.leaves, &source.dir) {
+        (Some(path), _) => std::fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| Ok(ByteOrder::Natural.from_hex::<T>(line)?))
+            .collect(),
+        (None, Some(dir)) => hash_dir::<T>(dir),
+        (None, None) => Err("either --leaves or --dir is required".into()),
+    }
+}
+
+fn hash_dir<T: ToHash>(dir: &Path) -> Result<Vec<T::Hash>, Box<dyn Error>> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.retain(|path| path.is_file());
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| Ok(T::try_hash(&std::fs::read(path)?)?))
+        .collect()
+}
@@ -0,0 +1,332 @@
+//! C ABI for building trees and creating/verifying proofs, see `include/merkle_tree.h`
+//!
+//! Every function returns one of the `MERKLE_*` status codes. Hashes are passed as
+//! pointer/length pairs and proofs use the compact format of
+//! [`MerkleProof::to_bytes`](crate::MerkleProof::to_bytes).
+use crate::dyn_merkle_tree::{DynMerkleTree, HashAlgorithm};
+use crate::error::tree_error::{TreeError, TreeErrorKind};
+use crate::merkle_tree::TreeResult;
+use std::slice;
+
+pub const MERKLE_OK: i32 = 0;
+pub const MERKLE_ERR_NULL_POINTER: i32 = -1;
+pub const MERKLE_ERR_UNKNOWN_ALGORITHM: i32 = -2;
+pub const MERKLE_ERR_HASH_SIZE: i32 = -3;
+pub const MERKLE_ERR_TREE_EMPTY: i32 = -4;
+pub const MERKLE_ERR_MALFORMED_PROOF: i32 = -5;
+pub const MERKLE_ERR_BUFFER_TOO_SMALL: i32 = -6;
+pub const MERKLE_ERR_INDEX_OUT_OF_BOUNDS: i32 = -7;
+pub const MERKLE_ERR_LEAF_NOT_FOUND: i32 = -8;
+pub const MERKLE_ERR_INTERNAL: i32 = -99;
+
+pub const MERKLE_ALGORITHM_KECCAK256: u32 = 0;
+pub const MERKLE_ALGORITHM_SHA3_256: u32 = 1;
+pub const MERKLE_ALGORITHM_SHA3_512: u32 = 2;
+
+/// Opaque tree handle owned by the caller until passed to [`merkle_tree_free`]
+pub struct MerkleTreeHandle {
+    tree: DynMerkleTree,
+}
+
+/// Creates an empty tree using one of the `MERKLE_ALGORITHM_*` hashers
+///
+/// # Safety
+/// `out_tree` must be a valid pointer to write the new handle to.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_tree_new(
+    algorithm: u32,
+    out_tree: *mut *mut MerkleTreeHandle,
+) -> i32 {
+    if out_tree.is_null() {
+        return MERKLE_ERR_NULL_POINTER;
+    }
+
+    match algorithm_of(algorithm).map(DynMerkleTree::new) {
+        Ok(tree) => {
+            *out_tree = Box::into_raw(Box::new(MerkleTreeHandle { tree }));
+            MERKLE_OK
+        }
+        Err(error) => status_of(&error),
+    }
+}
+
+/// Releases a tree created with [`merkle_tree_new`]; passing null is a no-op
+///
+/// # Safety
+/// `tree` must be null or a handle from [`merkle_tree_new`] that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_tree_free(tree: *mut MerkleTreeHandle) {
+    if !tree.is_null() {
+        drop(Box::from_raw(tree));
+    }
+}
+
+/// Appends a leaf hash to the tree
+///
+/// # Safety
+/// `tree` must be a live handle and `leaf` must point to `leaf_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_tree_append(
+    tree: *mut MerkleTreeHandle,
+    leaf: *const u8,
+    leaf_len: usize,
+) -> i32 {
+    if tree.is_null() || leaf.is_null() {
+        return MERKLE_ERR_NULL_POINTER;
+    }
+
+    status((*tree).tree.append(slice::from_raw_parts(leaf, leaf_len)))
+}
+
+/// Writes the root hash into `out`, storing its length in `out_len`
+///
+/// # Safety
+/// `tree` must be a live handle, `out` must point to `out_cap` writable bytes and
+/// `out_len` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_tree_root(
+    tree: *mut MerkleTreeHandle,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    if tree.is_null() {
+        return MERKLE_ERR_NULL_POINTER;
+    }
+
+    write_result((*tree).tree.root_hash(), out, out_cap, out_len)
+}
+
+/// Writes the serialized proof for `leaf` into `out`, storing its length in `out_len`
+///
+/// When `out_cap` is too small, `MERKLE_ERR_BUFFER_TOO_SMALL` is returned and `out_len`
+/// holds the required size.
+///
+/// # Safety
+/// `tree` must be a live handle, `leaf` must point to `leaf_len` readable bytes, `out`
+/// must point to `out_cap` writable bytes and `out_len` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_tree_proof(
+    tree: *mut MerkleTreeHandle,
+    leaf: *const u8,
+    leaf_len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    if tree.is_null() || leaf.is_null() {
+        return MERKLE_ERR_NULL_POINTER;
+    }
+
+    let proof = (*tree)
+        .tree
+        .get_proof(slice::from_raw_parts(leaf, leaf_len));
+    write_result(proof, out, out_cap, out_len)
+}
+
+/// Verifies a serialized proof, writing `1` (valid) or `0` (invalid) into `out_valid`
+///
+/// # Safety
+/// `root`, `leaf` and `proof` must point to the given number of readable bytes and
+/// `out_valid` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_proof_verify(
+    algorithm: u32,
+    root: *const u8,
+    root_len: usize,
+    leaf: *const u8,
+    leaf_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+    out_valid: *mut i32,
+) -> i32 {
+    if root.is_null() || leaf.is_null() || proof.is_null() || out_valid.is_null() {
+        return MERKLE_ERR_NULL_POINTER;
+    }
+
+    let is_valid = algorithm_of(algorithm).and_then(|algorithm| {
+        algorithm.validate(
+            slice::from_raw_parts(root, root_len),
+            slice::from_raw_parts(leaf, leaf_len),
+            slice::from_raw_parts(proof, proof_len),
+        )
+    });
+
+    match is_valid {
+        Ok(is_valid) => {
+            *out_valid = is_valid as i32;
+            MERKLE_OK
+        }
+        Err(error) => status_of(&error),
+    }
+}
+
+fn algorithm_of(algorithm: u32) -> TreeResult<HashAlgorithm> {
+    match algorithm {
+        MERKLE_ALGORITHM_KECCAK256 => Ok(HashAlgorithm::Keccak256),
+        MERKLE_ALGORITHM_SHA3_256 => Ok(HashAlgorithm::Sha256),
+        MERKLE_ALGORITHM_SHA3_512 => Ok(HashAlgorithm::Sha512),
+        _ => Err(TreeError::unknown_algorithm()),
+    }
+}
+
+unsafe fn write_result(
+    result: TreeResult<Vec<u8>>,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    if out.is_null() || out_len.is_null() {
+        return MERKLE_ERR_NULL_POINTER;
+    }
+
+    let bytes = match result {
+        Ok(bytes) => bytes,
+        Err(error) => return status_of(&error),
+    };
+
+    *out_len = bytes.len();
+    if bytes.len() > out_cap {
+        return MERKLE_ERR_BUFFER_TOO_SMALL;
+    }
+
+    out.copy_from_nonoverlapping(bytes.as_ptr(), bytes.len());
+    MERKLE_OK
+}
+
+fn status(result: TreeResult<()>) -> i32 {
+    match result {
+        Ok(()) => MERKLE_OK,
+        Err(error) => status_of(&error),
+    }
+}
+
+fn status_of(error: &TreeError) -> i32 {
+    match error.kind() {
+        TreeErrorKind::UnknownAlgorithm => MERKLE_ERR_UNKNOWN_ALGORITHM,
+        TreeErrorKind::HashSizeMismatch => MERKLE_ERR_HASH_SIZE,
+        TreeErrorKind::TreeEmpty => MERKLE_ERR_TREE_EMPTY,
+        TreeErrorKind::ProofMalformed
+        | TreeErrorKind::ProofEmpty
+        | TreeErrorKind::ProofLengthMismatch => MERKLE_ERR_MALFORMED_PROOF,
+        TreeErrorKind::IndexOutOfBounds => MERKLE_ERR_INDEX_OUT_OF_BOUNDS,
+        TreeErrorKind::LeafNotFound => MERKLE_ERR_LEAF_NOT_FOUND,
+        _ => MERKLE_ERR_INTERNAL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Keccak256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use std::ptr;
+
+    #[test]
+    fn test_ffi_round_trip() {
+        let leaves = raw_leaves_to_hashed_leaves::<Keccak256>(&["0", "1", "2"]);
+        unsafe {
+            let mut tree = ptr::null_mut();
+            assert_eq!(
+                merkle_tree_new(MERKLE_ALGORITHM_KECCAK256, &mut tree),
+                MERKLE_OK
+            );
+            for leaf in &leaves {
+                assert_eq!(
+                    merkle_tree_append(tree, leaf.as_ptr(), leaf.len()),
+                    MERKLE_OK
+                );
+            }
+
+            let (mut root, mut root_len) = ([0u8; 32], 0);
+            assert_eq!(
+                merkle_tree_root(tree, root.as_mut_ptr(), root.len(), &mut root_len),
+                MERKLE_OK
+            );
+
+            let (mut proof, mut proof_len) = ([0u8; 8], 0);
+            assert_eq!(
+                merkle_tree_proof(
+                    tree,
+                    leaves[1].as_ptr(),
+                    32,
+                    proof.as_mut_ptr(),
+                    proof.len(),
+                    &mut proof_len
+                ),
+                MERKLE_ERR_BUFFER_TOO_SMALL
+            );
+            let mut proof = vec![0u8; proof_len];
+            assert_eq!(
+                merkle_tree_proof(
+                    tree,
+                    leaves[1].as_ptr(),
+                    32,
+                    proof.as_mut_ptr(),
+                    proof.len(),
+                    &mut proof_len
+                ),
+                MERKLE_OK
+            );
+
+            let mut is_valid = 0;
+            assert_eq!(
+                merkle_proof_verify(
+                    MERKLE_ALGORITHM_KECCAK256,
+                    root.as_ptr(),
+                    root_len,
+                    leaves[1].as_ptr(),
+                    32,
+                    proof.as_ptr(),
+                    proof.len(),
+                    &mut is_valid
+                ),
+                MERKLE_OK
+            );
+            assert_eq!(is_valid, 1);
+
+            merkle_tree_free(tree);
+        }
+    }
+
+    #[test]
+    fn test_ffi_error_codes() {
+        unsafe {
+            let mut tree = ptr::null_mut();
+            assert_eq!(merkle_tree_new(42, &mut tree), MERKLE_ERR_UNKNOWN_ALGORITHM);
+            assert_eq!(
+                merkle_tree_new(MERKLE_ALGORITHM_SHA3_256, ptr::null_mut()),
+                MERKLE_ERR_NULL_POINTER
+            );
+
+            assert_eq!(
+                merkle_tree_new(MERKLE_ALGORITHM_SHA3_256, &mut tree),
+                MERKLE_OK
+            );
+            let (mut root, mut root_len) = ([0u8; 32], 0);
+            assert_eq!(
+                merkle_tree_root(tree, root.as_mut_ptr(), root.len(), &mut root_len),
+                MERKLE_ERR_TREE_EMPTY
+            );
+            assert_eq!(
+                merkle_tree_append(tree, root.as_ptr(), 31),
+                MERKLE_ERR_HASH_SIZE
+            );
+
+            let (mut proof, mut proof_len) = ([0u8; 64], 0);
+            assert_eq!(merkle_tree_append(tree, [1u8; 32].as_ptr(), 32), MERKLE_OK);
+            assert_eq!(
+                merkle_tree_proof(
+                    tree,
+                    root.as_ptr(),
+                    root.len(),
+                    proof.as_mut_ptr(),
+                    proof.len(),
+                    &mut proof_len
+                ),
+                MERKLE_ERR_LEAF_NOT_FOUND
+            );
+            merkle_tree_free(tree);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! `wasm-bindgen` wrappers exposing trees and proofs to JavaScript
+//!
+//! Hashes cross the boundary as `Uint8Array`s and proofs use the compact format of
+//! [`MerkleProof::to_bytes`](crate::MerkleProof::to_bytes). Algorithms are selected by
+//! name: `"keccak256"`, `"sha3-256"` or `"sha3-512"`.
+use crate::dyn_merkle_tree::{DynMerkleTree, HashAlgorithm};
+use crate::error::tree_error::TreeError;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(js_name = MerkleTree)]
+pub struct WasmMerkleTree {
+    tree: DynMerkleTree,
+}
+
+#[wasm_bindgen(js_class = MerkleTree)]
+impl WasmMerkleTree {
+    #[wasm_bindgen(constructor)]
+    pub fn new(algorithm: &str) -> Result<WasmMerkleTree, JsError> {
+        Ok(Self {
+            tree: DynMerkleTree::new(parse_algorithm(algorithm)?),
+        })
+    }
+
+    pub fn append(&mut self, leaf: &[u8]) -> Result<(), JsError> {
+        self.tree.append(leaf).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = rootHash)]
+    pub fn root_hash(&self) -> Result<Vec<u8>, JsError> {
+        self.tree.root_hash().map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = getProof)]
+    pub fn get_proof(&self, leaf: &[u8]) -> Result<Vec<u8>, JsError> {
+        self.tree.get_proof(leaf).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = getProofAt)]
+    pub fn get_proof_at(&self, index: usize) -> Result<Vec<u8>, JsError> {
+        self.tree.get_proof_at(index).map_err(to_js_error)
+    }
+}
+
+/// Hashes raw data with the named algorithm, e.g. to derive leaves
+#[wasm_bindgen(js_name = hashLeaf)]
+pub fn hash_leaf(algorithm: &str, data: &[u8]) -> Result<Vec<u8>, JsError> {
+    parse_algorithm(algorithm)?.hash(data).map_err(to_js_error)
+}
+
+/// Validates a serialized proof for `leaf` against `root`
+#[wasm_bindgen(js_name = validateProof)]
+pub fn validate_proof(
+    algorithm: &str,
+    root: &[u8],
+    leaf: &[u8],
+    proof: &[u8],
+) -> Result<bool, JsError> {
+    parse_algorithm(algorithm)?
+        .validate(root, leaf, proof)
+        .map_err(to_js_error)
+}
+
+fn parse_algorithm(algorithm: &str) -> Result<HashAlgorithm, JsError> {
+    algorithm.parse().map_err(to_js_error)
+}
+
+fn to_js_error(error: TreeError) -> JsError {
+    JsError::new(&error.to_string())
+}
@@ -0,0 +1,178 @@
+//! Per-file merkle trees as specified by BitTorrent v2 (BEP 52)
+//!
+//! A file is split into 16 KiB blocks hashed with SHA-256, i.e. [`Sha2_256`]. The block hashes
+//! are padded with zero hashes to a power of two and hashed positionally as
+//! `hash(left || right)`; the root is the file's `pieces root`. The layer whose nodes each
+//! cover one piece is published in the torrent's `piece layers`.
+
+use crate::chunker::read_chunk;
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::hash::Sha2_256;
+use crate::merkle_tree::TreeResult;
+use crate::ssz::{hash_pair, Chunk, SszTree};
+use std::io::Read;
+
+/// Size of the blocks forming the leaves of a file tree
+pub const BLOCK_SIZE: usize = 16 * 1024;
+
+/// BEP 52 merkle tree of a single file
+pub struct FileTree {
+    byte_len: u64,
+    tree: Option<SszTree<Sha2_256>>,
+}
+
+impl FileTree {
+    /// Reads the file to the end, hashing every 16 KiB block
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::bittorrent::FileTree;
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let file = vec![7u8; 100 * 1024];
+    ///     let tree = FileTree::from_reader(file.as_slice())?;
+    ///
+    ///     let pieces_root = tree.pieces_root().expect("file is not empty");
+    ///     assert_eq!(tree.piece_layer(64 * 1024)?.len(), 2);
+    ///     assert!(tree.piece_layer(128 * 1024)?.is_empty());
+    ///     assert_ne!(pieces_root, [0; 32]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_reader(mut source: impl Read) -> TreeResult<Self> {
+        let mut blocks = Vec::new();
+        let mut byte_len = 0;
+        let mut block = vec![0; BLOCK_SIZE];
+        loop {
+            let read = read_chunk(&mut source, &mut block)?;
+            if read == 0 {
+                break;
+            }
+            blocks.push(Sha2_256::try_hash(&block[..read])?);
+            byte_len += read as u64;
+            if read < BLOCK_SIZE {
+                break;
+            }
+        }
+
+        Self::from_block_hashes(&blocks, byte_len)
+    }
+
+    /// Builds the tree from already computed block hashes of a file of `byte_len` bytes
+    pub fn from_block_hashes(blocks: &[Chunk], byte_len: u64) -> TreeResult<Self> {
+        if blocks.len() as u64 != byte_len.div_ceil(BLOCK_SIZE as u64) {
+            return Err(TreeError::proof_length_mismatch(
+                "block count does not match the file length",
+            ));
+        }
+
+        let tree = match blocks.is_empty() {
+            true => None,
+            false => Some(SszTree::new(blocks, None)?),
+        };
+
+        Ok(Self { byte_len, tree })
+    }
+
+    pub fn byte_len(&self) -> u64 {
+        self.byte_len
+    }
+
+    /// The file's `pieces root`, absent for empty files
+    pub fn pieces_root(&self) -> Option<Chunk> {
+        self.tree.as_ref().map(|tree| tree.root())
+    }
+
+    /// Hashes of the subtrees covering each piece of `piece_length` bytes
+    ///
+    /// `piece_length` must be a power of two of at least [`BLOCK_SIZE`]. Files no larger than
+    /// one piece have no piece layer, so the result is empty for them.
+    pub fn piece_layer(&self, piece_length: usize) -> TreeResult<Vec<Chunk>> {
+        if piece_length < BLOCK_SIZE || !piece_length.is_power_of_two() {
+            return Err(TreeError::invalid_encoding(
+                "piece length must be a power of two of at least 16 KiB",
+            ));
+        }
+
+        match &self.tree {
+            Some(tree) if self.byte_len > piece_length as u64 => {
+                let height = (piece_length / BLOCK_SIZE).trailing_zeros() as usize;
+                Ok(tree.level(height).to_vec())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Verifies a piece layer received from a peer against the file's `pieces root`
+pub fn verify_piece_layer(piece_layer: &[Chunk], piece_length: usize, pieces_root: Chunk) -> bool {
+    if piece_length < BLOCK_SIZE || !piece_length.is_power_of_two() || piece_layer.is_empty() {
+        return false;
+    }
+
+    // padding pieces are full subtrees of zero blocks
+    let mut padding = [0u8; 32];
+    for _ in 0..(piece_length / BLOCK_SIZE).trailing_zeros() {
+        padding = hash_pair::<Sha2_256>(padding, padding);
+    }
+
+    let mut padded = piece_layer.to_vec();
+    padded.resize(piece_layer.len().next_power_of_two(), padding);
+    while padded.len() > 1 {
+        padded = padded
+            .chunks(2)
+            .map(|pair| hash_pair::<Sha2_256>(pair[0], pair[1]))
+            .collect();
+    }
+
+    padded[0] == pieces_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(left: Chunk, right: Chunk) -> Chunk {
+        Sha2_256::hash(&[left, right].concat())
+    }
+
+    #[test]
+    fn test_pieces_root_pads_blocks_with_zero_hashes() {
+        let file: Vec<u8> = (0..5 * BLOCK_SIZE - 100).map(|i| i as u8).collect();
+        let blocks: Vec<Chunk> = file.chunks(BLOCK_SIZE).map(Sha2_256::hash).collect();
+        let tree = FileTree::from_reader(file.as_slice()).unwrap();
+
+        let zero = [0u8; 32];
+        let expected = pair(
+            pair(pair(blocks[0], blocks[1]), pair(blocks[2], blocks[3])),
+            pair(pair(blocks[4], zero), pair(zero, zero)),
+        );
+        assert_eq!(tree.pieces_root(), Some(expected));
+
+        let layer = tree.piece_layer(2 * BLOCK_SIZE).unwrap();
+        assert_eq!(
+            layer,
+            vec![
+                pair(blocks[0], blocks[1]),
+                pair(blocks[2], blocks[3]),
+                pair(blocks[4], zero)
+            ]
+        );
+        assert!(verify_piece_layer(&layer, 2 * BLOCK_SIZE, expected));
+        assert!(!verify_piece_layer(&layer[..2], 2 * BLOCK_SIZE, expected));
+    }
+
+    #[test]
+    fn test_small_and_empty_files() {
+        let file = b"hello bittorrent";
+        let tree = FileTree::from_reader(file.as_slice()).unwrap();
+        assert_eq!(tree.pieces_root(), Some(Sha2_256::hash(file)));
+        assert!(tree.piece_layer(BLOCK_SIZE).unwrap().is_empty());
+        assert!(tree.piece_layer(3 * BLOCK_SIZE).is_err());
+
+        let empty = FileTree::from_reader([].as_slice()).unwrap();
+        assert_eq!(empty.pieces_root(), None);
+        assert!(FileTree::from_block_hashes(&[[0; 32]], 0).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use crate::error::tree_error::TreeError;
+use crate::merkle_tree::TreeResult;
+
+const BLOOM_FORMAT_VERSION: u8 = 1;
+const BLOOM_HEADER_LEN: usize = 13;
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Probabilistic set of leaves answering "maybe present" or "definitely absent"
+///
+/// Each item sets `hash_count` of `bit_count` bits, picked by double hashing, so a lookup
+/// costs a few bit tests and never a false negative. Attached to a
+/// [`MerkleTree`](crate::MerkleTree), it rejects most absent leaves before the leaf index or
+/// a storage backend is consulted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    bit_count: u64,
+    hash_count: u32,
+}
+
+impl BloomFilter {
+    /// Sizes the filter to hold `expected_items` at the given false positive rate
+    ///
+    /// The rate is clamped to `[1e-9, 0.5]`. Holding more items than expected raises the rate
+    /// but never causes false negatives.
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let items = expected_items.max(1) as f64;
+        let rate = false_positive_rate.clamp(1e-9, 0.5);
+        let bit_count = (-items * rate.ln() / std::f64::consts::LN_2.powi(2)).ceil() as u64;
+        let hash_count = (bit_count as f64 / items * std::f64::consts::LN_2).round() as u32;
+
+        Self::with_size(bit_count, hash_count)
+    }
+
+    /// Creates an empty filter of exactly `bit_count` bits probed `hash_count` times per item
+    pub fn with_size(bit_count: u64, hash_count: u32) -> Self {
+        let bit_count = bit_count.max(64);
+        Self {
+            bits: vec![0; bit_count.div_ceil(64) as usize],
+            bit_count,
+            hash_count: hash_count.max(1),
+        }
+    }
+
+    pub fn bit_count(&self) -> u64 {
+        self.bit_count
+    }
+
+    pub fn hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    pub fn insert(&mut self, item: &[u8]) {
+        for bit in self.probes(item) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Whether the item may have been inserted; `false` means it definitely was not
+    pub fn maybe_contains(&self, item: &[u8]) -> bool {
+        self.probes(item)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Serializes the filter, e.g. to store it next to the leaves of its tree
+    ///
+    /// Layout: `version (u8) | hash count (u32, BE) | bit count (u64, BE) | words (u64, BE)`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BLOOM_HEADER_LEN + self.bits.len() * 8);
+        bytes.push(BLOOM_FORMAT_VERSION);
+        bytes.extend_from_slice(&self.hash_count.to_be_bytes());
+        bytes.extend_from_slice(&self.bit_count.to_be_bytes());
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> TreeResult<Self> {
+        if bytes.len() < BLOOM_HEADER_LEN {
+            return Err(TreeError::invalid_encoding(
+                "bloom filter header is truncated",
+            ));
+        }
+        if bytes[0] != BLOOM_FORMAT_VERSION {
+            return Err(TreeError::invalid_encoding(
+                "unsupported bloom filter format version",
+            ));
+        }
+
+        let hash_count = u32::from_be_bytes(bytes[1..5].try_into().expect("slice of 4 bytes"));
+        let bit_count = u64::from_be_bytes(bytes[5..13].try_into().expect("slice of 8 bytes"));
+        let words = &bytes[BLOOM_HEADER_LEN..];
+        if hash_count == 0 || bit_count < 64 || words.len() as u64 != bit_count.div_ceil(64) * 8 {
+            return Err(TreeError::invalid_encoding(
+                "bloom filter size does not match its header",
+            ));
+        }
+
+        Ok(Self {
+            bits: words
+                .chunks_exact(8)
+                .map(|word| u64::from_be_bytes(word.try_into().expect("chunk of 8 bytes")))
+                .collect(),
+            bit_count,
+            hash_count,
+        })
+    }
+
+    /// Bits probed for the item, `h1 + i * h2` for every `i` below the hash count
+    fn probes(&self, item: &[u8]) -> impl Iterator<Item = u64> {
+        let (hash_count, bit_count) = (self.hash_count as u64, self.bit_count);
+        let first = item.iter().fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+        });
+        let second = mix(first) | 1;
+
+        (0..hash_count).map(move |probe| first.wrapping_add(probe.wrapping_mul(second)) % bit_count)
+    }
+}
+
+/// SplitMix64 finalizer, deriving the second probe hash from the first
+fn mix(value: u64) -> u64 {
+    let mut value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+
+    #[test]
+    fn test_no_false_negatives_and_bounded_false_positives() {
+        let mut filter = BloomFilter::new(1000, 0.01);
+        for item in 0..1000u32 {
+            filter.insert(&item.to_be_bytes());
+        }
+
+        assert!((0..1000u32).all(|item| filter.maybe_contains(&item.to_be_bytes())));
+        let false_positives = (1000..11_000u32)
+            .filter(|item| filter.maybe_contains(&item.to_be_bytes()))
+            .count();
+        assert!(false_positives < 200, "{false_positives} false positives");
+    }
+
+    #[test]
+    fn test_bytes_round_trip_and_reject_bad_sizes() {
+        let mut filter = BloomFilter::new(10, 0.05);
+        filter.insert(b"alice");
+
+        let bytes = filter.to_bytes();
+        assert_eq!(BloomFilter::from_bytes(&bytes).unwrap(), filter);
+        assert_eq!(
+            BloomFilter::from_bytes(&bytes[..bytes.len() - 1])
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::InvalidEncoding
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::TreeResult;
+use crate::utils::ct;
+
+/// Inclusion proof crossing nested trees, e.g. a leaf of a shard whose root is a leaf of a
+/// top tree
+///
+/// Hops are ordered from the innermost tree out. The root each hop folds to is the leaf of
+/// the next one, so the chain validates the original leaf against the outermost root alone.
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::{ChainedProof, Sha256, Sha256Proof, Sha256Tree, ToHash};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let shards: Vec<Vec<_>> = (0..3u8)
+///         .map(|shard| (0..5u8).map(|i| Sha256::hash(&[shard, i])).collect())
+///         .collect();
+///     let shard_trees: Vec<_> = shards.iter().map(|leaves| Sha256Tree::from_leaves(leaves)).collect();
+///     let shard_roots = shard_trees
+///         .iter()
+///         .map(|tree| tree.root_hash())
+///         .collect::<Result<Vec<_>, _>>()?;
+///     let top_tree = Sha256Tree::from_leaves(&shard_roots);
+///
+///     let proof = ChainedProof::new(Sha256Proof::new(shard_trees[1].get_proof_at(3)?))
+///         .then(Sha256Proof::new(top_tree.get_proof_at(1)?));
+///     assert!(proof.validate(top_tree.root_hash()?, shards[1][3]));
+///     assert!(!proof.validate(top_tree.root_hash()?, shards[0][3]));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct ChainedProof<T: ToHash> {
+    hops: Vec<MerkleProof<T>>,
+}
+
+impl<T: ToHash> ChainedProof<T> {
+    /// Starts the chain with the proof of the leaf in the innermost tree
+    pub fn new(proof: MerkleProof<T>) -> Self {
+        Self { hops: vec![proof] }
+    }
+
+    /// Builds a chain from hops ordered from the innermost tree out
+    pub fn from_hops(hops: Vec<MerkleProof<T>>) -> TreeResult<Self> {
+        if hops.is_empty() {
+            return Err(TreeError::proof_empty());
+        }
+
+        Ok(Self { hops })
+    }
+
+    /// Appends the proof of the current root in its enclosing tree
+    pub fn then(mut self, proof: MerkleProof<T>) -> Self {
+        self.hops.push(proof);
+        self
+    }
+
+    pub fn hops(&self) -> &[MerkleProof<T>] {
+        &self.hops
+    }
+
+    /// Validates that `leaf` is committed to by `root_hash` through every hop
+    pub fn validate(&self, root_hash: T::Hash, leaf: T::Hash) -> bool {
+        self.compute_root(leaf)
+            .is_ok_and(|root| ct::eq(root.as_ref(), root_hash.as_ref()))
+    }
+
+    /// Folds every hop in turn, returning the outermost root
+    pub fn compute_root(&self, leaf: T::Hash) -> TreeResult<T::Hash> {
+        self.hops
+            .iter()
+            .try_fold(leaf, |current, hop| hop.compute_root(current))
+    }
+
+    /// Roots of the intermediate trees, from the innermost one out, ending with the outermost
+    pub fn intermediate_roots(&self, leaf: T::Hash) -> TreeResult<Vec<T::Hash>> {
+        let mut roots = Vec::with_capacity(self.hops.len());
+        let mut current = leaf;
+        for hop in &self.hops {
+            current = hop.compute_root(current)?;
+            roots.push(current);
+        }
+
+        Ok(roots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{Sha256Proof, Sha256Tree};
+
+    #[test]
+    fn test_three_level_chain_validates() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3"]);
+        let inner = Sha256Tree::from_leaves(&leaves);
+        let inner_root = inner.root_hash().unwrap();
+
+        let middle_leaves = [Sha256::hash(b"a"), inner_root, Sha256::hash(b"b")];
+        let middle = Sha256Tree::from_leaves(&middle_leaves);
+        let middle_root = middle.root_hash().unwrap();
+
+        let outer_leaves = [middle_root, Sha256::hash(b"c")];
+        let outer = Sha256Tree::from_leaves(&outer_leaves);
+        let outer_root = outer.root_hash().unwrap();
+
+        let proof = ChainedProof::from_hops(vec![
+            Sha256Proof::new(inner.get_proof_at(2).unwrap()),
+            Sha256Proof::new(middle.get_proof_at(1).unwrap()),
+            Sha256Proof::new(outer.get_proof_at(0).unwrap()),
+        ])
+        .unwrap();
+
+        assert!(proof.validate(outer_root, leaves[2]));
+        assert!(!proof.validate(middle_root, leaves[2]));
+        assert_eq!(
+            proof.intermediate_roots(leaves[2]).unwrap(),
+            vec![inner_root, middle_root, outer_root]
+        );
+    }
+
+    #[test]
+    fn test_empty_chain_returns_error() {
+        assert_eq!(
+            ChainedProof::<Sha256>::from_hops(Vec::new())
+                .err()
+                .unwrap()
+                .kind(),
+            &TreeErrorKind::ProofEmpty
+        );
+    }
+}
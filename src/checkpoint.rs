@@ -0,0 +1,415 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+use crate::utils::base64;
+
+const CHECKPOINT_DOMAIN: &[u8] = b"merkle_tree checkpoint v1\n";
+const NOTE_SIGNATURE_PREFIX: &str = "\u{2014} ";
+const NOTE_TIMESTAMP_PREFIX: &str = "timestamp ";
+
+/// Commitment to the state of an append-only tree: its size and root
+///
+/// The origin names the log the checkpoint belongs to and the optional timestamp (seconds
+/// since the Unix epoch) records when the root was produced.
+pub struct Checkpoint<T: ToHash> {
+    pub origin: String,
+    pub tree_size: u64,
+    pub root_hash: T::Hash,
+    pub timestamp: Option<u64>,
+}
+
+/// Produces signatures over checkpoint messages (ed25519, HSM, KMS, ...)
+pub trait CheckpointSigner {
+    fn sign(&self, message: &[u8]) -> TreeResult<Vec<u8>>;
+}
+
+/// Checks signatures produced by a matching [`CheckpointSigner`]
+pub trait CheckpointVerifier {
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// Identity of a key in signed notes: its name and the 32-bit hash identifying it
+///
+/// Signature lines of a note read `— <name> <base64(key hash || signature)>`, so verifiers
+/// pick their signature out of a note by both.
+pub trait NoteKey {
+    fn key_name(&self) -> &str;
+    fn key_hash(&self) -> u32;
+}
+
+/// Signer or verifier `K` known under a name and key hash, for use with signed notes
+pub struct NamedKey<K> {
+    name: String,
+    key_hash: u32,
+    key: K,
+}
+
+impl<K> NamedKey<K> {
+    pub fn new(name: impl Into<String>, key_hash: u32, key: K) -> Self {
+        Self {
+            name: name.into(),
+            key_hash,
+            key,
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<K> NoteKey for NamedKey<K> {
+    fn key_name(&self) -> &str {
+        &self.name
+    }
+
+    fn key_hash(&self) -> u32 {
+        self.key_hash
+    }
+}
+
+impl<K: CheckpointSigner> CheckpointSigner for NamedKey<K> {
+    fn sign(&self, message: &[u8]) -> TreeResult<Vec<u8>> {
+        self.key.sign(message)
+    }
+}
+
+impl<K: CheckpointVerifier> CheckpointVerifier for NamedKey<K> {
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self.key.verify(message, signature)
+    }
+}
+
+impl<T: ToHash> Checkpoint<T> {
+    pub fn new(tree_size: u64, root_hash: T::Hash) -> Self {
+        Self {
+            origin: String::new(),
+            tree_size,
+            root_hash,
+            timestamp: None,
+        }
+    }
+
+    pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = origin.into();
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Canonical message covered by signatures over this checkpoint
+    ///
+    /// The origin and timestamp are appended only when set, so checkpoints without them keep
+    /// the message, and the signatures, they always had.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut message = [
+            CHECKPOINT_DOMAIN,
+            T::ALGORITHM.as_bytes(),
+            b"\n",
+            &self.tree_size.to_be_bytes(),
+            self.root_hash.as_ref(),
+        ]
+        .concat();
+        if !self.origin.is_empty() || self.timestamp.is_some() {
+            message.extend_from_slice(&(self.origin.len() as u64).to_be_bytes());
+            message.extend_from_slice(self.origin.as_bytes());
+            match self.timestamp {
+                Some(timestamp) => {
+                    message.push(1);
+                    message.extend_from_slice(&timestamp.to_be_bytes());
+                }
+                None => message.push(0),
+            }
+        }
+
+        message
+    }
+
+    /// Body of the checkpoint in the signed note format of transparency logs
+    ///
+    /// The origin, the decimal tree size and the base64 root each take a line, followed by a
+    /// `timestamp <seconds>` extension line when a timestamp is set. Fails with
+    /// `InvalidEncoding` when the origin is empty or spans several lines, as such a note
+    /// could not be parsed back.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Checkpoint, Sha2_256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let checkpoint = Checkpoint::<Sha2_256>::new(2, [0; 32]).with_origin("example.com/log");
+    ///     let note = checkpoint.to_note()?;
+    ///     assert_eq!(note, "example.com/log\n2\nAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=\n");
+    ///
+    ///     let parsed = Checkpoint::<Sha2_256>::from_note(&note)?;
+    ///     assert_eq!((parsed.tree_size, parsed.root_hash), (2, [0; 32]));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_note(&self) -> TreeResult<String> {
+        if self.origin.is_empty() || self.origin.contains('\n') {
+            return Err(TreeError::invalid_encoding(
+                "checkpoint origin must be one non-empty line",
+            ));
+        }
+
+        let mut note = format!(
+            "{}\n{}\n{}\n",
+            self.origin,
+            self.tree_size,
+            base64::encode(self.root_hash.as_ref())
+        );
+        if let Some(timestamp) = self.timestamp {
+            note.push_str(&format!("{NOTE_TIMESTAMP_PREFIX}{timestamp}\n"));
+        }
+
+        Ok(note)
+    }
+
+    /// Parses a checkpoint body produced by [`Checkpoint::to_note`], ignoring unknown
+    /// extension lines
+    pub fn from_note(note: &str) -> TreeResult<Self> {
+        let lines = note
+            .strip_suffix('\n')
+            .ok_or_else(|| TreeError::invalid_encoding("checkpoint must end with a newline"))?;
+        let mut lines = lines.split('\n');
+        let (Some(origin), Some(tree_size), Some(root_hash)) =
+            (lines.next(), lines.next(), lines.next())
+        else {
+            return Err(TreeError::invalid_encoding(
+                "checkpoint needs an origin, a size and a root",
+            ));
+        };
+        if origin.is_empty() {
+            return Err(TreeError::invalid_encoding("checkpoint origin is empty"));
+        }
+
+        let tree_size = tree_size
+            .parse::<u64>()
+            .ok()
+            .filter(|size| size.to_string() == tree_size)
+            .ok_or_else(|| TreeError::invalid_encoding("tree size must be a decimal number"))?;
+        let root_hash = base64::decode(root_hash)
+            .ok_or_else(|| TreeError::invalid_encoding("root must be base64"))?;
+        let root_hash = T::Hash::try_from(root_hash.as_slice())
+            .map_err(|_| TreeError::hash_size_mismatch("root length does not match the hasher"))?;
+
+        let mut checkpoint = Self::new(tree_size, root_hash).with_origin(origin);
+        for line in lines {
+            if line.is_empty() {
+                return Err(TreeError::invalid_encoding("checkpoint has an empty line"));
+            }
+            if let Some(timestamp) = line.strip_prefix(NOTE_TIMESTAMP_PREFIX) {
+                checkpoint.timestamp = Some(timestamp.parse().map_err(|_| {
+                    TreeError::invalid_encoding("timestamp must be a decimal number")
+                })?);
+            }
+        }
+
+        Ok(checkpoint)
+    }
+
+    /// Signs the note body, returning the signed note `body \n — name base64(hash || sig)`
+    ///
+    /// Fails like [`Checkpoint::to_note`] when the origin cannot be written to a note.
+    pub fn sign_note(&self, signer: &(impl CheckpointSigner + NoteKey)) -> TreeResult<String> {
+        let body = self.to_note()?;
+        let signature = signer.sign(body.as_bytes())?;
+        let signature = [&signer.key_hash().to_be_bytes()[..], &signature].concat();
+
+        Ok(format!(
+            "{body}\n{NOTE_SIGNATURE_PREFIX}{} {}\n",
+            signer.key_name(),
+            base64::encode(&signature)
+        ))
+    }
+
+    /// Parses a signed note, accepting it only when it carries a valid signature by `verifier`
+    ///
+    /// Signatures by other keys are ignored, so notes cosigned by witnesses still verify.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Checkpoint, CheckpointSigner, CheckpointVerifier, NamedKey, Sha2_256, TreeResult};
+    /// # struct Hmac;
+    /// # impl CheckpointSigner for Hmac {
+    /// #     fn sign(&self, message: &[u8]) -> TreeResult<Vec<u8>> { Ok(message.len().to_be_bytes().to_vec()) }
+    /// # }
+    /// # impl CheckpointVerifier for Hmac {
+    /// #     fn verify(&self, message: &[u8], signature: &[u8]) -> bool { signature == message.len().to_be_bytes() }
+    /// # }
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let key = NamedKey::new("example.com/log", 0x0102_0304, Hmac);
+    ///     let note = Checkpoint::<Sha2_256>::new(2, [0; 32])
+    ///         .with_origin("example.com/log")
+    ///         .sign_note(&key)?;
+    ///
+    ///     let checkpoint = Checkpoint::<Sha2_256>::verify_note(&note, &key)?;
+    ///     assert_eq!(checkpoint.origin, "example.com/log");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn verify_note(
+        note: &str,
+        verifier: &(impl CheckpointVerifier + NoteKey),
+    ) -> TreeResult<Self> {
+        let split = note
+            .find("\n\n")
+            .ok_or_else(|| TreeError::invalid_encoding("signed note has no signatures"))?;
+        let (body, signatures) = (&note[..split + 1], &note[split + 2..]);
+        let checkpoint = Self::from_note(body)?;
+
+        let signatures = signatures
+            .strip_suffix('\n')
+            .ok_or_else(|| TreeError::invalid_encoding("signed note must end with a newline"))?;
+        for line in signatures.split('\n') {
+            let (name, signature) = line
+                .strip_prefix(NOTE_SIGNATURE_PREFIX)
+                .and_then(|line| line.split_once(' '))
+                .ok_or_else(|| TreeError::invalid_encoding("malformed signature line"))?;
+            let signature = base64::decode(signature)
+                .filter(|signature| signature.len() > 4)
+                .ok_or_else(|| TreeError::invalid_encoding("malformed signature line"))?;
+            let key_hash = u32::from_be_bytes(signature[..4].try_into().unwrap());
+
+            if name == verifier.key_name()
+                && key_hash == verifier.key_hash()
+                && verifier.verify(body.as_bytes(), &signature[4..])
+            {
+                return Ok(checkpoint);
+            }
+        }
+
+        Err(TreeError::proof_invalid())
+    }
+
+    pub fn sign(self, signer: &impl CheckpointSigner) -> TreeResult<SignedCheckpoint<T>> {
+        let signature = signer.sign(&self.to_bytes())?;
+        Ok(SignedCheckpoint {
+            checkpoint: self,
+            signature,
+        })
+    }
+}
+
+pub struct SignedCheckpoint<T: ToHash> {
+    checkpoint: Checkpoint<T>,
+    signature: Vec<u8>,
+}
+
+impl<T: ToHash> SignedCheckpoint<T> {
+    pub fn checkpoint(&self) -> &Checkpoint<T> {
+        &self.checkpoint
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    pub fn verify(&self, verifier: &impl CheckpointVerifier) -> bool {
+        verifier.verify(&self.checkpoint.to_bytes(), &self.signature)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+
+    /// Toy signer for tests: the "signature" is the hash of key and message
+    pub(crate) struct KeyedHashSigner(pub [u8; 4]);
+
+    impl CheckpointSigner for KeyedHashSigner {
+        fn sign(&self, message: &[u8]) -> TreeResult<Vec<u8>> {
+            Ok(Sha256::hash(&[&self.0[..], message].concat()).to_vec())
+        }
+    }
+
+    impl CheckpointVerifier for KeyedHashSigner {
+        fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+            self.sign(message)
+                .map(|expected| expected == signature)
+                .unwrap_or(false)
+        }
+    }
+
+    #[test]
+    fn test_signed_notes() {
+        let key = NamedKey::new("log", 7, KeyedHashSigner([1, 2, 3, 4]));
+        let witness = NamedKey::new("witness", 9, KeyedHashSigner([5, 6, 7, 8]));
+        let checkpoint = Checkpoint::<Sha256>::new(3, Sha256::hash("root".as_bytes()))
+            .with_origin("example.com/log")
+            .with_timestamp(1_700_000_000);
+
+        let note = checkpoint.sign_note(&key).unwrap();
+        assert!(note.starts_with("example.com/log\n3\n"));
+        assert!(note.contains("\ntimestamp 1700000000\n\n\u{2014} log "));
+
+        let cosigned = format!("{}{}", note, &witness_line(&checkpoint, &witness));
+        for note in [&note, &cosigned] {
+            let parsed = Checkpoint::<Sha256>::verify_note(note, &key).unwrap();
+            assert_eq!(parsed.to_bytes(), checkpoint.to_bytes());
+        }
+        assert!(Checkpoint::<Sha256>::verify_note(&cosigned, &witness).is_ok());
+        assert!(Checkpoint::<Sha256>::verify_note(&note, &witness).is_err());
+
+        let tampered = note.replace("\n3\n", "\n4\n");
+        assert!(Checkpoint::<Sha256>::verify_note(&tampered, &key).is_err());
+        let renamed = NamedKey::new("other", 7, KeyedHashSigner([1, 2, 3, 4]));
+        assert!(Checkpoint::<Sha256>::verify_note(&note, &renamed).is_err());
+    }
+
+    fn witness_line(
+        checkpoint: &Checkpoint<Sha256>,
+        witness: &NamedKey<KeyedHashSigner>,
+    ) -> String {
+        let note = checkpoint.sign_note(witness).unwrap();
+        note[note.find("\n\n").unwrap() + 2..].to_owned()
+    }
+
+    #[test]
+    fn test_notes_need_a_single_line_origin() {
+        let key = NamedKey::new("log", 7, KeyedHashSigner([1, 2, 3, 4]));
+        let root = Sha256::hash("root".as_bytes());
+        for origin in ["", "example.com/log\n4"] {
+            let checkpoint = Checkpoint::<Sha256>::new(3, root).with_origin(origin);
+            assert_eq!(
+                checkpoint.to_note().unwrap_err().kind(),
+                &TreeErrorKind::InvalidEncoding
+            );
+            assert!(checkpoint.sign_note(&key).is_err());
+        }
+    }
+
+    #[test]
+    fn test_origin_and_timestamp_are_signed() {
+        let root = Sha256::hash("root".as_bytes());
+        let plain = Checkpoint::<Sha256>::new(3, root);
+        let signed = Checkpoint::<Sha256>::new(3, root)
+            .with_origin("log")
+            .sign(&KeyedHashSigner([1, 2, 3, 4]))
+            .unwrap();
+
+        assert_ne!(plain.to_bytes(), signed.checkpoint().to_bytes());
+        assert_ne!(
+            Checkpoint::<Sha256>::new(3, root)
+                .with_timestamp(0)
+                .to_bytes(),
+            plain.to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_signed_checkpoint_verification() {
+        let checkpoint = Checkpoint::<Sha256>::new(3, Sha256::hash("root".as_bytes()));
+        let signed = checkpoint.sign(&KeyedHashSigner([1, 2, 3, 4])).unwrap();
+
+        assert!(signed.verify(&KeyedHashSigner([1, 2, 3, 4])));
+        assert!(!signed.verify(&KeyedHashSigner([4, 3, 2, 1])));
+    }
+}
@@ -0,0 +1,220 @@
+//! Verified streaming of large byte sources, in the spirit of Bao
+//!
+//! A source is split into fixed-size chunks and a tree is built over the chunk hashes. Anyone
+//! holding the root can then verify an arbitrary byte range from the chunks covering it and a
+//! [`SliceProof`], so a download can be resumed from any offset and checked as it arrives.
+
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::proof_bundle::CompressedProofBundle;
+use std::io::{ErrorKind, Read};
+use std::ops::Range;
+
+/// Tree over the chunks of a byte source; the chunks themselves are not kept
+pub struct ChunkedTree<T: ToHash> {
+    chunk_size: usize,
+    byte_len: u64,
+    tree: MerkleTree<T>,
+}
+
+/// Proof for the chunks covering a byte range of a [`ChunkedTree`]
+pub struct SliceProof<T: ToHash> {
+    chunk_size: usize,
+    first_chunk: usize,
+    bundle: CompressedProofBundle<T>,
+}
+
+impl<T: ToHash> ChunkedTree<T> {
+    /// Reads `source` to the end in chunks of `chunk_size` bytes, hashing every chunk
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::chunker::ChunkedTree;
+    /// use merkle_tree::Sha256;
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let file: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+    ///     let tree = ChunkedTree::<Sha256>::from_reader(file.as_slice(), 1024)?;
+    ///     let root = tree.root_hash()?;
+    ///
+    ///     // the receiver asks for bytes 3000..3500 and gets the chunks covering them
+    ///     let proof = tree.slice_proof(3000..3500)?;
+    ///     let covering = &file[proof.byte_range()];
+    ///     let bytes = proof.verify_range(root, covering, 3000..3500)?;
+    ///     assert_eq!(bytes, &file[3000..3500]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_reader(mut source: impl Read, chunk_size: usize) -> TreeResult<Self> {
+        if chunk_size == 0 {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let mut tree = MerkleTree::new();
+        let mut byte_len = 0;
+        let mut chunk = vec![0; chunk_size];
+        loop {
+            let read = read_chunk(&mut source, &mut chunk)?;
+            if read == 0 {
+                break;
+            }
+            tree.append(T::try_hash(&chunk[..read])?);
+            byte_len += read as u64;
+            if read < chunk_size {
+                break;
+            }
+        }
+
+        Ok(Self {
+            chunk_size,
+            byte_len,
+            tree,
+        })
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Total number of bytes read from the source
+    pub fn byte_len(&self) -> u64 {
+        self.byte_len
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.tree.leaf_count()
+    }
+
+    pub fn root_hash(&self) -> TreeResult<T::Hash> {
+        self.tree.root_hash()
+    }
+
+    /// Creates the proof for the chunks covering the byte range
+    pub fn slice_proof(&self, range: Range<u64>) -> TreeResult<SliceProof<T>> {
+        if range.start >= range.end || range.end > self.byte_len {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let chunk_size = self.chunk_size as u64;
+        let first_chunk = (range.start / chunk_size) as usize;
+        let last_chunk = ((range.end - 1) / chunk_size) as usize;
+        let proofs = (first_chunk..=last_chunk)
+            .map(|chunk| Ok((chunk, self.tree.get_proof_at(chunk)?)))
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        Ok(SliceProof {
+            chunk_size: self.chunk_size,
+            first_chunk,
+            bundle: CompressedProofBundle::compress(&proofs),
+        })
+    }
+}
+
+impl<T: ToHash> SliceProof<T> {
+    /// Byte range of the source that has to be supplied alongside the proof
+    pub fn byte_range(&self) -> Range<usize> {
+        let start = self.first_chunk * self.chunk_size;
+        start..start + self.bundle.len() * self.chunk_size
+    }
+
+    /// Verifies the covering chunks against the root
+    ///
+    /// `covering` may end early when the last chunk is the end of the source.
+    pub fn verify(&self, root_hash: T::Hash, covering: &[u8]) -> TreeResult<()> {
+        let chunks: Vec<&[u8]> = covering.chunks(self.chunk_size).collect();
+        if chunks.len() != self.bundle.len() {
+            return Err(TreeError::proof_length_mismatch(
+                "covering bytes do not match the chunks of the proof",
+            ));
+        }
+
+        let leaves = chunks
+            .iter()
+            .map(|chunk| Ok(T::try_hash(chunk)?))
+            .collect::<TreeResult<Vec<_>>>()?;
+        match self
+            .bundle
+            .validate_all(root_hash, &leaves)
+            .into_iter()
+            .all(|is_valid| is_valid)
+        {
+            true => Ok(()),
+            false => Err(TreeError::proof_invalid()),
+        }
+    }
+
+    /// Verifies the covering chunks and returns the bytes of `range` within them
+    pub fn verify_range<'a>(
+        &self,
+        root_hash: T::Hash,
+        covering: &'a [u8],
+        range: Range<u64>,
+    ) -> TreeResult<&'a [u8]> {
+        self.verify(root_hash, covering)?;
+
+        let offset = self.byte_range().start as u64;
+        if range.start < offset || range.end < range.start {
+            return Err(TreeError::index_out_of_bounds());
+        }
+        covering
+            .get((range.start - offset) as usize..(range.end - offset) as usize)
+            .ok_or_else(TreeError::index_out_of_bounds)
+    }
+}
+
+/// Fills `chunk` from the source, returning fewer bytes only at the end of the source
+pub(crate) fn read_chunk(source: &mut impl Read, chunk: &mut [u8]) -> TreeResult<usize> {
+    let mut read = 0;
+    while read < chunk.len() {
+        match source.read(&mut chunk[read..]) {
+            Ok(0) => break,
+            Ok(count) => read += count,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => {
+                return Err(TreeError::storage_failed("failed to read source").with_source(error))
+            }
+        }
+    }
+
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+
+    #[test]
+    fn test_every_range_verifies_and_tampering_fails() {
+        let file: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+        let tree = ChunkedTree::<Sha256>::from_reader(file.as_slice(), 64).unwrap();
+        let root = tree.root_hash().unwrap();
+        assert_eq!(tree.chunk_count(), 16);
+        assert_eq!(tree.byte_len(), 1000);
+
+        for range in [0..1, 63..65, 100..900, 990..1000, 0..1000] {
+            let proof = tree.slice_proof(range.clone()).unwrap();
+            let covering = &file[proof.byte_range().start..proof.byte_range().end.min(1000)];
+            let bytes = proof.verify_range(root, covering, range.clone()).unwrap();
+            assert_eq!(bytes, &file[range.start as usize..range.end as usize]);
+        }
+
+        let proof = tree.slice_proof(100..200).unwrap();
+        let mut covering = file[proof.byte_range()].to_vec();
+        covering[5] ^= 1;
+        assert_eq!(
+            proof.verify(root, &covering).unwrap_err().kind(),
+            &TreeErrorKind::ProofInvalid
+        );
+        assert_eq!(
+            proof.verify(root, &covering[..64]).unwrap_err().kind(),
+            &TreeErrorKind::ProofLengthMismatch
+        );
+        assert_eq!(
+            tree.slice_proof(900..1001).err().unwrap().kind(),
+            &TreeErrorKind::IndexOutOfBounds
+        );
+    }
+}
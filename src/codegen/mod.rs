@@ -0,0 +1,2 @@
+mod solidity_verifier;
+pub use solidity_verifier::{solidity_verifier, SolidityHash, SolidityVerifierOptions};
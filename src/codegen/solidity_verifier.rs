@@ -0,0 +1,232 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::hash::{DomainSeparated, Keccak256, Sha2_256};
+use crate::merkle_tree::TreeResult;
+use crate::tree_policy::{PairOrdering, TreePolicy};
+
+const WORD_LEN: usize = 32;
+
+/// Hashers with a native Solidity counterpart
+pub trait SolidityHash: ToHash {
+    /// Name of the built-in Solidity function computing the same hash
+    const SOLIDITY_FUNCTION: &'static str;
+    /// Arguments of `abi.encodePacked` placed before the two children of a node
+    const NODE_PREFIX: &'static str = "";
+}
+
+impl SolidityHash for Keccak256 {
+    const SOLIDITY_FUNCTION: &'static str = "keccak256";
+}
+
+impl SolidityHash for Sha2_256 {
+    const SOLIDITY_FUNCTION: &'static str = "sha256";
+}
+
+impl SolidityHash for DomainSeparated<Keccak256> {
+    const SOLIDITY_FUNCTION: &'static str = "keccak256";
+    const NODE_PREFIX: &'static str = "bytes1(0x01), ";
+}
+
+impl SolidityHash for DomainSeparated<Sha2_256> {
+    const SOLIDITY_FUNCTION: &'static str = "sha256";
+    const NODE_PREFIX: &'static str = "bytes1(0x01), ";
+}
+
+pub struct SolidityVerifierOptions {
+    pub contract_name: String,
+    pub license: String,
+    pub pragma: String,
+}
+
+impl Default for SolidityVerifierOptions {
+    fn default() -> Self {
+        Self {
+            contract_name: "MerkleVerifier".to_owned(),
+            license: "MIT".to_owned(),
+            pragma: "^0.8.0".to_owned(),
+        }
+    }
+}
+
+/// Emits a Solidity contract verifying proofs against roots of a `MerkleTree<T>` built with
+/// `policy`
+///
+/// Nodes are hashed as `T` hashes them, domain separation included, and ordered as the
+/// policy's [`PairOrdering`] orders them: sorted and ascending trees take the sibling hashes
+/// of [`MerkleTree::get_proof_at`](crate::MerkleTree::get_proof_at), positional trees also
+/// take the sides of [`MerkleProof::positions`](crate::MerkleProof::positions), `true` for
+/// a sibling on the left. Odd nodes need no special handling, as proofs already carry the
+/// siblings the policy pairs them with. Fails with `HashSizeMismatch` for hashes that do not
+/// fit a `bytes32`.
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::codegen::{solidity_verifier, SolidityVerifierOptions};
+/// use merkle_tree::{DomainSeparated, Keccak256, PairOrdering, TreePolicy};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let policy = TreePolicy {
+///         pair_ordering: PairOrdering::Positional,
+///         ..TreePolicy::default()
+///     };
+///     let contract = solidity_verifier::<DomainSeparated<Keccak256>>(
+///         policy,
+///         SolidityVerifierOptions {
+///             contract_name: "AirdropVerifier".to_owned(),
+///             ..Default::default()
+///         },
+///     )?;
+///     assert!(contract.contains("contract AirdropVerifier"));
+///     assert!(contract.contains("bool[] calldata siblingIsLeft"));
+///
+///     Ok(())
+/// }
+/// ```
+pub fn solidity_verifier<T: SolidityHash>(
+    policy: TreePolicy,
+    options: SolidityVerifierOptions,
+) -> TreeResult<String> {
+    if T::HASH_LEN != WORD_LEN {
+        return Err(TreeError::hash_size_mismatch(
+            "solidity verifiers require 32-byte hashes",
+        ));
+    }
+
+    let (ordering, proof_args, args, fold, pair) = match policy.pair_ordering {
+        PairOrdering::Sorted => (
+            "sorted pairs, the greater hash first",
+            "bytes32[] calldata proof",
+            "proof",
+            "hashPair(computed, proof[i])",
+            Some("a <= b ? hashNode(b, a) : hashNode(a, b)"),
+        ),
+        PairOrdering::Ascending => (
+            "sorted pairs, the lesser hash first",
+            "bytes32[] calldata proof",
+            "proof",
+            "hashPair(computed, proof[i])",
+            Some("a <= b ? hashNode(a, b) : hashNode(b, a)"),
+        ),
+        PairOrdering::Positional => (
+            "positional pairs",
+            "bytes32[] calldata proof, bool[] calldata siblingIsLeft",
+            "proof, siblingIsLeft",
+            "siblingIsLeft[i] ? hashNode(proof[i], computed) : hashNode(computed, proof[i])",
+            None,
+        ),
+    };
+    let (check, pair) = match pair {
+        None => ("proof.length == siblingIsLeft.length && ", String::new()),
+        Some(pair) => (
+            "",
+            format!(
+                "\n    function hashPair(bytes32 a, bytes32 b) internal pure returns (bytes32) {{\n        return {pair};\n    }}\n"
+            ),
+        ),
+    };
+
+    Ok(format!(
+        r#"// SPDX-License-Identifier: {license}
+pragma solidity {pragma};
+
+/// Verifies proofs produced by the `merkle_tree` crate using {hash} with {ordering}
+contract {name} {{
+    function verify({proof_args}, bytes32 root, bytes32 leaf) external pure returns (bool) {{
+        return {check}processProof({args}, leaf) == root;
+    }}
+
+    function processProof({proof_args}, bytes32 leaf) public pure returns (bytes32 computed) {{
+        computed = leaf;
+        for (uint256 i = 0; i < proof.length; i++) {{
+            computed = {fold};
+        }}
+    }}
+{pair}
+    function hashNode(bytes32 left, bytes32 right) internal pure returns (bytes32) {{
+        return {hash}(abi.encodePacked({prefix}left, right));
+    }}
+}}
+"#,
+        license = options.license,
+        pragma = options.pragma,
+        name = options.contract_name,
+        hash = T::SOLIDITY_FUNCTION,
+        prefix = T::NODE_PREFIX,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+
+    struct Wide;
+
+    impl ToHash for Wide {
+        type Hash = [u8; 64];
+        const ALGORITHM: &'static str = "wide";
+
+        fn hash(value: &[u8]) -> Self::Hash {
+            let mut hash = [0; 64];
+            hash[..32].copy_from_slice(&Sha256::hash(value));
+            hash
+        }
+    }
+
+    impl SolidityHash for Wide {
+        const SOLIDITY_FUNCTION: &'static str = "wide";
+    }
+
+    fn policy(pair_ordering: PairOrdering) -> TreePolicy {
+        TreePolicy {
+            pair_ordering,
+            ..TreePolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_solidity_verifier_uses_options_and_hash() {
+        let contract = solidity_verifier::<Keccak256>(
+            TreePolicy::default(),
+            SolidityVerifierOptions {
+                contract_name: "RootVerifier".to_owned(),
+                license: "Apache-2.0".to_owned(),
+                pragma: "0.8.20".to_owned(),
+            },
+        )
+        .unwrap();
+
+        assert!(
+            contract.starts_with("// SPDX-License-Identifier: Apache-2.0\npragma solidity 0.8.20;")
+        );
+        assert!(contract.contains("contract RootVerifier {"));
+        assert!(contract.contains("a <= b ? hashNode(b, a) : hashNode(a, b)"));
+        assert!(contract.contains("keccak256(abi.encodePacked(left, right))"));
+    }
+
+    #[test]
+    fn test_solidity_verifier_follows_policy_and_hasher() {
+        let options = SolidityVerifierOptions::default;
+        let ascending =
+            solidity_verifier::<Sha2_256>(policy(PairOrdering::Ascending), options()).unwrap();
+        assert!(ascending.contains("a <= b ? hashNode(a, b) : hashNode(b, a)"));
+        assert!(ascending.contains("sha256(abi.encodePacked(left, right))"));
+
+        let positional = solidity_verifier::<DomainSeparated<Keccak256>>(
+            policy(PairOrdering::Positional),
+            options(),
+        )
+        .unwrap();
+        assert!(positional.contains("proof.length == siblingIsLeft.length && "));
+        assert!(positional.contains("siblingIsLeft[i] ? hashNode(proof[i], computed)"));
+        assert!(positional.contains("keccak256(abi.encodePacked(bytes1(0x01), left, right))"));
+        assert!(!positional.contains("hashPair"));
+
+        assert_eq!(
+            solidity_verifier::<Wide>(TreePolicy::default(), options())
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::HashSizeMismatch
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::MerkleTree;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+/// Collects leaves appended from many threads at once and finalizes them into a [`MerkleTree`]
+///
+/// Appends are spread round-robin over sharded buffers, so producers rarely contend for the
+/// same lock. Arrival order across threads is not deterministic, so the leaves are sorted by
+/// their bytes when finalizing: the same set of leaves always builds the same tree.
+pub struct ConcurrentTreeBuilder<T: ToHash> {
+    shards: Vec<Mutex<Vec<T::Hash>>>,
+    next_shard: AtomicUsize,
+}
+
+impl<T: ToHash> ConcurrentTreeBuilder<T> {
+    /// Creates a builder with one shard per available core
+    pub fn new() -> Self {
+        Self::with_shards(thread::available_parallelism().map_or(1, usize::from))
+    }
+
+    pub fn with_shards(shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| Mutex::new(Vec::new())).collect(),
+            next_shard: AtomicUsize::new(0),
+        }
+    }
+
+    /// Appends a leaf; safe to call from any number of threads through a shared reference
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{ConcurrentTreeBuilder, Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let builder = ConcurrentTreeBuilder::<Sha256>::new();
+    ///     std::thread::scope(|scope| {
+    ///         for worker in 0..4u8 {
+    ///             let builder = &builder;
+    ///             scope.spawn(move || builder.append(Sha256::hash(&[worker])));
+    ///         }
+    ///     });
+    ///
+    ///     let mut leaves: Vec<_> = (0..4u8).map(|i| Sha256::hash(&[i])).collect();
+    ///     leaves.sort();
+    ///     assert_eq!(builder.finalize().root_hash()?, Sha256Tree::from_leaves(&leaves).root_hash()?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn append(&self, leaf: T::Hash) {
+        self.shard().push(leaf);
+    }
+
+    /// Appends several leaves under a single lock
+    pub fn extend(&self, leaves: &[T::Hash]) {
+        self.shard().extend_from_slice(leaves);
+    }
+
+    /// Number of leaves appended so far
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Merges the shards, sorts the leaves by their bytes and builds the tree
+    pub fn finalize(self) -> MerkleTree<T> {
+        let mut leaves: Vec<T::Hash> = self
+            .shards
+            .into_iter()
+            .flat_map(|shard| shard.into_inner().unwrap_or_else(PoisonError::into_inner))
+            .collect();
+        leaves.sort_by(|left, right| left.as_ref().cmp(right.as_ref()));
+
+        MerkleTree::from_leaves(&leaves)
+    }
+
+    fn shard(&self) -> std::sync::MutexGuard<'_, Vec<T::Hash>> {
+        let shard = self.next_shard.fetch_add(1, Ordering::Relaxed) % self.shards.len();
+        self.shards[shard]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: ToHash> Default for ConcurrentTreeBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::Sha256;
+    use crate::{ConcurrentTreeBuilder, Sha256Tree, ToHash};
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_appends_are_deterministic() {
+        let build = || {
+            let builder = ConcurrentTreeBuilder::<Sha256>::with_shards(4);
+            thread::scope(|scope| {
+                for worker in 0..16u32 {
+                    let builder = &builder;
+                    scope.spawn(move || {
+                        for leaf in 0..64u32 {
+                            builder.append(Sha256::hash(&(worker * 64 + leaf).to_be_bytes()));
+                        }
+                    });
+                }
+            });
+            assert_eq!(builder.len(), 1024);
+            builder.finalize()
+        };
+
+        let mut leaves: Vec<_> = (0..1024u32)
+            .map(|leaf| Sha256::hash(&leaf.to_be_bytes()))
+            .collect();
+        leaves.sort();
+        let expected = Sha256Tree::from_leaves(&leaves).root_hash().unwrap();
+
+        assert_eq!(build().root_hash().unwrap(), expected);
+        assert_eq!(build().root_hash().unwrap(), expected);
+    }
+}
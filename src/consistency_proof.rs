@@ -0,0 +1,257 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+use crate::utils::ct;
+
+/// Proof that a tree of `old_size` leaves is a prefix of a tree of `new_size` leaves
+///
+/// Trees that promote the last odd node of a level have the same shape as RFC 6962 (split
+/// at the largest power of two below the size), so the RFC 9162 algorithms apply. Proofs
+/// combine pairs sorted, so only trees with the default [`TreePolicy`](crate::TreePolicy)
+/// produce them.
+pub struct ConsistencyProof<T: ToHash> {
+    proof: Vec<T::Hash>,
+}
+
+impl<T: ToHash> ConsistencyProof<T> {
+    pub fn new(proof: Vec<T::Hash>) -> Self {
+        Self { proof }
+    }
+
+    pub fn proof(&self) -> &[T::Hash] {
+        &self.proof
+    }
+
+    /// Validates that `old_root` (over `old_size` leaves) is a prefix of `new_root`
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{ConsistencyProof, Sha256, Sha256Tree, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut tree = Sha256Tree::new();
+    ///     for leaf in ["0", "1", "2"] {
+    ///         tree.append(Sha256::hash(leaf.as_bytes()));
+    ///     }
+    ///     let old_root = tree.root_hash()?;
+    ///     for leaf in ["3", "4", "5", "6"] {
+    ///         tree.append(Sha256::hash(leaf.as_bytes()));
+    ///     }
+    ///
+    ///     let proof = ConsistencyProof::<Sha256>::new(tree.consistency_proof(3)?);
+    ///     assert!(proof.validate(3, 7, old_root, tree.root_hash()?));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn validate(
+        &self,
+        old_size: usize,
+        new_size: usize,
+        old_root: T::Hash,
+        new_root: T::Hash,
+    ) -> bool {
+        self.reduce_proof(old_size, new_size, old_root)
+            .map(|(old, new)| {
+                ct::eq(old.as_ref(), old_root.as_ref()) & ct::eq(new.as_ref(), new_root.as_ref())
+            })
+            .unwrap_or(false)
+    }
+
+    fn reduce_proof(
+        &self,
+        old_size: usize,
+        new_size: usize,
+        old_root: T::Hash,
+    ) -> TreeResult<(T::Hash, T::Hash)> {
+        if old_size == 0 || old_size > new_size {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        if old_size == new_size {
+            return match self.proof.is_empty() {
+                true => Ok((old_root, old_root)),
+                false => Err(TreeError::proof_malformed(
+                    "proof between equal sizes must be empty",
+                )),
+            };
+        }
+
+        let mut path = self.proof.iter().copied();
+        let first = match old_size.is_power_of_two() {
+            true => old_root,
+            false => path.next().ok_or_else(TreeError::proof_empty)?,
+        };
+
+        let mut old_node = old_size - 1;
+        let mut new_node = new_size - 1;
+        while old_node & 1 == 1 {
+            old_node >>= 1;
+            new_node >>= 1;
+        }
+
+        let (mut old_hash, mut new_hash) = (first, first);
+        for sibling in path {
+            if new_node == 0 {
+                return Err(TreeError::proof_malformed("consistency proof is too long"));
+            }
+
+            if old_node & 1 == 1 || old_node == new_node {
+                old_hash = T::try_combine(sibling, old_hash)?;
+                new_hash = T::try_combine(sibling, new_hash)?;
+                while old_node & 1 == 0 && old_node != 0 {
+                    old_node >>= 1;
+                    new_node >>= 1;
+                }
+            } else {
+                new_hash = T::try_combine(new_hash, sibling)?;
+            }
+            old_node >>= 1;
+            new_node >>= 1;
+        }
+
+        match new_node {
+            0 => Ok((old_hash, new_hash)),
+            _ => Err(TreeError::proof_malformed("consistency proof is too short")),
+        }
+    }
+}
+
+/// RFC 6962 `SUBPROOF` over the given leaves
+pub(crate) fn consistency_path<T: ToHash>(
+    leaves: &[T::Hash],
+    old_size: usize,
+    complete: bool,
+) -> TreeResult<Vec<T::Hash>> {
+    let size = leaves.len();
+    if old_size == size {
+        return match complete {
+            true => Ok(Vec::new()),
+            false => Ok(vec![subtree_root::<T>(leaves)?]),
+        };
+    }
+
+    let split = largest_power_of_two_below(size);
+    let (left, right) = leaves.split_at(split);
+    let mut path = match old_size <= split {
+        true => consistency_path::<T>(left, old_size, complete)?,
+        false => consistency_path::<T>(right, old_size - split, false)?,
+    };
+    path.push(match old_size <= split {
+        true => subtree_root::<T>(right)?,
+        false => subtree_root::<T>(left)?,
+    });
+
+    Ok(path)
+}
+
+/// Root of a tree built over exactly the given leaves
+pub(crate) fn subtree_root<T: ToHash>(leaves: &[T::Hash]) -> TreeResult<T::Hash> {
+    match leaves.len() {
+        0 => Err(TreeError::tree_empty()),
+        1 => Ok(leaves[0]),
+        size => {
+            let (left, right) = leaves.split_at(largest_power_of_two_below(size));
+            Ok(T::try_combine(
+                subtree_root::<T>(left)?,
+                subtree_root::<T>(right)?,
+            )?)
+        }
+    }
+}
+
+/// Largest power of two strictly below `size`, which must be at least 2
+pub(crate) fn largest_power_of_two_below(size: usize) -> usize {
+    let mut split = 1;
+    while split << 1 < size {
+        split <<= 1;
+    }
+
+    split
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::tree_policy::{OddLeafPolicy, PairOrdering, TreePolicy};
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_consistency_between_every_size() {
+        let leaves_raw = ["0", "1", "2", "3", "4", "5", "6", "7", "8"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        for new_size in 1..=leaves.len() {
+            let new_tree = Sha256Tree::from_leaves(&leaves[..new_size]);
+            let new_root = new_tree.root_hash().unwrap();
+            assert_eq!(
+                subtree_root::<Sha256>(&leaves[..new_size]).unwrap(),
+                new_root
+            );
+
+            for old_size in 1..=new_size {
+                let old_root = Sha256Tree::from_leaves(&leaves[..old_size])
+                    .root_hash()
+                    .unwrap();
+                let proof =
+                    ConsistencyProof::<Sha256>::new(new_tree.consistency_proof(old_size).unwrap());
+
+                assert!(proof.validate(old_size, new_size, old_root, new_root));
+                assert!(
+                    !proof.validate(old_size, new_size, new_root, old_root) || old_size == new_size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_largest_power_of_two_below() {
+        assert_eq!(largest_power_of_two_below(2), 1);
+        assert_eq!(largest_power_of_two_below(5), 4);
+        assert_eq!(largest_power_of_two_below(8), 4);
+        assert_eq!(largest_power_of_two_below(9), 8);
+    }
+
+    #[test]
+    fn test_consistency_proofs_follow_the_tree_policy() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3", "4", "5", "6"]);
+
+        for pair_ordering in [
+            PairOrdering::Sorted,
+            PairOrdering::Positional,
+            PairOrdering::Ascending,
+        ] {
+            for odd_leaf in [
+                OddLeafPolicy::Promote,
+                OddLeafPolicy::DuplicateLast,
+                OddLeafPolicy::PadWithZeroHash,
+            ] {
+                let policy = TreePolicy {
+                    pair_ordering,
+                    odd_leaf,
+                    ..TreePolicy::default()
+                };
+                let old_tree = Sha256Tree::from_leaves(&leaves[..3]).with_policy(policy);
+                let new_tree = Sha256Tree::from_leaves(&leaves).with_policy(policy);
+
+                match new_tree.consistency_proof(3) {
+                    Ok(proof) => {
+                        assert_eq!(policy, TreePolicy::default());
+                        assert!(ConsistencyProof::<Sha256>::new(proof).validate(
+                            3,
+                            7,
+                            old_tree.root_hash().unwrap(),
+                            new_tree.root_hash().unwrap()
+                        ));
+                    }
+                    Err(error) => {
+                        assert_ne!(policy, TreePolicy::default());
+                        assert_eq!(error.kind(), &TreeErrorKind::ProofMalformed);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+
+/// Merkle tree committing to the same leaves under two hash algorithms at once
+pub struct DualHashTree<A: ToHash, B: ToHash> {
+    leaves: Vec<(A::Hash, B::Hash)>,
+}
+
+impl<A: ToHash, B: ToHash> DualHashTree<A, B> {
+    pub fn new() -> Self {
+        Self { leaves: Vec::new() }
+    }
+
+    pub fn from_values(values: &[&[u8]]) -> TreeResult<Self> {
+        let mut tree = Self::new();
+        for value in values {
+            tree.append(value)?;
+        }
+
+        Ok(tree)
+    }
+
+    /// Hashes the value under both algorithms and appends the resulting leaves
+    pub fn append(&mut self, value: &[u8]) -> TreeResult<()> {
+        self.leaves.push((A::try_hash(value)?, B::try_hash(value)?));
+        Ok(())
+    }
+
+    /// Creates the root hash under both algorithms in a single pass over the leaves
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{DualHashTree, Keccak256, Sha256, Sha256Tree, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let values: [&[u8]; 3] = [b"0", b"1", b"2"];
+    ///     let tree = DualHashTree::<Sha256, Keccak256>::from_values(&values)?;
+    ///     let (sha_root, _keccak_root) = tree.root_hashes()?;
+    ///
+    ///     let leaves: Vec<_> = values.iter().map(|value| Sha256::hash(value)).collect();
+    ///     assert_eq!(sha_root, Sha256Tree::from_leaves(&leaves).root_hash()?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn root_hashes(&self) -> TreeResult<(A::Hash, B::Hash)> {
+        if self.leaves.is_empty() {
+            return Err(TreeError::tree_empty());
+        }
+
+        let mut level = self.leaves.clone();
+        while level.len() > 1 {
+            let mut next_level = Vec::with_capacity(level.len().div_ceil(2));
+            let pairs = level.chunks_exact(2);
+            let odd = pairs.remainder().first().copied();
+            for pair in pairs {
+                let [(left_a, left_b), (right_a, right_b)] = [pair[0], pair[1]];
+                next_level.push((
+                    A::try_combine(left_a, right_a)?,
+                    B::try_combine(left_b, right_b)?,
+                ));
+            }
+            next_level.extend(odd);
+            level = next_level;
+        }
+
+        Ok(level[0])
+    }
+
+    /// Splits the dual tree into independent trees, e.g. for generating proofs per algorithm
+    pub fn to_trees(&self) -> (MerkleTree<A>, MerkleTree<B>) {
+        let (leaves_a, leaves_b): (Vec<A::Hash>, Vec<B::Hash>) =
+            self.leaves.iter().copied().unzip();
+
+        (
+            MerkleTree::from_leaves(&leaves_a),
+            MerkleTree::from_leaves(&leaves_b),
+        )
+    }
+}
+
+impl<A: ToHash, B: ToHash> Default for DualHashTree<A, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::{Keccak256, Sha256};
+    use crate::utils::test::{full_root_hash, raw_leaves_to_hashed_leaves};
+
+    #[test]
+    fn test_empty_dual_tree_returns_error() {
+        let tree = DualHashTree::<Sha256, Keccak256>::new();
+        assert_eq!(
+            tree.root_hashes().unwrap_err().kind(),
+            &TreeErrorKind::TreeEmpty
+        );
+    }
+
+    #[test]
+    fn test_dual_root_hashes_match_single_trees() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let values: Vec<&[u8]> = leaves_raw.iter().map(|leaf| leaf.as_bytes()).collect();
+        let tree = DualHashTree::<Sha256, Keccak256>::from_values(&values).unwrap();
+
+        let (sha_root, keccak_root) = tree.root_hashes().unwrap();
+        assert_eq!(
+            sha_root,
+            full_root_hash::<Sha256>(&raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw))
+        );
+        assert_eq!(
+            keccak_root,
+            full_root_hash::<Keccak256>(&raw_leaves_to_hashed_leaves::<Keccak256>(&leaves_raw))
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::hash::{Keccak256, Sha256, Sha512};
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::tree_policy::TreePolicy;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Hash algorithm of a [`DynMerkleTree`], e.g. parsed from a configuration file or request
+///
+/// Names are the [`ToHash::ALGORITHM`] of the matching hasher: `"keccak256"`, `"sha3-256"` and
+/// `"sha3-512"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HashAlgorithm {
+    #[cfg_attr(feature = "serde", serde(rename = "keccak256"))]
+    Keccak256,
+    #[cfg_attr(feature = "serde", serde(rename = "sha3-256"))]
+    Sha256,
+    #[cfg_attr(feature = "serde", serde(rename = "sha3-512"))]
+    Sha512,
+}
+
+macro_rules! dispatch {
+    ($algorithm:expr, $hasher:ident => $body:expr) => {
+        match $algorithm {
+            HashAlgorithm::Keccak256 => {
+                type $hasher = Keccak256;
+                $body
+            }
+            HashAlgorithm::Sha256 => {
+                type $hasher = Sha256;
+                $body
+            }
+            HashAlgorithm::Sha512 => {
+                type $hasher = Sha512;
+                $body
+            }
+        }
+    };
+}
+
+macro_rules! with_tree {
+    ($tree:expr, $inner:ident: $hasher:ident => $body:expr) => {
+        match $tree {
+            DynMerkleTree::Keccak256($inner) => {
+                type $hasher = Keccak256;
+                $body
+            }
+            DynMerkleTree::Sha256($inner) => {
+                type $hasher = Sha256;
+                $body
+            }
+            DynMerkleTree::Sha512($inner) => {
+                type $hasher = Sha512;
+                $body
+            }
+        }
+    };
+}
+
+impl HashAlgorithm {
+    pub const ALL: [HashAlgorithm; 3] = [Self::Keccak256, Self::Sha256, Self::Sha512];
+
+    pub fn name(self) -> &'static str {
+        dispatch!(self, H => H::ALGORITHM)
+    }
+
+    pub fn hash_len(self) -> usize {
+        dispatch!(self, H => H::hash_len())
+    }
+
+    /// Hashes raw data, e.g. to derive a leaf
+    pub fn hash(self, data: &[u8]) -> TreeResult<Vec<u8>> {
+        dispatch!(self, H => Ok(H::try_hash(data)?.into()))
+    }
+
+    /// Validates a proof serialized with [`MerkleProof::to_bytes`]
+    pub fn validate(self, root: &[u8], leaf: &[u8], proof: &[u8]) -> TreeResult<bool> {
+        dispatch!(self, H => {
+            let proof = MerkleProof::<H>::from_bytes(proof)?;
+            Ok(proof.validate(parse_hash(root)?, parse_hash(leaf)?))
+        })
+    }
+
+    /// Validates a proof serialized with [`MerkleProof::to_bytes`] for raw leaf data
+    pub fn validate_data(self, root: &[u8], data: &[u8], proof: &[u8]) -> TreeResult<bool> {
+        dispatch!(self, H => {
+            let proof = MerkleProof::<H>::from_bytes(proof)?;
+            Ok(proof.validate_data(parse_hash(root)?, data))
+        })
+    }
+
+    /// Checks that the bytes hold a proof of this algorithm serialized with
+    /// [`MerkleProof::to_bytes`]
+    pub fn check_proof(self, proof: &[u8]) -> TreeResult<()> {
+        dispatch!(self, H => MerkleProof::<H>::from_bytes(proof).map(|_| ()))
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = TreeError;
+
+    fn from_str(name: &str) -> TreeResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name() == name)
+            .ok_or_else(TreeError::unknown_algorithm)
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Tree whose hash algorithm is chosen at runtime, with hashes and proofs passed as bytes
+///
+/// Each variant wraps the [`MerkleTree`] of one algorithm, so services supporting several
+/// algorithms can hold their trees in one collection. Proofs use the compact format of
+/// [`MerkleProof::to_bytes`], with the sibling positions of trees whose [`TreePolicy`] needs
+/// them, and are checked with [`HashAlgorithm::validate`].
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::{DynMerkleTree, HashAlgorithm};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let algorithm: HashAlgorithm = "keccak256".parse()?;
+///     let mut tree = DynMerkleTree::new(algorithm);
+///     for data in ["alice", "bob", "carol"] {
+///         tree.append_data(data.as_bytes())?;
+///     }
+///
+///     let root = tree.root_hash()?;
+///     let proof = tree.get_proof_at(1)?;
+///     assert!(algorithm.validate(&root, &algorithm.hash(b"bob")?, &proof)?);
+///
+///     Ok(())
+/// }
+/// ```
+pub enum DynMerkleTree {
+    Keccak256(MerkleTree<Keccak256>),
+    Sha256(MerkleTree<Sha256>),
+    Sha512(MerkleTree<Sha512>),
+}
+
+impl DynMerkleTree {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        Self::with_policy(algorithm, TreePolicy::default())
+    }
+
+    /// Empty tree pairing and promoting its nodes by `policy`
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{DynMerkleTree, HashAlgorithm, PairOrdering, TreePolicy};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let policy = TreePolicy {
+    ///         pair_ordering: PairOrdering::Positional,
+    ///         ..TreePolicy::default()
+    ///     };
+    ///     let mut tree = DynMerkleTree::with_policy(HashAlgorithm::Sha256, policy);
+    ///     for data in ["alice", "bob", "carol"] {
+    ///         tree.append_data(data.as_bytes())?;
+    ///     }
+    ///
+    ///     let proof = tree.get_proof_at(2)?;
+    ///     assert!(HashAlgorithm::Sha256.validate_data(&tree.root_hash()?, b"carol", &proof)?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_policy(algorithm: HashAlgorithm, policy: TreePolicy) -> Self {
+        match algorithm {
+            HashAlgorithm::Keccak256 => Self::Keccak256(MerkleTree::new().with_policy(policy)),
+            HashAlgorithm::Sha256 => Self::Sha256(MerkleTree::new().with_policy(policy)),
+            HashAlgorithm::Sha512 => Self::Sha512(MerkleTree::new().with_policy(policy)),
+        }
+    }
+
+    /// Builds a tree over already hashed leaves, which must all be as long as the algorithm's
+    /// hashes
+    pub fn from_leaves(algorithm: HashAlgorithm, leaves: &[impl AsRef<[u8]>]) -> TreeResult<Self> {
+        let mut tree = Self::new(algorithm);
+        for leaf in leaves {
+            tree.append(leaf.as_ref())?;
+        }
+
+        Ok(tree)
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self {
+            Self::Keccak256(_) => HashAlgorithm::Keccak256,
+            Self::Sha256(_) => HashAlgorithm::Sha256,
+            Self::Sha512(_) => HashAlgorithm::Sha512,
+        }
+    }
+
+    pub fn policy(&self) -> TreePolicy {
+        with_tree!(self, tree: _H => tree.policy())
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        with_tree!(self, tree: _H => tree.leaf_count())
+    }
+
+    pub fn contains(&self, leaf: &[u8]) -> bool {
+        with_tree!(self, tree: _H => parse_hash(leaf).is_ok_and(|leaf| tree.contains(&leaf)))
+    }
+
+    /// Appends an already hashed leaf
+    ///
+    /// Fails with `HashSizeMismatch` when the leaf is not as long as the algorithm's hashes.
+    pub fn append(&mut self, leaf: &[u8]) -> TreeResult<()> {
+        with_tree!(self, tree: _H => {
+            tree.try_append(parse_hash(leaf)?)?;
+            Ok(())
+        })
+    }
+
+    /// Hashes raw data with the tree's algorithm and appends it, returning the leaf hash
+    pub fn append_data(&mut self, data: &[u8]) -> TreeResult<Vec<u8>> {
+        let leaf = self.algorithm().hash(data)?;
+        self.append(&leaf)?;
+        Ok(leaf)
+    }
+
+    pub fn root_hash(&self) -> TreeResult<Vec<u8>> {
+        with_tree!(self, tree: _H => Ok(tree.root_hash()?.into()))
+    }
+
+    /// Proof for the first occurrence of the leaf, serialized with [`MerkleProof::to_bytes`]
+    pub fn get_proof(&self, leaf: &[u8]) -> TreeResult<Vec<u8>> {
+        with_tree!(self, tree: _H => {
+            let index = tree
+                .index_of(&parse_hash(leaf)?)
+                .ok_or_else(TreeError::leaf_not_found)?;
+            Ok(tree.merkle_proof_at(index)?.to_bytes())
+        })
+    }
+
+    /// Proof for the leaf at `index`, serialized with [`MerkleProof::to_bytes`]
+    pub fn get_proof_at(&self, index: usize) -> TreeResult<Vec<u8>> {
+        with_tree!(self, tree: _H => Ok(tree.merkle_proof_at(index)?.to_bytes()))
+    }
+}
+
+fn parse_hash<H>(bytes: &[u8]) -> TreeResult<H>
+where
+    H: for<'a> TryFrom<&'a [u8]>,
+{
+    H::try_from(bytes)
+        .map_err(|_| TreeError::hash_size_mismatch("hash length does not match the hasher"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::tree_policy::{OddLeafPolicy, PairOrdering};
+
+    #[test]
+    fn test_dyn_tree_round_trip() {
+        for algorithm in HashAlgorithm::ALL {
+            let leaves: Vec<Vec<u8>> = ["0", "1", "2"]
+                .iter()
+                .map(|leaf| algorithm.hash(leaf.as_bytes()).unwrap())
+                .collect();
+            let tree = DynMerkleTree::from_leaves(algorithm, &leaves).unwrap();
+
+            let root = tree.root_hash().unwrap();
+            let proof = tree.get_proof(&leaves[2]).unwrap();
+            assert_eq!(tree.algorithm(), algorithm);
+            assert_eq!(root.len(), algorithm.hash_len());
+            assert_eq!(proof, tree.get_proof_at(2).unwrap());
+            assert!(algorithm.validate(&root, &leaves[2], &proof).unwrap());
+            assert!(!algorithm.validate(&root, &leaves[1], &proof).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_dyn_tree_matches_typed_tree() {
+        let mut tree = DynMerkleTree::new("sha3-512".parse().unwrap());
+        let leaf = tree.append_data(b"alice").unwrap();
+
+        let typed = MerkleTree::<Sha512>::from_leaves(&[Sha512::hash(b"alice")]);
+        assert!(tree.contains(&leaf));
+        assert_eq!(tree.root_hash().unwrap(), typed.root_hash().unwrap());
+    }
+
+    #[test]
+    fn test_dyn_tree_rejects_unknown_input() {
+        assert_eq!(
+            "md5".parse::<HashAlgorithm>().unwrap_err().kind(),
+            &TreeErrorKind::UnknownAlgorithm
+        );
+        assert_eq!(
+            DynMerkleTree::new(HashAlgorithm::Sha256)
+                .append(&[0; 31])
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::HashSizeMismatch
+        );
+        assert_eq!(HashAlgorithm::Keccak256.to_string(), "keccak256");
+        assert!(HashAlgorithm::Sha256.check_proof(&[0xff]).is_err());
+    }
+
+    #[test]
+    fn test_dyn_tree_follows_its_policy() {
+        let policy = TreePolicy {
+            pair_ordering: PairOrdering::Positional,
+            odd_leaf: OddLeafPolicy::DuplicateLast,
+            ..TreePolicy::default()
+        };
+        let leaves: Vec<_> = (0..5u8).map(|i| Keccak256::hash(&[i])).collect();
+        let tree = DynMerkleTree::from_leaves(HashAlgorithm::Keccak256, &leaves).unwrap();
+        let mut positional = DynMerkleTree::with_policy(HashAlgorithm::Keccak256, policy);
+        for leaf in &leaves {
+            positional.append(leaf).unwrap();
+        }
+
+        let typed = MerkleTree::<Keccak256>::from_leaves(&leaves).with_policy(policy);
+        let root = positional.root_hash().unwrap();
+        assert_eq!(positional.policy(), policy);
+        assert_eq!(root, typed.root_hash().unwrap());
+        assert_ne!(root, tree.root_hash().unwrap());
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = positional.get_proof_at(index).unwrap();
+            assert_eq!(proof, typed.merkle_proof_at(index).unwrap().to_bytes());
+            assert!(HashAlgorithm::Keccak256.check_proof(&proof).is_ok());
+            assert!(HashAlgorithm::Keccak256
+                .validate(&root, leaf, &proof)
+                .unwrap());
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result};
+
+/// Failure reported by a fallible hashing backend (e.g. an HSM or FFI hasher)
+#[derive(Debug)]
+pub struct HashError {
+    message: String,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl Error for HashError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+impl Display for HashError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl HashError {
+    pub fn new(message: &str) -> Self {
+        Self {
+            message: message.to_owned(),
+            source: None,
+        }
+    }
+
+    pub fn with_source(message: &str, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self {
+            message: message.to_owned(),
+            source: Some(source.into()),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
@@ -1 +1,3 @@
+pub mod hash_error;
+pub mod proof_error;
 pub mod tree_error;
@@ -0,0 +1,86 @@
+use crate::error::tree_error::{TreeError, TreeErrorKind};
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result};
+
+/// Reason a proof was rejected by [`MerkleProof::validate_detailed`](crate::MerkleProof::validate_detailed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProofErrorKind {
+    /// The proof has no siblings and the leaf is not the root
+    Empty,
+    /// The proof could not be folded, e.g. because it does not fit the hasher
+    Malformed,
+    /// The hasher failed while folding the proof
+    HashFailed,
+    /// The proof folded to a root other than the expected one
+    RootMismatch,
+}
+
+#[derive(Debug)]
+pub struct ProofError {
+    kind: ProofErrorKind,
+    message: String,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl Error for ProofError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+impl From<TreeError> for ProofError {
+    fn from(error: TreeError) -> Self {
+        let kind = match error.kind() {
+            TreeErrorKind::HashFailed => ProofErrorKind::HashFailed,
+            _ => ProofErrorKind::Malformed,
+        };
+
+        Self::new(kind, "Proof could not be folded into a root").with_source(error)
+    }
+}
+
+impl Display for ProofError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{:?}: {}", self.kind, self.message)
+    }
+}
+
+impl ProofError {
+    pub fn new(kind: ProofErrorKind, message: &str) -> Self {
+        Self {
+            kind,
+            message: message.to_owned(),
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn kind(&self) -> &ProofErrorKind {
+        &self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn empty() -> Self {
+        Self::new(
+            ProofErrorKind::Empty,
+            "Proof is empty and the leaf is not the root",
+        )
+    }
+
+    pub fn root_mismatch() -> Self {
+        Self::new(
+            ProofErrorKind::RootMismatch,
+            "Proof leads to a different root",
+        )
+    }
+}
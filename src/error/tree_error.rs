@@ -1,21 +1,54 @@
+use crate::error::hash_error::HashError;
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result};
 
-#[derive(Debug, PartialEq)]
+/// Category of a [`TreeError`]
+///
+/// New kinds are added as the crate grows, so matches outside the crate need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum TreeErrorKind {
     TreeEmpty,
     LeafEmpty,
     PathLeafEmpty,
     ProofEmpty,
+    ProofMalformed,
+    ProofLengthMismatch,
+    IndexOutOfBounds,
+    HashSizeMismatch,
+    InvalidEncoding,
+    TransformFailed,
+    HashFailed,
+    UnknownAlgorithm,
+    LeafNotFound,
+    LeafDuplicated,
+    ProofInvalid,
+    StorageFailed,
+    VersionUnknown,
+    VersionCommitted,
+    ArityInvalid,
 }
 
 #[derive(Debug)]
 pub struct TreeError {
     kind: TreeErrorKind,
     message: String,
+    source: Option<Box<dyn Error + Send + Sync>>,
 }
 
-impl Error for TreeError {}
+impl Error for TreeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+impl From<HashError> for TreeError {
+    fn from(error: HashError) -> Self {
+        Self::new(TreeErrorKind::HashFailed, "Hasher failed to produce a hash").with_source(error)
+    }
+}
 
 impl Display for TreeError {
     fn fmt(&self, f: &mut Formatter) -> Result {
@@ -28,9 +61,15 @@ impl TreeError {
         Self {
             kind,
             message: message.to_owned(),
+            source: None,
         }
     }
 
+    pub fn with_source(mut self, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
     pub fn kind(&self) -> &TreeErrorKind {
         &self.kind
     }
@@ -63,4 +102,81 @@ impl TreeError {
     pub fn proof_empty() -> Self {
         Self::new(TreeErrorKind::ProofEmpty, "proof is empty")
     }
+
+    pub fn proof_malformed(message: &str) -> Self {
+        Self::new(TreeErrorKind::ProofMalformed, message)
+    }
+
+    pub fn proof_length_mismatch(message: &str) -> Self {
+        Self::new(TreeErrorKind::ProofLengthMismatch, message)
+    }
+
+    pub fn index_out_of_bounds() -> Self {
+        Self::new(
+            TreeErrorKind::IndexOutOfBounds,
+            "Leaf index is outside of the tree",
+        )
+    }
+
+    pub fn hash_size_mismatch(message: &str) -> Self {
+        Self::new(TreeErrorKind::HashSizeMismatch, message)
+    }
+
+    pub fn invalid_encoding(message: &str) -> Self {
+        Self::new(TreeErrorKind::InvalidEncoding, message)
+    }
+
+    pub fn transform_failed(message: &str) -> Self {
+        Self::new(TreeErrorKind::TransformFailed, message)
+    }
+
+    pub fn unknown_algorithm() -> Self {
+        Self::new(
+            TreeErrorKind::UnknownAlgorithm,
+            "Hash algorithm is not supported",
+        )
+    }
+
+    pub fn leaf_not_found() -> Self {
+        Self::new(TreeErrorKind::LeafNotFound, "Leaf is not part of the tree")
+    }
+
+    pub fn leaf_duplicated() -> Self {
+        Self::new(
+            TreeErrorKind::LeafDuplicated,
+            "Leaf is already part of the tree",
+        )
+    }
+
+    pub fn proof_invalid() -> Self {
+        Self::new(
+            TreeErrorKind::ProofInvalid,
+            "Proof does not lead to a known node of the tree",
+        )
+    }
+
+    pub fn storage_failed(message: &str) -> Self {
+        Self::new(TreeErrorKind::StorageFailed, message)
+    }
+
+    pub fn version_unknown() -> Self {
+        Self::new(
+            TreeErrorKind::VersionUnknown,
+            "Version is newer than the tree",
+        )
+    }
+
+    pub fn version_committed() -> Self {
+        Self::new(
+            TreeErrorKind::VersionCommitted,
+            "Version is already committed",
+        )
+    }
+
+    pub fn arity_invalid() -> Self {
+        Self::new(
+            TreeErrorKind::ArityInvalid,
+            "Tree nodes need at least two children",
+        )
+    }
 }
@@ -0,0 +1,175 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// Half-open range of epochs `[not_before, not_after)` in which a leaf is considered valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidityWindow {
+    pub not_before: u64,
+    pub not_after: u64,
+}
+
+impl ValidityWindow {
+    pub fn new(not_before: u64, not_after: u64) -> Self {
+        Self {
+            not_before,
+            not_after,
+        }
+    }
+
+    pub fn contains(&self, epoch: u64) -> bool {
+        self.not_before <= epoch && epoch < self.not_after
+    }
+}
+
+/// Merkle tree whose leaves carry a validity window, emitting one root per epoch
+///
+/// The set of valid leaves only changes on window boundaries, so a tree is built once per
+/// segment between two boundaries, on first use, and keeps its root and levels for every
+/// epoch within that segment. Appending only drops the trees of the segments the new
+/// leaf's window covers.
+pub struct ExpiringTree<T: ToHash> {
+    leaves: Vec<(T::Hash, ValidityWindow)>,
+    segments: BTreeMap<u64, OnceLock<MerkleTree<T>>>,
+}
+
+impl<T: ToHash> ExpiringTree<T> {
+    pub fn new() -> Self {
+        Self {
+            leaves: Vec::new(),
+            segments: BTreeMap::new(),
+        }
+    }
+
+    /// Appends a leaf valid within the given window
+    pub fn append(&mut self, leaf: T::Hash, window: ValidityWindow) {
+        self.leaves.push((leaf, window));
+        // a new boundary splits a segment without changing the leaves on either side
+        self.segments.entry(window.not_before).or_default();
+        self.segments.entry(window.not_after).or_default();
+        for tree in self
+            .segments
+            .range_mut(window.not_before..window.not_after)
+            .map(|(_, tree)| tree)
+        {
+            *tree = OnceLock::new();
+        }
+    }
+
+    /// Leaves valid at the given epoch, in insertion order
+    pub fn leaves_at(&self, epoch: u64) -> Vec<T::Hash> {
+        self.leaves
+            .iter()
+            .filter(|(_, window)| window.contains(epoch))
+            .map(|(leaf, _)| *leaf)
+            .collect()
+    }
+
+    /// Creates the root hash over the leaves valid at the given epoch
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{ExpiringTree, Sha256, Sha256Tree, ToHash, ValidityWindow};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves = [Sha256::hash("0".as_bytes()), Sha256::hash("1".as_bytes())];
+    ///     let mut tree = ExpiringTree::<Sha256>::new();
+    ///     tree.append(leaves[0], ValidityWindow::new(0, 10));
+    ///     tree.append(leaves[1], ValidityWindow::new(0, 5));
+    ///
+    ///     assert_eq!(tree.root_at(3)?, Sha256Tree::from_leaves(&leaves).root_hash()?);
+    ///     assert_eq!(tree.root_at(7)?, leaves[0]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn root_at(&self, epoch: u64) -> TreeResult<T::Hash> {
+        self.tree_at(epoch)?.root_hash()
+    }
+
+    /// Creates a proof for the leaf against the root of the given epoch
+    pub fn get_proof_at(&self, leaf: T::Hash, epoch: u64) -> TreeResult<Vec<T::Hash>> {
+        self.tree_at(epoch)?.get_proof(leaf)
+    }
+
+    /// Tree over the leaves valid at the given epoch, shared by its whole segment
+    fn tree_at(&self, epoch: u64) -> TreeResult<&MerkleTree<T>> {
+        let (_, tree) = self
+            .segments
+            .range(..=epoch)
+            .next_back()
+            .ok_or_else(TreeError::tree_empty)?;
+
+        Ok(tree.get_or_init(|| MerkleTree::from_leaves(&self.leaves_at(epoch))))
+    }
+}
+
+impl<T: ToHash> Default for ExpiringTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::{full_root_hash, raw_leaves_to_hashed_leaves};
+    use crate::Sha256Proof;
+
+    #[test]
+    fn test_expired_leaves_are_excluded_from_root() {
+        let leaves_raw = ["0", "1", "2", "3", "4", "5"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let mut tree = ExpiringTree::<Sha256>::new();
+        for leaf in &leaves[..5] {
+            tree.append(*leaf, ValidityWindow::new(0, 100));
+        }
+        tree.append(leaves[5], ValidityWindow::new(10, 20));
+
+        assert_eq!(tree.root_at(5).unwrap(), full_root_hash::<Sha256>(&leaves));
+        assert_eq!(tree.root_at(50).unwrap(), full_root_hash::<Sha256>(&leaves));
+        assert_ne!(tree.root_at(15).unwrap(), full_root_hash::<Sha256>(&leaves));
+        assert_eq!(
+            tree.root_at(100).unwrap_err().kind(),
+            &TreeErrorKind::TreeEmpty
+        );
+    }
+
+    #[test]
+    fn test_appends_only_drop_the_covered_segments() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2"]);
+        let mut tree = ExpiringTree::<Sha256>::new();
+        tree.append(leaves[0], ValidityWindow::new(0, 10));
+        tree.append(leaves[1], ValidityWindow::new(0, 20));
+        let (early, late) = (tree.root_at(5).unwrap(), tree.root_at(15).unwrap());
+
+        tree.append(leaves[2], ValidityWindow::new(12, 14));
+        assert!(tree.segments[&0].get().is_some());
+        assert_eq!(tree.root_at(5).unwrap(), early);
+        assert_eq!(tree.root_at(15).unwrap(), late);
+        assert_eq!(
+            tree.root_at(13).unwrap(),
+            Sha256::combine(leaves[1], leaves[2])
+        );
+        assert_eq!(tree.root_at(16).unwrap(), leaves[1]);
+    }
+
+    #[test]
+    fn test_proof_at_epoch() {
+        let leaves_raw = ["0", "1", "2"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let mut tree = ExpiringTree::<Sha256>::new();
+        tree.append(leaves[0], ValidityWindow::new(0, 10));
+        tree.append(leaves[1], ValidityWindow::new(5, 10));
+        tree.append(leaves[2], ValidityWindow::new(0, 10));
+
+        let root = tree.root_at(2).unwrap();
+        let proof = Sha256Proof::new(tree.get_proof_at(leaves[2], 2).unwrap());
+        assert!(proof.validate(root, leaves[2]));
+    }
+}
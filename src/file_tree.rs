@@ -0,0 +1,228 @@
+use crate::error::hash_error::HashError;
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use rayon::prelude::*;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::ops::Deref;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+impl<T: ToHash> MerkleTree<T>
+where
+    T::Hash: Send,
+{
+    /// Builds a tree over the chunks of a file, hashing them in parallel
+    ///
+    /// The file is read in batches of chunks of `chunk_size` bytes, each batch being hashed
+    /// across the rayon thread pool while at most one batch is held in memory; the last chunk
+    /// is shorter when the size is not a multiple of `chunk_size`. The leaves are those of
+    /// [`ChunkedTree::from_reader`](crate::chunker::ChunkedTree::from_reader) and
+    /// [`TreeWriter`](crate::TreeWriter) over the same bytes.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256, Sha256Tree, TreeWriter};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("merkle_tree_from_file_doctest");
+    ///     let file: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+    ///     std::fs::write(&path, &file)?;
+    ///
+    ///     let tree = Sha256Tree::from_file(&path, 1024)?;
+    ///     let mut writer = TreeWriter::<Sha256>::new(1024)?;
+    ///     std::io::copy(&mut file.as_slice(), &mut writer)?;
+    ///     assert_eq!(tree.root_hash()?, writer.finalize()?);
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>, chunk_size: usize) -> TreeResult<Self> {
+        if chunk_size == 0 {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let mut file = open(path)?;
+        let batch_len = rayon::current_num_threads() * 4;
+        let mut leaves = Vec::new();
+        let mut finished = false;
+        while !finished {
+            let mut batch = Vec::with_capacity(batch_len);
+            while !finished && batch.len() < batch_len {
+                let mut chunk = Vec::with_capacity(chunk_size);
+                (&mut file)
+                    .take(chunk_size as u64)
+                    .read_to_end(&mut chunk)
+                    .map_err(|error| {
+                        TreeError::storage_failed("failed to read file").with_source(error)
+                    })?;
+                finished = chunk.len() < chunk_size;
+                if !chunk.is_empty() {
+                    batch.push(chunk);
+                }
+            }
+
+            leaves.extend(
+                batch
+                    .par_iter()
+                    .map(|chunk| T::try_hash(chunk))
+                    .collect::<Result<Vec<_>, HashError>>()?,
+            );
+        }
+
+        Ok(Self::from_leaves(&leaves))
+    }
+
+    /// Builds the tree of [`MerkleTree::from_file`] over a memory mapping of the file
+    ///
+    /// The chunks are hashed straight from the mapping, without copying them into buffers.
+    ///
+    /// # Safety
+    ///
+    /// No process may write to or truncate the file until this returns. A write changes the
+    /// bytes under the hasher, which observes memory changing behind a shared reference, and
+    /// reading a truncated part of the mapping raises `SIGBUS`.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::Sha256Tree;
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let path = std::env::temp_dir().join("merkle_tree_from_file_mapped_doctest");
+    ///     std::fs::write(&path, vec![7u8; 10_000])?;
+    ///
+    ///     // SAFETY: the file is private to this example and left alone while it is hashed
+    ///     let tree = unsafe { Sha256Tree::from_file_mapped(&path, 1024)? };
+    ///     assert_eq!(tree.root_hash()?, Sha256Tree::from_file(&path, 1024)?.root_hash()?);
+    ///
+    ///     std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub unsafe fn from_file_mapped(path: impl AsRef<Path>, chunk_size: usize) -> TreeResult<Self> {
+        if chunk_size == 0 {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let mapped = Mmap::map(&open(path)?)?;
+        let leaves = mapped
+            .par_chunks(chunk_size)
+            .map(T::try_hash)
+            .collect::<Result<Vec<_>, HashError>>()?;
+
+        Ok(Self::from_leaves(&leaves))
+    }
+}
+
+fn open(path: impl AsRef<Path>) -> TreeResult<File> {
+    File::open(path)
+        .map_err(|error| TreeError::storage_failed("failed to open file").with_source(error))
+}
+
+/// Read-only private mapping of a whole file, unmapped on drop
+///
+/// Only sound while the file is left unchanged, see [`MerkleTree::from_file_mapped`].
+struct Mmap {
+    address: *mut libc::c_void,
+    len: usize,
+}
+
+// the mapping is never written through, so sharing it is sharing a `&[u8]`
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    fn map(file: &File) -> TreeResult<Self> {
+        let len = file
+            .metadata()
+            .map_err(|error| {
+                TreeError::storage_failed("failed to read file size").with_source(error)
+            })?
+            .len() as usize;
+        // mapping zero bytes is an error, and there is nothing to map anyway
+        if len == 0 {
+            return Ok(Self {
+                address: std::ptr::null_mut(),
+                len,
+            });
+        }
+
+        // SAFETY: a fresh read-only mapping of an open file, checked for failure below
+        let address = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if address == libc::MAP_FAILED {
+            return Err(TreeError::storage_failed("failed to map file")
+                .with_source(io::Error::last_os_error()));
+        }
+
+        Ok(Self { address, len })
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self.len {
+            0 => &[],
+            // SAFETY: `address` maps `len` readable bytes until the mapping is dropped
+            len => unsafe { std::slice::from_raw_parts(self.address as *const u8, len) },
+        }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            // SAFETY: unmaps exactly the mapping created in `map`, which nothing borrows anymore
+            unsafe {
+                libc::munmap(self.address, self.len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunker::ChunkedTree;
+    use crate::hash::Sha256;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_file_trees_match_the_chunker() {
+        let directory = std::env::temp_dir();
+        for (name, len) in [
+            ("empty", 0u32),
+            ("partial", 1000),
+            ("aligned", 1024),
+            ("batched", 50_000),
+        ] {
+            let path = directory.join(format!("merkle_tree_from_file_{name}"));
+            let file: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            std::fs::write(&path, &file).unwrap();
+
+            let tree = Sha256Tree::from_file(&path, 64).unwrap();
+            // SAFETY: the file is private to this test and left alone while it is hashed
+            let mapped = unsafe { Sha256Tree::from_file_mapped(&path, 64) }.unwrap();
+            assert_eq!(tree.root_hash().ok(), mapped.root_hash().ok());
+            let expected = ChunkedTree::<Sha256>::from_reader(file.as_slice(), 64).unwrap();
+            assert_eq!(tree.leaf_count(), expected.chunk_count());
+            assert_eq!(tree.root_hash().ok(), expected.root_hash().ok());
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        assert!(Sha256Tree::from_file(directory.join("merkle_tree_missing_file"), 64).is_err());
+        assert!(Sha256Tree::from_file(&directory, 0).is_err());
+        // SAFETY: nothing is mapped when the chunk size is refused
+        assert!(unsafe { Sha256Tree::from_file_mapped(directory, 0) }.is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::error::hash_error::HashError;
+
+/// Hashes a batch of interior nodes in one call
+///
+/// Trees submit the pairs of a whole level through [`ToHash::try_hash_nodes`], so a hasher
+/// can route them to a backend that hashes several pairs at once (SIMD lanes, a GPU, an
+/// HSM taking batches) instead of paying for one digest setup per node. Each parent must be
+/// the hash of `left || right`, as [`ToHash::try_hash_node`] computes it.
+///
+/// [`ToHash::try_hash_nodes`]: crate::ToHash::try_hash_nodes
+/// [`ToHash::try_hash_node`]: crate::ToHash::try_hash_node
+pub trait HashBackend {
+    type Hash;
+
+    /// Appends the parent of every `(left, right)` pair to `parents`, in order
+    fn hash_nodes(
+        pairs: &[(Self::Hash, Self::Hash)],
+        parents: &mut Vec<Self::Hash>,
+    ) -> Result<(), HashError>;
+}
+
+#[cfg(feature = "simd")]
+pub use self::lanes::{KeccakLanes, Sha3Lanes};
+
+#[cfg(feature = "simd")]
+mod lanes {
+    use super::HashBackend;
+    use crate::error::hash_error::HashError;
+
+    /// Pairs hashed together, one per lane
+    const LANES: usize = 4;
+    /// Words absorbed per block by 256-bit Keccak
+    const RATE_WORDS: usize = 17;
+
+    const ROUND_CONSTANTS: [u64; 24] = [
+        0x0000000000000001,
+        0x0000000000008082,
+        0x800000000000808a,
+        0x8000000080008000,
+        0x000000000000808b,
+        0x0000000080000001,
+        0x8000000080008081,
+        0x8000000000008009,
+        0x000000000000008a,
+        0x0000000000000088,
+        0x0000000080008009,
+        0x000000008000000a,
+        0x000000008000808b,
+        0x800000000000008b,
+        0x8000000000008089,
+        0x8000000000008003,
+        0x8000000000008002,
+        0x8000000000000080,
+        0x000000000000800a,
+        0x800000008000000a,
+        0x8000000080008081,
+        0x8000000000008080,
+        0x0000000080000001,
+        0x8000000080008008,
+    ];
+    const ROTATIONS: [u32; 24] = [
+        1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+    ];
+    const PI: [usize; 24] = [
+        10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+    ];
+
+    type Lanes = [u64; LANES];
+
+    /// Keccak256 over several 64-byte nodes at once, as used by Ethereum
+    pub struct KeccakLanes;
+
+    /// SHA3-256 over several 64-byte nodes at once
+    pub struct Sha3Lanes;
+
+    impl HashBackend for KeccakLanes {
+        type Hash = [u8; 32];
+
+        fn hash_nodes(
+            pairs: &[([u8; 32], [u8; 32])],
+            parents: &mut Vec<[u8; 32]>,
+        ) -> Result<(), HashError> {
+            hash_nodes(pairs, 0x01, parents);
+            Ok(())
+        }
+    }
+
+    impl HashBackend for Sha3Lanes {
+        type Hash = [u8; 32];
+
+        fn hash_nodes(
+            pairs: &[([u8; 32], [u8; 32])],
+            parents: &mut Vec<[u8; 32]>,
+        ) -> Result<(), HashError> {
+            hash_nodes(pairs, 0x06, parents);
+            Ok(())
+        }
+    }
+
+    /// Hashes `left || right` of every pair, the 64 bytes fitting in a single padded block
+    fn hash_nodes(pairs: &[([u8; 32], [u8; 32])], padding: u8, parents: &mut Vec<[u8; 32]>) {
+        parents.reserve(pairs.len());
+        for batch in pairs.chunks(LANES) {
+            let mut state = [[0u64; LANES]; 25];
+            for (lane, (left, right)) in batch.iter().enumerate() {
+                for (word, bytes) in left
+                    .chunks_exact(8)
+                    .chain(right.chunks_exact(8))
+                    .enumerate()
+                {
+                    state[word][lane] = u64::from_le_bytes(bytes.try_into().unwrap());
+                }
+            }
+            state[8] = [padding as u64; LANES];
+            state[RATE_WORDS - 1] = [0x80 << 56; LANES];
+
+            keccak_f(&mut state);
+
+            parents.extend((0..batch.len()).map(|lane| {
+                let mut parent = [0u8; 32];
+                for (word, bytes) in parent.chunks_exact_mut(8).enumerate() {
+                    bytes.copy_from_slice(&state[word][lane].to_le_bytes());
+                }
+                parent
+            }));
+        }
+    }
+
+    /// Keccak-f[1600] applied to every lane, written lane-wise so it vectorizes
+    fn keccak_f(state: &mut [Lanes; 25]) {
+        for round_constant in ROUND_CONSTANTS {
+            let mut columns = [[0u64; LANES]; 5];
+            for (x, column) in columns.iter_mut().enumerate() {
+                for lane in 0..LANES {
+                    column[lane] = state[x][lane]
+                        ^ state[x + 5][lane]
+                        ^ state[x + 10][lane]
+                        ^ state[x + 15][lane]
+                        ^ state[x + 20][lane];
+                }
+            }
+            for x in 0..5 {
+                for lane in 0..LANES {
+                    let theta =
+                        columns[(x + 4) % 5][lane] ^ columns[(x + 1) % 5][lane].rotate_left(1);
+                    for y in (0..25).step_by(5) {
+                        state[y + x][lane] ^= theta;
+                    }
+                }
+            }
+
+            let mut current = state[1];
+            for (position, rotation) in PI.into_iter().zip(ROTATIONS) {
+                let next = state[position];
+                for lane in 0..LANES {
+                    state[position][lane] = current[lane].rotate_left(rotation);
+                }
+                current = next;
+            }
+
+            for y in (0..25).step_by(5) {
+                let row = [
+                    state[y],
+                    state[y + 1],
+                    state[y + 2],
+                    state[y + 3],
+                    state[y + 4],
+                ];
+                for x in 0..5 {
+                    for lane in 0..LANES {
+                        state[y + x][lane] =
+                            row[x][lane] ^ (!row[(x + 1) % 5][lane] & row[(x + 2) % 5][lane]);
+                    }
+                }
+            }
+
+            for word in &mut state[0] {
+                *word ^= round_constant;
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::hash::to_hash::ToHash;
+        use crate::hash::{Keccak256, Sha256};
+
+        #[test]
+        fn test_lanes_match_the_scalar_hashers() {
+            let pairs: Vec<_> = (0..7u8)
+                .map(|i| (Sha256::hash(&[i]), Keccak256::hash(&[i])))
+                .collect();
+
+            let mut keccak = Vec::new();
+            KeccakLanes::hash_nodes(&pairs, &mut keccak).unwrap();
+            let mut sha3 = Vec::new();
+            Sha3Lanes::hash_nodes(&pairs, &mut sha3).unwrap();
+
+            for (index, (left, right)) in pairs.iter().enumerate() {
+                let node = [&left[..], &right[..]].concat();
+                assert_eq!(keccak[index], Keccak256::hash(&node));
+                assert_eq!(sha3[index], Sha256::hash(&node));
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+use crate::utils::hex;
+
+/// Byte order in which hashes are exchanged with the outside world
+///
+/// Hashing always operates on the natural digest order. Some ecosystems present digests
+/// reversed (e.g. Bitcoin txids and block hashes are displayed back to front), so values
+/// crossing the boundary are converted with [`ByteOrder::apply`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteOrder {
+    #[default]
+    Natural,
+    Reversed,
+}
+
+impl ByteOrder {
+    /// Converts a hash between natural order and this order (the conversion is symmetric)
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{ByteOrder, Sha2_256, ToHash};
+    ///
+    /// let hash = Sha2_256::hash("0".as_bytes());
+    /// let reversed = ByteOrder::Reversed.apply::<Sha2_256>(hash);
+    /// assert_eq!(reversed[0], hash[31]);
+    /// assert_eq!(ByteOrder::Reversed.apply::<Sha2_256>(reversed), hash);
+    /// ```
+    pub fn apply<T: ToHash>(self, hash: T::Hash) -> T::Hash {
+        match self {
+            Self::Natural => hash,
+            Self::Reversed => {
+                let mut bytes = hash.as_ref().to_vec();
+                bytes.reverse();
+                T::Hash::try_from(bytes.as_slice())
+                    .unwrap_or_else(|_| unreachable!("reversing keeps the hash length"))
+            }
+        }
+    }
+
+    /// Hashes the value and returns the digest in this order
+    pub fn hash<T: ToHash>(self, value: &[u8]) -> T::Hash {
+        self.apply::<T>(T::hash(value))
+    }
+
+    /// Renders a natural-order hash as hex in this order
+    pub fn to_hex<T: ToHash>(self, hash: T::Hash) -> String {
+        hex::encode(self.apply::<T>(hash).as_ref())
+    }
+
+    /// Parses hex given in this order into a natural-order hash
+    pub fn from_hex<T: ToHash>(self, hash: &str) -> TreeResult<T::Hash> {
+        let bytes = hex::decode(hash).ok_or_else(|| TreeError::invalid_encoding("invalid hex"))?;
+        let hash = T::Hash::try_from(bytes.as_slice())
+            .map_err(|_| TreeError::hash_size_mismatch("hash length does not match the hasher"))?;
+
+        Ok(self.apply::<T>(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha2_256;
+
+    #[test]
+    fn test_reversed_hex_round_trip() {
+        // bitcoin genesis block hash, as displayed
+        let displayed = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+        let hash = ByteOrder::Reversed.from_hex::<Sha2_256>(displayed).unwrap();
+
+        assert_eq!(hash[0], 0x6f);
+        assert_eq!(ByteOrder::Reversed.to_hex::<Sha2_256>(hash), displayed);
+        assert_eq!(
+            ByteOrder::Natural.to_hex::<Sha2_256>(hash),
+            hex::encode(&hash)
+        );
+    }
+}
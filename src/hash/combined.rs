@@ -0,0 +1,231 @@
+use crate::error::hash_error::HashError;
+use crate::hash::to_hash::ToHash;
+use std::io::Read;
+use std::marker::PhantomData;
+
+/// Encoding of two children into the preimage of their parent
+///
+/// Which child comes first is decided by the tree's
+/// [`PairOrdering`](crate::PairOrdering); a strategy only decides how the ordered pair is
+/// laid out before hashing, so trees can match external systems with [`Combined`] instead
+/// of a hand-written [`ToHash`] that shadows the hash.
+pub trait CombineStrategy {
+    /// Name of the encoding, reported by [`ToHash::node_encoding`]
+    fn name() -> String;
+
+    fn hash_node<H: ToHash>(left: H::Hash, right: H::Hash) -> Result<H::Hash, HashError>;
+
+    /// Appends the parent of every pair to `parents`, see [`ToHash::try_hash_nodes`]
+    fn hash_nodes<H: ToHash>(
+        pairs: &[(H::Hash, H::Hash)],
+        parents: &mut Vec<H::Hash>,
+    ) -> Result<(), HashError> {
+        parents.reserve(pairs.len());
+        for (left, right) in pairs {
+            parents.push(Self::hash_node::<H>(*left, *right)?);
+        }
+
+        Ok(())
+    }
+}
+
+/// `H(left || right)`, the encoding of the hasher itself
+pub struct Concat;
+
+/// `H(PREFIX || left || right)`
+pub struct Prefixed<const PREFIX: u8>;
+
+/// `H(len(left) || left || len(right) || right)`, lengths as u64 big-endian
+pub struct LengthPrefixed;
+
+impl CombineStrategy for Concat {
+    fn name() -> String {
+        "concat".to_owned()
+    }
+
+    fn hash_node<H: ToHash>(left: H::Hash, right: H::Hash) -> Result<H::Hash, HashError> {
+        H::try_hash_node(left, right)
+    }
+
+    fn hash_nodes<H: ToHash>(
+        pairs: &[(H::Hash, H::Hash)],
+        parents: &mut Vec<H::Hash>,
+    ) -> Result<(), HashError> {
+        H::try_hash_nodes(pairs, parents)
+    }
+}
+
+impl<const PREFIX: u8> CombineStrategy for Prefixed<PREFIX> {
+    fn name() -> String {
+        format!("prefixed-{PREFIX:02x}")
+    }
+
+    fn hash_node<H: ToHash>(left: H::Hash, right: H::Hash) -> Result<H::Hash, HashError> {
+        H::try_hash(&[&[PREFIX], left.as_ref(), right.as_ref()].concat())
+    }
+}
+
+impl CombineStrategy for LengthPrefixed {
+    fn name() -> String {
+        "length-prefixed".to_owned()
+    }
+
+    fn hash_node<H: ToHash>(left: H::Hash, right: H::Hash) -> Result<H::Hash, HashError> {
+        let (left, right) = (left.as_ref(), right.as_ref());
+        H::try_hash(
+            &[
+                &(left.len() as u64).to_be_bytes(),
+                left,
+                &(right.len() as u64).to_be_bytes(),
+                right,
+            ]
+            .concat(),
+        )
+    }
+}
+
+/// Hasher `H` whose interior nodes are encoded by the strategy `C`
+///
+/// Leaves are hashed by `H` unchanged, so existing leaf hashes stay valid. Strategies hash
+/// nodes with the leaf hash of `H`, so wrapping a domain separated `H` fails to compile.
+/// [`DomainSeparated`](crate::DomainSeparated) and [`Truncated`](crate::Truncated) encode
+/// nodes themselves and would ignore `C`, so wrapping a `Combined` hasher in them fails to
+/// compile as well: build trees of `Combined` hashers with
+/// [`TreeBuilder::without_domain_separation`](crate::TreeBuilder::without_domain_separation).
+///
+/// ```compile_fail
+/// use merkle_tree::{Combined, LengthPrefixed, Sha256, TreeBuilder};
+/// // The default builder wraps the hasher in `DomainSeparated`
+/// let tree = TreeBuilder::<Combined<Sha256, LengthPrefixed>>::new()
+///     .leaf_data(b"a")
+///     .build();
+/// ```
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::{Combined, MerkleProof, MerkleTree, Prefixed, Sha256, ToHash};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     type Hasher = Combined<Sha256, Prefixed<0x01>>;
+///     let leaves: Vec<_> = ["a", "b", "c"].iter().map(|leaf| Hasher::hash(leaf.as_bytes())).collect();
+///     let tree = MerkleTree::<Hasher>::from_leaves(&leaves);
+///
+///     let proof = MerkleProof::<Hasher>::new(tree.get_proof_at(1)?);
+///     assert!(proof.validate(tree.root_hash()?, leaves[1]));
+///     assert_ne!(tree.root_hash()?, MerkleTree::<Sha256>::from_leaves(&leaves).root_hash()?);
+///
+///     Ok(())
+/// }
+/// ```
+pub struct Combined<H: ToHash, C: CombineStrategy> {
+    hasher: PhantomData<(H, C)>,
+}
+
+impl<H: ToHash, C: CombineStrategy> Combined<H, C> {
+    const NOT_SEPARATED: () = assert!(
+        !H::DOMAIN_SEPARATED,
+        "combine strategies hash nodes with the leaf hash of H, which must not be domain separated"
+    );
+}
+
+impl<H: ToHash, C: CombineStrategy> ToHash for Combined<H, C> {
+    type Hash = H::Hash;
+    const ALGORITHM: &'static str = H::ALGORITHM;
+    const HASH_LEN: usize = H::HASH_LEN;
+    const ENCODES_NODES: bool = true;
+
+    fn hash(value: &[u8]) -> Self::Hash {
+        let () = Self::NOT_SEPARATED;
+        H::hash(value)
+    }
+
+    fn node_encoding() -> String {
+        C::name()
+    }
+
+    fn hash_node(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        Self::try_hash_node(left, right)
+            .expect("hasher failed, use try_hash_node to handle failures")
+    }
+
+    fn try_hash(value: &[u8]) -> Result<Self::Hash, HashError> {
+        let () = Self::NOT_SEPARATED;
+        H::try_hash(value)
+    }
+
+    fn hash_reader(reader: impl Read) -> Result<Self::Hash, HashError> {
+        let () = Self::NOT_SEPARATED;
+        H::hash_reader(reader)
+    }
+
+    fn try_hash_node(left: Self::Hash, right: Self::Hash) -> Result<Self::Hash, HashError> {
+        let () = Self::NOT_SEPARATED;
+        C::hash_node::<H>(left, right)
+    }
+
+    fn try_hash_nodes(
+        pairs: &[(Self::Hash, Self::Hash)],
+        parents: &mut Vec<Self::Hash>,
+    ) -> Result<(), HashError> {
+        let () = Self::NOT_SEPARATED;
+        C::hash_nodes::<H>(pairs, parents)
+    }
+
+    fn hash_len() -> usize {
+        H::hash_len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha256;
+    use crate::tree_builder::TreeBuilder;
+
+    #[test]
+    fn test_strategies_encode_the_ordered_pair() {
+        let left = Sha256::hash(b"0");
+        let right = Sha256::hash(b"1");
+
+        assert_eq!(
+            Combined::<Sha256, Concat>::hash_node(left, right),
+            Sha256::hash_node(left, right)
+        );
+        assert_eq!(
+            Combined::<Sha256, Prefixed<0x01>>::hash_node(left, right),
+            Sha256::hash(&[&[0x01], &left[..], &right[..]].concat())
+        );
+
+        let mut preimage = 32u64.to_be_bytes().to_vec();
+        preimage.extend_from_slice(&left);
+        preimage.extend_from_slice(&32u64.to_be_bytes());
+        preimage.extend_from_slice(&right);
+        assert_eq!(
+            Combined::<Sha256, LengthPrefixed>::hash_node(left, right),
+            Sha256::hash(&preimage)
+        );
+
+        let (high, low) = match left <= right {
+            true => (right, left),
+            false => (left, right),
+        };
+        assert_eq!(
+            Combined::<Sha256, LengthPrefixed>::combine(left, right),
+            Combined::<Sha256, LengthPrefixed>::hash_node(high, low)
+        );
+    }
+
+    #[test]
+    fn test_builder_applies_strategies_without_domain_separation() {
+        type Hasher = Combined<Sha256, LengthPrefixed>;
+        let (a, b) = (Sha256::hash(b"a"), Sha256::hash(b"b"));
+        let tree = TreeBuilder::<Hasher>::new()
+            .without_domain_separation()
+            .leaf_data(b"a")
+            .leaf_data(b"b")
+            .build()
+            .unwrap();
+
+        assert_eq!(tree.root_hash().unwrap(), Hasher::combine(a, b));
+        assert_ne!(tree.root_hash().unwrap(), Sha256::combine(a, b));
+    }
+}
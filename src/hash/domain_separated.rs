@@ -0,0 +1,104 @@
+use crate::error::hash_error::HashError;
+use crate::hash::to_hash::ToHash;
+use std::io::Read;
+use std::marker::PhantomData;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hasher `H` with RFC 6962 style domain separation between leaves and interior nodes
+///
+/// Leaf data is hashed as `H(0x00 || data)` and interior nodes as `H(0x01 || left || right)`,
+/// so an interior node can never be presented as a leaf (second-preimage attack). Leaves
+/// must be hashed with this hasher rather than with `H` directly. Wrapping a hasher that
+/// encodes its own nodes, such as [`Combined`](crate::Combined), fails to compile.
+pub struct DomainSeparated<H: ToHash> {
+    hasher: PhantomData<H>,
+}
+
+impl<H: ToHash> DomainSeparated<H> {
+    const NODES_PLAIN: () = assert!(
+        !H::ENCODES_NODES,
+        "domain separation replaces the node encoding of H, which must hash nodes as left || right"
+    );
+}
+
+impl<H: ToHash> ToHash for DomainSeparated<H> {
+    type Hash = H::Hash;
+    const ALGORITHM: &'static str = H::ALGORITHM;
+    const DOMAIN_SEPARATED: bool = true;
+
+    fn hash(value: &[u8]) -> Self::Hash {
+        let () = Self::NODES_PLAIN;
+        H::hash(&[&[LEAF_PREFIX], value].concat())
+    }
+
+    fn node_encoding() -> String {
+        "rfc6962".to_owned()
+    }
+
+    fn hash_node(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        let () = Self::NODES_PLAIN;
+        H::hash(&[&[NODE_PREFIX], left.as_ref(), right.as_ref()].concat())
+    }
+
+    fn try_hash(value: &[u8]) -> Result<Self::Hash, HashError> {
+        let () = Self::NODES_PLAIN;
+        H::try_hash(&[&[LEAF_PREFIX], value].concat())
+    }
+
+    fn hash_reader(reader: impl Read) -> Result<Self::Hash, HashError> {
+        let () = Self::NODES_PLAIN;
+        H::hash_reader([LEAF_PREFIX].as_slice().chain(reader))
+    }
+
+    fn try_hash_node(left: Self::Hash, right: Self::Hash) -> Result<Self::Hash, HashError> {
+        let () = Self::NODES_PLAIN;
+        H::try_hash(&[&[NODE_PREFIX], left.as_ref(), right.as_ref()].concat())
+    }
+
+    fn try_hash_children(children: &[Self::Hash]) -> Result<Self::Hash, HashError> {
+        let () = Self::NODES_PLAIN;
+        let mut preimage = vec![NODE_PREFIX];
+        for child in children {
+            preimage.extend_from_slice(child.as_ref());
+        }
+
+        H::try_hash(&preimage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha256;
+
+    #[test]
+    fn test_leaves_and_nodes_use_distinct_prefixes() {
+        let left = Sha256::hash("0".as_bytes());
+        let right = Sha256::hash("1".as_bytes());
+        let concat = [left, right].concat();
+
+        assert_ne!(
+            DomainSeparated::<Sha256>::hash(&concat),
+            DomainSeparated::<Sha256>::hash_node(left, right)
+        );
+
+        let (high, low) = match left <= right {
+            true => (right, left),
+            false => (left, right),
+        };
+        assert_eq!(
+            DomainSeparated::<Sha256>::combine(left, right),
+            Sha256::hash(&[&[NODE_PREFIX][..], &high, &low].concat())
+        );
+    }
+
+    #[test]
+    fn test_hash_reader_prefixes_the_stream() {
+        assert_eq!(
+            DomainSeparated::<Sha256>::hash_reader(b"leaf".as_slice()).unwrap(),
+            DomainSeparated::<Sha256>::hash(b"leaf")
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use crate::error::hash_error::HashError;
+use crate::hash::to_hash::ToHash;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter, Result};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Longest digest a [`DynHash`] can hold, the width of SHA-512 and BLAKE2b
+pub const MAX_DYN_HASH_LEN: usize = 64;
+
+/// Digest whose length is only known at runtime, stored inline so it stays `Copy`
+#[derive(Clone, Copy)]
+pub struct DynHash {
+    bytes: [u8; MAX_DYN_HASH_LEN],
+    len: u8,
+}
+
+impl DynHash {
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl AsRef<[u8]> for DynHash {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len()]
+    }
+}
+
+impl TryFrom<&[u8]> for DynHash {
+    type Error = HashError;
+
+    fn try_from(value: &[u8]) -> std::result::Result<Self, Self::Error> {
+        if value.len() > MAX_DYN_HASH_LEN {
+            return Err(HashError::new("digest is longer than 64 bytes"));
+        }
+
+        let mut bytes = [0; MAX_DYN_HASH_LEN];
+        bytes[..value.len()].copy_from_slice(value);
+        Ok(Self {
+            bytes,
+            len: value.len() as u8,
+        })
+    }
+}
+
+impl From<DynHash> for Vec<u8> {
+    fn from(hash: DynHash) -> Self {
+        hash.as_ref().to_vec()
+    }
+}
+
+impl IntoIterator for DynHash {
+    type Item = u8;
+    type IntoIter = std::iter::Take<std::array::IntoIter<u8, MAX_DYN_HASH_LEN>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.bytes.into_iter().take(self.len())
+    }
+}
+
+impl PartialEq for DynHash {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl Eq for DynHash {}
+
+impl Hash for DynHash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state);
+    }
+}
+
+impl PartialOrd for DynHash {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.as_ref().cmp(other.as_ref()))
+    }
+}
+
+impl Debug for DynHash {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.debug_tuple("DynHash").field(&self.as_ref()).finish()
+    }
+}
+
+/// Hashing algorithm whose digest length is chosen at runtime, e.g. configurable-length
+/// BLAKE2 or a digest truncated to a length loaded from configuration
+///
+/// Trees and proofs use it through [`DynHasher`], which stores the digests as [`DynHash`].
+pub trait ToHashDyn {
+    const ALGORITHM: &'static str;
+
+    /// Length of every digest produced by [`ToHashDyn::hash_dyn`], at most [`MAX_DYN_HASH_LEN`]
+    fn output_len() -> usize;
+
+    fn hash_dyn(value: &[u8]) -> std::result::Result<Vec<u8>, HashError>;
+}
+
+/// Adapts a [`ToHashDyn`] hasher to [`ToHash`], so `MerkleTree<DynHasher<H>>` and
+/// `MerkleProof<DynHasher<H>>` work with digests of a runtime length
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::error::hash_error::HashError;
+/// use merkle_tree::{DynHasher, MerkleProof, MerkleTree, Sha512, ToHash, ToHashDyn};
+///
+/// /// SHA-512 truncated to a length read from configuration
+/// struct ConfiguredSha512 {}
+///
+/// impl ToHashDyn for ConfiguredSha512 {
+///     const ALGORITHM: &'static str = "sha3-512/truncated";
+///
+///     fn output_len() -> usize {
+///         20
+///     }
+///
+///     fn hash_dyn(value: &[u8]) -> Result<Vec<u8>, HashError> {
+///         Ok(Sha512::hash(value)[..Self::output_len()].to_vec())
+///     }
+/// }
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     type Hasher = DynHasher<ConfiguredSha512>;
+///     let leaves: Vec<_> = (0..5).map(|i| Hasher::hash(&[i])).collect();
+///     let tree = MerkleTree::<Hasher>::from_leaves(&leaves);
+///
+///     let root = tree.root_hash()?;
+///     assert_eq!(root.len(), 20);
+///     assert!(MerkleProof::<Hasher>::new(tree.get_proof_at(3)?).validate(root, leaves[3]));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct DynHasher<H: ToHashDyn> {
+    hasher: PhantomData<H>,
+}
+
+impl<H: ToHashDyn> ToHash for DynHasher<H> {
+    type Hash = DynHash;
+    const ALGORITHM: &'static str = H::ALGORITHM;
+    /// Upper bound only, [`ToHash::hash_len`] returns the configured length
+    const HASH_LEN: usize = MAX_DYN_HASH_LEN;
+
+    fn hash(value: &[u8]) -> Self::Hash {
+        Self::try_hash(value).expect("dynamic hasher failed, use try_hash to handle failures")
+    }
+
+    fn try_hash(value: &[u8]) -> std::result::Result<Self::Hash, HashError> {
+        let digest = H::hash_dyn(value)?;
+        if digest.len() != H::output_len() {
+            return Err(HashError::new(
+                "digest length does not match the configured output length",
+            ));
+        }
+
+        DynHash::try_from(digest.as_slice())
+    }
+
+    fn hash_len() -> usize {
+        H::output_len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha512;
+    use crate::merkle_proof::MerkleProof;
+    use crate::merkle_tree::MerkleTree;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static OUTPUT_LEN: AtomicUsize = AtomicUsize::new(24);
+
+    struct Configured {}
+
+    impl ToHashDyn for Configured {
+        const ALGORITHM: &'static str = "configured";
+
+        fn output_len() -> usize {
+            OUTPUT_LEN.load(Ordering::Relaxed)
+        }
+
+        fn hash_dyn(value: &[u8]) -> std::result::Result<Vec<u8>, HashError> {
+            Ok(Sha512::hash(value)[..Self::output_len()].to_vec())
+        }
+    }
+
+    #[test]
+    fn test_runtime_length_tree_and_proof_bytes() {
+        type Hasher = DynHasher<Configured>;
+        let leaves: Vec<_> = (0..5u8).map(|i| Hasher::hash(&[i])).collect();
+        let tree = MerkleTree::<Hasher>::from_leaves(&leaves);
+        let root = tree.root_hash().unwrap();
+        assert_eq!(root.len(), 24);
+
+        let proof = MerkleProof::<Hasher>::new(tree.get_proof_at(2).unwrap());
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes[2], 24);
+
+        let decoded = MerkleProof::<Hasher>::from_bytes(&bytes).unwrap();
+        assert!(decoded.validate(root, leaves[2]));
+        assert_eq!(tree.index_of(&leaves[3]), Some(3));
+        assert_eq!(
+            tree.get_proof(leaves[2]).unwrap(),
+            tree.get_proof_at(2).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_dyn_hash_limits() {
+        assert!(DynHash::try_from(&[0u8; 65][..]).is_err());
+
+        let short = DynHash::try_from(&[1u8, 2][..]).unwrap();
+        let long = DynHash::try_from(&[1u8, 2, 0][..]).unwrap();
+        assert_ne!(short, long);
+        assert!(short < long);
+        assert_eq!(long.into_iter().collect::<Vec<_>>(), vec![1, 2, 0]);
+    }
+}
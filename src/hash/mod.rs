@@ -1,5 +1,22 @@
+mod backend;
+pub mod byte_order;
+mod combined;
+mod domain_separated;
+mod dyn_hash;
 mod sha;
+mod tiger;
 pub mod to_hash;
+mod truncated;
+pub use backend::HashBackend;
+#[cfg(feature = "simd")]
+pub use backend::{KeccakLanes, Sha3Lanes};
+pub use byte_order::ByteOrder;
+pub use combined::{CombineStrategy, Combined, Concat, LengthPrefixed, Prefixed};
+pub use domain_separated::DomainSeparated;
+pub use dyn_hash::{DynHash, DynHasher, ToHashDyn, MAX_DYN_HASH_LEN};
 pub use sha::keccak256::Keccak256;
 pub use sha::sha256::Sha256;
+pub use sha::sha2_256::Sha2_256;
 pub use sha::sha512::Sha512;
+pub use tiger::Tiger;
+pub use truncated::Truncated;
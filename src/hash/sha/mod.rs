@@ -1,3 +1,4 @@
 pub mod keccak256;
 pub mod sha256;
+pub mod sha2_256;
 pub mod sha512;
@@ -1,16 +1,35 @@
-use crate::hash::to_hash::ToHash;
+use crate::error::hash_error::HashError;
+use crate::hash::to_hash::{for_each_chunk, ToHash};
+#[cfg(feature = "simd")]
+use crate::hash::{HashBackend, Sha3Lanes};
 use sha3::{Digest, Sha3_256};
+use std::io::Read;
 
 pub struct Sha256 {}
 
 impl ToHash for Sha256 {
     type Hash = [u8; 32];
+    const ALGORITHM: &'static str = "sha3-256";
 
     fn hash(value: &[u8]) -> Self::Hash {
         let mut hasher = Sha3_256::new();
         hasher.update(value);
         hasher.finalize().into()
     }
+
+    fn hash_reader(reader: impl Read) -> Result<Self::Hash, HashError> {
+        let mut hasher = Sha3_256::new();
+        for_each_chunk(reader, |chunk| hasher.update(chunk))?;
+        Ok(hasher.finalize().into())
+    }
+
+    #[cfg(feature = "simd")]
+    fn try_hash_nodes(
+        pairs: &[(Self::Hash, Self::Hash)],
+        parents: &mut Vec<Self::Hash>,
+    ) -> Result<(), HashError> {
+        Sha3Lanes::hash_nodes(pairs, parents)
+    }
 }
 
 #[cfg(test)]
@@ -31,4 +50,14 @@ mod tests {
 
         assert_eq!(Sha256::hash(&value_1), Sha256::hash(&value_1))
     }
+
+    #[test]
+    fn test_hash_reader_matches_hash_across_chunks() {
+        let value: Vec<u8> = (0..150_000u32).map(|i| i as u8).collect();
+
+        assert_eq!(
+            Sha256::hash_reader(value.as_slice()).unwrap(),
+            Sha256::hash(&value)
+        );
+    }
 }
@@ -0,0 +1,57 @@
+use crate::error::hash_error::HashError;
+use crate::hash::to_hash::{for_each_chunk, ToHash};
+use sha2::{Digest, Sha256 as Sha2Sha256};
+use std::io::Read;
+
+/// SHA-256 from the SHA-2 family, as used by Bitcoin, SSZ and BitTorrent v2
+pub struct Sha2_256 {}
+
+impl ToHash for Sha2_256 {
+    type Hash = [u8; 32];
+    const ALGORITHM: &'static str = "sha2-256";
+
+    fn hash(value: &[u8]) -> Self::Hash {
+        let mut hasher = Sha2Sha256::new();
+        hasher.update(value);
+        hasher.finalize().into()
+    }
+
+    fn hash_reader(reader: impl Read) -> Result<Self::Hash, HashError> {
+        let mut hasher = Sha2Sha256::new();
+        for_each_chunk(reader, |chunk| hasher.update(chunk))?;
+        Ok(hasher.finalize().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_two_different_values() {
+        let value_1: [u8; 1] = [0];
+        let value_2: [u8; 1] = [1];
+
+        assert_ne!(Sha2_256::hash(&value_1), Sha2_256::hash(&value_2));
+    }
+
+    #[test]
+    fn test_hash_known_value() {
+        let expected_hash = [
+            186, 120, 22, 191, 143, 1, 207, 234, 65, 65, 64, 222, 93, 174, 34, 35, 176, 3, 97, 163,
+            150, 23, 122, 156, 180, 16, 255, 97, 242, 0, 21, 173,
+        ];
+
+        assert_eq!(Sha2_256::hash("abc".as_bytes()), expected_hash);
+    }
+
+    #[test]
+    fn test_hash_reader_matches_hash_across_chunks() {
+        let value: Vec<u8> = (0..150_000u32).map(|i| i as u8).collect();
+
+        assert_eq!(
+            Sha2_256::hash_reader(value.as_slice()).unwrap(),
+            Sha2_256::hash(&value)
+        );
+    }
+}
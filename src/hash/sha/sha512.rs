@@ -1,16 +1,25 @@
-use crate::hash::to_hash::ToHash;
+use crate::error::hash_error::HashError;
+use crate::hash::to_hash::{for_each_chunk, ToHash};
 use sha3::{Digest, Sha3_512};
+use std::io::Read;
 
 pub struct Sha512 {}
 
 impl ToHash for Sha512 {
     type Hash = [u8; 64];
+    const ALGORITHM: &'static str = "sha3-512";
 
     fn hash(value: &[u8]) -> Self::Hash {
         let mut hasher = Sha3_512::new();
         hasher.update(value);
         hasher.finalize().into()
     }
+
+    fn hash_reader(reader: impl Read) -> Result<Self::Hash, HashError> {
+        let mut hasher = Sha3_512::new();
+        for_each_chunk(reader, |chunk| hasher.update(chunk))?;
+        Ok(hasher.finalize().into())
+    }
 }
 
 #[cfg(test)]
@@ -31,4 +40,26 @@ mod tests {
 
         assert_eq!(Sha512::hash(&value_1), Sha512::hash(&value_1))
     }
+
+    #[test]
+    fn test_node_hash_of_wide_hashes() {
+        let left = Sha512::hash(&[0]);
+        let right = Sha512::hash(&[1]);
+
+        assert_eq!(Sha512::HASH_LEN, 64);
+        assert_eq!(
+            Sha512::hash_node(left, right),
+            Sha512::hash(&[left, right].concat())
+        );
+    }
+
+    #[test]
+    fn test_hash_reader_matches_hash_across_chunks() {
+        let value: Vec<u8> = (0..150_000u32).map(|i| i as u8).collect();
+
+        assert_eq!(
+            Sha512::hash_reader(value.as_slice()).unwrap(),
+            Sha512::hash(&value)
+        );
+    }
 }
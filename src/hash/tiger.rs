@@ -0,0 +1,233 @@
+use crate::error::hash_error::HashError;
+use crate::hash::to_hash::{for_each_chunk, ToHash};
+use std::io::Read;
+use std::sync::OnceLock;
+
+const INITIAL_STATE: [u64; 3] = [0x0123456789ABCDEF, 0xFEDCBA9876543210, 0xF096A5B4C3B2E187];
+const SBOX_SEED: &[u8; 64] = b"Tiger - A Fast New Hash Function, by Ross Anderson and Eli Biham";
+const SBOX_PASSES: usize = 5;
+
+type SBoxes = [[u64; 256]; 4];
+
+/// Tiger/192 (Anderson and Biham), the hash behind THEX and Tiger Tree Hash values
+pub struct Tiger {}
+
+impl ToHash for Tiger {
+    type Hash = [u8; 24];
+    const ALGORITHM: &'static str = "tiger";
+
+    fn hash(value: &[u8]) -> Self::Hash {
+        let mut state = TigerState::new();
+        state.update(value);
+        state.finish()
+    }
+
+    fn hash_reader(reader: impl Read) -> Result<Self::Hash, HashError> {
+        let mut state = TigerState::new();
+        for_each_chunk(reader, |chunk| state.update(chunk))?;
+        Ok(state.finish())
+    }
+}
+
+/// Running Tiger computation, fed any number of times before the padding is applied
+struct TigerState {
+    sboxes: &'static SBoxes,
+    state: [u64; 3],
+    block: [u8; 64],
+    block_len: usize,
+    len: u64,
+}
+
+impl TigerState {
+    fn new() -> Self {
+        Self {
+            sboxes: sboxes(),
+            state: INITIAL_STATE,
+            block: [0u8; 64],
+            block_len: 0,
+            len: 0,
+        }
+    }
+
+    fn update(&mut self, mut value: &[u8]) {
+        self.len += value.len() as u64;
+        if self.block_len > 0 {
+            let taken = value.len().min(64 - self.block_len);
+            self.block[self.block_len..self.block_len + taken].copy_from_slice(&value[..taken]);
+            self.block_len += taken;
+            value = &value[taken..];
+            if self.block_len < 64 {
+                return;
+            }
+            compress(self.sboxes, &mut self.state, &self.block);
+            self.block_len = 0;
+        }
+
+        let mut blocks = value.chunks_exact(64);
+        for block in &mut blocks {
+            compress(self.sboxes, &mut self.state, block.try_into().unwrap());
+        }
+        let remainder = blocks.remainder();
+        self.block[..remainder.len()].copy_from_slice(remainder);
+        self.block_len = remainder.len();
+    }
+
+    fn finish(mut self) -> [u8; 24] {
+        // original Tiger padding: 0x01, zeros and the length in bits, little endian
+        let remainder = &self.block[..self.block_len];
+        let mut tail = [0u8; 128];
+        tail[..remainder.len()].copy_from_slice(remainder);
+        tail[remainder.len()] = 0x01;
+        let tail_len = match remainder.len() < 56 {
+            true => 64,
+            false => 128,
+        };
+        tail[tail_len - 8..tail_len].copy_from_slice(&(self.len << 3).to_le_bytes());
+        for block in tail[..tail_len].chunks_exact(64) {
+            compress(self.sboxes, &mut self.state, block.try_into().unwrap());
+        }
+
+        let mut digest = [0u8; 24];
+        for (bytes, word) in digest.chunks_exact_mut(8).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+}
+
+/// S-boxes derived from the seed string as in the reference implementation
+fn sboxes() -> &'static SBoxes {
+    static SBOXES: OnceLock<SBoxes> = OnceLock::new();
+    SBOXES.get_or_init(|| {
+        let mut table = [[0u64; 256]; 4];
+        for sbox in table.iter_mut() {
+            for (index, entry) in sbox.iter_mut().enumerate() {
+                *entry = u64::from_le_bytes([index as u8; 8]);
+            }
+        }
+
+        let mut state = INITIAL_STATE;
+        let mut abc = 2;
+        for _ in 0..SBOX_PASSES {
+            for index in 0..256 {
+                for sbox in 0..4 {
+                    abc += 1;
+                    if abc == 3 {
+                        abc = 0;
+                        compress(&table, &mut state, SBOX_SEED);
+                    }
+
+                    let selectors = state[abc].to_le_bytes();
+                    for (column, selector) in selectors.into_iter().enumerate() {
+                        let shift = column * 8;
+                        let mask = 0xffu64 << shift;
+                        let other = selector as usize;
+                        let left = table[sbox][index] & mask;
+                        let right = table[sbox][other] & mask;
+                        table[sbox][index] = (table[sbox][index] & !mask) | right;
+                        table[sbox][other] = (table[sbox][other] & !mask) | left;
+                    }
+                }
+            }
+        }
+
+        table
+    })
+}
+
+fn compress(sboxes: &SBoxes, state: &mut [u64; 3], block: &[u8; 64]) {
+    let mut words = [0u64; 8];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    let [mut a, mut b, mut c] = *state;
+    pass(sboxes, &mut a, &mut b, &mut c, &words, 5);
+    key_schedule(&mut words);
+    pass(sboxes, &mut c, &mut a, &mut b, &words, 7);
+    key_schedule(&mut words);
+    pass(sboxes, &mut b, &mut c, &mut a, &words, 9);
+
+    state[0] ^= a;
+    state[1] = b.wrapping_sub(state[1]);
+    state[2] = c.wrapping_add(state[2]);
+}
+
+fn pass(sboxes: &SBoxes, a: &mut u64, b: &mut u64, c: &mut u64, words: &[u64; 8], mul: u64) {
+    round(sboxes, a, b, c, words[0], mul);
+    round(sboxes, b, c, a, words[1], mul);
+    round(sboxes, c, a, b, words[2], mul);
+    round(sboxes, a, b, c, words[3], mul);
+    round(sboxes, b, c, a, words[4], mul);
+    round(sboxes, c, a, b, words[5], mul);
+    round(sboxes, a, b, c, words[6], mul);
+    round(sboxes, b, c, a, words[7], mul);
+}
+
+fn round(sboxes: &SBoxes, a: &mut u64, b: &mut u64, c: &mut u64, word: u64, mul: u64) {
+    *c ^= word;
+    let bytes = c.to_le_bytes();
+    let [t1, t2, t3, t4] = sboxes;
+    *a = a.wrapping_sub(
+        t1[bytes[0] as usize]
+            ^ t2[bytes[2] as usize]
+            ^ t3[bytes[4] as usize]
+            ^ t4[bytes[6] as usize],
+    );
+    *b = b.wrapping_add(
+        t4[bytes[1] as usize]
+            ^ t3[bytes[3] as usize]
+            ^ t2[bytes[5] as usize]
+            ^ t1[bytes[7] as usize],
+    );
+    *b = b.wrapping_mul(mul);
+}
+
+fn key_schedule(x: &mut [u64; 8]) {
+    x[0] = x[0].wrapping_sub(x[7] ^ 0xA5A5A5A5A5A5A5A5);
+    x[1] ^= x[0];
+    x[2] = x[2].wrapping_add(x[1]);
+    x[3] = x[3].wrapping_sub(x[2] ^ (!x[1] << 19));
+    x[4] ^= x[3];
+    x[5] = x[5].wrapping_add(x[4]);
+    x[6] = x[6].wrapping_sub(x[5] ^ (!x[4] >> 23));
+    x[7] ^= x[6];
+    x[0] = x[0].wrapping_add(x[7]);
+    x[1] = x[1].wrapping_sub(x[0] ^ (!x[7] << 19));
+    x[2] ^= x[1];
+    x[3] = x[3].wrapping_add(x[2]);
+    x[4] = x[4].wrapping_sub(x[3] ^ (!x[2] >> 23));
+    x[5] ^= x[4];
+    x[6] = x[6].wrapping_add(x[5]);
+    x[7] = x[7].wrapping_sub(x[6] ^ 0x0123456789ABCDEF);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex;
+
+    #[test]
+    fn test_hash_known_values() {
+        assert_eq!(
+            hex::encode(&Tiger::hash(b"")),
+            "3293ac630c13f0245f92bbb1766e16167a4e58492dde73f3"
+        );
+        assert_eq!(
+            hex::encode(&Tiger::hash(b"abc")),
+            "2aab1484e8c158f2bfb8c5ff41b57a525129131c957b5f93"
+        );
+    }
+
+    #[test]
+    fn test_hash_reader_matches_hash_across_chunks() {
+        let value: Vec<u8> = (0..150_000u32).map(|i| i as u8).collect();
+        // chained readers yield chunks that end mid-block
+        let reader = value[..7]
+            .chain(&value[7..100])
+            .chain(&value[100..70_001])
+            .chain(&value[70_001..]);
+
+        assert_eq!(Tiger::hash_reader(reader).unwrap(), Tiger::hash(&value));
+    }
+}
@@ -1,12 +1,195 @@
+use crate::error::hash_error::HashError;
+use std::io::{ErrorKind, Read};
+
+/// Hashing algorithm used to build trees and validate proofs
+///
+/// Trees and proofs only call the fallible [`ToHash::try_hash`], [`ToHash::try_hash_node`] and
+/// [`ToHash::try_combine`].
+/// Infallible hashers implement `hash`; backends that can fail (HSMs, FFI hashers) override
+/// `try_hash` as well so that failures surface as a `TreeErrorKind::HashFailed` error.
 pub trait ToHash {
-    type Hash: Copy + PartialEq + PartialOrd + IntoIterator + Into<Vec<u8>>;
+    type Hash: Copy + Eq + std::hash::Hash + PartialOrd + AsRef<[u8]> + for<'a> TryFrom<&'a [u8]>;
+
+    /// Stable identifier of the algorithm, used when exchanging proofs between systems
+    const ALGORITHM: &'static str;
+
+    /// Width in bytes of a single hash, the size of `Hash` unless overridden
+    const HASH_LEN: usize = std::mem::size_of::<Self::Hash>();
+
+    /// Whether leaves and interior nodes are hashed under distinct prefixes, see
+    /// [`DomainSeparated`](crate::DomainSeparated)
+    const DOMAIN_SEPARATED: bool = false;
+
+    /// Whether interior nodes are encoded by the hasher itself, see
+    /// [`Combined`](crate::Combined), rather than hashed as `left || right`
+    const ENCODES_NODES: bool = false;
+
+    /// Longest input the default [`ToHash::hash_reader`] holds in memory
+    const MAX_BUFFERED_READ_LEN: u64 = 256 << 20;
 
     fn hash(value: &[u8]) -> Self::Hash;
+
+    /// Name of the encoding of interior nodes, `concat` for `hash(left || right)`
+    ///
+    /// Exchanged along with [`ToHash::ALGORITHM`] so that hashers sharing an algorithm but
+    /// hashing nodes differently, such as [`DomainSeparated`](crate::DomainSeparated) or
+    /// [`Combined`](crate::Combined) ones, are told apart.
+    fn node_encoding() -> String {
+        "concat".to_owned()
+    }
+
+    /// Hashes an interior node from its children, in the given order
+    fn hash_node(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        with_concatenated(left.as_ref(), right.as_ref(), Self::hash)
+    }
+
     fn combine(left: Self::Hash, right: Self::Hash) -> Self::Hash {
         if left <= right {
-            return Self::hash(&[right.into(), left.into()].concat());
+            return Self::hash_node(right, left);
+        }
+
+        Self::hash_node(left, right)
+    }
+
+    fn try_hash(value: &[u8]) -> Result<Self::Hash, HashError> {
+        Ok(Self::hash(value))
+    }
+
+    /// Hashes everything the reader yields, as [`ToHash::try_hash`] would hash it at once
+    ///
+    /// The default buffers the whole input, as `try_hash` needs it in one slice, and fails
+    /// once it exceeds [`ToHash::MAX_BUFFERED_READ_LEN`]. Hashers with an incremental core,
+    /// such as the SHA-2, Keccak and Tiger ones, override it to feed buffered chunks as they
+    /// are read, so multi-gigabyte leaves are never held in memory.
+    fn hash_reader(reader: impl Read) -> Result<Self::Hash, HashError> {
+        let mut value = Vec::new();
+        reader
+            .take(Self::MAX_BUFFERED_READ_LEN + 1)
+            .read_to_end(&mut value)
+            .map_err(|error| HashError::with_source("failed to read the value to hash", error))?;
+        if value.len() as u64 > Self::MAX_BUFFERED_READ_LEN {
+            return Err(HashError::new(
+                "value exceeds the length this hasher buffers, override hash_reader to stream it",
+            ));
+        }
+
+        Self::try_hash(&value)
+    }
+
+    fn try_hash_node(left: Self::Hash, right: Self::Hash) -> Result<Self::Hash, HashError> {
+        with_concatenated(left.as_ref(), right.as_ref(), Self::try_hash)
+    }
+
+    /// Appends the parent of every `(left, right)` pair to `parents`, hashing them in order
+    ///
+    /// Trees hash each level through this method. Hashers backed by a batched
+    /// [`HashBackend`](crate::HashBackend) override it to hash several pairs at once.
+    fn try_hash_nodes(
+        pairs: &[(Self::Hash, Self::Hash)],
+        parents: &mut Vec<Self::Hash>,
+    ) -> Result<(), HashError> {
+        parents.reserve(pairs.len());
+        for (left, right) in pairs {
+            parents.push(Self::try_hash_node(*left, *right)?);
+        }
+
+        Ok(())
+    }
+
+    /// Hashes an interior node of a k-ary tree from its children, in the given order
+    ///
+    /// Two children are hashed as [`ToHash::try_hash_node`], wider nodes as the hash of their
+    /// concatenation.
+    fn try_hash_children(children: &[Self::Hash]) -> Result<Self::Hash, HashError> {
+        match children {
+            [left, right] => Self::try_hash_node(*left, *right),
+            _ => Self::try_hash(
+                &children
+                    .iter()
+                    .flat_map(AsRef::as_ref)
+                    .copied()
+                    .collect::<Vec<_>>(),
+            ),
+        }
+    }
+
+    fn try_combine(left: Self::Hash, right: Self::Hash) -> Result<Self::Hash, HashError> {
+        if left <= right {
+            return Self::try_hash_node(right, left);
+        }
+
+        Self::try_hash_node(left, right)
+    }
+
+    /// Width in bytes of a single hash produced by this hasher
+    fn hash_len() -> usize {
+        Self::HASH_LEN
+    }
+}
+
+/// Size of the chunks read by incremental [`ToHash::hash_reader`] implementations
+const READ_BUFFER_LEN: usize = 64 * 1024;
+
+/// Passes the reader's output to `update` chunk by chunk, until the end of the input
+pub(crate) fn for_each_chunk(
+    mut reader: impl Read,
+    mut update: impl FnMut(&[u8]),
+) -> Result<(), HashError> {
+    let mut buffer = vec![0u8; READ_BUFFER_LEN];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => update(&buffer[..read]),
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => {
+                return Err(HashError::with_source(
+                    "failed to read the value to hash",
+                    error,
+                ))
+            }
         }
+    }
+}
+
+/// Longest pair of hashes concatenated on the stack rather than on the heap
+const NODE_BUFFER_LEN: usize = 128;
+
+/// Calls `hash` with `left || right`, without allocating for hashes of up to 64 bytes
+fn with_concatenated<R>(left: &[u8], right: &[u8], hash: impl FnOnce(&[u8]) -> R) -> R {
+    let len = left.len() + right.len();
+    if len > NODE_BUFFER_LEN {
+        return hash(&[left, right].concat());
+    }
+
+    let mut buffer = [0u8; NODE_BUFFER_LEN];
+    buffer[..left.len()].copy_from_slice(left);
+    buffer[left.len()..len].copy_from_slice(right);
+    hash(&buffer[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha256;
+
+    struct Buffered;
+
+    impl ToHash for Buffered {
+        type Hash = [u8; 32];
+        const ALGORITHM: &'static str = "buffered";
+        const MAX_BUFFERED_READ_LEN: u64 = 16;
+
+        fn hash(value: &[u8]) -> Self::Hash {
+            Sha256::hash(value)
+        }
+    }
 
-        Self::hash(&[left.into(), right.into()].concat())
+    #[test]
+    fn test_default_hash_reader_is_bounded() {
+        assert_eq!(
+            Buffered::hash_reader([7u8; 16].as_slice()).unwrap(),
+            Sha256::hash(&[7u8; 16])
+        );
+        assert!(Buffered::hash_reader([7u8; 17].as_slice()).is_err());
     }
 }
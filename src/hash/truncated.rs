@@ -0,0 +1,105 @@
+use crate::error::hash_error::HashError;
+use crate::hash::to_hash::ToHash;
+use std::io::Read;
+use std::marker::PhantomData;
+
+/// Hasher `H` whose output is cut to its first `N` bytes, e.g. 20-byte Keccak-256 nodes
+///
+/// Leaves are `H(data)[..N]` and nodes `H(left || right)[..N]` over the truncated children,
+/// so proofs carry `N`-byte siblings. Domain separation goes around the truncation, as
+/// `DomainSeparated<Truncated<H, N>>`; wrapping an already separated `H`, or one encoding its
+/// own nodes, fails to compile, as does an `N` of zero or wider than `H`.
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::{Keccak256, MerkleProof, MerkleTree, ToHash, Truncated};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     type Keccak160 = Truncated<Keccak256, 20>;
+///     let leaves: Vec<_> = [b"a", b"b", b"c"].iter().map(|data| Keccak160::hash(*data)).collect();
+///     let tree = MerkleTree::<Keccak160>::from_leaves(&leaves);
+///
+///     assert_eq!(leaves[0][..], Keccak256::hash(b"a")[..20]);
+///     let proof = MerkleProof::<Keccak160>::new(tree.get_proof_at(2)?);
+///     assert!(proof.validate(tree.root_hash()?, leaves[2]));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct Truncated<H: ToHash, const N: usize> {
+    hasher: PhantomData<H>,
+}
+
+impl<H: ToHash, const N: usize> Truncated<H, N> {
+    const WIDTH_FITS: () = assert!(
+        N > 0 && N <= H::HASH_LEN && !H::DOMAIN_SEPARATED && !H::ENCODES_NODES,
+        "truncation must keep 1 to H::HASH_LEN bytes of a hasher hashing nodes as left || right"
+    );
+
+    fn truncate(hash: H::Hash) -> [u8; N] {
+        let () = Self::WIDTH_FITS;
+        let mut truncated = [0u8; N];
+        truncated.copy_from_slice(&hash.as_ref()[..N]);
+        truncated
+    }
+}
+
+impl<H: ToHash, const N: usize> ToHash for Truncated<H, N> {
+    type Hash = [u8; N];
+    const ALGORITHM: &'static str = H::ALGORITHM;
+
+    fn hash(value: &[u8]) -> Self::Hash {
+        Self::truncate(H::hash(value))
+    }
+
+    fn try_hash(value: &[u8]) -> Result<Self::Hash, HashError> {
+        Ok(Self::truncate(H::try_hash(value)?))
+    }
+
+    fn hash_reader(reader: impl Read) -> Result<Self::Hash, HashError> {
+        Ok(Self::truncate(H::hash_reader(reader)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{DomainSeparated, Keccak256, Sha512};
+    use crate::merkle_proof::MerkleProof;
+    use crate::merkle_tree::MerkleTree;
+
+    type Keccak160 = Truncated<Keccak256, 20>;
+
+    #[test]
+    fn test_nodes_hash_the_truncated_children() {
+        let left = Keccak160::hash(b"0");
+        let right = Keccak160::hash(b"1");
+
+        assert_eq!(Keccak160::hash_len(), 20);
+        assert_eq!(
+            Keccak160::hash_node(left, right)[..],
+            Keccak256::hash(&[left, right].concat())[..20]
+        );
+        assert_eq!(
+            DomainSeparated::<Keccak160>::hash_node(left, right)[..],
+            Keccak256::hash(&[&[0x01], &left[..], &right[..]].concat())[..20]
+        );
+    }
+
+    #[test]
+    fn test_truncated_proofs_round_trip_and_validate() {
+        type Sha512Half = DomainSeparated<Truncated<Sha512, 32>>;
+        let leaves: Vec<_> = (0..7u8).map(|i| Sha512Half::hash(&[i])).collect();
+        let tree = MerkleTree::<Sha512Half>::from_leaves(&leaves);
+        let root = tree.root_hash().unwrap();
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let bytes = tree.merkle_proof_at(index).unwrap().to_bytes();
+            let proof = MerkleProof::<Sha512Half>::from_bytes(&bytes).unwrap();
+            assert!(proof.validate(root, *leaf));
+        }
+        assert!(
+            MerkleProof::<Keccak160>::from_bytes(&tree.merkle_proof_at(0).unwrap().to_bytes())
+                .is_err()
+        );
+    }
+}
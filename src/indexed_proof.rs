@@ -0,0 +1,251 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::Position;
+use crate::merkle_tree::{path_from_levels, MerkleTree, TreeResult};
+use crate::tree_policy::{OddLeafPolicy, PairOrdering};
+use crate::utils::ct;
+
+const INDEXED_PROOF_FORMAT_VERSION: u8 = 1;
+const INDEXED_PROOF_HEADER_LEN: usize = 22;
+
+/// Inclusion proof bound to the slot of its leaf, as in RFC 6962 audit paths
+///
+/// The proof records the leaf index and the tree size, and the side of every sibling is
+/// derived from them rather than from the hashes, so a proof only validates for the slot the
+/// leaf actually occupies. Only trees with [`PairOrdering::Positional`] and
+/// [`OddLeafPolicy::Promote`] commit to positions, so only they produce these proofs.
+pub struct IndexedProof<T: ToHash> {
+    index: usize,
+    tree_size: usize,
+    proof: Vec<T::Hash>,
+}
+
+impl<T: ToHash> IndexedProof<T> {
+    pub fn new(proof: Vec<T::Hash>, index: usize, tree_size: usize) -> Self {
+        Self {
+            index,
+            tree_size,
+            proof,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn tree_size(&self) -> usize {
+        self.tree_size
+    }
+
+    pub fn proof(&self) -> &[T::Hash] {
+        &self.proof
+    }
+
+    /// Validates the proof for `leaf` in the slot `index` of a tree of `tree_size` leaves
+    ///
+    /// Fails when the proof was issued for another slot or tree size, or when the number of
+    /// siblings does not match the path of that slot.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{PairOrdering, Sha256, ToHash, TreeBuilder};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let tree = ["a", "b", "a", "c", "d"]
+    ///         .iter()
+    ///         .fold(TreeBuilder::<Sha256>::new(), |builder, leaf| builder.leaf_data(leaf.as_bytes()))
+    ///         .pair_ordering(PairOrdering::Positional)
+    ///         .build()?;
+    ///     let root = tree.root_hash()?;
+    ///     let leaf = tree.leaf_at(2).unwrap();
+    ///
+    ///     let proof = tree.indexed_proof_at(2)?;
+    ///     assert!(proof.validate_at(root, leaf, 2, 5));
+    ///     // the same leaf also sits in slot 0, but this proof cannot vouch for it
+    ///     assert!(!proof.validate_at(root, leaf, 0, 5));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn validate_at(
+        &self,
+        root_hash: T::Hash,
+        leaf: T::Hash,
+        index: usize,
+        tree_size: usize,
+    ) -> bool {
+        if index != self.index || tree_size != self.tree_size {
+            return false;
+        }
+
+        self.compute_root(leaf)
+            .is_ok_and(|root| ct::eq(root.as_ref(), root_hash.as_ref()))
+    }
+
+    /// Folds the proof from the leaf up along the path of the recorded slot
+    pub fn compute_root(&self, leaf: T::Hash) -> TreeResult<T::Hash> {
+        let positions = slot_positions(self.index, self.tree_size)?;
+        if positions.len() != self.proof.len() {
+            return Err(TreeError::proof_length_mismatch(
+                "proof does not match the path of its slot",
+            ));
+        }
+
+        self.proof
+            .iter()
+            .zip(positions)
+            .try_fold(leaf, |current, (sibling, position)| {
+                Ok(match position {
+                    Position::Left => T::try_hash_node(*sibling, current)?,
+                    Position::Right => T::try_hash_node(current, *sibling)?,
+                })
+            })
+    }
+
+    /// Serializes the proof into a compact, versioned byte format
+    ///
+    /// Layout: `version (u8) | hash length (u8) | index (u64, BE) | tree size (u64, BE) |
+    /// sibling count (u32, BE) | hashes`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(INDEXED_PROOF_HEADER_LEN + self.proof.len() * T::hash_len());
+        bytes.push(INDEXED_PROOF_FORMAT_VERSION);
+        bytes.push(T::hash_len() as u8);
+        bytes.extend_from_slice(&(self.index as u64).to_be_bytes());
+        bytes.extend_from_slice(&(self.tree_size as u64).to_be_bytes());
+        bytes.extend_from_slice(&(self.proof.len() as u32).to_be_bytes());
+        for hash in &self.proof {
+            bytes.extend_from_slice(hash.as_ref());
+        }
+
+        bytes
+    }
+
+    /// Deserializes a proof previously produced by [`IndexedProof::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> TreeResult<Self> {
+        if bytes.len() < INDEXED_PROOF_HEADER_LEN {
+            return Err(TreeError::proof_malformed("proof header is truncated"));
+        }
+
+        if bytes[0] != INDEXED_PROOF_FORMAT_VERSION {
+            return Err(TreeError::proof_malformed(
+                "unsupported proof format version",
+            ));
+        }
+
+        let hash_len = T::hash_len();
+        if bytes[1] as usize != hash_len {
+            return Err(TreeError::hash_size_mismatch(
+                "hash length does not match the hasher",
+            ));
+        }
+
+        let index = u64::from_be_bytes(bytes[2..10].try_into().unwrap()) as usize;
+        let tree_size = u64::from_be_bytes(bytes[10..18].try_into().unwrap()) as usize;
+        let count = u32::from_be_bytes(bytes[18..22].try_into().unwrap()) as usize;
+        let body = &bytes[INDEXED_PROOF_HEADER_LEN..];
+        if count.checked_mul(hash_len) != Some(body.len()) {
+            return Err(TreeError::proof_malformed(
+                "sibling count does not match the proof length",
+            ));
+        }
+
+        let proof = body
+            .chunks_exact(hash_len)
+            .map(|chunk| {
+                T::Hash::try_from(chunk)
+                    .map_err(|_| TreeError::proof_malformed("invalid hash in proof"))
+            })
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        Ok(Self::new(proof, index, tree_size))
+    }
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Creates the proof of the leaf at `index`, bound to that index and the tree size
+    ///
+    /// Fails with `ProofMalformed` unless the tree orders pairs positionally and promotes odd
+    /// nodes, as only such trees commit to the slot of each leaf.
+    pub fn indexed_proof_at(&self, index: usize) -> TreeResult<IndexedProof<T>> {
+        let policy = self.policy();
+        if policy.pair_ordering != PairOrdering::Positional
+            || policy.odd_leaf != OddLeafPolicy::Promote
+        {
+            return Err(TreeError::proof_malformed(
+                "index-bound proofs need a positional tree that promotes odd nodes",
+            ));
+        }
+        if index >= self.leaf_count() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let proof = path_from_levels::<T>(self.cached_levels()?, index, &policy)?
+            .into_iter()
+            .map(|(sibling, _)| sibling)
+            .collect();
+
+        Ok(IndexedProof::new(proof, index, self.leaf_count()))
+    }
+}
+
+/// Sides of the siblings on the path of the slot `index`, skipping levels where it is promoted
+fn slot_positions(index: usize, tree_size: usize) -> TreeResult<Vec<Position>> {
+    if index >= tree_size {
+        return Err(TreeError::index_out_of_bounds());
+    }
+
+    let mut positions = Vec::new();
+    let (mut index, mut size) = (index, tree_size);
+    while size > 1 {
+        if index % 2 == 1 {
+            positions.push(Position::Left);
+        } else if index + 1 < size {
+            positions.push(Position::Right);
+        }
+        index /= 2;
+        size = size.div_ceil(2);
+    }
+
+    Ok(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{IndexedProof, PairOrdering, Sha256Tree, TreeBuilder};
+
+    #[test]
+    fn test_proofs_bind_every_slot() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3", "4", "5", "6"]);
+        let tree = TreeBuilder::<Sha256>::new()
+            .without_domain_separation()
+            .leaf_hashes(leaves.iter().copied())
+            .pair_ordering(PairOrdering::Positional)
+            .build()
+            .unwrap();
+        let root = tree.root_hash().unwrap();
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.indexed_proof_at(index).unwrap();
+            assert!(proof.validate_at(root, *leaf, index, 7));
+            assert!(!proof.validate_at(root, *leaf, index, 8));
+
+            let moved = IndexedProof::<Sha256>::new(proof.proof().to_vec(), (index + 1) % 7, 7);
+            assert!(!moved.validate_at(root, *leaf, (index + 1) % 7, 7));
+
+            let decoded = IndexedProof::<Sha256>::from_bytes(&proof.to_bytes()).unwrap();
+            assert_eq!(decoded.to_bytes(), proof.to_bytes());
+            assert_eq!((decoded.index(), decoded.tree_size()), (index, 7));
+        }
+    }
+
+    #[test]
+    fn test_sorted_trees_cannot_bind_slots() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2"]);
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let error = tree.indexed_proof_at(0).err().unwrap();
+        assert_eq!(error.kind(), &TreeErrorKind::ProofMalformed);
+    }
+}
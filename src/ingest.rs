@@ -0,0 +1,488 @@
+//! Trees built from tabular records, CSV rows or JSON lines
+//!
+//! A [`LeafEncoding`] names the columns, or fields, that make up a leaf and lays their values
+//! out before hashing: either length-prefixed as text, or converted to Solidity types and
+//! packed like `abi.encodePacked`, so allow-list roots can be checked on-chain. Each record
+//! becomes one leaf, in input order.
+
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::utils::hex;
+use std::io::{BufRead, BufReader, Read};
+
+/// Solidity type a value is converted to by [`LeafEncoding::abi_packed`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbiType {
+    /// 20 bytes, from hex
+    Address,
+    /// 32 bytes big-endian, from decimal or `0x` hex
+    Uint256,
+    /// 32 bytes, from hex
+    Bytes32,
+    /// 1 byte, from `true` or `false`
+    Bool,
+    /// UTF-8 bytes of the value, unpadded
+    String,
+    /// Raw bytes, from hex, unpadded
+    Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Packing {
+    LengthPrefixed,
+    AbiPacked(Vec<AbiType>),
+}
+
+/// Columns making up a leaf and the layout of their values before hashing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeafEncoding {
+    fields: Vec<String>,
+    packing: Packing,
+}
+
+impl LeafEncoding {
+    /// Concatenates each value as `len (u32, BE) || UTF-8 text`, so no two rows encode alike
+    pub fn length_prefixed(fields: &[&str]) -> Self {
+        Self {
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+            packing: Packing::LengthPrefixed,
+        }
+    }
+
+    /// Converts each value to its Solidity type and concatenates them like `abi.encodePacked`
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::ingest::{AbiType, LeafEncoding};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let encoding = LeafEncoding::abi_packed(&[
+    ///         ("account", AbiType::Address),
+    ///         ("amount", AbiType::Uint256),
+    ///     ]);
+    ///     let packed = encoding.encode(&["0x00000000000000000000000000000000000000aa", "256"])?;
+    ///
+    ///     assert_eq!(packed.len(), 20 + 32);
+    ///     assert_eq!(packed[19], 0xaa);
+    ///     assert_eq!(packed[50..], [1, 0]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn abi_packed(fields: &[(&str, AbiType)]) -> Self {
+        Self {
+            fields: fields.iter().map(|(field, _)| field.to_string()).collect(),
+            packing: Packing::AbiPacked(fields.iter().map(|(_, abi_type)| *abi_type).collect()),
+        }
+    }
+
+    /// Names of the columns, or fields, making up a leaf, in encoding order
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Lays out the values of one record, given in the order of [`LeafEncoding::fields`]
+    ///
+    /// Fails with `InvalidEncoding` when the number of values does not match the fields or a
+    /// value does not parse as its type.
+    pub fn encode(&self, values: &[&str]) -> TreeResult<Vec<u8>> {
+        if values.len() != self.fields.len() {
+            return Err(TreeError::invalid_encoding(
+                "record does not have a value for every field",
+            ));
+        }
+
+        let mut encoded = Vec::new();
+        match &self.packing {
+            Packing::LengthPrefixed => {
+                for value in values {
+                    encoded.extend_from_slice(&(value.len() as u32).to_be_bytes());
+                    encoded.extend_from_slice(value.as_bytes());
+                }
+            }
+            Packing::AbiPacked(types) => {
+                for (value, abi_type) in values.iter().zip(types) {
+                    encoded.extend_from_slice(&pack(value, *abi_type)?);
+                }
+            }
+        }
+
+        Ok(encoded)
+    }
+
+    /// Hashes the encoded values of one record into a leaf
+    pub fn leaf<T: ToHash>(&self, values: &[&str]) -> TreeResult<T::Hash> {
+        Ok(T::try_hash(&self.encode(values)?)?)
+    }
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Builds a tree with one leaf per CSV row, reading the columns named by `encoding`
+    ///
+    /// The first row is the header. Fields follow RFC 4180: they may be quoted, with `""`
+    /// escaping a quote, and quoted fields may span lines. Empty lines are skipped.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::ingest::LeafEncoding;
+    /// use merkle_tree::{Sha256, Sha256Tree, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let csv = "name,amount,note\nalice,100,\"first, and \"\"only\"\"\"\nbob,25,\n";
+    ///     let encoding = LeafEncoding::length_prefixed(&["name", "amount"]);
+    ///     let tree = Sha256Tree::from_csv(csv.as_bytes(), &encoding)?;
+    ///
+    ///     assert_eq!(tree.leaf_count(), 2);
+    ///     assert_eq!(tree.leaves()[1], encoding.leaf::<Sha256>(&["bob", "25"])?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_csv(source: impl Read, encoding: &LeafEncoding) -> TreeResult<Self> {
+        let mut records = CsvRecords::new(source);
+        let header = records
+            .next()
+            .transpose()?
+            .ok_or_else(|| TreeError::invalid_encoding("csv has no header row"))?;
+        let columns = encoding
+            .fields()
+            .iter()
+            .map(|field| {
+                header
+                    .iter()
+                    .position(|column| column == field)
+                    .ok_or_else(|| {
+                        TreeError::invalid_encoding(&format!("csv has no column {field:?}"))
+                    })
+            })
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        let mut leaves = Vec::new();
+        for record in records {
+            let record = record?;
+            let values = columns
+                .iter()
+                .map(|column| record.get(*column).map(String::as_str))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| TreeError::invalid_encoding("csv row is missing columns"))?;
+            leaves.push(encoding.leaf::<T>(&values)?);
+        }
+
+        Ok(Self::from_leaves(&leaves))
+    }
+
+    /// Builds a tree with one leaf per JSON object line, reading the fields named by `encoding`
+    ///
+    /// String values are used as they are, numbers and booleans as their JSON text. Numbers
+    /// past `u64` lose precision in JSON parsers, so large amounts should be strings.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::ingest::{AbiType, LeafEncoding};
+    /// use merkle_tree::{Keccak256, Keccak256Tree};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let lines = r#"{"account": "0x00000000000000000000000000000000000000aa", "amount": 100}
+    /// {"account": "0x00000000000000000000000000000000000000bb", "amount": "2500"}
+    /// "#;
+    ///     let encoding = LeafEncoding::abi_packed(&[
+    ///         ("account", AbiType::Address),
+    ///         ("amount", AbiType::Uint256),
+    ///     ]);
+    ///     let tree = Keccak256Tree::from_jsonl(lines.as_bytes(), &encoding)?;
+    ///
+    ///     assert_eq!(
+    ///         tree.leaves()[1],
+    ///         encoding.leaf::<Keccak256>(&["0x00000000000000000000000000000000000000bb", "2500"])?
+    ///     );
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn from_jsonl(source: impl Read, encoding: &LeafEncoding) -> TreeResult<Self> {
+        use serde_json::Value;
+
+        let mut leaves = Vec::new();
+        for line in BufReader::new(source).lines() {
+            let line = line.map_err(|error| {
+                TreeError::storage_failed("failed to read source").with_source(error)
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record: Value = serde_json::from_str(&line).map_err(|error| {
+                TreeError::invalid_encoding("line is not valid json").with_source(error)
+            })?;
+            let values = encoding
+                .fields()
+                .iter()
+                .map(|field| match record.get(field) {
+                    Some(Value::String(value)) => Ok(value.clone()),
+                    Some(value @ (Value::Number(_) | Value::Bool(_))) => Ok(value.to_string()),
+                    _ => Err(TreeError::invalid_encoding(&format!(
+                        "json line has no scalar field {field:?}"
+                    ))),
+                })
+                .collect::<TreeResult<Vec<_>>>()?;
+            let values: Vec<_> = values.iter().map(String::as_str).collect();
+            leaves.push(encoding.leaf::<T>(&values)?);
+        }
+
+        Ok(Self::from_leaves(&leaves))
+    }
+}
+
+/// Converts a value to the `abi.encodePacked` bytes of its type
+fn pack(value: &str, abi_type: AbiType) -> TreeResult<Vec<u8>> {
+    match abi_type {
+        AbiType::Address => fixed_hex(value, 20, "address"),
+        AbiType::Bytes32 => fixed_hex(value, 32, "bytes32"),
+        AbiType::Uint256 => parse_uint256(value).map(Vec::from),
+        AbiType::Bool => match value {
+            "true" => Ok(vec![1]),
+            "false" => Ok(vec![0]),
+            _ => Err(TreeError::invalid_encoding("bool must be true or false")),
+        },
+        AbiType::String => Ok(value.as_bytes().to_vec()),
+        AbiType::Bytes => {
+            hex::decode(value).ok_or_else(|| TreeError::invalid_encoding("invalid hex"))
+        }
+    }
+}
+
+fn fixed_hex(value: &str, len: usize, type_name: &str) -> TreeResult<Vec<u8>> {
+    hex::decode(value)
+        .filter(|bytes| bytes.len() == len)
+        .ok_or_else(|| TreeError::invalid_encoding(&format!("invalid {type_name} {value:?}")))
+}
+
+/// Parses a decimal or `0x` hex integer into 32 big-endian bytes
+fn parse_uint256(value: &str) -> TreeResult<[u8; 32]> {
+    let invalid = || TreeError::invalid_encoding(&format!("invalid uint256 {value:?}"));
+    let mut word = [0u8; 32];
+    if let Some(digits) = value.strip_prefix("0x") {
+        let padded = format!("{digits:0>64}");
+        let bytes = hex::decode(&padded)
+            .filter(|bytes| bytes.len() == 32)
+            .ok_or_else(invalid)?;
+        word.copy_from_slice(&bytes);
+        return Ok(word);
+    }
+
+    if value.is_empty() {
+        return Err(invalid());
+    }
+    for digit in value.bytes() {
+        if !digit.is_ascii_digit() {
+            return Err(invalid());
+        }
+        let mut carry = (digit - b'0') as u32;
+        for byte in word.iter_mut().rev() {
+            let current = *byte as u32 * 10 + carry;
+            *byte = current as u8;
+            carry = current >> 8;
+        }
+        if carry != 0 {
+            return Err(invalid());
+        }
+    }
+
+    Ok(word)
+}
+
+/// RFC 4180 records read line by line, joining lines inside quoted fields
+struct CsvRecords<R: Read> {
+    source: BufReader<R>,
+}
+
+impl<R: Read> CsvRecords<R> {
+    fn new(source: R) -> Self {
+        Self {
+            source: BufReader::new(source),
+        }
+    }
+
+    fn read_line(&mut self, line: &mut String) -> TreeResult<usize> {
+        self.source
+            .read_line(line)
+            .map_err(|error| TreeError::storage_failed("failed to read source").with_source(error))
+    }
+
+    fn read_record(&mut self) -> TreeResult<Option<Vec<String>>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if !line.trim_end_matches(['\r', '\n']).is_empty() {
+                break;
+            }
+        }
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        loop {
+            let mut chars = line.chars().peekable();
+            while let Some(char) = chars.next() {
+                match (char, in_quotes) {
+                    ('"', true) if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    ('"', true) => in_quotes = false,
+                    ('"', false) if field.is_empty() => in_quotes = true,
+                    (',', false) => fields.push(std::mem::take(&mut field)),
+                    ('\r' | '\n', false) => {}
+                    (char, _) => field.push(char),
+                }
+            }
+            if !in_quotes {
+                break;
+            }
+
+            line.clear();
+            if self.read_line(&mut line)? == 0 {
+                return Err(TreeError::invalid_encoding(
+                    "csv ends inside a quoted field",
+                ));
+            }
+        }
+        fields.push(field);
+
+        Ok(Some(fields))
+    }
+}
+
+impl<R: Read> Iterator for CsvRecords<R> {
+    type Item = TreeResult<Vec<String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::{Keccak256, Sha256};
+
+    fn records(csv: &str) -> Vec<Vec<String>> {
+        CsvRecords::new(csv.as_bytes())
+            .collect::<TreeResult<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_csv_quoting_and_line_endings() {
+        assert_eq!(
+            records("a,b\r\n\"x, y\",\"multi\nline \"\"q\"\"\"\r\n\n,last\n"),
+            vec![
+                vec!["a".to_string(), "b".to_string()],
+                vec!["x, y".to_string(), "multi\nline \"q\"".to_string()],
+                vec![String::new(), "last".to_string()],
+            ]
+        );
+        assert_eq!(
+            CsvRecords::new("a,\"open\n".as_bytes())
+                .next()
+                .unwrap()
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::InvalidEncoding
+        );
+    }
+
+    #[test]
+    fn test_length_prefixes_keep_rows_apart() {
+        let encoding = LeafEncoding::length_prefixed(&["a", "b"]);
+        assert_ne!(
+            encoding.encode(&["ab", "c"]).unwrap(),
+            encoding.encode(&["a", "bc"]).unwrap()
+        );
+        assert_eq!(
+            encoding.encode(&["ab", ""]).unwrap(),
+            [0, 0, 0, 2, b'a', b'b', 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_abi_packing_matches_solidity_layout() {
+        let encoding = LeafEncoding::abi_packed(&[
+            ("flag", AbiType::Bool),
+            ("amount", AbiType::Uint256),
+            ("name", AbiType::String),
+            ("data", AbiType::Bytes),
+        ]);
+        let packed = encoding
+            .encode(&["true", "0x0102", "ab", "0xff00"])
+            .unwrap();
+
+        let mut expected = vec![1];
+        expected.extend_from_slice(&[0; 30]);
+        expected.extend_from_slice(&[1, 2, b'a', b'b', 0xff, 0x00]);
+        assert_eq!(packed, expected);
+    }
+
+    #[test]
+    fn test_uint256_parsing_bounds() {
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(parse_uint256(max).unwrap(), [0xff; 32]);
+        assert_eq!(parse_uint256("0").unwrap(), [0; 32]);
+        assert_eq!(parse_uint256("1000").unwrap()[30..], [0x03, 0xe8]);
+        for invalid in ["", "-1", "1.5", "0x1g", &format!("{max}0")] {
+            assert!(parse_uint256(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_csv_tree_reads_named_columns() {
+        let csv = "amount,account\n5,0x00000000000000000000000000000000000000aa\n";
+        let encoding = LeafEncoding::abi_packed(&[
+            ("account", AbiType::Address),
+            ("amount", AbiType::Uint256),
+        ]);
+        let tree = MerkleTree::<Keccak256>::from_csv(csv.as_bytes(), &encoding).unwrap();
+        assert_eq!(
+            tree.leaves(),
+            [encoding
+                .leaf::<Keccak256>(&["0x00000000000000000000000000000000000000aa", "5"])
+                .unwrap()]
+        );
+
+        let missing = LeafEncoding::length_prefixed(&["balance"]);
+        assert_eq!(
+            MerkleTree::<Sha256>::from_csv(csv.as_bytes(), &missing)
+                .err()
+                .unwrap()
+                .kind(),
+            &TreeErrorKind::InvalidEncoding
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_jsonl_and_csv_build_the_same_tree() {
+        let encoding = LeafEncoding::length_prefixed(&["name", "amount"]);
+        let csv = "name,amount\nalice,100\nbob,true\n";
+        let jsonl = "{\"name\":\"alice\",\"amount\":100}\n\n{\"amount\":true,\"name\":\"bob\"}\n";
+
+        assert_eq!(
+            MerkleTree::<Sha256>::from_jsonl(jsonl.as_bytes(), &encoding)
+                .unwrap()
+                .leaves(),
+            MerkleTree::<Sha256>::from_csv(csv.as_bytes(), &encoding)
+                .unwrap()
+                .leaves()
+        );
+        assert_eq!(
+            MerkleTree::<Sha256>::from_jsonl("{\"name\":\"carol\"}".as_bytes(), &encoding)
+                .err()
+                .unwrap()
+                .kind(),
+            &TreeErrorKind::InvalidEncoding
+        );
+    }
+}
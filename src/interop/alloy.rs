@@ -0,0 +1,75 @@
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use alloy_primitives::B256;
+
+impl<T: ToHash<Hash = [u8; 32]>> MerkleTree<T> {
+    pub fn from_b256_leaves(leaves: &[B256]) -> Self {
+        let leaves: Vec<[u8; 32]> = leaves.iter().map(|leaf| leaf.0).collect();
+        Self::from_leaves(&leaves)
+    }
+
+    pub fn append_b256(&mut self, leaf: B256) {
+        self.append(leaf.0);
+    }
+
+    /// Creates a root hash for the given tree as an alloy `B256`
+    ///
+    /// ##Examples
+    /// ```
+    /// use alloy_primitives::{keccak256, B256};
+    /// use merkle_tree::Keccak256Tree;
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<B256> = ["0", "1", "2"].iter().map(keccak256).collect();
+    ///     let tree = Keccak256Tree::from_b256_leaves(&leaves);
+    ///
+    ///     assert_eq!(tree.root_hash_b256()?, B256::from(tree.root_hash()?));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn root_hash_b256(&self) -> TreeResult<B256> {
+        self.root_hash().map(B256::from)
+    }
+
+    pub fn get_proof_b256(&self, leaf: B256) -> TreeResult<Vec<B256>> {
+        Ok(self
+            .get_proof(leaf.0)?
+            .into_iter()
+            .map(B256::from)
+            .collect())
+    }
+}
+
+impl<T: ToHash<Hash = [u8; 32]>> MerkleProof<T> {
+    pub fn from_b256(proof: &[B256]) -> Self {
+        Self::new(proof.iter().map(|sibling| sibling.0).collect())
+    }
+
+    pub fn to_b256(&self) -> Vec<B256> {
+        self.proof().iter().copied().map(B256::from).collect()
+    }
+
+    pub fn validate_b256(&self, root_hash: B256, leaf: B256) -> bool {
+        self.validate(root_hash.0, leaf.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Keccak256, Keccak256Proof, Keccak256Tree, ToHash};
+    use alloy_primitives::{keccak256, B256};
+
+    #[test]
+    fn test_b256_leaves_match_array_leaves() {
+        let leaves: Vec<B256> = ["0", "1", "2", "3", "4"].iter().map(keccak256).collect();
+        assert_eq!(leaves[0].0, Keccak256::hash("0".as_bytes()));
+
+        let tree = Keccak256Tree::from_b256_leaves(&leaves);
+        let root = tree.root_hash_b256().unwrap();
+        let proof = Keccak256Proof::from_b256(&tree.get_proof_b256(leaves[3]).unwrap());
+
+        assert_eq!(proof.to_b256(), tree.get_proof_b256(leaves[3]).unwrap());
+        assert!(proof.validate_b256(root, leaves[3]));
+    }
+}
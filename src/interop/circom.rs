@@ -0,0 +1,128 @@
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::{MerkleProof, Position};
+use serde::Serialize;
+
+/// Witness input of circom inclusion templates, e.g. circomlib-style `MerkleTreeChecker`s
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CircomInput {
+    path_elements: Vec<String>,
+    path_indices: Vec<u8>,
+}
+
+impl<T: ToHash> MerkleProof<T> {
+    /// Exports the proof as the `{ pathElements, pathIndices }` witness input of circom
+    /// inclusion templates, to be fed to snarkjs
+    ///
+    /// Siblings are written as decimal strings of their big-endian value, from the leaf up.
+    /// Each index is `0` when the running hash is the left input of its parent and `1` when
+    /// it is the right one. Sorted-pair proofs get the sides that sorting picks for `leaf`, so
+    /// a circuit hashing `(left, right)` by those indices reproduces the root.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{PairOrdering, Sha256, ToHash, TreeBuilder};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let tree = TreeBuilder::<Sha256>::new()
+    ///         .without_domain_separation()
+    ///         .pair_ordering(PairOrdering::Positional)
+    ///         .leaf_data(b"alice")
+    ///         .leaf_data(b"bob")
+    ///         .leaf_data(b"carol")
+    ///         .leaf_data(b"dave")
+    ///         .build()?;
+    ///
+    ///     let input = tree.merkle_proof_at(2)?.to_circom_input(tree.leaves()[2]);
+    ///     assert!(input.ends_with(r#""pathIndices":[0,1]}"#));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_circom_input(&self, leaf: T::Hash) -> String {
+        let input = CircomInput {
+            path_elements: self
+                .proof()
+                .iter()
+                .map(|sibling| to_decimal(sibling.as_ref()))
+                .collect(),
+            path_indices: self
+                .resolve_positions(leaf)
+                .into_iter()
+                .map(|position| match position {
+                    Position::Right => 0,
+                    Position::Left => 1,
+                })
+                .collect(),
+        };
+
+        serde_json::to_string(&input).expect("circom input always serializes")
+    }
+}
+
+/// Decimal representation of a big-endian unsigned integer
+fn to_decimal(bytes: &[u8]) -> String {
+    let mut value = bytes.to_vec();
+    let mut digits = Vec::new();
+    while value.iter().any(|byte| *byte != 0) {
+        let mut remainder = 0u32;
+        for byte in value.iter_mut() {
+            let current = (remainder << 8) | *byte as u32;
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+
+    String::from_utf8(digits).expect("digits are ascii")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_decimal_conversion() {
+        assert_eq!(to_decimal(&[]), "0");
+        assert_eq!(to_decimal(&[0, 0]), "0");
+        assert_eq!(to_decimal(&[1, 0]), "256");
+        assert_eq!(to_decimal(&[0xff; 8]), u64::MAX.to_string());
+        assert_eq!(
+            to_decimal(&[0xff; 16]),
+            "340282366920938463463374607431768211455"
+        );
+    }
+
+    #[test]
+    fn test_indices_rebuild_the_root() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3", "4"]);
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let root = tree.root_hash().unwrap();
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = MerkleProof::<Sha256>::new(tree.get_proof_at(index).unwrap());
+            let input: serde_json::Value =
+                serde_json::from_str(&proof.to_circom_input(*leaf)).unwrap();
+
+            let indices = input["pathIndices"].as_array().unwrap();
+            let mut current = *leaf;
+            for (sibling, path_index) in proof.proof().iter().zip(indices) {
+                current = match path_index.as_u64() {
+                    Some(0) => Sha256::hash_node(current, *sibling),
+                    _ => Sha256::hash_node(*sibling, current),
+                };
+            }
+            assert_eq!(current, root);
+            assert_eq!(
+                input["pathElements"][0],
+                to_decimal(&proof.proof()[0]).as_str()
+            );
+        }
+    }
+}
@@ -0,0 +1,406 @@
+//! ICS-23 commitment proofs, as verified by IBC light clients
+//!
+//! The structures mirror the `cosmos.ics23.v1` protobuf messages and encode to the same
+//! bytes, so a proof exported here can be submitted wherever an ICS-23 `CommitmentProof` is
+//! expected. The leaf is committed as `hash(0x00 || key || value)` and interior nodes as
+//! `hash(0x01 || left || right)`, so only trees of
+//! [`DomainSeparated`](crate::DomainSeparated) hashers can be exported: verifiers reject inner
+//! operations whose prefix starts with the leaf prefix, which an empty prefix always does.
+use crate::error::tree_error::{TreeError, TreeErrorKind};
+use crate::hash::to_hash::ToHash;
+use crate::hash::{Keccak256, Sha2_256};
+use crate::merkle_proof::Position;
+use crate::merkle_tree::{path_from_levels, MerkleTree, TreeResult};
+use crate::tree_policy::PairOrdering;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashOp {
+    NoHash = 0,
+    Sha256 = 1,
+    Keccak256 = 3,
+}
+
+impl HashOp {
+    /// Operation matching the hasher, which must be SHA-256 or Keccak-256
+    pub fn of<T: ToHash>() -> TreeResult<Self> {
+        match T::ALGORITHM {
+            "sha2-256" => Ok(Self::Sha256),
+            "keccak256" => Ok(Self::Keccak256),
+            _ => Err(TreeError::unknown_algorithm()),
+        }
+    }
+
+    fn apply(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::NoHash => data.to_vec(),
+            Self::Sha256 => Sha2_256::hash(data).to_vec(),
+            Self::Keccak256 => Keccak256::hash(data).to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthOp {
+    NoPrefix = 0,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeafOp {
+    pub hash: HashOp,
+    pub prehash_key: HashOp,
+    pub prehash_value: HashOp,
+    pub length: LengthOp,
+    pub prefix: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerOp {
+    pub hash: HashOp,
+    pub prefix: Vec<u8>,
+    pub suffix: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistenceProof {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+    pub leaf: LeafOp,
+    pub path: Vec<InnerOp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitmentProof {
+    Exist(ExistenceProof),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerSpec {
+    pub child_order: Vec<i32>,
+    pub child_size: i32,
+    pub min_prefix_length: i32,
+    pub max_prefix_length: i32,
+    pub empty_child: Vec<u8>,
+    pub hash: HashOp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofSpec {
+    pub leaf_spec: LeafOp,
+    pub inner_spec: InnerSpec,
+    pub max_depth: i32,
+    pub min_depth: i32,
+}
+
+impl LeafOp {
+    /// Leaf operation of trees hashed with `T`, which must be domain separated
+    pub fn of<T: ToHash>() -> TreeResult<Self> {
+        if !T::DOMAIN_SEPARATED {
+            return Err(TreeError::new(
+                TreeErrorKind::UnknownAlgorithm,
+                "ICS-23 proofs need a domain separated hasher",
+            ));
+        }
+
+        Ok(Self {
+            hash: HashOp::of::<T>()?,
+            prehash_key: HashOp::NoHash,
+            prehash_value: HashOp::NoHash,
+            length: LengthOp::NoPrefix,
+            prefix: vec![LEAF_PREFIX],
+        })
+    }
+
+    pub fn apply(&self, key: &[u8], value: &[u8]) -> Vec<u8> {
+        let key = self.prehash_key.apply(key);
+        let value = self.prehash_value.apply(value);
+        self.hash
+            .apply(&[self.prefix.as_slice(), &key, &value].concat())
+    }
+
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_uint(&mut bytes, 1, self.hash as u64);
+        write_uint(&mut bytes, 2, self.prehash_key as u64);
+        write_uint(&mut bytes, 3, self.prehash_value as u64);
+        write_uint(&mut bytes, 4, self.length as u64);
+        write_bytes(&mut bytes, 5, &self.prefix);
+        bytes
+    }
+}
+
+impl InnerOp {
+    pub fn apply(&self, child: &[u8]) -> Vec<u8> {
+        self.hash
+            .apply(&[self.prefix.as_slice(), child, &self.suffix].concat())
+    }
+
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_uint(&mut bytes, 1, self.hash as u64);
+        write_bytes(&mut bytes, 2, &self.prefix);
+        write_bytes(&mut bytes, 3, &self.suffix);
+        bytes
+    }
+}
+
+impl ExistenceProof {
+    /// Root the proof commits to, to be compared with the trusted root
+    pub fn calculate_root(&self) -> Vec<u8> {
+        self.path
+            .iter()
+            .fold(self.leaf.apply(&self.key, &self.value), |node, inner| {
+                inner.apply(&node)
+            })
+    }
+
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_bytes(&mut bytes, 1, &self.key);
+        write_bytes(&mut bytes, 2, &self.value);
+        write_message(&mut bytes, 3, &self.leaf.encode_to_vec());
+        for inner in &self.path {
+            write_message(&mut bytes, 4, &inner.encode_to_vec());
+        }
+        bytes
+    }
+}
+
+impl CommitmentProof {
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match self {
+            Self::Exist(proof) => write_message(&mut bytes, 1, &proof.encode_to_vec()),
+        }
+        bytes
+    }
+}
+
+impl InnerSpec {
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut child_order = Vec::new();
+        for child in &self.child_order {
+            write_varint(&mut child_order, *child as u64);
+        }
+        write_bytes(&mut bytes, 1, &child_order);
+        write_uint(&mut bytes, 2, self.child_size as u64);
+        write_uint(&mut bytes, 3, self.min_prefix_length as u64);
+        write_uint(&mut bytes, 4, self.max_prefix_length as u64);
+        write_bytes(&mut bytes, 5, &self.empty_child);
+        write_uint(&mut bytes, 6, self.hash as u64);
+        bytes
+    }
+}
+
+impl ProofSpec {
+    /// Spec of trees hashed with `T`, for registering the tree's commitments with a light client
+    ///
+    /// Fails with `UnknownAlgorithm` unless `T` is domain separated. Sorted trees hash the
+    /// greater child first, so a sibling may appear in either the prefix or the suffix of an
+    /// inner operation; the prefix length bound allows for both.
+    pub fn of<T: ToHash>() -> TreeResult<Self> {
+        Ok(Self {
+            leaf_spec: LeafOp::of::<T>()?,
+            inner_spec: InnerSpec {
+                child_order: vec![0, 1],
+                child_size: T::hash_len() as i32,
+                min_prefix_length: 1,
+                max_prefix_length: 1,
+                empty_child: Vec::new(),
+                hash: HashOp::of::<T>()?,
+            },
+            max_depth: 0,
+            min_depth: 0,
+        })
+    }
+
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_message(&mut bytes, 1, &self.leaf_spec.encode_to_vec());
+        write_message(&mut bytes, 2, &self.inner_spec.encode_to_vec());
+        write_uint(&mut bytes, 3, self.max_depth as u64);
+        write_uint(&mut bytes, 4, self.min_depth as u64);
+        bytes
+    }
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Creates the ICS-23 existence proof of the leaf at `index`, committed as `key || value`
+    ///
+    /// The leaf must have been hashed from the concatenation of `key` and `value` with the
+    /// tree's hasher, otherwise `LeafNotFound` is returned. Fails with `UnknownAlgorithm`
+    /// unless the hasher is domain separated.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::ics23::{CommitmentProof, ProofSpec};
+    /// use merkle_tree::{DomainSeparated, Sha2_256, TreeBuilder};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let tree = TreeBuilder::<Sha2_256>::new()
+    ///         .leaf_data(b"alice=10")
+    ///         .leaf_data(b"bob=20")
+    ///         .leaf_data(b"carol=30")
+    ///         .build()?;
+    ///
+    ///     let proof = tree.ics23_existence_proof(1, b"bob", b"=20")?;
+    ///     assert_eq!(proof.calculate_root(), tree.root_hash()?);
+    ///
+    ///     let bytes = CommitmentProof::Exist(proof).encode_to_vec();
+    ///     let spec = ProofSpec::of::<DomainSeparated<Sha2_256>>()?;
+    ///     assert_eq!(spec.leaf_spec.prefix, [0x00]);
+    ///     assert!(!bytes.is_empty());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn ics23_existence_proof(
+        &self,
+        index: usize,
+        key: &[u8],
+        value: &[u8],
+    ) -> TreeResult<ExistenceProof> {
+        let leaf_op = LeafOp::of::<T>()?;
+        let leaf = self
+            .leaf_at(index)
+            .ok_or_else(TreeError::index_out_of_bounds)?;
+        if T::try_hash(&[key, value].concat())? != leaf {
+            return Err(TreeError::leaf_not_found());
+        }
+
+        let hash = HashOp::of::<T>()?;
+        let policy = self.policy();
+        let mut node = leaf;
+        let mut path = Vec::new();
+        for (sibling, position) in path_from_levels::<T>(self.cached_levels()?, index, &policy)? {
+            let sibling_first = match policy.pair_ordering {
+                PairOrdering::Sorted => sibling > node,
+                PairOrdering::Positional | PairOrdering::Ascending => position == Position::Left,
+            };
+            let (prefix, suffix) = match sibling_first {
+                true => ([&[NODE_PREFIX], sibling.as_ref()].concat(), Vec::new()),
+                false => (vec![NODE_PREFIX], sibling.as_ref().to_vec()),
+            };
+            path.push(InnerOp {
+                hash,
+                prefix,
+                suffix,
+            });
+            node = policy.combine::<T>(node, sibling)?;
+        }
+
+        Ok(ExistenceProof {
+            key: key.to_vec(),
+            value: value.to_vec(),
+            leaf: leaf_op,
+            path,
+        })
+    }
+}
+
+fn write_uint(bytes: &mut Vec<u8>, field: u64, value: u64) {
+    if value != 0 {
+        write_varint(bytes, field << 3);
+        write_varint(bytes, value);
+    }
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, field: u64, value: &[u8]) {
+    if !value.is_empty() {
+        write_message(bytes, field, value);
+    }
+}
+
+fn write_message(bytes: &mut Vec<u8>, field: u64, message: &[u8]) {
+    write_varint(bytes, field << 3 | 2);
+    write_varint(bytes, message.len() as u64);
+    bytes.extend_from_slice(message);
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::DomainSeparated;
+    use crate::tree_builder::TreeBuilder;
+    use crate::tree_policy::OddLeafPolicy;
+
+    #[test]
+    fn test_every_leaf_calculates_the_root() {
+        let data: Vec<String> = (0..7).map(|i| format!("key{i}=value{i}")).collect();
+        for ordering in [
+            PairOrdering::Sorted,
+            PairOrdering::Positional,
+            PairOrdering::Ascending,
+        ] {
+            let tree = data
+                .iter()
+                .fold(TreeBuilder::<Keccak256>::new(), |builder, leaf| {
+                    builder.leaf_data(leaf.as_bytes())
+                })
+                .pair_ordering(ordering)
+                .odd_leaf_policy(OddLeafPolicy::DuplicateLast)
+                .build()
+                .unwrap();
+            let root = tree.root_hash().unwrap();
+
+            for (index, leaf) in data.iter().enumerate() {
+                let (key, value) = leaf.split_at(4);
+                let proof = tree
+                    .ics23_existence_proof(index, key.as_bytes(), value.as_bytes())
+                    .unwrap();
+                assert_eq!(proof.calculate_root(), root);
+                assert!(proof.path.iter().all(|inner| inner.prefix.len() % 32 == 1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_protobuf_encoding() {
+        type Hasher = DomainSeparated<Sha2_256>;
+        let tree = MerkleTree::<Hasher>::from_leaves(&[Hasher::hash(b"ab"), [0; 32]]);
+        let proof = tree.ics23_existence_proof(0, b"a", b"b").unwrap();
+        assert!(tree.ics23_existence_proof(1, b"a", b"b").is_err());
+
+        let bytes = CommitmentProof::Exist(proof).encode_to_vec();
+        let inner = [&[0x08, 0x01, 0x12, 0x01, 0x01, 0x1a, 0x20][..], &[0; 32]].concat();
+        let exist = [
+            &[
+                0x0a, 0x01, b'a', 0x12, 0x01, b'b', 0x1a, 0x05, 0x08, 0x01, 0x2a, 0x01, 0x00, 0x22,
+                0x27,
+            ][..],
+            &inner,
+        ]
+        .concat();
+        assert_eq!(bytes, [&[0x0a, exist.len() as u8][..], &exist].concat());
+
+        let spec = ProofSpec::of::<Hasher>().unwrap();
+        assert_eq!(spec.inner_spec.min_prefix_length, 1);
+        assert!(ProofSpec::of::<crate::hash::Sha256>().is_err());
+    }
+
+    #[test]
+    fn test_hashers_without_domain_separation_are_refused() {
+        let tree = MerkleTree::<Sha2_256>::from_leaves(&[Sha2_256::hash(b"ab"), [7; 32]]);
+
+        assert_eq!(
+            tree.ics23_existence_proof(0, b"a", b"b")
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::UnknownAlgorithm
+        );
+        assert_eq!(
+            ProofSpec::of::<Sha2_256>().unwrap_err().kind(),
+            &TreeErrorKind::UnknownAlgorithm
+        );
+    }
+}
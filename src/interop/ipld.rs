@@ -0,0 +1,252 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::tree_policy::PairOrdering;
+use crate::utils::base32;
+use std::fmt::{Display, Formatter, Result};
+
+const CID_VERSION: u64 = 1;
+const RAW_CODEC: u64 = 0x55;
+const DAG_CBOR_CODEC: u64 = 0x71;
+const CBOR_LINK_TAG: u8 = 42;
+
+/// Content identifier (CIDv1) of an IPLD block
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cid {
+    bytes: Vec<u8>,
+}
+
+impl Cid {
+    fn new<T: ToHash>(codec: u64, digest: T::Hash) -> TreeResult<Self> {
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, CID_VERSION);
+        write_varint(&mut bytes, codec);
+        write_varint(&mut bytes, multihash_code(T::ALGORITHM)?);
+        write_varint(&mut bytes, T::hash_len() as u64);
+        bytes.extend_from_slice(digest.as_ref());
+        Ok(Self { bytes })
+    }
+
+    /// Binary form of the CID
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Multicodec of the block: `0x71` (dag-cbor) for interior nodes, `0x55` (raw) for leaves
+    pub fn codec(&self) -> u64 {
+        self.bytes[1] as u64
+    }
+}
+
+impl Display for Cid {
+    /// Multibase base32 form, e.g. `bafyrei…`
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "b{}", base32::encode(&self.bytes).to_ascii_lowercase())
+    }
+}
+
+/// dag-cbor block of an interior node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpldBlock {
+    pub cid: Cid,
+    pub data: Vec<u8>,
+}
+
+/// A tree exported as IPLD blocks, see [`MerkleTree::to_ipld`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpldDag {
+    pub root: Cid,
+    pub blocks: Vec<IpldBlock>,
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Exports the tree as dag-cbor blocks linked by CIDv1s, using the tree's hasher as the
+    /// multihash
+    ///
+    /// Every interior node becomes a block `{"hash": node hash, "links": [child, child]}`, its
+    /// children in the order they were hashed. Leaves are linked as raw CIDs of their hashes,
+    /// so they resolve to the leaf data when it is stored in IPFS as raw blocks. Promoted odd
+    /// nodes are linked directly. Only plain sha2-256, sha3-256, sha3-512 and keccak256
+    /// hashers have a multihash code; others fail with `UnknownAlgorithm`.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha2_256, MerkleTree, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = ["a", "b", "c"].iter().map(|leaf| Sha2_256::hash(leaf.as_bytes())).collect();
+    ///     let dag = MerkleTree::<Sha2_256>::from_leaves(&leaves).to_ipld()?;
+    ///
+    ///     assert_eq!(dag.blocks.len(), 2);
+    ///     assert!(dag.root.to_string().starts_with("bafyrei"));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_ipld(&self) -> TreeResult<IpldDag> {
+        if T::DOMAIN_SEPARATED {
+            return Err(TreeError::unknown_algorithm());
+        }
+
+        let levels = self.levels()?;
+        let policy = self.policy();
+        let mut blocks = Vec::new();
+        let mut cids = self
+            .leaves()
+            .iter()
+            .map(|leaf| Cid::new::<T>(RAW_CODEC, *leaf))
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        for level in levels.iter().take(levels.len() - 1) {
+            let mut parents = Vec::new();
+            for (pair, pair_cids) in level.chunks(2).zip(cids.chunks(2)) {
+                let (left, left_cid, right, right_cid) = match (pair, pair_cids) {
+                    ([left, right], [left_cid, right_cid]) => {
+                        (*left, left_cid.clone(), *right, right_cid.clone())
+                    }
+                    _ => match policy.odd_sibling::<T>(pair[0])? {
+                        None => {
+                            parents.push(pair_cids[0].clone());
+                            continue;
+                        }
+                        Some(sibling) if sibling == pair[0] => {
+                            (pair[0], pair_cids[0].clone(), sibling, pair_cids[0].clone())
+                        }
+                        Some(sibling) => (
+                            pair[0],
+                            pair_cids[0].clone(),
+                            sibling,
+                            Cid::new::<T>(RAW_CODEC, sibling)?,
+                        ),
+                    },
+                };
+
+                let (first, second) = match policy.pair_ordering {
+                    PairOrdering::Sorted if left <= right => (right_cid, left_cid),
+                    PairOrdering::Ascending if left > right => (right_cid, left_cid),
+                    _ => (left_cid, right_cid),
+                };
+                let parent = policy.combine::<T>(left, right)?;
+                let data = encode_node(parent.as_ref(), &first, &second);
+                let cid = Cid::new::<T>(DAG_CBOR_CODEC, T::try_hash(&data)?)?;
+
+                blocks.push(IpldBlock {
+                    cid: cid.clone(),
+                    data,
+                });
+                parents.push(cid);
+            }
+            cids = parents;
+        }
+
+        Ok(IpldDag {
+            root: cids.swap_remove(0),
+            blocks,
+        })
+    }
+}
+
+fn multihash_code(algorithm: &str) -> TreeResult<u64> {
+    match algorithm {
+        "sha2-256" => Ok(0x12),
+        "sha3-512" => Ok(0x14),
+        "sha3-256" => Ok(0x16),
+        "keccak256" => Ok(0x1b),
+        _ => Err(TreeError::unknown_algorithm()),
+    }
+}
+
+/// Encodes `{"hash": bytes, "links": [link, link]}` as canonical dag-cbor
+fn encode_node(hash: &[u8], first: &Cid, second: &Cid) -> Vec<u8> {
+    let mut data = vec![0xa2];
+    write_cbor_text(&mut data, "hash");
+    write_cbor_header(&mut data, 2, hash.len() as u64);
+    data.extend_from_slice(hash);
+    write_cbor_text(&mut data, "links");
+    write_cbor_header(&mut data, 4, 2);
+    for link in [first, second] {
+        data.extend_from_slice(&[0xd8, CBOR_LINK_TAG]);
+        // links are byte strings of the binary CID behind the identity multibase prefix
+        write_cbor_header(&mut data, 2, link.bytes.len() as u64 + 1);
+        data.push(0x00);
+        data.extend_from_slice(&link.bytes);
+    }
+    data
+}
+
+fn write_cbor_text(data: &mut Vec<u8>, text: &str) {
+    write_cbor_header(data, 3, text.len() as u64);
+    data.extend_from_slice(text.as_bytes());
+}
+
+fn write_cbor_header(data: &mut Vec<u8>, major: u8, len: u64) {
+    let major = major << 5;
+    match len {
+        0..=23 => data.push(major | len as u8),
+        24..=0xff => data.extend_from_slice(&[major | 24, len as u8]),
+        0x100..=0xffff => {
+            data.push(major | 25);
+            data.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            data.push(major | 26);
+            data.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{DomainSeparated, Sha256, Sha2_256};
+    use crate::tree_policy::OddLeafPolicy;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+
+    #[test]
+    fn test_blocks_link_children_and_hash_to_their_cids() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha2_256>(&["0", "1", "2"]);
+        let tree = MerkleTree::<Sha2_256>::from_leaves(&leaves);
+        let dag = tree.to_ipld().unwrap();
+
+        // [01] and the root; 2 is promoted and linked from the root directly
+        assert_eq!(dag.blocks.len(), 2);
+        assert_eq!(dag.root, dag.blocks[1].cid);
+        assert_eq!(dag.root.codec(), DAG_CBOR_CODEC);
+        for block in &dag.blocks {
+            let expected = Cid::new::<Sha2_256>(DAG_CBOR_CODEC, Sha2_256::hash(&block.data));
+            assert_eq!(block.cid, expected.unwrap());
+        }
+
+        let leaf_cid = Cid::new::<Sha2_256>(RAW_CODEC, leaves[2]).unwrap();
+        assert_eq!(
+            leaf_cid.to_string(),
+            format!("bafkrei{}", &leaf_cid.to_string()[7..])
+        );
+        let root_block = &dag.blocks[1].data;
+        assert!(root_block
+            .windows(leaf_cid.as_bytes().len())
+            .any(|window| window == leaf_cid.as_bytes()));
+        assert!(root_block
+            .windows(32)
+            .any(|window| window == tree.root_hash().unwrap()));
+    }
+
+    #[test]
+    fn test_duplicated_odd_nodes_and_unsupported_hashers() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2"]);
+        let tree = MerkleTree::<Sha256>::from_leaves(&leaves)
+            .with_odd_leaf_policy(OddLeafPolicy::DuplicateLast);
+        assert_eq!(tree.to_ipld().unwrap().blocks.len(), 3);
+
+        let separated = raw_leaves_to_hashed_leaves::<DomainSeparated<Sha256>>(&["0"]);
+        let tree = MerkleTree::<DomainSeparated<Sha256>>::from_leaves(&separated);
+        assert!(tree.to_ipld().is_err());
+    }
+}
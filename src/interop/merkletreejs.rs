@@ -0,0 +1,120 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::{MerkleProof, Position};
+use crate::merkle_tree::TreeResult;
+use crate::utils::hex;
+use serde::{Deserialize, Serialize};
+
+/// Proof node as emitted by `MerkleTree.getProof` in merkletreejs, with hex-encoded data
+#[derive(Serialize, Deserialize)]
+struct MerkleTreeJsNode {
+    position: Position,
+    data: String,
+}
+
+impl<T: ToHash> MerkleProof<T> {
+    /// Exports the proof as a merkletreejs `[{ position, data }]` JSON array
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Keccak256, Keccak256Proof, Keccak256Tree, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves = [
+    ///         Keccak256::hash("0".as_bytes()),
+    ///         Keccak256::hash("1".as_bytes()),
+    ///         Keccak256::hash("2".as_bytes()),
+    ///     ];
+    ///     let tree = Keccak256Tree::from_leaves(&leaves);
+    ///     let proof = Keccak256Proof::new(tree.get_proof(leaves[2])?);
+    ///
+    ///     let json = proof.to_merkletreejs_json(leaves[2]);
+    ///     let imported = Keccak256Proof::from_merkletreejs_json(&json)?;
+    ///     assert!(imported.validate(tree.root_hash()?, leaves[2]));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_merkletreejs_json(&self, leaf: T::Hash) -> String {
+        let nodes: Vec<MerkleTreeJsNode> = self
+            .proof()
+            .iter()
+            .zip(self.resolve_positions(leaf))
+            .map(|(sibling, position)| MerkleTreeJsNode {
+                position,
+                data: format!("0x{}", hex::encode(sibling.as_ref())),
+            })
+            .collect();
+
+        serde_json::to_string(&nodes).expect("proof nodes always serialize")
+    }
+
+    /// Parses a merkletreejs `[{ position, data }]` JSON array into a positional proof
+    pub fn from_merkletreejs_json(json: &str) -> TreeResult<Self> {
+        let nodes: Vec<MerkleTreeJsNode> = serde_json::from_str(json)
+            .map_err(|_| TreeError::proof_malformed("invalid merkletreejs proof json"))?;
+
+        let mut proof = Vec::with_capacity(nodes.len());
+        let mut positions = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let bytes = hex::decode(&node.data)
+                .ok_or_else(|| TreeError::proof_malformed("invalid hex in proof node"))?;
+            let hash = T::Hash::try_from(bytes.as_slice())
+                .map_err(|_| TreeError::proof_malformed("hash length does not match the hasher"))?;
+            proof.push(hash);
+            positions.push(node.position);
+        }
+
+        Self::with_positions(proof, positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Keccak256;
+    use crate::merkle_proof::Position;
+    use crate::utils::test::{full_root_hash, raw_leaves_to_hashed_leaves};
+    use crate::{Keccak256Proof, Keccak256Tree};
+
+    #[test]
+    fn test_merkletreejs_round_trip() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Keccak256>(&leaves_raw);
+
+        let tree = Keccak256Tree::from_leaves(&leaves);
+        let proof = Keccak256Proof::new(tree.get_proof(leaves[3]).unwrap());
+        let imported =
+            Keccak256Proof::from_merkletreejs_json(&proof.to_merkletreejs_json(leaves[3])).unwrap();
+
+        assert_eq!(imported.proof(), proof.proof());
+        assert!(imported.validate(full_root_hash::<Keccak256>(&leaves), leaves[3]));
+    }
+
+    #[test]
+    fn test_merkletreejs_positional_import() {
+        let leaves_raw = ["a", "b"];
+        let leaves = raw_leaves_to_hashed_leaves::<Keccak256>(&leaves_raw);
+        let json = format!(
+            r#"[{{"position":"left","data":"0x{}"}}]"#,
+            crate::utils::hex::encode(&leaves[0])
+        );
+
+        let proof = Keccak256Proof::from_merkletreejs_json(&json).unwrap();
+        assert_eq!(proof.positions(), Some(&[Position::Left][..]));
+
+        let root = <Keccak256 as crate::ToHash>::hash(&[leaves[0], leaves[1]].concat());
+        assert!(proof.validate(root, leaves[1]));
+    }
+
+    #[test]
+    fn test_merkletreejs_rejects_wrong_hash_length() {
+        let json = r#"[{"position":"right","data":"0x00ff"}]"#;
+        assert_eq!(
+            Keccak256Proof::from_merkletreejs_json(json)
+                .err()
+                .unwrap()
+                .kind(),
+            &TreeErrorKind::ProofMalformed
+        );
+    }
+}
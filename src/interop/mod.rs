@@ -0,0 +1,12 @@
+#[cfg(feature = "alloy")]
+mod alloy;
+#[cfg(feature = "serde")]
+mod circom;
+#[cfg(feature = "ics23")]
+pub mod ics23;
+#[cfg(feature = "ipld")]
+pub(crate) mod ipld;
+#[cfg(feature = "serde")]
+mod merkletreejs;
+pub(crate) mod rs_merkle;
+pub mod solidity;
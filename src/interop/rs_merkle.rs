@@ -0,0 +1,282 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::tree_policy::{OddLeafPolicy, PairOrdering};
+use crate::utils::ct;
+
+/// Multi-proof in the format of the `rs_merkle` crate
+///
+/// `rs_merkle` hashes pairs positionally, left child first, and promotes the last node of
+/// odd levels, so its roots are those of a tree with [`PairOrdering::Positional`] and
+/// [`OddLeafPolicy::Promote`]. Its proofs are flat lists of sibling hashes: level by level
+/// from the leaves up, left to right within a level, skipping siblings that the proven
+/// leaves already determine. Serialized, they are the hashes concatenated in that order.
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::{PairOrdering, RsMerkleProof, Sha256, ToHash, TreeBuilder};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let leaves: Vec<_> = ["a", "b", "c", "d", "e", "f"].iter().map(|leaf| Sha256::hash(leaf.as_bytes())).collect();
+///     let tree = TreeBuilder::<Sha256>::new()
+///         .without_domain_separation()
+///         .leaf_hashes(leaves.iter().copied())
+///         .pair_ordering(PairOrdering::Positional)
+///         .build()?;
+///
+///     let bytes = tree.rs_merkle_proof(&[3, 4])?.to_bytes();
+///     let proof = RsMerkleProof::<Sha256>::from_bytes(&bytes)?;
+///     assert!(proof.verify(tree.root_hash()?, &[3, 4], &leaves[3..5], leaves.len()));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct RsMerkleProof<T: ToHash> {
+    proof_hashes: Vec<T::Hash>,
+}
+
+impl<T: ToHash> RsMerkleProof<T> {
+    pub fn new(proof_hashes: Vec<T::Hash>) -> Self {
+        Self { proof_hashes }
+    }
+
+    pub fn proof_hashes(&self) -> &[T::Hash] {
+        &self.proof_hashes
+    }
+
+    /// Parses a proof serialized by `rs_merkle`'s `MerkleProof::to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> TreeResult<Self> {
+        let hash_len = T::hash_len();
+        if !bytes.len().is_multiple_of(hash_len) {
+            return Err(TreeError::proof_malformed(
+                "proof length is not a multiple of the hash length",
+            ));
+        }
+
+        let proof_hashes = bytes
+            .chunks_exact(hash_len)
+            .map(|chunk| {
+                T::Hash::try_from(chunk)
+                    .map_err(|_| TreeError::proof_malformed("invalid hash in proof"))
+            })
+            .collect::<TreeResult<_>>()?;
+
+        Ok(Self::new(proof_hashes))
+    }
+
+    /// Serializes the proof the way `rs_merkle`'s `MerkleProof::to_bytes` does
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.proof_hashes
+            .iter()
+            .flat_map(|hash| hash.as_ref().iter().copied())
+            .collect()
+    }
+
+    /// Root of a tree of `total_leaves_count` leaves holding `leaf_hashes` at `leaf_indices`
+    ///
+    /// Fails with `ProofMalformed` when the indices are not distinct slots of the tree, or
+    /// when the proof holds too few or too many hashes for them.
+    pub fn root(
+        &self,
+        leaf_indices: &[usize],
+        leaf_hashes: &[T::Hash],
+        total_leaves_count: usize,
+    ) -> TreeResult<T::Hash> {
+        if leaf_indices.is_empty() || leaf_indices.len() != leaf_hashes.len() {
+            return Err(TreeError::proof_malformed(
+                "every proven leaf needs exactly one index",
+            ));
+        }
+
+        let mut layer: Vec<(usize, T::Hash)> = leaf_indices
+            .iter()
+            .copied()
+            .zip(leaf_hashes.iter().copied())
+            .collect();
+        layer.sort_by_key(|(index, _)| *index);
+        if layer.windows(2).any(|pair| pair[0].0 == pair[1].0)
+            || layer
+                .last()
+                .is_some_and(|(index, _)| *index >= total_leaves_count)
+        {
+            return Err(TreeError::proof_malformed(
+                "leaf indices are not distinct slots of the tree",
+            ));
+        }
+
+        let mut proof_hashes = self.proof_hashes.iter();
+        let mut width = total_leaves_count;
+        while width > 1 {
+            let indices: Vec<usize> = layer.iter().map(|(index, _)| *index).collect();
+            for sibling in missing_siblings(&indices, width) {
+                let hash = proof_hashes
+                    .next()
+                    .ok_or_else(|| TreeError::proof_malformed("proof is missing hashes"))?;
+                layer.push((sibling, *hash));
+            }
+            layer.sort_by_key(|(index, _)| *index);
+
+            let mut parents = Vec::with_capacity(layer.len().div_ceil(2));
+            let mut nodes = layer.iter().peekable();
+            while let Some((index, hash)) = nodes.next() {
+                let parent = match nodes.next_if(|(sibling, _)| *sibling == index ^ 1) {
+                    Some((_, right)) => T::try_hash_node(*hash, *right)?,
+                    None => *hash,
+                };
+                parents.push((index / 2, parent));
+            }
+            layer = parents;
+            width = width.div_ceil(2);
+        }
+
+        if proof_hashes.next().is_some() {
+            return Err(TreeError::proof_malformed("proof has unused hashes"));
+        }
+
+        Ok(layer[0].1)
+    }
+
+    /// Verifies that `leaf_hashes` sit at `leaf_indices` in the tree with the given root
+    pub fn verify(
+        &self,
+        root_hash: T::Hash,
+        leaf_indices: &[usize],
+        leaf_hashes: &[T::Hash],
+        total_leaves_count: usize,
+    ) -> bool {
+        match self.root(leaf_indices, leaf_hashes, total_leaves_count) {
+            Ok(root) => ct::eq(root.as_ref(), root_hash.as_ref()),
+            Err(_) => false,
+        }
+    }
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Creates an `rs_merkle` multi-proof of the leaves at `leaf_indices`
+    ///
+    /// Fails with `ProofMalformed` unless the tree orders pairs positionally and promotes odd
+    /// nodes, the only shape `rs_merkle` builds.
+    pub fn rs_merkle_proof(&self, leaf_indices: &[usize]) -> TreeResult<RsMerkleProof<T>> {
+        let policy = self.policy();
+        if policy.pair_ordering != PairOrdering::Positional
+            || policy.odd_leaf != OddLeafPolicy::Promote
+        {
+            return Err(TreeError::proof_malformed(
+                "rs_merkle proofs need a positional tree that promotes odd nodes",
+            ));
+        }
+        if leaf_indices.iter().any(|index| *index >= self.leaf_count()) {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let levels = self.cached_levels()?;
+        let mut indices = leaf_indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut proof_hashes = Vec::new();
+        for level in levels.iter().take(levels.len() - 1) {
+            proof_hashes.extend(
+                missing_siblings(&indices, level.len())
+                    .into_iter()
+                    .map(|sibling| level[sibling]),
+            );
+            indices = indices.into_iter().map(|index| index / 2).collect();
+            indices.dedup();
+        }
+
+        Ok(RsMerkleProof::new(proof_hashes))
+    }
+}
+
+/// Siblings of the sorted `indices` that are neither among them nor past the end of a level
+/// of `width` nodes, in the order `rs_merkle` stores them
+fn missing_siblings(indices: &[usize], width: usize) -> Vec<usize> {
+    indices
+        .iter()
+        .map(|index| index ^ 1)
+        .filter(|sibling| *sibling < width && indices.binary_search(sibling).is_err())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{Sha256Tree, TreeBuilder};
+
+    fn positional_tree(leaves: &[[u8; 32]]) -> Sha256Tree {
+        TreeBuilder::<Sha256>::new()
+            .without_domain_separation()
+            .leaf_hashes(leaves.iter().copied())
+            .pair_ordering(PairOrdering::Positional)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rs_merkle_layout() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["a", "b", "c", "d", "e", "f"]);
+        let tree = positional_tree(&leaves);
+        let root = tree.root_hash().unwrap();
+
+        // siblings of 3 and 4, then of their parents 1 and 2, the right one being promoted
+        let proof = tree.rs_merkle_proof(&[4, 3]).unwrap();
+        assert_eq!(
+            proof.proof_hashes(),
+            [
+                leaves[2],
+                leaves[5],
+                Sha256::hash_node(leaves[0], leaves[1])
+            ]
+        );
+        assert_eq!(
+            proof.root(&[4, 3], &[leaves[4], leaves[3]], 6).unwrap(),
+            root
+        );
+        assert!(!proof.verify(root, &[3, 4], &[leaves[4], leaves[3]], 6));
+
+        let parsed = RsMerkleProof::<Sha256>::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(parsed.verify(root, &[3, 4], &leaves[3..5], 6));
+        assert!(RsMerkleProof::<Sha256>::from_bytes(&[0; 33]).is_err());
+    }
+
+    #[test]
+    fn test_single_leaf_proofs_match_positional_paths() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3", "4", "5", "6"]);
+        let tree = positional_tree(&leaves);
+        let root = tree.root_hash().unwrap();
+
+        for index in 0..leaves.len() {
+            let proof = tree.rs_merkle_proof(&[index]).unwrap();
+            assert_eq!(proof.proof_hashes(), tree.get_proof_at(index).unwrap());
+            assert!(proof.verify(root, &[index], &[leaves[index]], leaves.len()));
+        }
+
+        let everything: Vec<usize> = (0..leaves.len()).collect();
+        let proof = tree.rs_merkle_proof(&everything).unwrap();
+        assert!(proof.proof_hashes().is_empty());
+        assert!(proof.verify(root, &everything, &leaves, leaves.len()));
+    }
+
+    #[test]
+    fn test_malformed_proofs_are_rejected() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3", "4"]);
+        let tree = positional_tree(&leaves);
+        let proof = tree.rs_merkle_proof(&[1]).unwrap();
+
+        let error = proof
+            .root(&[1, 1], &[leaves[1], leaves[1]], 5)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), &TreeErrorKind::ProofMalformed);
+        assert!(proof.root(&[5], &[leaves[1]], 5).is_err());
+        assert!(proof.root(&[1], &[leaves[1]], 2).is_err());
+        assert!(proof.root(&[1], &[leaves[1]], 16).is_err());
+
+        let sorted = Sha256Tree::from_leaves(&leaves);
+        let error = sorted.rs_merkle_proof(&[1]).err().unwrap();
+        assert_eq!(error.kind(), &TreeErrorKind::ProofMalformed);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::TreeResult;
+use crate::utils::hex;
+
+const WORD_LEN: usize = 32;
+
+/// Orders a pair ascending, as OpenZeppelin's `MerkleProof` does before hashing a pair
+pub fn openzeppelin_sorted_pair<H: PartialOrd>(left: H, right: H) -> (H, H) {
+    if left <= right {
+        return (left, right);
+    }
+
+    (right, left)
+}
+
+/// Combines two nodes following OpenZeppelin's commutative `hash(min || max)` convention
+///
+/// [`ToHash::combine`] places the greater hash first, so only trees built with
+/// [`PairOrdering::Ascending`](crate::PairOrdering::Ascending) and without domain separation
+/// have the roots OpenZeppelin's `MerkleProof.verify` checks against.
+pub fn openzeppelin_combine<T: ToHash>(left: T::Hash, right: T::Hash) -> T::Hash {
+    let (low, high) = openzeppelin_sorted_pair(left, right);
+    T::hash(&[low.as_ref(), high.as_ref()].concat())
+}
+
+impl<T: ToHash> MerkleProof<T> {
+    /// ABI-encodes the proof as a standalone `bytes32[]` argument
+    ///
+    /// OpenZeppelin's `MerkleProof.verify` accepts the proofs of trees built with
+    /// [`PairOrdering::Ascending`](crate::PairOrdering::Ascending) and without domain
+    /// separation, see [`openzeppelin_combine`].
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Keccak256, Keccak256Proof, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let proof = Keccak256Proof::new(vec![Keccak256::hash("0".as_bytes())]);
+    ///     let calldata = proof.to_solidity_calldata()?;
+    ///
+    ///     // offset, length and a single element
+    ///     assert_eq!(calldata.len(), 3 * 32);
+    ///     assert_eq!(calldata[31], 0x20);
+    ///     assert_eq!(calldata[63], 1);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_solidity_calldata(&self) -> TreeResult<Vec<u8>> {
+        if T::hash_len() != WORD_LEN {
+            return Err(TreeError::hash_size_mismatch(
+                "solidity proofs require 32-byte hashes",
+            ));
+        }
+
+        let mut calldata = Vec::with_capacity((self.proof().len() + 2) * WORD_LEN);
+        calldata.extend_from_slice(&abi_word(WORD_LEN as u64));
+        calldata.extend_from_slice(&abi_word(self.proof().len() as u64));
+        for sibling in self.proof() {
+            calldata.extend_from_slice(sibling.as_ref());
+        }
+
+        Ok(calldata)
+    }
+
+    /// ABI-encodes the proof as a `0x`-prefixed hex string
+    pub fn to_solidity_calldata_hex(&self) -> TreeResult<String> {
+        Ok(format!("0x{}", hex::encode(&self.to_solidity_calldata()?)))
+    }
+}
+
+fn abi_word(value: u64) -> [u8; WORD_LEN] {
+    let mut word = [0u8; WORD_LEN];
+    word[WORD_LEN - 8..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::{Keccak256, Sha512};
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{Keccak256Proof, PairOrdering, Sha512Proof, TreeBuilder};
+
+    #[test]
+    fn test_solidity_calldata_layout() {
+        let leaves = raw_leaves_to_hashed_leaves::<Keccak256>(&["0", "1"]);
+        let proof = Keccak256Proof::new(leaves.clone());
+        let calldata = proof.to_solidity_calldata().unwrap();
+
+        assert_eq!(calldata.len(), 4 * WORD_LEN);
+        assert_eq!(&calldata[..WORD_LEN], &abi_word(32));
+        assert_eq!(&calldata[WORD_LEN..2 * WORD_LEN], &abi_word(2));
+        assert_eq!(&calldata[2 * WORD_LEN..3 * WORD_LEN], &leaves[0]);
+        assert_eq!(
+            proof.to_solidity_calldata_hex().unwrap(),
+            format!("0x{}", hex::encode(&calldata))
+        );
+    }
+
+    #[test]
+    fn test_solidity_calldata_requires_32_byte_hashes() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha512>(&["0"]);
+        assert_eq!(
+            Sha512Proof::new(leaves)
+                .to_solidity_calldata()
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::HashSizeMismatch
+        );
+    }
+
+    #[test]
+    fn test_openzeppelin_combine_is_commutative() {
+        let leaves = raw_leaves_to_hashed_leaves::<Keccak256>(&["0", "1"]);
+        let (low, high) = openzeppelin_sorted_pair(leaves[0], leaves[1]);
+
+        assert!(low <= high);
+        assert_eq!(
+            openzeppelin_combine::<Keccak256>(leaves[0], leaves[1]),
+            openzeppelin_combine::<Keccak256>(leaves[1], leaves[0])
+        );
+        assert_eq!(
+            openzeppelin_combine::<Keccak256>(leaves[0], leaves[1]),
+            Keccak256::hash(&[low, high].concat())
+        );
+    }
+
+    #[test]
+    fn test_ascending_trees_pass_openzeppelin_verification() {
+        let leaves = raw_leaves_to_hashed_leaves::<Keccak256>(&["0", "1", "2", "3", "4", "5"]);
+        let tree = TreeBuilder::<Keccak256>::new()
+            .without_domain_separation()
+            .leaf_hashes(leaves.iter().copied())
+            .pair_ordering(PairOrdering::Ascending)
+            .build()
+            .unwrap();
+        let root = tree.root_hash().unwrap();
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.merkle_proof_at(index).unwrap();
+            assert!(proof.validate(root, *leaf));
+
+            // MerkleProof.processProof: fold the bytes32[] with the commutative hash
+            let calldata = proof.to_solidity_calldata().unwrap();
+            let computed =
+                calldata[2 * WORD_LEN..]
+                    .chunks(WORD_LEN)
+                    .fold(*leaf, |current, sibling| {
+                        openzeppelin_combine::<Keccak256>(current, sibling.try_into().unwrap())
+                    });
+            assert_eq!(computed, root);
+        }
+    }
+}
@@ -0,0 +1,658 @@
+//! Jellyfish Merkle Tree, the versioned sparse merkle tree of Diem and Aptos
+//!
+//! Keys are hashed and addressed by the nibbles of their hash. Internal nodes have up to 16
+//! children and are hashed as a four level binary sparse merkle tree over them, in which an
+//! empty subtree hashes to an all-zero placeholder and a subtree holding a single leaf hashes
+//! to that leaf. Updates write new nodes keyed by `(version, nibble path)` and never touch the
+//! nodes of older versions, so every version stays readable and provable.
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+use crate::store::KeyedNodeStore;
+use crate::utils::ct;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+const NULL_TAG: u8 = 0;
+const INTERNAL_TAG: u8 = 1;
+const LEAF_TAG: u8 = 2;
+const CHILDREN: usize = 16;
+
+#[derive(Clone, Copy)]
+struct Child<H> {
+    hash: H,
+    version: u64,
+    is_leaf: bool,
+}
+
+type Children<H> = [Option<Child<H>>; CHILDREN];
+
+enum Node<H> {
+    Null,
+    Internal(Children<H>),
+    Leaf { key_hash: H, value: Vec<u8> },
+}
+
+/// Versioned sparse merkle tree with 16-ary internal nodes over a [`KeyedNodeStore`]
+///
+/// Versions are written in sequence starting at 0, each one applying a set of updates on top
+/// of the previous version.
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::jmt::JellyfishMerkleTree;
+/// use merkle_tree::store::MemoryStore;
+/// use merkle_tree::Sha256;
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut tree = JellyfishMerkleTree::<Sha256, _>::new(MemoryStore::new());
+///     let first = tree.put_value_set(&[(b"alice", Some(b"10")), (b"bob", Some(b"20"))], 0)?;
+///     let second = tree.put_value_set(&[(b"alice", Some(b"15"))], 1)?;
+///
+///     let (value, proof) = tree.get_with_proof(b"alice", 0)?;
+///     assert_eq!(value.as_deref(), Some(&b"10"[..]));
+///     assert!(proof.validate(first, b"alice", Some(b"10")));
+///
+///     let (value, proof) = tree.get_with_proof(b"carol", 1)?;
+///     assert_eq!(value, None);
+///     assert!(proof.validate(second, b"carol", None));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct JellyfishMerkleTree<T: ToHash, S: KeyedNodeStore> {
+    store: S,
+    hasher: PhantomData<T>,
+}
+
+/// Proof of the value of a key, or of its absence, in a [`JellyfishMerkleTree`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseMerkleProof<T: ToHash> {
+    /// Key hash and value hash of the leaf the path ends in, if it ends in one
+    leaf: Option<(T::Hash, T::Hash)>,
+    /// Siblings from the bottom of the path up to the root
+    siblings: Vec<T::Hash>,
+}
+
+impl<T: ToHash, S: KeyedNodeStore> JellyfishMerkleTree<T, S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            hasher: PhantomData,
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Applies the updates on top of the previous version and returns the root of `version`
+    ///
+    /// A value of `None` removes the key. Version 0 starts from the empty tree, any later
+    /// version requires the previous one to exist. Committed versions are never rewritten:
+    /// writing one again fails with `VersionCommitted`.
+    pub fn put_value_set(
+        &mut self,
+        updates: &[(&[u8], Option<&[u8]>)],
+        version: u64,
+    ) -> TreeResult<T::Hash> {
+        if self.node(version, &[])?.is_some() {
+            return Err(TreeError::version_committed());
+        }
+
+        let base = match version.checked_sub(1) {
+            None => None,
+            Some(base) => match self.node(base, &[])? {
+                Some(_) => Some(base),
+                None => return Err(TreeError::version_unknown()),
+            },
+        };
+
+        self.apply(updates, base, version)
+    }
+
+    /// Applies the updates on top of the root of `base` (or the empty tree), writing `version`
+    ///
+    /// With `base` equal to `version` the version is rewritten in place, which keeps a single
+    /// version alive instead of one per update.
+    pub(crate) fn apply(
+        &mut self,
+        updates: &[(&[u8], Option<&[u8]>)],
+        base: Option<u64>,
+        version: u64,
+    ) -> TreeResult<T::Hash> {
+        let mut root_version = base;
+        for (key, value) in updates {
+            let key_hash = T::try_hash(key)?;
+            let root = match root_version {
+                Some(root_version) => self.existing_node(root_version, &[])?,
+                None => Node::Null,
+            };
+            match self.update(
+                root,
+                root_version,
+                &mut Vec::new(),
+                &key_hash,
+                *value,
+                version,
+            )? {
+                None => self.put_node(version, &[], &Node::Null)?,
+                Some(child) if child.version != version => {
+                    let root = self.existing_node(child.version, &[])?;
+                    self.put_node(version, &[], &root)?;
+                }
+                Some(_) => {}
+            }
+            root_version = Some(version);
+        }
+
+        match root_version {
+            Some(root_version) if root_version != version => {
+                let root = self.existing_node(root_version, &[])?;
+                self.put_node(version, &[], &root)?;
+            }
+            None => self.put_node(version, &[], &Node::Null)?,
+            _ => {}
+        }
+
+        self.root_hash(version)
+    }
+
+    pub fn root_hash(&self, version: u64) -> TreeResult<T::Hash> {
+        let root = self
+            .node(version, &[])?
+            .ok_or_else(TreeError::version_unknown)?;
+        node_hash::<T>(&root)
+    }
+
+    pub fn get(&self, key: &[u8], version: u64) -> TreeResult<Option<Vec<u8>>> {
+        Ok(self.get_with_proof(key, version)?.0)
+    }
+
+    /// Looks up the value of `key` at `version` together with the proof of it
+    pub fn get_with_proof(
+        &self,
+        key: &[u8],
+        version: u64,
+    ) -> TreeResult<(Option<Vec<u8>>, SparseMerkleProof<T>)> {
+        let key_hash = T::try_hash(key)?;
+        let mut node = self
+            .node(version, &[])?
+            .ok_or_else(TreeError::version_unknown)?;
+        let mut path = Vec::new();
+        let mut siblings = Vec::new();
+
+        let (value, leaf) = 'descend: loop {
+            let children = match node {
+                Node::Null => break (None, None),
+                Node::Leaf {
+                    key_hash: leaf_key,
+                    value,
+                } => {
+                    let leaf = Some((leaf_key, T::try_hash(&value)?));
+                    match leaf_key == key_hash {
+                        true => break (Some(value), leaf),
+                        false => break (None, leaf),
+                    }
+                }
+                Node::Internal(children) => children,
+            };
+
+            let nibble = nibble(key_hash.as_ref(), path.len());
+            let mut range = 0..CHILDREN;
+            loop {
+                let middle = range.start + range.len() / 2;
+                let (near, far) = match nibble < middle {
+                    true => (range.start..middle, middle..range.end),
+                    false => (middle..range.end, range.start..middle),
+                };
+                siblings.push(subtree_hash::<T>(&children, far)?);
+                range = near;
+
+                let mut present = range
+                    .clone()
+                    .filter_map(|index| children[index].map(|child| (index, child)));
+                match (present.next(), present.next()) {
+                    (None, _) => break 'descend (None, None),
+                    (Some((index, child)), None) if child.is_leaf || range.len() == 1 => {
+                        path.push(index as u8);
+                        node = self.existing_node(child.version, &path)?;
+                        continue 'descend;
+                    }
+                    _ => {}
+                }
+            }
+        };
+
+        siblings.reverse();
+        Ok((value, SparseMerkleProof { leaf, siblings }))
+    }
+
+    /// Applies one update below the node at `path`, returning the node's replacement
+    fn update(
+        &mut self,
+        node: Node<T::Hash>,
+        node_version: Option<u64>,
+        path: &mut Vec<u8>,
+        key_hash: &T::Hash,
+        value: Option<&[u8]>,
+        version: u64,
+    ) -> TreeResult<Option<Child<T::Hash>>> {
+        match node {
+            Node::Null => match value {
+                Some(value) => self.put_leaf(version, path, key_hash, value).map(Some),
+                None => Ok(None),
+            },
+            Node::Leaf {
+                key_hash: leaf_key,
+                value: leaf_value,
+            } => match value {
+                Some(value) if leaf_key == *key_hash => {
+                    self.put_leaf(version, path, key_hash, value).map(Some)
+                }
+                None if leaf_key == *key_hash => Ok(None),
+                None => Ok(Some(Child {
+                    hash: leaf_hash::<T>(&leaf_key, &T::try_hash(&leaf_value)?)?,
+                    version: node_version.unwrap_or(version),
+                    is_leaf: true,
+                })),
+                Some(value) => self
+                    .split(path, (&leaf_key, &leaf_value), (key_hash, value), version)
+                    .map(Some),
+            },
+            Node::Internal(mut children) => {
+                let index = nibble(key_hash.as_ref(), path.len());
+                path.push(index as u8);
+                let child = match children[index] {
+                    Some(child) => self.existing_node(child.version, path)?,
+                    None => Node::Null,
+                };
+                let child_version = children[index].map(|child| child.version);
+                children[index] =
+                    self.update(child, child_version, path, key_hash, value, version)?;
+                path.pop();
+
+                let mut present =
+                    (0..CHILDREN).filter_map(|index| children[index].map(|c| (index, c)));
+                match (present.next(), present.next()) {
+                    (None, _) => Ok(None),
+                    (Some((index, child)), None) if child.is_leaf => {
+                        // a lone leaf moves up to take the place of its parent
+                        path.push(index as u8);
+                        let leaf = self.existing_node(child.version, path)?;
+                        path.pop();
+                        self.put_node(version, path, &leaf)?;
+                        Ok(Some(Child { version, ..child }))
+                    }
+                    _ => self.put_internal(version, path, children).map(Some),
+                }
+            }
+        }
+    }
+
+    /// Pushes an existing leaf down until its key hash diverges from the new one
+    fn split(
+        &mut self,
+        path: &mut Vec<u8>,
+        existing: (&T::Hash, &[u8]),
+        new: (&T::Hash, &[u8]),
+        version: u64,
+    ) -> TreeResult<Child<T::Hash>> {
+        let existing_index = nibble(existing.0.as_ref(), path.len());
+        let new_index = nibble(new.0.as_ref(), path.len());
+        let mut children: Children<T::Hash> = [None; CHILDREN];
+
+        path.push(existing_index as u8);
+        if existing_index == new_index {
+            children[existing_index] = Some(self.split(path, existing, new, version)?);
+            path.pop();
+        } else {
+            children[existing_index] = Some(self.put_leaf(version, path, existing.0, existing.1)?);
+            path.pop();
+            path.push(new_index as u8);
+            children[new_index] = Some(self.put_leaf(version, path, new.0, new.1)?);
+            path.pop();
+        }
+
+        self.put_internal(version, path, children)
+    }
+
+    fn put_leaf(
+        &mut self,
+        version: u64,
+        path: &[u8],
+        key_hash: &T::Hash,
+        value: &[u8],
+    ) -> TreeResult<Child<T::Hash>> {
+        let leaf = Node::Leaf {
+            key_hash: *key_hash,
+            value: value.to_vec(),
+        };
+        self.put_node(version, path, &leaf)?;
+
+        Ok(Child {
+            hash: leaf_hash::<T>(key_hash, &T::try_hash(value)?)?,
+            version,
+            is_leaf: true,
+        })
+    }
+
+    fn put_internal(
+        &mut self,
+        version: u64,
+        path: &[u8],
+        children: Children<T::Hash>,
+    ) -> TreeResult<Child<T::Hash>> {
+        let hash = subtree_hash::<T>(&children, 0..CHILDREN)?;
+        self.put_node(version, path, &Node::Internal(children))?;
+
+        Ok(Child {
+            hash,
+            version,
+            is_leaf: false,
+        })
+    }
+
+    fn put_node(&mut self, version: u64, path: &[u8], node: &Node<T::Hash>) -> TreeResult<()> {
+        self.store
+            .put_node(&node_key(version, path), &encode_node::<T>(node))
+    }
+
+    fn node(&self, version: u64, path: &[u8]) -> TreeResult<Option<Node<T::Hash>>> {
+        self.store
+            .get_node(&node_key(version, path))?
+            .map(|bytes| decode_node::<T>(&bytes))
+            .transpose()
+    }
+
+    fn existing_node(&self, version: u64, path: &[u8]) -> TreeResult<Node<T::Hash>> {
+        self.node(version, path)?
+            .ok_or_else(|| TreeError::storage_failed("node is missing from the store"))
+    }
+}
+
+impl<T: ToHash> SparseMerkleProof<T> {
+    /// Proof of absence from the empty tree
+    pub(crate) fn empty() -> Self {
+        Self {
+            leaf: None,
+            siblings: Vec::new(),
+        }
+    }
+
+    pub fn siblings(&self) -> &[T::Hash] {
+        &self.siblings
+    }
+
+    /// Checks that `key` holds `value` at `root_hash`, or is absent when `value` is `None`
+    pub fn validate(&self, root_hash: T::Hash, key: &[u8], value: Option<&[u8]>) -> bool {
+        self.compute_root(key, value)
+            .is_ok_and(|root| ct::eq(root.as_ref(), root_hash.as_ref()))
+    }
+
+    fn compute_root(&self, key: &[u8], value: Option<&[u8]>) -> TreeResult<T::Hash> {
+        let key_hash = T::try_hash(key)?;
+        let depth = self.siblings.len();
+        if depth > T::hash_len() * 8 {
+            return Err(TreeError::proof_malformed(
+                "proof is deeper than the key hash",
+            ));
+        }
+
+        let mut current = match (value, &self.leaf) {
+            (Some(value), Some((leaf_key, value_hash)))
+                if *leaf_key == key_hash && *value_hash == T::try_hash(value)? =>
+            {
+                leaf_hash::<T>(leaf_key, value_hash)?
+            }
+            (None, None) => placeholder::<T>()?,
+            // another key's leaf sits where the key would be
+            (None, Some((leaf_key, value_hash)))
+                if *leaf_key != key_hash
+                    && (0..depth).all(|bit| {
+                        bit_at(leaf_key.as_ref(), bit) == bit_at(key_hash.as_ref(), bit)
+                    }) =>
+            {
+                leaf_hash::<T>(leaf_key, value_hash)?
+            }
+            _ => return Err(TreeError::proof_invalid()),
+        };
+
+        for (height, sibling) in self.siblings.iter().enumerate() {
+            current = match bit_at(key_hash.as_ref(), depth - 1 - height) {
+                true => internal_hash::<T>(sibling, &current)?,
+                false => internal_hash::<T>(&current, sibling)?,
+            };
+        }
+
+        Ok(current)
+    }
+}
+
+fn node_hash<T: ToHash>(node: &Node<T::Hash>) -> TreeResult<T::Hash> {
+    match node {
+        Node::Null => placeholder::<T>(),
+        Node::Internal(children) => subtree_hash::<T>(children, 0..CHILDREN),
+        Node::Leaf { key_hash, value } => leaf_hash::<T>(key_hash, &T::try_hash(value)?),
+    }
+}
+
+/// Hash of the binary subtree over the children in `range`
+fn subtree_hash<T: ToHash>(
+    children: &Children<T::Hash>,
+    range: Range<usize>,
+) -> TreeResult<T::Hash> {
+    let mut present = children[range.clone()].iter().flatten();
+    match (present.next(), present.next()) {
+        (None, _) => placeholder::<T>(),
+        (Some(child), None) if child.is_leaf || range.len() == 1 => Ok(child.hash),
+        _ => {
+            let middle = range.start + range.len() / 2;
+            internal_hash::<T>(
+                &subtree_hash::<T>(children, range.start..middle)?,
+                &subtree_hash::<T>(children, middle..range.end)?,
+            )
+        }
+    }
+}
+
+fn leaf_hash<T: ToHash>(key_hash: &T::Hash, value_hash: &T::Hash) -> TreeResult<T::Hash> {
+    Ok(T::try_hash(
+        &[&[LEAF_PREFIX], key_hash.as_ref(), value_hash.as_ref()].concat(),
+    )?)
+}
+
+fn internal_hash<T: ToHash>(left: &T::Hash, right: &T::Hash) -> TreeResult<T::Hash> {
+    Ok(T::try_hash(
+        &[&[NODE_PREFIX], left.as_ref(), right.as_ref()].concat(),
+    )?)
+}
+
+pub(crate) fn placeholder<T: ToHash>() -> TreeResult<T::Hash> {
+    T::Hash::try_from(&vec![0; T::hash_len()])
+        .map_err(|_| TreeError::hash_size_mismatch("hasher cannot produce a zero hash"))
+}
+
+fn nibble(hash: &[u8], index: usize) -> usize {
+    match index % 2 {
+        0 => (hash[index / 2] >> 4) as usize,
+        _ => (hash[index / 2] & 0x0f) as usize,
+    }
+}
+
+fn bit_at(hash: &[u8], index: usize) -> bool {
+    hash[index / 8] >> (7 - index % 8) & 1 == 1
+}
+
+/// `version (u64, BE) | one byte per nibble of the path`
+fn node_key(version: u64, path: &[u8]) -> Vec<u8> {
+    [&version.to_be_bytes()[..], path].concat()
+}
+
+/// Null: `0`, leaf: `2 | key hash | value`, internal: `1 | child bitmap (u16, BE) | leaf
+/// bitmap (u16, BE)`, then `version (u64, BE) | hash` of every child
+fn encode_node<T: ToHash>(node: &Node<T::Hash>) -> Vec<u8> {
+    match node {
+        Node::Null => vec![NULL_TAG],
+        Node::Leaf { key_hash, value } => [&[LEAF_TAG], key_hash.as_ref(), value].concat(),
+        Node::Internal(children) => {
+            let (mut existing, mut leaves) = (0u16, 0u16);
+            let mut bytes = vec![INTERNAL_TAG, 0, 0, 0, 0];
+            for (index, child) in children.iter().enumerate() {
+                if let Some(child) = child {
+                    existing |= 1 << index;
+                    leaves |= (child.is_leaf as u16) << index;
+                    bytes.extend_from_slice(&child.version.to_be_bytes());
+                    bytes.extend_from_slice(child.hash.as_ref());
+                }
+            }
+            bytes[1..3].copy_from_slice(&existing.to_be_bytes());
+            bytes[3..5].copy_from_slice(&leaves.to_be_bytes());
+            bytes
+        }
+    }
+}
+
+fn decode_node<T: ToHash>(bytes: &[u8]) -> TreeResult<Node<T::Hash>> {
+    let malformed = || TreeError::storage_failed("stored jellyfish node is malformed");
+    let hash = |bytes: &[u8]| T::Hash::try_from(bytes).map_err(|_| malformed());
+    let hash_len = T::hash_len();
+
+    match bytes.split_first() {
+        Some((&NULL_TAG, [])) => Ok(Node::Null),
+        Some((&LEAF_TAG, rest)) if rest.len() >= hash_len => Ok(Node::Leaf {
+            key_hash: hash(&rest[..hash_len])?,
+            value: rest[hash_len..].to_vec(),
+        }),
+        Some((&INTERNAL_TAG, rest)) if rest.len() >= 4 => {
+            let existing = u16::from_be_bytes([rest[0], rest[1]]);
+            let leaves = u16::from_be_bytes([rest[2], rest[3]]);
+            let mut entries = rest[4..].chunks(8 + hash_len);
+            let mut children: Children<T::Hash> = [None; CHILDREN];
+            for (index, slot) in children.iter_mut().enumerate() {
+                if existing >> index & 1 == 1 {
+                    let entry = entries.next().filter(|entry| entry.len() == 8 + hash_len);
+                    let entry = entry.ok_or_else(malformed)?;
+                    *slot = Some(Child {
+                        version: u64::from_be_bytes(entry[..8].try_into().unwrap()),
+                        hash: hash(&entry[8..])?,
+                        is_leaf: leaves >> index & 1 == 1,
+                    });
+                }
+            }
+            match entries.next() {
+                None => Ok(Node::Internal(children)),
+                Some(_) => Err(malformed()),
+            }
+        }
+        _ => Err(malformed()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::store::MemoryStore;
+
+    fn key(index: u32) -> Vec<u8> {
+        format!("key-{index}").into_bytes()
+    }
+
+    #[test]
+    fn test_versions_stay_readable_and_provable() {
+        let mut tree = JellyfishMerkleTree::<Sha256, _>::new(MemoryStore::new());
+        let mut roots = Vec::new();
+        for version in 0..4u32 {
+            let keys: Vec<_> = (version * 50..(version + 1) * 50).map(key).collect();
+            let updates: Vec<_> = keys
+                .iter()
+                .map(|key| (key.as_slice(), Some(&key[..])))
+                .collect();
+            roots.push(tree.put_value_set(&updates, version as u64).unwrap());
+        }
+
+        for (version, root) in roots.iter().enumerate() {
+            for index in [0, 49, 50, 120, 199] {
+                let key = key(index);
+                let (value, proof) = tree.get_with_proof(&key, version as u64).unwrap();
+                let expected = (index < (version as u32 + 1) * 50).then_some(key.as_slice());
+                assert_eq!(value.as_deref(), expected);
+                assert!(proof.validate(*root, &key, expected));
+                assert!(!proof.validate(*root, &key, Some(b"forged")));
+            }
+        }
+    }
+
+    #[test]
+    fn test_root_only_depends_on_contents() {
+        let keys: Vec<_> = (0..40).map(key).collect();
+        let mut forward = JellyfishMerkleTree::<Sha256, _>::new(MemoryStore::new());
+        let mut backward = JellyfishMerkleTree::<Sha256, _>::new(MemoryStore::new());
+        for (version, key) in keys.iter().enumerate() {
+            forward
+                .put_value_set(&[(key, Some(b"value"))], version as u64)
+                .unwrap();
+            backward
+                .put_value_set(&[(&keys[39 - version], Some(b"value"))], version as u64)
+                .unwrap();
+        }
+        assert_eq!(
+            forward.root_hash(39).unwrap(),
+            backward.root_hash(39).unwrap()
+        );
+
+        // removing keys collapses the tree back to the same shape as never inserting them
+        let removals: Vec<_> = keys[10..]
+            .iter()
+            .map(|key| (key.as_slice(), None))
+            .collect();
+        let root = forward.put_value_set(&removals, 40).unwrap();
+        let mut expected = JellyfishMerkleTree::<Sha256, _>::new(MemoryStore::new());
+        let updates: Vec<_> = keys[..10]
+            .iter()
+            .map(|key| (key.as_slice(), Some(&b"value"[..])))
+            .collect();
+        assert_eq!(root, expected.put_value_set(&updates, 0).unwrap());
+
+        let all: Vec<_> = keys[..10]
+            .iter()
+            .map(|key| (key.as_slice(), None))
+            .collect();
+        assert_eq!(forward.put_value_set(&all, 41).unwrap(), [0; 32]);
+        assert_eq!(
+            forward.get(&keys[0], 40).unwrap().as_deref(),
+            Some(&b"value"[..])
+        );
+    }
+
+    #[test]
+    fn test_versions_must_be_sequential() {
+        let mut tree = JellyfishMerkleTree::<Sha256, _>::new(MemoryStore::new());
+        let error = tree
+            .put_value_set(&[(b"key", Some(b"value"))], 1)
+            .unwrap_err();
+        assert_eq!(error.kind(), &TreeErrorKind::VersionUnknown);
+        assert_eq!(tree.put_value_set(&[], 0).unwrap(), [0; 32]);
+        assert!(tree.root_hash(1).is_err());
+    }
+
+    #[test]
+    fn test_committed_versions_are_never_rewritten() {
+        let mut tree = JellyfishMerkleTree::<Sha256, _>::new(MemoryStore::new());
+        tree.put_value_set(&[(b"alice", Some(b"10"))], 0).unwrap();
+        let first = tree.put_value_set(&[(b"bob", Some(b"20"))], 1).unwrap();
+        tree.put_value_set(&[(b"carol", Some(b"30"))], 2).unwrap();
+
+        for version in [0, 1] {
+            let error = tree
+                .put_value_set(&[(b"mallory", Some(b"0"))], version)
+                .unwrap_err();
+            assert_eq!(error.kind(), &TreeErrorKind::VersionCommitted);
+        }
+        assert_eq!(tree.root_hash(1).unwrap(), first);
+        assert_eq!(tree.get(b"mallory", 2).unwrap(), None);
+    }
+}
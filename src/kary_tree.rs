@@ -0,0 +1,319 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+use crate::utils::ct;
+
+/// Merkle tree whose interior nodes have up to `arity` children, e.g. 4-ary or 16-ary
+///
+/// Each level is split into groups of `arity` consecutive nodes, left to right, and every group
+/// is hashed with [`ToHash::try_hash_children`]. The last group of a level may be shorter; a
+/// lone last node is promoted unchanged. Wider nodes shorten proofs to `log_arity(n)` levels
+/// at the cost of `arity - 1` siblings per level. With an arity of 2 the tree matches a
+/// positional [`MerkleTree`](crate::MerkleTree) promoting odd nodes.
+pub struct KaryTree<T: ToHash> {
+    arity: usize,
+    leaves: Vec<T::Hash>,
+}
+
+impl<T: ToHash> KaryTree<T> {
+    /// Creates an empty tree, failing with `ArityInvalid` for an arity below 2
+    pub fn new(arity: usize) -> TreeResult<Self> {
+        if arity < 2 {
+            return Err(TreeError::arity_invalid());
+        }
+
+        Ok(Self {
+            arity,
+            leaves: Vec::new(),
+        })
+    }
+
+    pub fn from_leaves(arity: usize, leaves: &[T::Hash]) -> TreeResult<Self> {
+        let mut tree = Self::new(arity)?;
+        tree.leaves.extend_from_slice(leaves);
+
+        Ok(tree)
+    }
+
+    pub fn append(&mut self, leaf: T::Hash) {
+        self.leaves.push(leaf);
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn leaves(&self) -> &[T::Hash] {
+        &self.leaves
+    }
+
+    /// Number of levels between the leaves and the root
+    pub fn depth(&self) -> usize {
+        let (mut len, mut depth) = (self.leaves.len(), 0);
+        while len > 1 {
+            len = len.div_ceil(self.arity);
+            depth += 1;
+        }
+
+        depth
+    }
+
+    pub fn root_hash(&self) -> TreeResult<T::Hash> {
+        let levels = self.levels()?;
+        Ok(levels[levels.len() - 1][0])
+    }
+
+    /// Creates the proof of the leaf at `index`, one group of siblings per level
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{KaryTree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..20).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = KaryTree::<Sha256>::from_leaves(4, &leaves)?;
+    ///     let root = tree.root_hash()?;
+    ///
+    ///     let proof = tree.proof_at(9)?;
+    ///     assert_eq!(proof.groups().len(), tree.depth());
+    ///     assert!(proof.validate(root, leaves[9]));
+    ///     assert!(!proof.validate(root, leaves[8]));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn proof_at(&self, index: usize) -> TreeResult<KaryProof<T>> {
+        if index >= self.leaves.len() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let levels = self.levels()?;
+        let mut groups = Vec::with_capacity(levels.len() - 1);
+        let mut position = index;
+        for level in levels.iter().take(levels.len() - 1) {
+            let start = position - position % self.arity;
+            let end = (start + self.arity).min(level.len());
+            if end - start > 1 {
+                groups.push(
+                    (start..end)
+                        .filter(|sibling| *sibling != position)
+                        .map(|sibling| level[sibling])
+                        .collect(),
+                );
+            }
+            position /= self.arity;
+        }
+
+        Ok(KaryProof::new(self.arity, index, self.leaves.len(), groups))
+    }
+
+    /// Every level of the tree, from the leaves up to the root
+    fn levels(&self) -> TreeResult<Vec<Vec<T::Hash>>> {
+        if self.leaves.is_empty() {
+            return Err(TreeError::tree_empty());
+        }
+
+        let mut levels = vec![self.leaves.clone()];
+        while levels[levels.len() - 1].len() > 1 {
+            let level = levels[levels.len() - 1]
+                .chunks(self.arity)
+                .map(|group| match group {
+                    [node] => Ok(*node),
+                    children => T::try_hash_children(children),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            levels.push(level);
+        }
+
+        Ok(levels)
+    }
+}
+
+/// Inclusion proof of a [`KaryTree`] leaf, bound to its slot
+///
+/// Siblings are grouped per level from the leaf up, in the order they appear in their node.
+/// The slot of the leaf inside each node, and the size of each node, follow from the arity,
+/// the leaf index and the leaf count, so a proof only validates for the slot it was issued for.
+pub struct KaryProof<T: ToHash> {
+    arity: usize,
+    index: usize,
+    leaf_count: usize,
+    groups: Vec<Vec<T::Hash>>,
+}
+
+impl<T: ToHash> KaryProof<T> {
+    pub fn new(arity: usize, index: usize, leaf_count: usize, groups: Vec<Vec<T::Hash>>) -> Self {
+        Self {
+            arity,
+            index,
+            leaf_count,
+            groups,
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn groups(&self) -> &[Vec<T::Hash>] {
+        &self.groups
+    }
+
+    pub fn validate(&self, root_hash: T::Hash, leaf: T::Hash) -> bool {
+        self.compute_root(leaf)
+            .is_ok_and(|root| ct::eq(root.as_ref(), root_hash.as_ref()))
+    }
+
+    /// Folds the proof from the leaf up, inserting the running hash at its slot in every node
+    ///
+    /// Fails when a group does not hold the siblings of its node, or when groups are missing
+    /// or left over.
+    pub fn compute_root(&self, leaf: T::Hash) -> TreeResult<T::Hash> {
+        if self.arity < 2 {
+            return Err(TreeError::arity_invalid());
+        }
+        if self.index >= self.leaf_count {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let mut groups = self.groups.iter();
+        let (mut current, mut position, mut len) = (leaf, self.index, self.leaf_count);
+        while len > 1 {
+            let start = position - position % self.arity;
+            let width = self.arity.min(len - start);
+            if width > 1 {
+                let siblings = groups.next().filter(|group| group.len() == width - 1);
+                let siblings = siblings.ok_or_else(|| {
+                    TreeError::proof_length_mismatch("sibling group does not match its node")
+                })?;
+
+                let slot = position - start;
+                let mut children = Vec::with_capacity(width);
+                children.extend_from_slice(&siblings[..slot]);
+                children.push(current);
+                children.extend_from_slice(&siblings[slot..]);
+                current = T::try_hash_children(&children)?;
+            }
+            position /= self.arity;
+            len = len.div_ceil(self.arity);
+        }
+
+        match groups.next() {
+            Some(_) => Err(TreeError::proof_length_mismatch(
+                "proof has more groups than the tree has levels",
+            )),
+            None => Ok(current),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::{DomainSeparated, Sha256};
+    use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering, TreePolicy};
+    use crate::MerkleTree;
+
+    fn leaves(count: u8) -> Vec<[u8; 32]> {
+        (0..count).map(|i| Sha256::hash(&[i])).collect()
+    }
+
+    #[test]
+    fn test_binary_tree_matches_positional_merkle_tree() {
+        for count in 1..12 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::<Sha256>::from_leaves(&leaves).with_policy(TreePolicy {
+                pair_ordering: PairOrdering::Positional,
+                odd_leaf: OddLeafPolicy::Promote,
+                duplicates: DuplicatePolicy::Allow,
+            });
+
+            assert_eq!(
+                KaryTree::<Sha256>::from_leaves(2, &leaves)
+                    .unwrap()
+                    .root_hash()
+                    .unwrap(),
+                tree.root_hash().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_wide_nodes_hash_their_children_together() {
+        let leaves = leaves(6);
+        let tree = KaryTree::<DomainSeparated<Sha256>>::from_leaves(4, &leaves).unwrap();
+
+        let left = Sha256::hash(&[&[0x01], leaves[..4].concat().as_slice()].concat());
+        let right = Sha256::hash(&[&[0x01], leaves[4..].concat().as_slice()].concat());
+        assert_eq!(
+            tree.root_hash().unwrap(),
+            Sha256::hash(&[[0x01].as_slice(), &left, &right].concat())
+        );
+        assert_eq!(tree.depth(), 2);
+    }
+
+    #[test]
+    fn test_proofs_validate_for_every_arity_and_slot() {
+        let leaves = leaves(37);
+        for arity in [2, 3, 4, 16] {
+            for count in 1..leaves.len() {
+                let tree = KaryTree::<Sha256>::from_leaves(arity, &leaves[..count]).unwrap();
+                let root = tree.root_hash().unwrap();
+                for (index, leaf) in leaves[..count].iter().enumerate() {
+                    let proof = tree.proof_at(index).unwrap();
+                    assert!(proof.validate(root, *leaf), "{arity}-ary, {count} leaves");
+                    assert!(proof.groups().len() <= tree.depth());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_proofs_are_bound_to_their_slot() {
+        let leaves = leaves(10);
+        let tree = KaryTree::<Sha256>::from_leaves(4, &leaves).unwrap();
+        let root = tree.root_hash().unwrap();
+        let proof = tree.proof_at(5).unwrap();
+
+        let moved = KaryProof::<Sha256>::new(4, 6, 10, proof.groups().to_vec());
+        assert!(!moved.validate(root, leaves[5]));
+
+        let mut groups = proof.groups().to_vec();
+        groups[0].pop();
+        assert_eq!(
+            KaryProof::<Sha256>::new(4, 5, 10, groups)
+                .compute_root(leaves[5])
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::ProofLengthMismatch
+        );
+    }
+
+    #[test]
+    fn test_invalid_arity_and_empty_tree_return_errors() {
+        assert_eq!(
+            KaryTree::<Sha256>::new(1).err().unwrap().kind(),
+            &TreeErrorKind::ArityInvalid
+        );
+        assert_eq!(
+            KaryTree::<Sha256>::new(4)
+                .unwrap()
+                .root_hash()
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::TreeEmpty
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::hash::to_hash::ToHash;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+/// Leaves per frozen chunk, and most leaves indexed outside of a frozen run
+const CHUNK_LEN: usize = 4096;
+
+/// Leaves of a tree with the index of their first occurrences, shared with its snapshots
+///
+/// Leaves are kept in frozen chunks of [`CHUNK_LEN`] behind `Arc`s plus a short tail, and
+/// their index in frozen runs plus a short map of recent entries. Cloning only copies the
+/// tail, the recent entries and the pointers, so the first append after a snapshot no longer
+/// copies every leaf. Runs are merged as they grow, like the digits of a binary counter, so a
+/// lookup probes O(log n) maps.
+pub(crate) struct Leaves<T: ToHash> {
+    chunks: Vec<Arc<[T::Hash]>>,
+    tail: Vec<T::Hash>,
+    runs: Vec<Arc<HashMap<T::Hash, usize>>>,
+    recent: HashMap<T::Hash, usize>,
+    /// Every leaf in one slice, built on first use after a change
+    contiguous: OnceLock<Box<[T::Hash]>>,
+}
+
+impl<T: ToHash> Leaves<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            tail: Vec::new(),
+            runs: Vec::new(),
+            recent: HashMap::new(),
+            contiguous: OnceLock::new(),
+        }
+    }
+
+    pub(crate) fn from_slice(leaves: &[T::Hash]) -> Self {
+        let mut positions = HashMap::with_capacity(leaves.len());
+        for (index, leaf) in leaves.iter().enumerate() {
+            positions.entry(*leaf).or_insert(index);
+        }
+
+        let full = leaves.len() - leaves.len() % CHUNK_LEN;
+        Self {
+            chunks: leaves[..full].chunks(CHUNK_LEN).map(Arc::from).collect(),
+            tail: leaves[full..].to_vec(),
+            runs: vec![Arc::new(positions)],
+            recent: HashMap::new(),
+            contiguous: OnceLock::new(),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.chunks.len() * CHUNK_LEN + self.tail.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of distinct leaves
+    pub(crate) fn distinct_len(&self) -> usize {
+        self.runs.iter().map(|run| run.len()).sum::<usize>() + self.recent.len()
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<T::Hash> {
+        match self.chunks.get(index / CHUNK_LEN) {
+            Some(chunk) => Some(chunk[index % CHUNK_LEN]),
+            None => self
+                .tail
+                .get(index - self.chunks.len() * CHUNK_LEN)
+                .copied(),
+        }
+    }
+
+    /// Index of the first occurrence of the leaf
+    pub(crate) fn position(&self, leaf: &T::Hash) -> Option<usize> {
+        self.recent
+            .get(leaf)
+            .or_else(|| self.runs.iter().find_map(|run| run.get(leaf)))
+            .copied()
+    }
+
+    /// The leaves in order, as the slices they are stored in
+    pub(crate) fn segments(&self) -> Vec<&[T::Hash]> {
+        let mut segments: Vec<&[T::Hash]> = self.chunks.iter().map(|chunk| &chunk[..]).collect();
+        segments.push(&self.tail);
+        segments
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T::Hash> + '_ {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter())
+            .chain(&self.tail)
+    }
+
+    /// Every leaf in one slice, copied out of the chunks once per change
+    pub(crate) fn as_slice(&self) -> &[T::Hash] {
+        if self.chunks.is_empty() {
+            return &self.tail;
+        }
+
+        self.contiguous
+            .get_or_init(|| self.segments().concat().into())
+    }
+
+    pub(crate) fn push(&mut self, leaf: T::Hash) {
+        if self.position(&leaf).is_none() {
+            self.recent.insert(leaf, self.len());
+        }
+        self.tail.push(leaf);
+        self.contiguous = OnceLock::new();
+
+        if self.tail.len() == CHUNK_LEN {
+            let chunk = std::mem::take(&mut self.tail);
+            self.chunks.push(chunk.into());
+        }
+        if self.recent.len() == CHUNK_LEN {
+            self.freeze_recent();
+        }
+    }
+
+    /// Moves the recent entries into a run, merging runs no larger than it
+    fn freeze_recent(&mut self) {
+        let mut run = std::mem::take(&mut self.recent);
+        while let Some(last) = self.runs.last() {
+            if last.len() > run.len() {
+                break;
+            }
+            let mut merged = Arc::unwrap_or_clone(self.runs.pop().expect("a run was found"));
+            merged.extend(run);
+            run = merged;
+        }
+        self.runs.push(Arc::new(run));
+    }
+
+    /// Keeps the first `len` leaves, rebuilding the chunks and the index
+    pub(crate) fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            *self = Self::from_slice(&self.as_slice()[..len]);
+        }
+    }
+
+    /// Replaces the leaf at `index`, which must be in bounds
+    pub(crate) fn replace(&mut self, index: usize, leaf: T::Hash) {
+        let old = match self.chunks.get_mut(index / CHUNK_LEN) {
+            Some(chunk) => {
+                let mut copy = chunk.to_vec();
+                let old = std::mem::replace(&mut copy[index % CHUNK_LEN], leaf);
+                *chunk = copy.into();
+                old
+            }
+            None => {
+                let offset = index - self.chunks.len() * CHUNK_LEN;
+                std::mem::replace(&mut self.tail[offset], leaf)
+            }
+        };
+        self.contiguous = OnceLock::new();
+
+        if self.position(&old) == Some(index) {
+            let next = self.iter().position(|candidate| *candidate == old);
+            self.set_position(old, next);
+        }
+        match self.position(&leaf) {
+            Some(first) if first <= index => {}
+            Some(_) => self.set_position(leaf, Some(index)),
+            None => {
+                self.recent.insert(leaf, index);
+            }
+        }
+    }
+
+    /// Moves or removes the index entry of a leaf already in the index
+    fn set_position(&mut self, leaf: T::Hash, position: Option<usize>) {
+        let map = match self.recent.contains_key(&leaf) {
+            true => &mut self.recent,
+            false => match self.runs.iter_mut().find(|run| run.contains_key(&leaf)) {
+                Some(run) => Arc::make_mut(run),
+                None => return,
+            },
+        };
+        match position {
+            Some(position) => map.insert(leaf, position),
+            None => map.remove(&leaf),
+        };
+    }
+}
+
+impl<T: ToHash> Clone for Leaves<T> {
+    fn clone(&self) -> Self {
+        Self {
+            chunks: self.chunks.clone(),
+            tail: self.tail.clone(),
+            runs: self.runs.clone(),
+            recent: self.recent.clone(),
+            contiguous: OnceLock::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha256;
+
+    fn leaf(i: usize) -> [u8; 32] {
+        Sha256::hash(&(i % 5000).to_be_bytes())
+    }
+
+    #[test]
+    fn test_leaves_match_a_flat_index() {
+        let expected: Vec<_> = (0..3 * CHUNK_LEN + 100).map(leaf).collect();
+        let mut leaves = Leaves::<Sha256>::new();
+        for hash in &expected {
+            leaves.push(*hash);
+        }
+
+        assert_eq!(leaves.len(), expected.len());
+        assert_eq!(leaves.as_slice(), &expected[..]);
+        assert_eq!(leaves.distinct_len(), 5000);
+        assert!(leaves.runs.len() < 4);
+        for (index, hash) in expected.iter().enumerate() {
+            assert_eq!(leaves.get(index), Some(*hash));
+            assert_eq!(leaves.position(hash), Some(index % 5000));
+        }
+
+        leaves.replace(1, leaf(4999));
+        assert_eq!(leaves.position(&leaf(1)), Some(5001));
+        assert_eq!(leaves.position(&leaf(4999)), Some(1));
+
+        leaves.truncate(CHUNK_LEN + 1);
+        assert_eq!(leaves.len(), CHUNK_LEN + 1);
+        assert_eq!(leaves.position(&leaf(CHUNK_LEN + 1)), None);
+    }
+
+    #[test]
+    fn test_clones_share_the_frozen_chunks() {
+        let mut leaves = Leaves::<Sha256>::new();
+        for index in 0..2 * CHUNK_LEN {
+            leaves.push(leaf(index));
+        }
+
+        let (appended, replacement) = (Sha256::hash(b"appended"), Sha256::hash(b"replacement"));
+        let snapshot = leaves.clone();
+        leaves.push(appended);
+        leaves.replace(0, replacement);
+
+        assert!(Arc::ptr_eq(&leaves.chunks[1], &snapshot.chunks[1]));
+        assert_eq!(snapshot.len(), 2 * CHUNK_LEN);
+        assert_eq!(snapshot.get(0), Some(leaf(0)));
+        assert_eq!(snapshot.position(&appended), None);
+        assert_eq!(leaves.position(&appended), Some(2 * CHUNK_LEN));
+        assert_eq!(leaves.position(&replacement), Some(0));
+    }
+}
@@ -1,15 +1,123 @@
+#[cfg(feature = "serde")]
+pub use crate::airdrop::{AirdropClaim, AirdropExport};
+pub use crate::audit_log::{AuditLog, EntryProof};
+#[cfg(feature = "ffi")]
+pub use crate::bindings::ffi;
+#[cfg(feature = "wasm")]
+pub use crate::bindings::wasm;
+pub use crate::bloom_filter::BloomFilter;
+pub use crate::chained_proof::ChainedProof;
+pub use crate::checkpoint::{
+    Checkpoint, CheckpointSigner, CheckpointVerifier, NamedKey, NoteKey, SignedCheckpoint,
+};
+pub use crate::concurrent_builder::ConcurrentTreeBuilder;
+pub use crate::consistency_proof::ConsistencyProof;
+pub use crate::dual_hash_tree::DualHashTree;
+pub use crate::dyn_merkle_tree::{DynMerkleTree, HashAlgorithm};
+pub use crate::expiring_tree::{ExpiringTree, ValidityWindow};
 pub use crate::hash::to_hash::ToHash;
+pub use crate::hash::ByteOrder;
+pub use crate::hash::DomainSeparated;
+pub use crate::hash::HashBackend;
 pub use crate::hash::Keccak256;
 pub use crate::hash::Sha256;
+pub use crate::hash::Sha2_256;
 pub use crate::hash::Sha512;
-pub use crate::merkle_proof::MerkleProof;
-pub use crate::merkle_tree::MerkleTree;
+pub use crate::hash::Tiger;
+pub use crate::hash::Truncated;
+pub use crate::hash::{CombineStrategy, Combined, Concat, LengthPrefixed, Prefixed};
+pub use crate::hash::{DynHash, DynHasher, ToHashDyn, MAX_DYN_HASH_LEN};
+#[cfg(feature = "simd")]
+pub use crate::hash::{KeccakLanes, Sha3Lanes};
+pub use crate::indexed_proof::IndexedProof;
+#[cfg(feature = "ics23")]
+pub use crate::interop::ics23;
+#[cfg(feature = "ipld")]
+pub use crate::interop::ipld::{Cid, IpldBlock, IpldDag};
+pub use crate::interop::rs_merkle::RsMerkleProof;
+pub use crate::interop::solidity::{openzeppelin_combine, openzeppelin_sorted_pair};
+pub use crate::kary_tree::{KaryProof, KaryTree};
+pub use crate::merkle_forest::MerkleForest;
+pub use crate::merkle_map::MerkleMap;
+pub use crate::merkle_proof::{MerkleProof, Position};
+pub use crate::merkle_tree::{MerkleTree, TreeResult};
+pub use crate::partial_tree::PartialTree;
+#[cfg(feature = "private")]
+pub use crate::private_tree::{PrivateTree, Salt, SaltedProof};
+pub use crate::proof_bundle::CompressedProofBundle;
+pub use crate::proof_cursor::ProofCursor;
+#[cfg(feature = "serde")]
+pub use crate::proof_response::{ProofFormat, ProofResponse, PROOF_RESPONSE_VERSION};
+#[cfg(feature = "ed25519")]
+pub use crate::signed_root::SignedRoot;
+pub use crate::transparency_log::TransparencyLog;
+pub use crate::tree_builder::TreeBuilder;
+pub use crate::tree_levels::TreeLevels;
+pub use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering, TreePolicy};
+pub use crate::tree_snapshot::TreeSnapshot;
+pub use crate::tree_writer::TreeWriter;
+pub use crate::update_proof::UpdateProof;
+#[cfg(feature = "test-utils")]
+pub use crate::utils::test as test_utils;
+pub use crate::value_tree::{ValueProof, ValueTree};
+pub use crate::version::Version;
 
+#[cfg(feature = "serde")]
+mod airdrop;
+mod audit_log;
+#[cfg(any(feature = "ffi", feature = "wasm"))]
+mod bindings;
+pub mod bittorrent;
+mod bloom_filter;
+mod chained_proof;
+mod checkpoint;
+pub mod chunker;
+pub mod codegen;
+mod concurrent_builder;
+mod consistency_proof;
+mod dual_hash_tree;
+mod dyn_merkle_tree;
 pub mod error;
+mod expiring_tree;
+#[cfg(all(feature = "mmap", unix))]
+mod file_tree;
 mod hash;
+mod indexed_proof;
+pub mod ingest;
+mod interop;
+pub mod jmt;
+mod kary_tree;
+mod leaves;
+mod merkle_forest;
+mod merkle_map;
 mod merkle_proof;
 mod merkle_tree;
+pub mod mpt;
+mod partial_tree;
+#[cfg(feature = "private")]
+mod private_tree;
+mod proof_bundle;
+mod proof_cursor;
+#[cfg(feature = "serde")]
+mod proof_response;
+pub mod proof_transform;
+mod render;
+#[cfg(feature = "ed25519")]
+mod signed_root;
+pub mod ssz;
+pub mod store;
+pub mod tendermint;
+pub mod thex;
+mod transparency_log;
+mod tree_builder;
+mod tree_levels;
+mod tree_policy;
+mod tree_snapshot;
+mod tree_writer;
+mod update_proof;
 mod utils;
+mod value_tree;
+mod version;
 
 pub type Keccak256Tree = MerkleTree<Keccak256>;
 pub type Keccak256Proof = MerkleProof<Keccak256>;
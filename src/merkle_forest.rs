@@ -0,0 +1,285 @@
+use crate::chained_proof::ChainedProof;
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::tree_policy::TreePolicy;
+
+/// Independent shard trees whose roots are bagged into a single super-root
+///
+/// Each shard is an ordinary [`MerkleTree`] that can be built, stored and updated on its own.
+/// The super-root is the root of a tree over the shard roots, in shard order, so a leaf is
+/// proven by its shard proof followed by the proof of the shard root, as a [`ChainedProof`].
+/// Shards and the tree of roots share the policy of the forest, so its [`DuplicatePolicy`]
+/// applies to the leaves of each shard and to the shard roots.
+///
+/// [`DuplicatePolicy`]: crate::DuplicatePolicy
+pub struct MerkleForest<T: ToHash> {
+    shards: Vec<MerkleTree<T>>,
+    policy: TreePolicy,
+}
+
+impl<T: ToHash> MerkleForest<T> {
+    pub fn new() -> Self {
+        Self {
+            shards: Vec::new(),
+            policy: TreePolicy::default(),
+        }
+    }
+
+    /// Creates an empty forest whose shards and tree of roots follow `policy`
+    pub fn with_policy(policy: TreePolicy) -> Self {
+        Self {
+            shards: Vec::new(),
+            policy,
+        }
+    }
+
+    pub fn from_shards(shards: &[Vec<T::Hash>]) -> TreeResult<Self> {
+        let mut forest = Self::new();
+        for leaves in shards {
+            forest.push_shard(leaves)?;
+        }
+
+        Ok(forest)
+    }
+
+    /// Builds the shards across the rayon thread pool, computing each shard root as it goes
+    ///
+    /// Fails when a shard is empty, its root cannot be hashed or it breaks the duplicate policy.
+    #[cfg(feature = "rayon")]
+    pub fn par_from_shards(shards: &[Vec<T::Hash>], policy: TreePolicy) -> TreeResult<Self>
+    where
+        T::Hash: Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        let shards = shards
+            .par_iter()
+            .map(|leaves| {
+                let tree = MerkleTree::from_leaves(leaves)
+                    .with_policy(policy)
+                    .enforce_duplicate_policy()?;
+                tree.root_hash()?;
+                Ok(tree)
+            })
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        Ok(Self { shards, policy })
+    }
+
+    /// Adds a shard over the leaves, returning its index
+    ///
+    /// Repeated leaves are dropped or rejected as the forest's duplicate policy demands.
+    pub fn push_shard(&mut self, leaves: &[T::Hash]) -> TreeResult<usize> {
+        let shard = MerkleTree::from_leaves(leaves)
+            .with_policy(self.policy)
+            .enforce_duplicate_policy()?;
+        self.shards.push(shard);
+        Ok(self.shards.len() - 1)
+    }
+
+    pub fn policy(&self) -> TreePolicy {
+        self.policy
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    pub fn shard(&self, shard: usize) -> Option<&MerkleTree<T>> {
+        self.shards.get(shard)
+    }
+
+    /// Mutable access to a shard, e.g. to append leaves; the super-root follows its new root
+    pub fn shard_mut(&mut self, shard: usize) -> Option<&mut MerkleTree<T>> {
+        self.shards.get_mut(shard)
+    }
+
+    /// Number of leaves over all shards
+    pub fn leaf_count(&self) -> usize {
+        self.shards.iter().map(MerkleTree::leaf_count).sum()
+    }
+
+    /// Roots of the shards, in shard order
+    pub fn shard_roots(&self) -> TreeResult<Vec<T::Hash>> {
+        self.shards.iter().map(MerkleTree::root_hash).collect()
+    }
+
+    /// Tree over the shard roots, whose root is the super-root
+    ///
+    /// Shards with equal roots follow the duplicate policy: a deduplicated tree holds their
+    /// root once, and a rejecting forest fails here.
+    pub fn roots_tree(&self) -> TreeResult<MerkleTree<T>> {
+        MerkleTree::from_leaves(&self.shard_roots()?)
+            .with_policy(self.policy)
+            .enforce_duplicate_policy()
+    }
+
+    pub fn root_hash(&self) -> TreeResult<T::Hash> {
+        if self.shards.is_empty() {
+            return Err(TreeError::tree_empty());
+        }
+
+        self.roots_tree()?.root_hash()
+    }
+
+    /// Creates the proof of the leaf at `index` in `shard`, validated against the super-root
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{MerkleForest, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let shards: Vec<Vec<_>> = (0..4u8)
+    ///         .map(|shard| (0..6u8).map(|i| Sha256::hash(&[shard, i])).collect())
+    ///         .collect();
+    ///     let forest = MerkleForest::<Sha256>::from_shards(&shards)?;
+    ///
+    ///     let proof = forest.proof(2, 5)?;
+    ///     assert!(proof.validate(forest.root_hash()?, shards[2][5]));
+    ///     assert!(!proof.validate(forest.root_hash()?, shards[1][5]));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn proof(&self, shard: usize, index: usize) -> TreeResult<ChainedProof<T>> {
+        let shard_tree = self
+            .shards
+            .get(shard)
+            .ok_or_else(TreeError::index_out_of_bounds)?;
+
+        // the roots tree may have dropped repeated roots, so look the shard root up
+        let roots = self.roots_tree()?;
+        let position = roots
+            .index_of(&shard_tree.root_hash()?)
+            .ok_or_else(TreeError::leaf_not_found)?;
+
+        Ok(ChainedProof::new(shard_tree.merkle_proof_at(index)?)
+            .then(roots.merkle_proof_at(position)?))
+    }
+}
+
+impl<T: ToHash> Default for MerkleForest<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering};
+
+    fn shards(count: u8, size: u8) -> Vec<Vec<[u8; 32]>> {
+        (0..count)
+            .map(|shard| (0..size).map(|i| Sha256::hash(&[shard, i])).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_proofs_validate_against_the_super_root() {
+        let policy = TreePolicy {
+            pair_ordering: PairOrdering::Positional,
+            odd_leaf: OddLeafPolicy::Promote,
+            duplicates: DuplicatePolicy::Allow,
+        };
+        let shards = shards(5, 3);
+        let mut forest = MerkleForest::<Sha256>::with_policy(policy);
+        for leaves in &shards {
+            forest.push_shard(leaves).unwrap();
+        }
+        let root = forest.root_hash().unwrap();
+
+        assert_eq!(forest.leaf_count(), 15);
+        for (shard, leaves) in shards.iter().enumerate() {
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = forest.proof(shard, index).unwrap();
+                assert!(proof.validate(root, *leaf));
+                assert_eq!(
+                    proof.intermediate_roots(*leaf).unwrap()[0],
+                    forest.shard(shard).unwrap().root_hash().unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_super_root_follows_shard_updates() {
+        let mut forest = MerkleForest::<Sha256>::from_shards(&shards(3, 4)).unwrap();
+        let root = forest.root_hash().unwrap();
+
+        forest.shard_mut(1).unwrap().append(Sha256::hash(b"new"));
+        assert_ne!(forest.root_hash().unwrap(), root);
+        assert!(forest
+            .proof(1, 4)
+            .unwrap()
+            .validate(forest.root_hash().unwrap(), Sha256::hash(b"new")));
+    }
+
+    #[test]
+    fn test_empty_forest_and_missing_shard_return_errors() {
+        let forest = MerkleForest::<Sha256>::new();
+        assert_eq!(
+            forest.root_hash().unwrap_err().kind(),
+            &TreeErrorKind::TreeEmpty
+        );
+        assert_eq!(
+            forest.proof(0, 0).err().unwrap().kind(),
+            &TreeErrorKind::IndexOutOfBounds
+        );
+    }
+
+    #[test]
+    fn test_duplicates_follow_the_forest_policy() {
+        let policy = |duplicates| TreePolicy {
+            duplicates,
+            ..TreePolicy::default()
+        };
+        let leaves = shards(1, 3).remove(0);
+        let repeated = vec![leaves[0], leaves[1], leaves[0], leaves[2]];
+
+        let mut forest = MerkleForest::<Sha256>::with_policy(policy(DuplicatePolicy::Reject));
+        assert_eq!(
+            forest.push_shard(&repeated).unwrap_err().kind(),
+            &TreeErrorKind::LeafDuplicated
+        );
+        forest.push_shard(&leaves).unwrap();
+        forest.push_shard(&leaves).unwrap();
+        assert_eq!(
+            forest.root_hash().unwrap_err().kind(),
+            &TreeErrorKind::LeafDuplicated
+        );
+
+        let mut forest = MerkleForest::<Sha256>::with_policy(policy(DuplicatePolicy::Deduplicate));
+        forest.push_shard(&repeated).unwrap();
+        forest.push_shard(&leaves).unwrap();
+        forest.push_shard(&shards(2, 2)[1]).unwrap();
+        assert_eq!(forest.shard(0).unwrap().leaf_count(), 3);
+        assert_eq!(forest.roots_tree().unwrap().leaf_count(), 2);
+
+        let root = forest.root_hash().unwrap();
+        for shard in 0..3 {
+            assert!(forest
+                .proof(shard, 1)
+                .unwrap()
+                .validate(root, forest.shard(shard).unwrap().leaf_at(1).unwrap()));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_build_matches_sequential() {
+        let shards = shards(8, 5);
+        let forest =
+            MerkleForest::<Sha256>::par_from_shards(&shards, TreePolicy::default()).unwrap();
+
+        assert_eq!(
+            forest.root_hash().unwrap(),
+            MerkleForest::<Sha256>::from_shards(&shards)
+                .unwrap()
+                .root_hash()
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use crate::hash::to_hash::ToHash;
+use crate::jmt::{placeholder, JellyfishMerkleTree, SparseMerkleProof};
+use crate::merkle_tree::TreeResult;
+use crate::store::MemoryStore;
+use std::marker::PhantomData;
+
+/// Authenticated key-value map whose root commits to every entry
+///
+/// Backed by an in-memory [`JellyfishMerkleTree`] that is rewritten in place, so only the
+/// current state is kept. Proofs show either the value of a key or that the key is absent.
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::{MerkleMap, Sha256};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut balances = MerkleMap::<&str, String, Sha256>::new();
+///     balances.insert("alice", "10".to_string())?;
+///     balances.insert("bob", "20".to_string())?;
+///     let root = balances.root()?;
+///
+///     let proof = balances.prove(&"alice")?;
+///     assert!(proof.validate(root, b"alice", Some(b"10")));
+///     let proof = balances.prove(&"carol")?;
+///     assert!(proof.validate(root, b"carol", None));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct MerkleMap<K: AsRef<[u8]>, V: AsRef<[u8]>, T: ToHash> {
+    tree: JellyfishMerkleTree<T, MemoryStore>,
+    len: usize,
+    written: bool,
+    entries: PhantomData<(K, V)>,
+}
+
+impl<K: AsRef<[u8]>, V: AsRef<[u8]>, T: ToHash> MerkleMap<K, V, T> {
+    pub fn new() -> Self {
+        Self {
+            tree: JellyfishMerkleTree::new(MemoryStore::new()),
+            len: 0,
+            written: false,
+            entries: PhantomData,
+        }
+    }
+
+    /// Sets the value of `key`, returning the value it replaces
+    pub fn insert(&mut self, key: K, value: V) -> TreeResult<Option<Vec<u8>>> {
+        let previous = self.get(&key)?;
+        self.write(key.as_ref(), Some(value.as_ref()))?;
+        if previous.is_none() {
+            self.len += 1;
+        }
+
+        Ok(previous)
+    }
+
+    pub fn get(&self, key: &K) -> TreeResult<Option<Vec<u8>>> {
+        match self.written {
+            true => self.tree.get(key.as_ref(), 0),
+            false => Ok(None),
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> TreeResult<bool> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// Removes `key`, returning the value it held
+    pub fn remove(&mut self, key: &K) -> TreeResult<Option<Vec<u8>>> {
+        let previous = self.get(key)?;
+        if previous.is_some() {
+            self.write(key.as_ref(), None)?;
+            self.len -= 1;
+        }
+
+        Ok(previous)
+    }
+
+    /// Root committing to every entry, all zeros for the empty map
+    pub fn root(&self) -> TreeResult<T::Hash> {
+        match self.written {
+            true => self.tree.root_hash(0),
+            false => placeholder::<T>(),
+        }
+    }
+
+    /// Proof of the value of `key`, or of its absence, against [`MerkleMap::root`]
+    pub fn prove(&self, key: &K) -> TreeResult<SparseMerkleProof<T>> {
+        match self.written {
+            true => Ok(self.tree.get_with_proof(key.as_ref(), 0)?.1),
+            false => Ok(SparseMerkleProof::empty()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn write(&mut self, key: &[u8], value: Option<&[u8]>) -> TreeResult<()> {
+        let base = self.written.then_some(0);
+        self.tree.apply(&[(key, value)], base, 0)?;
+        self.written = true;
+        Ok(())
+    }
+}
+
+impl<K: AsRef<[u8]>, V: AsRef<[u8]>, T: ToHash> Default for MerkleMap<K, V, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::Sha256;
+    use crate::MerkleMap;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = MerkleMap::<Vec<u8>, Vec<u8>, Sha256>::new();
+        let empty_root = map.root().unwrap();
+        assert_eq!(empty_root, [0; 32]);
+
+        for index in 0..100u8 {
+            assert_eq!(map.insert(vec![index], vec![index, index]).unwrap(), None);
+        }
+        assert_eq!(map.insert(vec![7], vec![0]).unwrap(), Some(vec![7, 7]));
+        assert_eq!(map.len(), 100);
+        let root = map.root().unwrap();
+
+        let proof = map.prove(&vec![7]).unwrap();
+        assert!(proof.validate(root, &[7], Some(&[0])));
+        assert!(!proof.validate(root, &[7], Some(&[7, 7])));
+
+        for index in 0..100u8 {
+            assert!(map.remove(&vec![index]).unwrap().is_some());
+        }
+        assert!(map.is_empty());
+        assert_eq!(map.remove(&vec![0]).unwrap(), None);
+        assert_eq!(map.root().unwrap(), empty_root);
+        assert!(map
+            .prove(&vec![1])
+            .unwrap()
+            .validate(empty_root, &[1], None));
+    }
+
+    #[test]
+    fn test_root_is_independent_of_history() {
+        let mut left = MerkleMap::<String, &str, Sha256>::new();
+        let mut right = MerkleMap::<String, &str, Sha256>::new();
+        for index in 0..20 {
+            left.insert(format!("key{index}"), "value").unwrap();
+            right.insert(format!("key{}", 19 - index), "value").unwrap();
+            right.insert(format!("temp{index}"), "value").unwrap();
+        }
+        for index in 0..20 {
+            right.remove(&format!("temp{index}")).unwrap();
+        }
+
+        assert_eq!(left.root().unwrap(), right.root().unwrap());
+    }
+}
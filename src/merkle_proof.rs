@@ -1,14 +1,279 @@
+use crate::error::proof_error::ProofError;
 use crate::error::tree_error::TreeError;
 use crate::hash::to_hash::ToHash;
+use crate::hash::ByteOrder;
 use crate::merkle_tree::TreeResult;
+use crate::utils::ct;
+
+const PROOF_FORMAT_VERSION: u8 = 1;
+const PROOF_HEADER_LEN: usize = 7;
+const PROOF_FLAG_POSITIONS: u8 = 0b0000_0001;
+const PROOF_FLAG_DOMAIN_SEPARATED: u8 = 0b0000_0010;
+
+/// Side on which a sibling hash sits relative to the running hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Position {
+    Left,
+    Right,
+}
 
 pub struct MerkleProof<T: ToHash> {
     proof: Vec<T::Hash>,
+    positions: Option<Vec<Position>>,
 }
 
 impl<T: ToHash> MerkleProof<T> {
     pub fn new(proof: Vec<T::Hash>) -> Self {
-        Self { proof }
+        Self {
+            proof,
+            positions: None,
+        }
+    }
+
+    /// Creates a positional proof, folding each sibling on its recorded side instead of
+    /// using the sorted-pair convention of [`ToHash::combine`]
+    pub fn with_positions(proof: Vec<T::Hash>, positions: Vec<Position>) -> TreeResult<Self> {
+        if proof.len() != positions.len() {
+            return Err(TreeError::proof_length_mismatch(
+                "proof and positions must have the same length",
+            ));
+        }
+
+        Ok(Self {
+            proof,
+            positions: Some(positions),
+        })
+    }
+
+    pub fn proof(&self) -> &[T::Hash] {
+        &self.proof
+    }
+
+    pub fn positions(&self) -> Option<&[Position]> {
+        self.positions.as_deref()
+    }
+
+    /// Sibling positions as seen when folding from the given leaf
+    ///
+    /// Sorted-pair proofs do not record positions, so they are derived from the ordering
+    /// [`ToHash::combine`] applies: the greater hash is always placed on the left.
+    pub fn resolve_positions(&self, leaf: T::Hash) -> Vec<Position> {
+        if let Some(positions) = &self.positions {
+            return positions.clone();
+        }
+
+        let mut current = leaf;
+        let mut positions = Vec::with_capacity(self.proof.len());
+        for sibling in &self.proof {
+            positions.push(if *sibling >= current {
+                Position::Left
+            } else {
+                Position::Right
+            });
+            current = T::combine(current, *sibling);
+        }
+
+        positions
+    }
+
+    /// Parses a proof from hex siblings, each with or without a `0x` prefix
+    ///
+    /// Fails with `HashSizeMismatch` when a sibling does not match the hasher's output size.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..3).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let hex = Sha256Proof::new(tree.get_proof_at(1)?).to_hex_prefixed();
+    ///
+    ///     let siblings: Vec<_> = hex.iter().map(String::as_str).collect();
+    ///     let proof = Sha256Proof::from_hex(&siblings)?;
+    ///     assert!(proof.validate(tree.root_hash()?, leaves[1]));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_hex(siblings: &[&str]) -> TreeResult<Self> {
+        let proof = siblings
+            .iter()
+            .map(|sibling| ByteOrder::Natural.from_hex::<T>(sibling))
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        Ok(Self::new(proof))
+    }
+
+    /// Siblings as lowercase hex without a prefix
+    pub fn to_hex(&self) -> Vec<String> {
+        self.proof
+            .iter()
+            .map(|sibling| ByteOrder::Natural.to_hex::<T>(*sibling))
+            .collect()
+    }
+
+    /// Siblings as `0x`-prefixed lowercase hex
+    pub fn to_hex_prefixed(&self) -> Vec<String> {
+        self.to_hex()
+            .into_iter()
+            .map(|sibling| format!("0x{sibling}"))
+            .collect()
+    }
+
+    /// Serializes the proof into a compact, versioned byte format
+    ///
+    /// Layout: `version (u8) | flags (u8) | hash length (u8) | sibling count (u32, BE) | hashes`,
+    /// followed by a bitmap of sibling positions (set bit = left) when the positions flag is set.
+    /// A second flag records whether the proof uses [`DomainSeparated`](crate::DomainSeparated)
+    /// hashing, so it cannot be decoded for a hasher following the other convention.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves = [
+    ///         Sha256::hash("0".as_bytes()),
+    ///         Sha256::hash("1".as_bytes()),
+    ///         Sha256::hash("2".as_bytes()),
+    ///     ];
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let proof = Sha256Proof::new(tree.get_proof(leaves[1])?);
+    ///
+    ///     let bytes = proof.to_bytes();
+    ///     assert_eq!(bytes.len(), 7 + 2 * 32);
+    ///     assert_eq!(Sha256Proof::from_bytes(&bytes)?.proof(), proof.proof());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PROOF_HEADER_LEN + self.proof.len() * T::hash_len());
+        bytes.push(PROOF_FORMAT_VERSION);
+        bytes.push(self.flags());
+        bytes.push(T::hash_len() as u8);
+        bytes.extend_from_slice(&(self.proof.len() as u32).to_be_bytes());
+        for hash in &self.proof {
+            bytes.extend_from_slice(hash.as_ref());
+        }
+
+        if let Some(positions) = &self.positions {
+            let mut bitmap = vec![0u8; positions.len().div_ceil(8)];
+            for (index, position) in positions.iter().enumerate() {
+                if *position == Position::Left {
+                    bitmap[index / 8] |= 1 << (index % 8);
+                }
+            }
+            bytes.extend_from_slice(&bitmap);
+        }
+
+        bytes
+    }
+
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.positions.is_some() {
+            flags |= PROOF_FLAG_POSITIONS;
+        }
+        if T::DOMAIN_SEPARATED {
+            flags |= PROOF_FLAG_DOMAIN_SEPARATED;
+        }
+
+        flags
+    }
+
+    /// Serializes the proof like [`MerkleProof::to_bytes`], writing every hash in `order`
+    pub fn to_bytes_ordered(&self, order: ByteOrder) -> Vec<u8> {
+        self.map_hashes(order).to_bytes()
+    }
+
+    /// Deserializes a proof whose hashes were written in `order`
+    pub fn from_bytes_ordered(bytes: &[u8], order: ByteOrder) -> TreeResult<Self> {
+        Ok(Self::from_bytes(bytes)?.map_hashes(order))
+    }
+
+    fn map_hashes(&self, order: ByteOrder) -> Self {
+        Self {
+            proof: self
+                .proof
+                .iter()
+                .map(|hash| order.apply::<T>(*hash))
+                .collect(),
+            positions: self.positions.clone(),
+        }
+    }
+
+    /// Deserializes a proof previously produced by [`MerkleProof::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> TreeResult<Self> {
+        if bytes.len() < PROOF_HEADER_LEN {
+            return Err(TreeError::proof_malformed("proof header is truncated"));
+        }
+
+        if bytes[0] != PROOF_FORMAT_VERSION {
+            return Err(TreeError::proof_malformed(
+                "unsupported proof format version",
+            ));
+        }
+
+        let flags = bytes[1];
+        if flags & !(PROOF_FLAG_POSITIONS | PROOF_FLAG_DOMAIN_SEPARATED) != 0 {
+            return Err(TreeError::proof_malformed("unsupported proof flags"));
+        }
+
+        if (flags & PROOF_FLAG_DOMAIN_SEPARATED != 0) != T::DOMAIN_SEPARATED {
+            return Err(TreeError::proof_malformed(
+                "domain separation does not match the hasher",
+            ));
+        }
+
+        let hash_len = T::hash_len();
+        if bytes[2] as usize != hash_len {
+            return Err(TreeError::hash_size_mismatch(
+                "hash length does not match the hasher",
+            ));
+        }
+
+        let count = u32::from_be_bytes([bytes[3], bytes[4], bytes[5], bytes[6]]) as usize;
+        let bitmap_len = match flags & PROOF_FLAG_POSITIONS {
+            0 => 0,
+            _ => count.div_ceil(8),
+        };
+        let body = &bytes[PROOF_HEADER_LEN..];
+        if count
+            .checked_mul(hash_len)
+            .and_then(|len| len.checked_add(bitmap_len))
+            != Some(body.len())
+        {
+            return Err(TreeError::proof_malformed(
+                "sibling count does not match the proof length",
+            ));
+        }
+
+        let (hashes, bitmap) = body.split_at(body.len() - bitmap_len);
+        let proof = hashes
+            .chunks_exact(hash_len)
+            .map(|chunk| {
+                T::Hash::try_from(chunk)
+                    .map_err(|_| TreeError::proof_malformed("invalid hash in proof"))
+            })
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        if bitmap_len == 0 {
+            return Ok(Self::new(proof));
+        }
+
+        let positions = (0..count)
+            .map(|index| match bitmap[index / 8] >> (index % 8) & 1 {
+                1 => Position::Left,
+                _ => Position::Right,
+            })
+            .collect();
+
+        Self::with_positions(proof, positions)
     }
 
     /// Validates a partial proof against a root hash
@@ -24,7 +289,7 @@ impl<T: ToHash> MerkleProof<T> {
     ///         Sha256::hash("3".as_bytes()),
     ///         Sha256::hash("4".as_bytes()),
     ///     ];
-    ///     let mut tree = Keccak256Tree::from_leaves(&leaves);
+    ///     let tree = Keccak256Tree::from_leaves(&leaves);
     ///     let hash = tree.root_hash()?;
     ///     let proof = tree.get_proof(leaves[3])?;
     ///
@@ -34,29 +299,197 @@ impl<T: ToHash> MerkleProof<T> {
     ///     Ok(())
     /// }
     pub fn validate(&self, root_hash: T::Hash, leaf: T::Hash) -> bool {
-        let proof_root_hash = match self.reduce_proof(leaf) {
-            Ok(hash) => hash,
-            Err(_) => return false,
-        };
+        self.validate_detailed(root_hash, leaf).is_ok()
+    }
+
+    /// Validates like [`MerkleProof::validate`], saying why a rejected proof failed
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::error::proof_error::ProofErrorKind;
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let root = tree.root_hash()?;
+    ///
+    ///     let proof = Sha256Proof::new(tree.get_proof_at(3)?);
+    ///     proof.validate_detailed(root, leaves[3])?;
+    ///
+    ///     let error = proof.validate_detailed(root, leaves[2]).unwrap_err();
+    ///     assert_eq!(error.kind(), &ProofErrorKind::RootMismatch);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn validate_detailed(&self, root_hash: T::Hash, leaf: T::Hash) -> Result<(), ProofError> {
+        if ct::eq(self.compute_root(leaf)?.as_ref(), root_hash.as_ref()) {
+            return Ok(());
+        }
 
-        root_hash == proof_root_hash
+        match self.proof.is_empty() {
+            true => Err(ProofError::empty()),
+            false => Err(ProofError::root_mismatch()),
+        }
     }
 
-    fn reduce_proof(&self, leaf: T::Hash) -> TreeResult<<T as ToHash>::Hash> {
-        let mut proof = self.proof.clone();
-        proof.insert(0, leaf);
-        proof
-            .into_iter()
-            .reduce(|left, right| T::combine(left, right))
-            .ok_or_else(TreeError::proof_empty)
+    /// Validates like [`MerkleProof::validate`], rejecting proofs of more than `max_depth`
+    /// siblings before hashing anything
+    ///
+    /// Bounds the work spent on untrusted proofs and turns away proofs padded past the depth
+    /// of the tree, e.g. with [`MerkleTree::expected_proof_len`](crate::MerkleTree::expected_proof_len)
+    /// of the published leaf count as the bound.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let root = tree.root_hash()?;
+    ///     let max_depth = Sha256Tree::expected_proof_len(leaves.len());
+    ///
+    ///     let proof = Sha256Proof::new(tree.get_proof_at(3)?);
+    ///     assert!(proof.validate_with_max_depth(root, leaves[3], max_depth));
+    ///     assert!(!proof.validate_with_max_depth(root, leaves[3], max_depth - 1));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn validate_with_max_depth(
+        &self,
+        root_hash: T::Hash,
+        leaf: T::Hash,
+        max_depth: usize,
+    ) -> bool {
+        self.proof.len() <= max_depth && self.validate(root_hash, leaf)
+    }
+
+    /// Validates many `(leaf, proof)` pairs against the same root, returning one result per pair
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let root = tree.root_hash()?;
+    ///
+    ///     let claims = [
+    ///         (leaves[1], Sha256Proof::new(tree.get_proof_at(1)?)),
+    ///         (leaves[2], Sha256Proof::new(tree.get_proof_at(3)?)),
+    ///     ];
+    ///     assert_eq!(Sha256Proof::validate_batch(root, &claims), vec![true, false]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn validate_batch(root_hash: T::Hash, claims: &[(T::Hash, Self)]) -> Vec<bool> {
+        claims
+            .iter()
+            .map(|(leaf, proof)| proof.validate(root_hash, *leaf))
+            .collect()
+    }
+
+    /// Validates like [`MerkleProof::validate_batch`], spreading the pairs over the rayon
+    /// thread pool
+    #[cfg(feature = "rayon")]
+    pub fn par_validate_batch(root_hash: T::Hash, claims: &[(T::Hash, Self)]) -> Vec<bool>
+    where
+        T::Hash: Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        claims
+            .par_iter()
+            .map(|(leaf, proof)| proof.validate(root_hash, *leaf))
+            .collect()
+    }
+
+    /// Validates the proof for raw leaf data, hashed with the leaf convention of `T`
+    ///
+    /// The data is hashed exactly once, so pass the payload itself rather than its hash.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let payloads: [&[u8]; 3] = [b"alice", b"bob", b"carol"];
+    ///     let leaves: Vec<_> = payloads.iter().map(|data| Sha256::hash(data)).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     let proof = Sha256Proof::new(tree.get_proof_at(1)?);
+    ///     assert!(proof.validate_data(tree.root_hash()?, b"bob"));
+    ///     assert!(!proof.validate_data(tree.root_hash()?, &leaves[1]));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn validate_data(&self, root_hash: T::Hash, data: &[u8]) -> bool {
+        match T::try_hash(data) {
+            Ok(leaf) => self.validate(root_hash, leaf),
+            Err(_) => false,
+        }
+    }
+
+    /// Folds the proof from the leaf up, returning the root it leads to
+    pub fn compute_root(&self, leaf: T::Hash) -> TreeResult<T::Hash> {
+        if let Some(positions) = &self.positions {
+            return self.proof.iter().zip(positions).try_fold(
+                leaf,
+                |current, (sibling, position)| {
+                    Ok(match position {
+                        Position::Left => T::try_hash_node(*sibling, current)?,
+                        Position::Right => T::try_hash_node(current, *sibling)?,
+                    })
+                },
+            );
+        }
+
+        Ok(self
+            .proof
+            .iter()
+            .try_fold(leaf, |current, sibling| T::try_combine(current, *sibling))?)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::hash::Sha256;
+    use crate::error::proof_error::ProofErrorKind;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::{ByteOrder, DomainSeparated, Sha256};
     use crate::utils::test::{full_root_hash, raw_leaves_to_hashed_leaves};
-    use crate::{Sha256Proof, Sha256Tree};
+    use crate::{MerkleProof, Sha256Proof, Sha256Tree};
+
+    #[test]
+    fn test_hex_round_trip_and_length_checks() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3", "4"]);
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let proof = Sha256Proof::new(tree.get_proof_at(3).unwrap());
+
+        let plain = proof.to_hex();
+        let prefixed = proof.to_hex_prefixed();
+        assert_eq!(prefixed[0], format!("0x{}", plain[0]));
+        for hex in [plain, prefixed] {
+            let siblings: Vec<_> = hex.iter().map(String::as_str).collect();
+            assert_eq!(
+                Sha256Proof::from_hex(&siblings).unwrap().proof(),
+                proof.proof()
+            );
+        }
+
+        let short = Sha256Proof::from_hex(&["0xabcd"]).err().unwrap();
+        assert_eq!(short.kind(), &TreeErrorKind::HashSizeMismatch);
+        let invalid = Sha256Proof::from_hex(&["0xzz"]).err().unwrap();
+        assert_eq!(invalid.kind(), &TreeErrorKind::InvalidEncoding);
+        assert_eq!(
+            Sha256Tree::from_hex_leaves(&[&tree.root_hex().unwrap()])
+                .unwrap()
+                .root_hash()
+                .unwrap(),
+            tree.root_hash().unwrap()
+        );
+    }
 
     #[test]
     fn test_valid_proof() {
@@ -76,9 +509,180 @@ mod tests {
 
         let full_hash = full_root_hash::<Sha256>(&leaves);
 
-        let mut tree = Sha256Tree::from_leaves(&leaves);
+        let tree = Sha256Tree::from_leaves(&leaves);
         let partial_proof = tree.get_proof(leaves[3]).unwrap();
         let is_valid = Sha256Proof::new(partial_proof);
         assert!(is_valid.validate(full_hash, leaves[3]));
     }
+
+    #[test]
+    fn test_validate_detailed_reports_failures() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let root = full_root_hash::<Sha256>(&leaves);
+        let proof = Sha256Proof::new(tree.get_proof_at(1).unwrap());
+        assert_eq!(proof.compute_root(leaves[1]).unwrap(), root);
+        assert!(proof.validate_detailed(root, leaves[1]).is_ok());
+        assert_eq!(
+            proof.validate_detailed(root, leaves[0]).unwrap_err().kind(),
+            &ProofErrorKind::RootMismatch
+        );
+
+        let empty = Sha256Proof::new(Vec::new());
+        assert!(empty.validate_detailed(leaves[0], leaves[0]).is_ok());
+        assert_eq!(
+            empty.validate_detailed(root, leaves[0]).unwrap_err().kind(),
+            &ProofErrorKind::Empty
+        );
+    }
+
+    #[test]
+    fn test_validate_data_hashes_once() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let root = full_root_hash::<Sha256>(&leaves);
+        let proof = Sha256Proof::new(tree.get_proof_at(2).unwrap());
+
+        assert!(proof.validate_data(root, "2".as_bytes()));
+        assert!(!proof.validate_data(root, &leaves[2]));
+        assert!(!proof.validate_data(root, "3".as_bytes()));
+    }
+
+    #[test]
+    fn test_validate_batch() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let mut claims: Vec<_> = (0..leaves.len())
+            .map(|index| {
+                (
+                    leaves[index],
+                    Sha256Proof::new(tree.get_proof_at(index).unwrap()),
+                )
+            })
+            .collect();
+        claims[3].0 = leaves[2];
+
+        assert_eq!(
+            Sha256Proof::validate_batch(full_root_hash::<Sha256>(&leaves), &claims),
+            vec![true, true, true, false, true]
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_validate_batch_matches_sequential() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let root = full_root_hash::<Sha256>(&leaves);
+        let claims: Vec<_> = (0..leaves.len())
+            .map(|index| {
+                (
+                    leaves[index],
+                    Sha256Proof::new(tree.get_proof_at(index).unwrap()),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            Sha256Proof::par_validate_batch(root, &claims),
+            Sha256Proof::validate_batch(root, &claims)
+        );
+    }
+
+    #[test]
+    fn test_proof_bytes_round_trip() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let proof = Sha256Proof::new(tree.get_proof(leaves[3]).unwrap());
+        let decoded = Sha256Proof::from_bytes(&proof.to_bytes()).unwrap();
+
+        assert_eq!(decoded.proof(), proof.proof());
+        assert!(decoded.validate(full_root_hash::<Sha256>(&leaves), leaves[3]));
+    }
+
+    #[test]
+    fn test_positional_proof_bytes_round_trip() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let sorted = Sha256Proof::new(tree.get_proof(leaves[3]).unwrap());
+        let positional = Sha256Proof::with_positions(
+            sorted.proof().to_vec(),
+            sorted.resolve_positions(leaves[3]),
+        )
+        .unwrap();
+        let decoded = Sha256Proof::from_bytes(&positional.to_bytes()).unwrap();
+
+        assert_eq!(decoded.positions(), positional.positions());
+        assert!(decoded.validate(full_root_hash::<Sha256>(&leaves), leaves[3]));
+    }
+
+    #[test]
+    fn test_reversed_proof_bytes() {
+        let leaves_raw = ["0", "1", "2"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let proof = Sha256Proof::new(vec![leaves[1]]);
+        let bytes = proof.to_bytes_ordered(ByteOrder::Reversed);
+        assert_eq!(bytes[7], leaves[1][31]);
+
+        let decoded = Sha256Proof::from_bytes_ordered(&bytes, ByteOrder::Reversed).unwrap();
+        assert_eq!(decoded.proof(), proof.proof());
+    }
+
+    #[test]
+    fn test_proof_bytes_record_domain_separation() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1"]);
+        let bytes = MerkleProof::<DomainSeparated<Sha256>>::new(vec![leaves[1]]).to_bytes();
+
+        assert!(MerkleProof::<DomainSeparated<Sha256>>::from_bytes(&bytes).is_ok());
+        assert_eq!(
+            Sha256Proof::from_bytes(&bytes).err().unwrap().kind(),
+            &TreeErrorKind::ProofMalformed
+        );
+    }
+
+    #[test]
+    fn test_max_depth_rejects_padded_proofs() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3"]);
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let root = tree.root_hash().unwrap();
+        let max_depth = Sha256Tree::expected_proof_len(leaves.len());
+
+        let mut siblings = tree.get_proof_at(1).unwrap();
+        assert!(
+            Sha256Proof::new(siblings.clone()).validate_with_max_depth(root, leaves[1], max_depth)
+        );
+
+        siblings.push(leaves[0]);
+        assert!(!Sha256Proof::new(siblings).validate_with_max_depth(root, leaves[1], max_depth));
+    }
+
+    #[test]
+    fn test_proof_bytes_rejects_truncated_input() {
+        let leaves_raw = ["0", "1", "2"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let bytes = Sha256Proof::new(tree.get_proof(leaves[0]).unwrap()).to_bytes();
+
+        assert_eq!(
+            Sha256Proof::from_bytes(&bytes[..bytes.len() - 1])
+                .err()
+                .unwrap()
+                .kind(),
+            &TreeErrorKind::ProofMalformed
+        );
+    }
 }
@@ -1,33 +1,845 @@
+use crate::bloom_filter::BloomFilter;
+use crate::consistency_proof::consistency_path;
 use crate::error::tree_error::TreeError;
 use crate::hash::to_hash::ToHash;
+use crate::hash::ByteOrder;
+use crate::leaves::Leaves;
+use crate::merkle_proof::{MerkleProof, Position};
+use crate::proof_cursor::ProofCursor;
+use crate::tree_levels::TreeLevels;
+use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering, TreePolicy};
+use crate::tree_snapshot::TreeSnapshot;
+use std::io::Read;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
 
 pub type TreeResult<T> = Result<T, TreeError>;
 
 pub struct MerkleTree<T: ToHash> {
-    leaves: Vec<T::Hash>,
-    path: Vec<T::Hash>,
-    current_path_leaf: Option<T::Hash>,
+    leaves: Arc<Leaves<T>>,
+    policy: TreePolicy,
+    root: OnceLock<T::Hash>,
+    levels: OnceLock<Arc<TreeLevels<T>>>,
+    bloom: Option<BloomFilter>,
 }
 
 impl<T: ToHash> MerkleTree<T> {
     pub fn new() -> Self {
         Self {
-            leaves: Vec::new(),
-            path: Vec::new(),
-            current_path_leaf: None,
+            leaves: Arc::new(Leaves::new()),
+            policy: TreePolicy::default(),
+            root: OnceLock::new(),
+            levels: OnceLock::new(),
+            bloom: None,
         }
     }
 
     pub fn from_leaves(leaves: &[T::Hash]) -> Self {
         Self {
-            leaves: leaves.into(),
-            path: Vec::new(),
-            current_path_leaf: None,
+            leaves: Arc::new(Leaves::from_slice(leaves)),
+            policy: TreePolicy::default(),
+            root: OnceLock::new(),
+            levels: OnceLock::new(),
+            bloom: None,
         }
     }
 
+    /// Builds a tree from hex leaf hashes, each with or without a `0x` prefix
+    ///
+    /// Fails with `HashSizeMismatch` when a leaf does not match the hasher's output size.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let tree = Sha256Tree::from_hex_leaves(&[
+    ///         "0x2a9d1b4f0e1c7e8e5e9b3b9ea3ce5c5d6a2d8b6f1a1b6b2e9f4c0d7e3a8b5c91",
+    ///         "f3e2d1c0b9a8979685746352413021100f1e2d3c4b5a69788796a5b4c3d2e1f0",
+    ///     ])?;
+    ///     assert_eq!(tree.leaf_count(), 2);
+    ///     assert!(tree.root_hex_prefixed()?.starts_with("0x"));
+    ///     assert!(Sha256Tree::from_hex_leaves(&["0x1234"]).is_err());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_hex_leaves(leaves: &[&str]) -> TreeResult<Self> {
+        let leaves = leaves
+            .iter()
+            .map(|leaf| ByteOrder::Natural.from_hex::<T>(leaf))
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        Ok(Self::from_leaves(&leaves))
+    }
+
+    /// Root hash as lowercase hex without a prefix
+    pub fn root_hex(&self) -> TreeResult<String> {
+        Ok(ByteOrder::Natural.to_hex::<T>(self.root_hash()?))
+    }
+
+    /// Root hash as `0x`-prefixed lowercase hex
+    pub fn root_hex_prefixed(&self) -> TreeResult<String> {
+        Ok(format!("0x{}", self.root_hex()?))
+    }
+
+    pub(crate) fn with_policy(mut self, policy: TreePolicy) -> Self {
+        self.policy = policy;
+        self.invalidate();
+        self
+    }
+
+    /// Selects how the last node of odd levels is handled, e.g. to reproduce Bitcoin roots
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{OddLeafPolicy, Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..3).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree =
+    ///         Sha256Tree::from_leaves(&leaves).with_odd_leaf_policy(OddLeafPolicy::DuplicateLast);
+    ///
+    ///     let expected = Sha256::combine(
+    ///         Sha256::combine(leaves[0], leaves[1]),
+    ///         Sha256::combine(leaves[2], leaves[2]),
+    ///     );
+    ///     assert_eq!(tree.root_hash()?, expected);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_odd_leaf_policy(mut self, odd_leaf: OddLeafPolicy) -> Self {
+        self.policy.odd_leaf = odd_leaf;
+        self.invalidate();
+        self
+    }
+
+    /// Selects what happens to leaves equal to one already in the tree, applying it to the
+    /// current leaves as well
+    ///
+    /// Fails with `LeafDuplicated` when rejecting duplicates and the tree already holds some;
+    /// deduplicating drops every occurrence after the first.
+    pub fn with_duplicate_policy(mut self, duplicates: DuplicatePolicy) -> TreeResult<Self> {
+        self.policy.duplicates = duplicates;
+        self.enforce_duplicate_policy()
+    }
+
+    /// Checks the current leaves against the duplicate policy, dropping or rejecting repeats
+    pub(crate) fn enforce_duplicate_policy(self) -> TreeResult<Self> {
+        if self.leaves.distinct_len() == self.leaves.len() {
+            return Ok(self);
+        }
+
+        match self.policy.duplicates {
+            DuplicatePolicy::Allow => Ok(self),
+            DuplicatePolicy::Reject => Err(TreeError::leaf_duplicated()),
+            DuplicatePolicy::Deduplicate => {
+                let leaves = self
+                    .leaves
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, leaf)| self.leaves.position(leaf) == Some(*index))
+                    .map(|(_, leaf)| *leaf)
+                    .collect::<Vec<_>>();
+                // every kept leaf is already in the filter, so it stays free of false negatives
+                let mut tree = Self::from_leaves(&leaves).with_policy(self.policy);
+                tree.bloom = self.bloom;
+                Ok(tree)
+            }
+        }
+    }
+
+    /// Rules the tree pairs and promotes its nodes by
+    pub fn policy(&self) -> TreePolicy {
+        self.policy
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Number of levels between the leaves and the root, i.e. the length of the longest proof
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     assert_eq!(tree.depth(), 3);
+    ///     assert_eq!(tree.get_proof_at(3)?.len(), tree.depth());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn depth(&self) -> usize {
+        Self::expected_proof_len(self.leaves.len())
+    }
+
+    /// Longest proof a tree of `leaf_count` leaves can produce, whatever its policy
+    ///
+    /// Verifiers of untrusted proofs can pass it to
+    /// [`MerkleProof::validate_with_max_depth`] to turn away padded proofs before hashing them.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::Sha256Tree;
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     assert_eq!(Sha256Tree::expected_proof_len(1), 0);
+    ///     assert_eq!(Sha256Tree::expected_proof_len(4), 2);
+    ///     assert_eq!(Sha256Tree::expected_proof_len(5), 3);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn expected_proof_len(leaf_count: usize) -> usize {
+        leaf_count.next_power_of_two().trailing_zeros() as usize
+    }
+
+    /// Number of siblings in the proof of the leaf at `index`, without building the proof
+    ///
+    /// Padding policies give every leaf [`MerkleTree::depth`] siblings, while promoting odd
+    /// nodes shortens the paths running along the right edge of the tree.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     assert_eq!(tree.proof_len_for(0)?, 3);
+    ///     assert_eq!(tree.proof_len_for(4)?, 1);
+    ///     assert_eq!(tree.proof_len_for(4)?, tree.get_proof_at(4)?.len());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn proof_len_for(&self, index: usize) -> TreeResult<usize> {
+        if index >= self.leaves.len() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let (mut index, mut len, mut proof_len) = (index, self.leaves.len(), 0);
+        while len > 1 {
+            if index ^ 1 < len || self.policy.odd_leaf != OddLeafPolicy::Promote {
+                proof_len += 1;
+            }
+            index /= 2;
+            len = len.div_ceil(2);
+        }
+
+        Ok(proof_len)
+    }
+
+    /// Every leaf in order
+    ///
+    /// Leaves are stored in chunks shared with snapshots, so the first call after a change
+    /// copies them into one slice, kept until the next change. [`MerkleTree::leaf_at`] reads
+    /// a single leaf without copying.
+    pub fn leaves(&self) -> &[T::Hash] {
+        self.leaves.as_slice()
+    }
+
+    pub fn leaf_at(&self, index: usize) -> Option<T::Hash> {
+        self.leaves.get(index)
+    }
+
+    /// Whether the leaf is part of the tree, looked up in constant time
+    pub fn contains(&self, leaf: &T::Hash) -> bool {
+        self.leaves.position(leaf).is_some()
+    }
+
+    /// Whether the leaf may be part of the tree, answered by the tree's bloom filter
+    ///
+    /// `false` means the leaf is definitely absent; `true` may be a false positive, to be
+    /// confirmed with [`MerkleTree::contains`]. Without a bloom filter the answer is exact.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..100u8).map(|i| Sha256::hash(&[i])).collect();
+    ///     let mut tree = Sha256Tree::from_leaves(&leaves).with_bloom_filter(0.01);
+    ///     tree.append(Sha256::hash(b"late"));
+    ///
+    ///     assert!(leaves.iter().all(|leaf| tree.maybe_contains(leaf)));
+    ///     assert!(tree.maybe_contains(&Sha256::hash(b"late")));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn maybe_contains(&self, leaf: &T::Hash) -> bool {
+        match &self.bloom {
+            Some(bloom) => bloom.maybe_contains(leaf.as_ref()),
+            None => self.contains(leaf),
+        }
+    }
+
+    /// Builds a bloom filter over the leaves, sized for the current leaf count
+    ///
+    /// Leaves appended later are added to the filter, raising its false positive rate once
+    /// the tree outgrows the size it was built for.
+    pub fn with_bloom_filter(self, false_positive_rate: f64) -> Self {
+        let mut bloom = BloomFilter::new(self.leaves.len(), false_positive_rate);
+        for leaf in self.leaves.iter() {
+            bloom.insert(leaf.as_ref());
+        }
+
+        self.attach_bloom_filter(bloom)
+    }
+
+    /// Attaches a filter already holding the leaves, e.g. read back with
+    /// [`BloomFilter::from_bytes`] next to a persisted tree, without rehashing them
+    pub fn attach_bloom_filter(mut self, bloom: BloomFilter) -> Self {
+        self.bloom = Some(bloom);
+        self
+    }
+
+    pub fn bloom_filter(&self) -> Option<&BloomFilter> {
+        self.bloom.as_ref()
+    }
+
+    /// Index of the first occurrence of the leaf, looked up in constant time
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut tree = Sha256Tree::new();
+    ///     tree.append(Sha256::hash("0".as_bytes()));
+    ///     tree.append(Sha256::hash("1".as_bytes()));
+    ///
+    ///     assert_eq!(tree.index_of(&Sha256::hash("1".as_bytes())), Some(1));
+    ///     assert!(!tree.contains(&Sha256::hash("2".as_bytes())));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn index_of(&self, leaf: &T::Hash) -> Option<usize> {
+        self.leaves.position(leaf)
+    }
+
     /// Appends a leaf to the tree
     ///
+    /// # Panics
+    /// When the tree rejects duplicate leaves and the leaf is already part of it, see
+    /// [`MerkleTree::try_append`] to handle rejections.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut tree = Sha256Tree::new();
+    ///     tree.append(Sha256::hash("0".as_bytes()));
+    ///     tree.append(Sha256::hash("1".as_bytes()));
+    ///     tree.append(Sha256::hash("2".as_bytes()));
+    ///     tree.append(Sha256::hash("3".as_bytes()));
+    ///     tree.append(Sha256::hash("4".as_bytes()));
+    ///
+    ///     let hash = tree.root_hash()?;
+    ///     let expected_hash = [
+    ///         79, 188, 211, 24, 18, 206, 74, 81, 223, 16, 54, 39, 77, 67, 62, 72, 61,
+    ///         164, 15, 45, 199, 119, 195, 239, 187, 172, 49, 64, 115, 159, 58, 83
+    ///     ];
+    ///     assert_eq!(hash, expected_hash);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn append(&mut self, leaf: T::Hash) {
+        self.try_append(leaf)
+            .expect("duplicate leaf rejected, use try_append to handle rejections");
+    }
+
+    /// Appends a leaf according to the tree's [`DuplicatePolicy`], returning whether it was
+    /// added
+    ///
+    /// Fails with `LeafDuplicated` when the tree rejects duplicates and already holds the leaf.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{DuplicatePolicy, Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut tree = Sha256Tree::new().with_duplicate_policy(DuplicatePolicy::Reject)?;
+    ///     assert!(tree.try_append(Sha256::hash(b"alice"))?);
+    ///     assert!(tree.try_append(Sha256::hash(b"alice")).is_err());
+    ///
+    ///     let mut tree = tree.with_duplicate_policy(DuplicatePolicy::Deduplicate)?;
+    ///     assert!(!tree.try_append(Sha256::hash(b"alice"))?);
+    ///     assert_eq!(tree.leaf_count(), 1);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn try_append(&mut self, leaf: T::Hash) -> TreeResult<bool> {
+        if self.contains(&leaf) {
+            match self.policy.duplicates {
+                DuplicatePolicy::Allow => {}
+                DuplicatePolicy::Reject => return Err(TreeError::leaf_duplicated()),
+                DuplicatePolicy::Deduplicate => return Ok(false),
+            }
+        }
+
+        Arc::make_mut(&mut self.leaves).push(leaf);
+        if let Some(bloom) = &mut self.bloom {
+            bloom.insert(leaf.as_ref());
+        }
+        self.invalidate();
+        Ok(true)
+    }
+
+    /// Hashes the reader's contents as one leaf and appends it, returning the leaf hash
+    ///
+    /// The data is hashed in chunks by [`ToHash::hash_reader`], so a leaf can be a file far
+    /// larger than memory. Duplicates follow the tree's [`DuplicatePolicy`], as in
+    /// [`MerkleTree::try_append`].
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut tree = Sha256Tree::new();
+    ///     let leaf = tree.append_from_reader(std::io::Cursor::new(b"alice"))?;
+    ///
+    ///     assert_eq!(leaf, Sha256::hash(b"alice"));
+    ///     assert_eq!(tree.leaf_count(), 1);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn append_from_reader(&mut self, reader: impl Read) -> TreeResult<T::Hash> {
+        let leaf = T::hash_reader(reader)?;
+        self.try_append(leaf)?;
+        Ok(leaf)
+    }
+
+    pub(crate) fn truncate(&mut self, len: usize) {
+        Arc::make_mut(&mut self.leaves).truncate(len);
+        self.invalidate();
+    }
+
+    /// Replaces the leaf at `index`, which must be in bounds
+    pub(crate) fn replace_leaf(&mut self, index: usize, leaf: T::Hash) {
+        Arc::make_mut(&mut self.leaves).replace(index, leaf);
+        if let Some(bloom) = &mut self.bloom {
+            bloom.insert(leaf.as_ref());
+        }
+        self.invalidate();
+    }
+
+    /// Drops the cached root and levels after the leaves or the policy changed
+    fn invalidate(&mut self) {
+        self.root = OnceLock::new();
+        self.levels = OnceLock::new();
+    }
+
+    /// Creates a root hash for the given tree
+    ///
+    /// The root is computed once and cached until the next append, so the tree can be shared
+    /// behind `&` or an `Arc` and read without a lock. Only the root is kept, unless the levels
+    /// were already built for a proof.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves = [
+    ///         Sha256::hash("0".as_bytes()),
+    ///         Sha256::hash("1".as_bytes()),
+    ///         Sha256::hash("2".as_bytes()),
+    ///         Sha256::hash("3".as_bytes()),
+    ///         Sha256::hash("4".as_bytes()),
+    ///     ];
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let hash = tree.root_hash()?;
+    ///     let expected_hash = [
+    ///         79, 188, 211, 24, 18, 206, 74, 81, 223, 16, 54, 39, 77, 67, 62, 72, 61,
+    ///         164, 15, 45, 199, 119, 195, 239, 187, 172, 49, 64, 115, 159, 58, 83
+    ///     ];
+    ///     assert_eq!(hash, expected_hash);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn root_hash(&self) -> TreeResult<T::Hash> {
+        if let Some(root) = self.root.get() {
+            return Ok(*root);
+        }
+
+        let root = match self.levels.get().and_then(|levels| levels.root()) {
+            Some(root) => root,
+            None => root_of::<T>(&self.leaves.segments(), &self.policy)?,
+        };
+
+        Ok(*self.root.get_or_init(|| root))
+    }
+
+    /// Creates a proof (path) for validating presence of the leaf in the tree
+    ///
+    /// Fails with [`TreeErrorKind::LeafNotFound`](crate::error::tree_error::TreeErrorKind)
+    /// when the leaf is not part of the tree.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves = [
+    ///         Sha256::hash("0".as_bytes()),
+    ///         Sha256::hash("1".as_bytes()),
+    ///         Sha256::hash("2".as_bytes()),
+    ///         Sha256::hash("3".as_bytes()),
+    ///         Sha256::hash("4".as_bytes()),
+    ///     ];
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let proof = tree.get_proof(leaves[3])?;
+    ///     let expected_proof = vec![
+    ///         leaves[2],
+    ///         Sha256::combine(leaves[0], leaves[1]),
+    ///         leaves[4],
+    ///     ];
+    ///
+    ///     assert_eq!(proof, expected_proof);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn get_proof(&self, leaf: T::Hash) -> TreeResult<Vec<T::Hash>> {
+        match self.index_of(&leaf) {
+            Some(index) => self.get_proof_at(index),
+            None => Err(TreeError::leaf_not_found()),
+        }
+    }
+
+    /// Creates a proof (path) for the leaf at the given index
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves = [
+    ///         Sha256::hash("0".as_bytes()),
+    ///         Sha256::hash("1".as_bytes()),
+    ///         Sha256::hash("2".as_bytes()),
+    ///     ];
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     assert_eq!(tree.get_proof_at(1)?, tree.get_proof(leaves[1])?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn get_proof_at(&self, index: usize) -> TreeResult<Vec<T::Hash>> {
+        if index >= self.leaves.len() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        proof_from_levels::<T>(self.cached_levels()?, index, &self.policy)
+    }
+
+    /// Creates a [`MerkleProof`] for the leaf at the given index, recording sibling positions
+    /// unless the tree uses [`PairOrdering::Sorted`](crate::PairOrdering::Sorted)
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{PairOrdering, Sha256, ToHash, TreeBuilder};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let tree = TreeBuilder::<Sha256>::new()
+    ///         .pair_ordering(PairOrdering::Positional)
+    ///         .leaf_data(b"alice")
+    ///         .leaf_data(b"bob")
+    ///         .leaf_data(b"carol")
+    ///         .build()?;
+    ///
+    ///     let proof = tree.merkle_proof_at(2)?;
+    ///     assert!(proof.positions().is_some());
+    ///     assert!(proof.validate_data(tree.root_hash()?, b"carol"));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn merkle_proof_at(&self, index: usize) -> TreeResult<MerkleProof<T>> {
+        if index >= self.leaves.len() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let path = path_from_levels::<T>(self.cached_levels()?, index, &self.policy)?;
+        let (proof, positions) = path.into_iter().unzip();
+        match self.policy.pair_ordering {
+            PairOrdering::Sorted => Ok(MerkleProof::new(proof)),
+            PairOrdering::Positional | PairOrdering::Ascending => {
+                MerkleProof::with_positions(proof, positions)
+            }
+        }
+    }
+
+    /// Checks a proof for the leaf at the given index against this tree
+    ///
+    /// Unlike [`MerkleProof::validate`], the failure says why the proof was rejected: the index
+    /// is outside of the tree, the proof has the wrong number of siblings for that leaf, or
+    /// it does not lead to the root.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::error::tree_error::TreeErrorKind;
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let proof = tree.get_proof_at(3)?;
+    ///
+    ///     tree.verify_proof_at(3, &proof)?;
+    ///     let error = tree.verify_proof_at(3, &proof[..2]).unwrap_err();
+    ///     assert_eq!(error.kind(), &TreeErrorKind::ProofLengthMismatch);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn verify_proof_at(&self, index: usize, proof: &[T::Hash]) -> TreeResult<()> {
+        let expected = self.merkle_proof_at(index)?;
+        if proof.len() != expected.proof().len() {
+            return Err(TreeError::proof_length_mismatch(
+                "proof length does not match the depth of the leaf",
+            ));
+        }
+
+        let candidate: MerkleProof<T> = match expected.positions() {
+            Some(positions) => MerkleProof::with_positions(proof.to_vec(), positions.to_vec())?,
+            None => MerkleProof::new(proof.to_vec()),
+        };
+        match candidate.validate(self.root_hash()?, self.leaves.as_slice()[index]) {
+            true => Ok(()),
+            false => Err(TreeError::proof_invalid()),
+        }
+    }
+
+    /// Creates the proof of every leaf, in leaf order, from a single pass over the levels
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let root = tree.root_hash()?;
+    ///
+    ///     let proofs = tree.proofs_for_all_leaves()?;
+    ///     assert_eq!(proofs.len(), leaves.len());
+    ///     for (leaf, proof) in leaves.iter().zip(proofs) {
+    ///         assert!(Sha256Proof::new(proof).validate(root, *leaf));
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn proofs_for_all_leaves(&self) -> TreeResult<Vec<Vec<T::Hash>>> {
+        let levels = self.cached_levels()?;
+
+        (0..self.leaves.len())
+            .map(|index| proof_from_levels::<T>(levels, index, &self.policy))
+            .collect()
+    }
+
+    /// Creates a cursor walking every leaf in order together with its proof
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let root = tree.root_hash()?;
+    ///
+    ///     for (index, leaf, proof) in tree.cursor()? {
+    ///         assert_eq!(leaf, leaves[index]);
+    ///         assert!(Sha256Proof::new(proof).validate(root, leaf));
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn cursor(&self) -> TreeResult<ProofCursor<T>> {
+        Ok(ProofCursor::new(
+            Arc::clone(self.cached_levels()?),
+            self.policy,
+        ))
+    }
+
+    /// Creates proofs for `count` leaves picked pseudo-randomly from the seed
+    ///
+    /// The index of each sample is derived from `hash(seed || sample number)`, so anyone
+    /// holding the seed (e.g. a block hash) can re-derive which leaves had to be revealed.
+    /// Samples are drawn independently and may repeat.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..10).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let root = tree.root_hash()?;
+    ///
+    ///     for (index, proof) in tree.sample_proofs("block".as_bytes(), 3)? {
+    ///         assert!(Sha256Proof::new(proof).validate(root, leaves[index]));
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn sample_proofs(
+        &self,
+        seed: &[u8],
+        count: usize,
+    ) -> TreeResult<Vec<(usize, Vec<T::Hash>)>> {
+        if self.leaves.is_empty() {
+            return Err(TreeError::tree_empty());
+        }
+
+        let levels = self.cached_levels()?;
+        (0..count as u64)
+            .map(|sample| {
+                let digest = T::try_hash(&[seed, &sample.to_be_bytes()].concat())?;
+                let index = digest
+                    .as_ref()
+                    .iter()
+                    .take(8)
+                    .fold(0u64, |acc, byte| acc << 8 | *byte as u64)
+                    % self.leaves.len() as u64;
+
+                let index = index as usize;
+                Ok((index, proof_from_levels::<T>(levels, index, &self.policy)?))
+            })
+            .collect()
+    }
+
+    /// Creates a consistency proof showing that the first `old_size` leaves form a prefix
+    /// of this tree, see [`ConsistencyProof`](crate::ConsistencyProof)
+    ///
+    /// Fails with `ProofMalformed` unless the tree sorts pairs and promotes odd nodes, the
+    /// only shape the RFC 6962 algorithms verify.
+    pub fn consistency_proof(&self, old_size: usize) -> TreeResult<Vec<T::Hash>> {
+        if self.policy.pair_ordering != PairOrdering::Sorted
+            || self.policy.odd_leaf != OddLeafPolicy::Promote
+        {
+            return Err(TreeError::proof_malformed(
+                "consistency proofs need a tree that sorts pairs and promotes odd nodes",
+            ));
+        }
+        if old_size == 0 || old_size > self.leaves.len() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        consistency_path::<T>(self.leaves.as_slice(), old_size, true)
+    }
+
+    /// Indices of the leaves that differ between this tree and `other`
+    ///
+    /// Both trees are descended from the root together and only subtrees whose hashes differ
+    /// are entered, so the descent costs O(k log n) for k differences. It runs over the cached
+    /// levels of both trees: a tree whose levels are not cached yet, after being built or
+    /// changed, first hashes its O(n) interior nodes once. Leaves present in only one of the
+    /// trees are reported as differing.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut leaves: Vec<_> = (0..8).map(|i| Sha256::hash(&[i])).collect();
+    ///     let replica = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     leaves[5] = Sha256::hash("changed".as_bytes());
+    ///     leaves.push(Sha256::hash(&[8]));
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     assert_eq!(tree.diff(&replica)?, vec![5, 8]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn diff(&self, other: &Self) -> TreeResult<Vec<usize>> {
+        let levels = self.cached_levels()?;
+        let other_levels = other.cached_levels()?;
+
+        let mut differences = Vec::new();
+        let top = levels.len().max(other_levels.len()) - 1;
+        diff_subtree::<T>(levels, other_levels, top, 0, &mut differences);
+
+        Ok(differences)
+    }
+
+    /// Whether both trees commit to the same leaves in the same shape, compared by root
+    ///
+    /// Roots are cached, so comparing trees whose roots were already taken is O(1). Two empty
+    /// trees are equal.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..8).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let mut replica = tree.clone();
+    ///     assert!(tree.structurally_equal(&replica)?);
+    ///
+    ///     replica.append(Sha256::hash(&[8]));
+    ///     assert!(!tree.structurally_equal(&replica)?);
+    ///     assert_eq!(tree.first_divergence(&replica)?, Some((0, 8)));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn structurally_equal(&self, other: &Self) -> TreeResult<bool> {
+        match (self.is_empty(), other.is_empty()) {
+            (true, true) => Ok(true),
+            (false, false) => Ok(self.root_hash()? == other.root_hash()?),
+            _ => Ok(false),
+        }
+    }
+
+    /// Where the trees diverge, as the `(level, index)` of the first node on the way down from
+    /// the root whose differences do not narrow to a single child, or `None` when they match
+    ///
+    /// A single differing leaf is reported as `(0, index)`; differences spread over both halves
+    /// of a subtree are reported as the root of that subtree.
+    pub fn first_divergence(&self, other: &Self) -> TreeResult<Option<(usize, usize)>> {
+        let levels = self.cached_levels()?;
+        let other_levels = other.cached_levels()?;
+        let node = |levels: &TreeLevels<T>, level: usize, index: usize| {
+            levels
+                .get(level)
+                .and_then(|nodes| nodes.get(index).copied())
+        };
+        let differs = |level, index| node(levels, level, index) != node(other_levels, level, index);
+
+        let (mut level, mut index) = (levels.len().max(other_levels.len()) - 1, 0);
+        if !differs(level, index) {
+            return Ok(None);
+        }
+        while level > 0 {
+            match (
+                differs(level - 1, 2 * index),
+                differs(level - 1, 2 * index + 1),
+            ) {
+                (true, false) => index *= 2,
+                (false, true) => index = 2 * index + 1,
+                _ => break,
+            }
+            level -= 1;
+        }
+
+        Ok(Some((level, index)))
+    }
+
+    /// Takes a cheap, immutable view of the tree as it is now
+    ///
+    /// The snapshot shares the leaves, their index and any cached levels with the tree and can
+    /// be sent to other threads. Later changes never affect an existing snapshot: leaves are
+    /// stored in frozen chunks, so the first append after a snapshot only copies the last,
+    /// partial chunk and the most recent index entries rather than every leaf.
+    ///
     /// ##Examples
     /// ```
     /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
@@ -35,158 +847,264 @@ impl<T: ToHash> MerkleTree<T> {
     ///     let mut tree = Sha256Tree::new();
     ///     tree.append(Sha256::hash("0".as_bytes()));
     ///     tree.append(Sha256::hash("1".as_bytes()));
+    ///
+    ///     let snapshot = tree.read_snapshot();
+    ///     let reader = std::thread::spawn(move || snapshot.root_hash());
     ///     tree.append(Sha256::hash("2".as_bytes()));
-    ///     tree.append(Sha256::hash("3".as_bytes()));
-    ///     tree.append(Sha256::hash("4".as_bytes()));
     ///
-    ///     let hash = tree.root_hash()?;
-    ///     let expected_hash = [
-    ///         79, 188, 211, 24, 18, 206, 74, 81, 223, 16, 54, 39, 77, 67, 62, 72, 61,
-    ///         164, 15, 45, 199, 119, 195, 239, 187, 172, 49, 64, 115, 159, 58, 83
-    ///     ];
-    ///     assert_eq!(hash, expected_hash);
+    ///     assert_ne!(reader.join().unwrap()?, tree.root_hash()?);
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub fn append(&mut self, leaf: T::Hash) {
-        self.leaves.push(leaf);
+    pub fn read_snapshot(&self) -> TreeSnapshot<T> {
+        TreeSnapshot::new(
+            Arc::clone(&self.leaves),
+            self.policy,
+            self.levels.get().cloned(),
+        )
     }
 
-    /// Creates a root hash for the given tree
+    /// Every level of the tree, from the leaves up to the root
+    ///
+    /// The levels are built on first use and shared with every later proof, node lookup and
+    /// cursor until the leaves or policy change.
     ///
     /// ##Examples
     /// ```
     /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
     /// fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let leaves = [
-    ///         Sha256::hash("0".as_bytes()),
-    ///         Sha256::hash("1".as_bytes()),
-    ///         Sha256::hash("2".as_bytes()),
-    ///         Sha256::hash("3".as_bytes()),
-    ///         Sha256::hash("4".as_bytes()),
-    ///     ];
-    ///     let mut tree = Sha256Tree::from_leaves(&leaves);
-    ///     let hash = tree.root_hash()?;
-    ///     let expected_hash = [
-    ///         79, 188, 211, 24, 18, 206, 74, 81, 223, 16, 54, 39, 77, 67, 62, 72, 61,
-    ///         164, 15, 45, 199, 119, 195, 239, 187, 172, 49, 64, 115, 159, 58, 83
-    ///     ];
-    ///     assert_eq!(hash, expected_hash);
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     let levels = tree.levels()?;
+    ///     let widths: Vec<_> = levels.iter().map(|level| level.len()).collect();
+    ///     assert_eq!(widths, vec![5, 3, 2, 1]);
+    ///     assert_eq!(levels.iter().last(), Some(&[tree.root_hash()?][..]));
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub fn root_hash(&mut self) -> TreeResult<T::Hash> {
-        if self.leaves.is_empty() {
-            return Err(TreeError::tree_empty());
+    pub fn levels(&self) -> TreeResult<&TreeLevels<T>> {
+        Ok(self.cached_levels()?)
+    }
+
+    /// Every node of the tree as `(level, index, hash)`, level by level from the leaves up
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     let root = tree.root_hash()?;
+    ///     assert_eq!(tree.nodes()?.last(), Some((3, 0, root)));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn nodes(&self) -> TreeResult<impl Iterator<Item = (usize, usize, T::Hash)> + '_> {
+        Ok(self.cached_levels()?.nodes())
+    }
+
+    /// Node at `index` within `level`, the leaves being level 0 and the root the last level
+    ///
+    /// Fails with `IndexOutOfBounds` when the level or index lies outside the tree.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     assert_eq!(tree.node(0, 3)?, leaves[3]);
+    ///     assert_eq!(tree.node(1, 1)?, Sha256::combine(leaves[2], leaves[3]));
+    ///     assert_eq!(tree.node(3, 0)?, tree.root_hash()?);
+    ///     assert!(tree.node(1, 3).is_err());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn node(&self, level: usize, index: usize) -> TreeResult<T::Hash> {
+        if index >= self.leaves.len() {
+            return Err(TreeError::index_out_of_bounds());
         }
 
-        Ok(self.reduce_tree(&mut self.leaves.clone(), false)?[0])
+        self.cached_levels()?
+            .get(level)
+            .and_then(|nodes| nodes.get(index).copied())
+            .ok_or_else(TreeError::index_out_of_bounds)
     }
 
-    /// Creates a proof (path) for validating presence of the leaf in the tree
+    /// Root of a tree over the leaves in `range`, built with this tree's policy
+    ///
+    /// Commits to the range on its own, e.g. to anchor each shard of a tree independently.
+    /// When the range is aligned to its length, a power of two, this is the node above it,
+    /// so `subtree_root(2048..3072)` equals `node(10, 2)`.
     ///
     /// ##Examples
     /// ```
     /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
     /// fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     let leaves = [
-    ///         Sha256::hash("0".as_bytes()),
-    ///         Sha256::hash("1".as_bytes()),
-    ///         Sha256::hash("2".as_bytes()),
-    ///         Sha256::hash("3".as_bytes()),
-    ///         Sha256::hash("4".as_bytes()),
-    ///     ];
-    ///     let mut tree = Sha256Tree::from_leaves(&leaves);
-    ///     let proof = tree.get_proof(leaves[3])?;
-    ///     let expected_proof = vec![
-    ///         leaves[2],
-    ///         Sha256::combine(leaves[0], leaves[1]),
-    ///         leaves[4],
-    ///     ];
+    ///     let leaves: Vec<_> = (0..6).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
     ///
-    ///     assert_eq!(proof, expected_proof);
+    ///     assert_eq!(tree.subtree_root(4..6)?, tree.node(1, 2)?);
+    ///     assert_eq!(tree.subtree_root(1..4)?, Sha256Tree::from_leaves(&leaves[1..4]).root_hash()?);
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub fn get_proof(&mut self, leaf: T::Hash) -> TreeResult<Vec<T::Hash>> {
-        self.current_path_leaf = Some(leaf);
-        self.reduce_tree(&mut self.leaves.clone(), true)?;
-        let proof = self.path.clone();
-        self.clear_path();
+    pub fn subtree_root(&self, range: Range<usize>) -> TreeResult<T::Hash> {
+        if range.start >= range.end || range.end > self.leaves.len() {
+            return Err(TreeError::index_out_of_bounds());
+        }
 
-        Ok(proof)
+        root_of::<T>(&[&self.leaves.as_slice()[range]], &self.policy)
     }
 
-    fn clear_path(&mut self) {
-        self.path = Vec::new();
-        self.current_path_leaf = None;
+    /// Levels of the tree, built on first use and kept until the leaves or policy change
+    pub(crate) fn cached_levels(&self) -> TreeResult<&Arc<TreeLevels<T>>> {
+        if let Some(levels) = self.levels.get() {
+            return Ok(levels);
+        }
+
+        let levels = levels_of::<T>(&self.leaves.segments(), &self.policy)?;
+        Ok(self.levels.get_or_init(|| Arc::new(levels)))
     }
+}
 
-    fn reduce_tree(
-        &mut self,
-        leaves: &mut Vec<T::Hash>,
-        generate_path: bool,
-    ) -> TreeResult<Vec<T::Hash>> {
-        let mut processed_leaves = self.process_leaves_in_pairs(leaves, generate_path)?;
+/// Every level of a tree over the given leaves, stored in one or more segments, from the
+/// leaves up to the root
+pub(crate) fn levels_of<T: ToHash>(
+    leaves: &[&[T::Hash]],
+    policy: &TreePolicy,
+) -> TreeResult<TreeLevels<T>> {
+    TreeLevels::build(
+        leaves,
+        |children, parents| policy.combine_pairs::<T>(children, parents),
+        |node| policy.lift_odd::<T>(node),
+    )
+}
 
-        if processed_leaves.len() > 1 {
-            processed_leaves = self.reduce_tree(&mut processed_leaves, generate_path)?;
-        }
+/// Pairs submitted to the hasher at once by [`root_of`]
+const ROOT_BATCH_LEN: usize = 64;
 
-        Ok(processed_leaves)
+/// Root of a tree over the given leaves, stored in one or more segments, reduced level by
+/// level inside a single buffer
+///
+/// Each parent overwrites the front of the buffer, so only the leaves are ever copied and no
+/// intermediate level is kept, unlike [`levels_of`]. Pairs are hashed in batches of
+/// [`ROOT_BATCH_LEN`], whose parents land before any pair not yet read.
+pub(crate) fn root_of<T: ToHash>(
+    leaves: &[&[T::Hash]],
+    policy: &TreePolicy,
+) -> TreeResult<T::Hash> {
+    let mut nodes = leaves.concat();
+    let mut len = nodes.len();
+    if len == 0 {
+        return Err(TreeError::tree_empty());
     }
 
-    fn process_leaves_in_pairs(
-        &mut self,
-        leaves: &mut Vec<T::Hash>,
-        generate_path: bool,
-    ) -> TreeResult<Vec<T::Hash>> {
-        let mut processed_leaves = vec![];
-        for index in 0..leaves.len() / 2 {
-            let leaf_left = leaves[2 * index];
-            let leaf_right = leaves[2 * index + 1];
-            let combined_leaf = T::combine(leaf_left, leaf_right);
-            processed_leaves.push(combined_leaf);
-            if generate_path {
-                self.add_to_path(leaf_left, leaf_right, combined_leaf)?;
-            }
+    let mut parents = Vec::with_capacity(ROOT_BATCH_LEN);
+    while len > 1 {
+        for start in (0..len / 2).step_by(ROOT_BATCH_LEN) {
+            let end = (start + ROOT_BATCH_LEN).min(len / 2);
+            policy.combine_pairs::<T>(&nodes[2 * start..2 * end], &mut parents)?;
+            nodes[start..end].copy_from_slice(&parents);
+            parents.clear();
         }
-
-        if leaves.len() % 2 == 1 {
-            processed_leaves.push(*leaves.last().ok_or_else(TreeError::leaf_empty)?);
+        if len % 2 == 1 {
+            nodes[len / 2] = policy.lift_odd::<T>(nodes[len - 1])?;
         }
+        len = len.div_ceil(2);
+    }
+
+    Ok(nodes[0])
+}
+
+/// Collects the leaf indices below `(level, index)` whose hashes differ between the trees
+fn diff_subtree<T: ToHash>(
+    levels: &TreeLevels<T>,
+    other_levels: &TreeLevels<T>,
+    level: usize,
+    index: usize,
+    differences: &mut Vec<usize>,
+) {
+    let node = levels.get(level).and_then(|hashes| hashes.get(index));
+    let other_node = other_levels.get(level).and_then(|hashes| hashes.get(index));
+    match (node, other_node) {
+        (None, None) => return,
+        (Some(node), Some(other_node)) if node == other_node => return,
+        _ => {}
+    }
 
-        Ok(processed_leaves)
+    if level == 0 {
+        differences.push(index);
+        return;
     }
 
-    fn add_to_path(
-        &mut self,
-        leaf_left: T::Hash,
-        leaf_right: T::Hash,
-        combined_leaf: T::Hash,
-    ) -> TreeResult<()> {
-        let current_path_leaf = self
-            .current_path_leaf
-            .ok_or_else(TreeError::path_leaf_not_set)?;
+    diff_subtree::<T>(levels, other_levels, level - 1, index * 2, differences);
+    diff_subtree::<T>(levels, other_levels, level - 1, index * 2 + 1, differences);
+}
 
-        if leaf_left != current_path_leaf && leaf_right != current_path_leaf {
-            return Ok(());
-        }
+/// Collects the siblings of the node at `index` on every level below the root
+pub(crate) fn proof_from_levels<T: ToHash>(
+    levels: &TreeLevels<T>,
+    index: usize,
+    policy: &TreePolicy,
+) -> TreeResult<Vec<T::Hash>> {
+    Ok(path_from_levels::<T>(levels, index, policy)?
+        .into_iter()
+        .map(|(sibling, _)| sibling)
+        .collect())
+}
 
-        if leaf_left == current_path_leaf {
-            self.path.push(leaf_right);
+/// Collects the siblings of the node at `index` together with the side each one sits on
+pub(crate) fn path_from_levels<T: ToHash>(
+    levels: &TreeLevels<T>,
+    index: usize,
+    policy: &TreePolicy,
+) -> TreeResult<Vec<(T::Hash, Position)>> {
+    let mut path = Vec::with_capacity(levels.len());
+    let mut index = index;
+    for level in levels.iter().take(levels.len() - 1) {
+        let sibling = match level.get(index ^ 1) {
+            Some(sibling) => Some(*sibling),
+            None => policy.odd_sibling::<T>(level[index])?,
+        };
+        if let Some(sibling) = sibling {
+            path.push((
+                sibling,
+                policy.sibling_position(index, level[index], sibling),
+            ));
         }
+        index /= 2;
+    }
 
-        if leaf_right == current_path_leaf {
-            self.path.push(leaf_left);
-        }
+    Ok(path)
+}
 
-        self.current_path_leaf = Some(combined_leaf);
+impl<T: ToHash> Clone for MerkleTree<T> {
+    fn clone(&self) -> Self {
+        Self {
+            leaves: Arc::clone(&self.leaves),
+            policy: self.policy,
+            root: self.root.clone(),
+            levels: self.levels.clone(),
+            bloom: self.bloom.clone(),
+        }
+    }
+}
 
-        Ok(())
+/// Trees are equal when they hold the same leaves under the same policy
+impl<T: ToHash> PartialEq for MerkleTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.policy == other.policy
+            && (Arc::ptr_eq(&self.leaves, &other.leaves)
+                || self.leaves.iter().eq(other.leaves.iter()))
     }
 }
 
@@ -199,26 +1117,249 @@ impl<T: ToHash> Default for MerkleTree<T> {
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
+    use crate::error::hash_error::HashError;
     use crate::error::tree_error::TreeErrorKind;
-    use crate::hash::Sha256;
+    use crate::hash::{Sha256, Sha2_256};
+    use crate::utils::hex;
     use crate::utils::test::{full_root_hash, raw_leaves_to_hashed_leaves};
-    use crate::Sha256Tree;
+    use crate::{Sha256Proof, Sha256Tree, TreeBuilder};
+    use std::error::Error;
 
     #[test]
     fn test_empty_tree_returns_error() {
-        let mut tree = Sha256Tree::from_leaves(&[]);
+        let tree = Sha256Tree::from_leaves(&[]);
         assert_eq!(
             tree.root_hash().unwrap_err().kind(),
             &TreeErrorKind::TreeEmpty
         );
     }
 
+    #[test]
+    fn test_nodes_and_subtree_roots_match_the_levels() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3", "4", "5", "6"]);
+        for odd_leaf in [OddLeafPolicy::Promote, OddLeafPolicy::DuplicateLast] {
+            let tree = Sha256Tree::from_leaves(&leaves).with_odd_leaf_policy(odd_leaf);
+            for (level, index, hash) in tree.nodes().unwrap() {
+                assert_eq!(tree.node(level, index).unwrap(), hash);
+            }
+            assert!(tree.node(tree.depth() + 1, 0).is_err());
+
+            for (start, size) in [(0, 1), (4, 2), (0, 4), (0, 8)] {
+                let end = (start + size).min(leaves.len());
+                let level = size.trailing_zeros() as usize;
+                let subtree = Sha256Tree::from_leaves(&leaves[start..end])
+                    .with_odd_leaf_policy(odd_leaf)
+                    .root_hash()
+                    .unwrap();
+                assert_eq!(tree.subtree_root(start..end).unwrap(), subtree);
+                if end - start == size {
+                    assert_eq!(subtree, tree.node(level, start / size).unwrap());
+                }
+            }
+        }
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        assert!(tree.subtree_root(3..3).is_err());
+        assert!(tree.subtree_root(3..8).is_err());
+    }
+
+    #[test]
+    fn test_structural_comparisons() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3", "4", "5"]);
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let copy = tree.clone();
+        assert!(tree == copy);
+        assert!(tree.structurally_equal(&copy).unwrap());
+        assert_eq!(tree.first_divergence(&copy).unwrap(), None);
+
+        let mut changed = leaves.clone();
+        changed[3] = leaves[0];
+        let other = Sha256Tree::from_leaves(&changed);
+        assert!(tree != other);
+        assert!(!tree.structurally_equal(&other).unwrap());
+        assert_eq!(tree.first_divergence(&other).unwrap(), Some((0, 3)));
+
+        changed[1] = leaves[0];
+        let other = Sha256Tree::from_leaves(&changed);
+        assert_eq!(tree.first_divergence(&other).unwrap(), Some((2, 0)));
+
+        let shorter = Sha256Tree::from_leaves(&leaves[..5]);
+        assert_eq!(tree.first_divergence(&shorter).unwrap(), Some((0, 5)));
+        assert_eq!(shorter.first_divergence(&tree).unwrap(), Some((0, 5)));
+
+        // ascending leaves, so the sorted tree hashes every pair the other way around
+        let mut ascending = leaves.clone();
+        ascending.sort();
+        let tree = Sha256Tree::from_leaves(&ascending);
+        let positional = tree.clone().with_policy(TreePolicy {
+            pair_ordering: PairOrdering::Positional,
+            ..TreePolicy::default()
+        });
+        assert!(tree != positional);
+        assert!(!tree.structurally_equal(&positional).unwrap());
+        assert!(Sha256Tree::new()
+            .structurally_equal(&Sha256Tree::new())
+            .unwrap());
+        assert!(!Sha256Tree::new().structurally_equal(&tree).unwrap());
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "0", "2", "1"]);
+
+        let error = Sha256Tree::from_leaves(&leaves)
+            .with_duplicate_policy(DuplicatePolicy::Reject)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), &TreeErrorKind::LeafDuplicated);
+
+        let mut tree = Sha256Tree::from_leaves(&leaves)
+            .with_duplicate_policy(DuplicatePolicy::Deduplicate)
+            .unwrap();
+        assert_eq!(tree.leaves(), [leaves[0], leaves[1], leaves[3]]);
+        tree.append(leaves[0]);
+        assert_eq!(tree.leaf_count(), 3);
+        for (index, leaf) in tree.leaves().iter().enumerate() {
+            assert_eq!(tree.index_of(leaf), Some(index));
+        }
+
+        let mut tree = tree.with_duplicate_policy(DuplicatePolicy::Allow).unwrap();
+        assert!(tree.try_append(leaves[0]).unwrap());
+        assert_eq!(tree.index_of(&leaves[0]), Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate leaf rejected")]
+    fn test_append_panics_on_rejected_duplicates() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0"]);
+        let mut tree = Sha256Tree::from_leaves(&leaves)
+            .with_duplicate_policy(DuplicatePolicy::Reject)
+            .unwrap();
+        tree.append(leaves[0]);
+    }
+
+    struct UnavailableHash {}
+
+    impl ToHash for UnavailableHash {
+        type Hash = [u8; 32];
+        const ALGORITHM: &'static str = "unavailable";
+
+        fn hash(_value: &[u8]) -> Self::Hash {
+            unreachable!("trees only use try_hash")
+        }
+
+        fn try_hash(_value: &[u8]) -> Result<Self::Hash, HashError> {
+            Err(HashError::new("backend unavailable"))
+        }
+    }
+
+    #[test]
+    fn test_hasher_failure_is_reported() {
+        let tree = MerkleTree::<UnavailableHash>::from_leaves(&[[0; 32], [1; 32]]);
+        let error = tree.root_hash().unwrap_err();
+
+        assert_eq!(error.kind(), &TreeErrorKind::HashFailed);
+        assert_eq!(error.source().unwrap().to_string(), "backend unavailable");
+    }
+
+    /// Cheap hasher so that trees with millions of leaves stay fast in debug builds
+    struct XorHash {}
+
+    impl ToHash for XorHash {
+        type Hash = [u8; 8];
+        const ALGORITHM: &'static str = "xor";
+
+        fn hash(value: &[u8]) -> Self::Hash {
+            value.chunks(8).fold([0; 8], |mut acc, chunk| {
+                acc.iter_mut()
+                    .zip(chunk)
+                    .for_each(|(acc, byte)| *acc ^= byte);
+                acc.rotate_left(1);
+                acc
+            })
+        }
+    }
+
+    #[test]
+    fn test_huge_tree_reduces_on_small_stack() {
+        let leaves: Vec<_> = (0..(1u64 << 21) + 1).map(u64::to_be_bytes).collect();
+        let expected = levels_of::<XorHash>(&[&leaves], &TreePolicy::default()).unwrap();
+
+        let root = std::thread::Builder::new()
+            .stack_size(32 * 1024)
+            .spawn(move || MerkleTree::<XorHash>::from_leaves(&leaves).root_hash())
+            .unwrap()
+            .join()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(expected.len(), 23);
+        assert_eq!(root, expected[22][0]);
+    }
+
+    #[test]
+    fn test_introspection() {
+        let tree = Sha256Tree::new();
+        assert!(tree.is_empty());
+        assert_eq!(tree.leaf_count(), 0);
+        assert_eq!(tree.depth(), 0);
+        assert_eq!(tree.leaf_at(0), None);
+
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves[..1]);
+        assert_eq!(tree.depth(), 0);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        assert!(!tree.is_empty());
+        assert_eq!(tree.leaf_count(), 5);
+        assert_eq!(tree.depth(), 3);
+        assert_eq!(tree.leaves(), &leaves[..]);
+        assert_eq!(tree.leaf_at(4), Some(leaves[4]));
+        assert_eq!(tree.leaf_at(5), None);
+    }
+
+    #[test]
+    fn test_membership_tracks_appends_and_truncation() {
+        let leaves_raw = ["0", "1", "2", "1"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let mut tree = Sha256Tree::from_leaves(&leaves[..2]);
+        tree.append(leaves[2]);
+        tree.append(leaves[3]);
+        assert!(tree.contains(&leaves[2]));
+        assert_eq!(tree.index_of(&leaves[1]), Some(1));
+
+        tree.truncate(2);
+        assert!(!tree.contains(&leaves[2]));
+        assert_eq!(tree.index_of(&leaves[1]), Some(1));
+        assert_eq!(tree.index_of(&leaves[0]), Some(0));
+    }
+
     #[test]
     fn test_valid_root_hash() {
         let leaves_raw = ["0", "1", "2", "3", "4"];
         let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
 
-        let mut tree = Sha256Tree::from_leaves(&leaves);
+        let tree = Sha256Tree::from_leaves(&leaves);
+        assert_eq!(tree.root_hash().unwrap(), full_root_hash::<Sha256>(&leaves));
+    }
+
+    #[test]
+    fn test_cached_root_is_shared_and_invalidated() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Arc::new(Sha256Tree::from_leaves(&leaves[..4]));
+        let reader = {
+            let tree = Arc::clone(&tree);
+            std::thread::spawn(move || tree.root_hash().unwrap())
+        };
+        assert_eq!(reader.join().unwrap(), tree.root_hash().unwrap());
+
+        let mut tree = Arc::try_unwrap(tree).ok().unwrap();
+        tree.append(leaves[4]);
         assert_eq!(tree.root_hash().unwrap(), full_root_hash::<Sha256>(&leaves));
     }
 
@@ -238,7 +1379,261 @@ pub(crate) mod tests {
         // proof for leaf [2 <-, [01] <-, -> 4]
         let expected_proof = vec![leaves[2], Sha256::combine(leaves[0], leaves[1]), leaves[4]];
 
-        let mut tree = Sha256Tree::from_leaves(&leaves);
+        let tree = Sha256Tree::from_leaves(&leaves);
         assert_eq!(tree.get_proof(leaves[3]).unwrap(), expected_proof);
+        assert_eq!(tree.get_proof_at(3).unwrap(), expected_proof);
+        assert_eq!(
+            tree.get_proof(Sha256::hash("5".as_bytes()))
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::LeafNotFound
+        );
+        assert_eq!(
+            tree.get_proof_at(5).unwrap_err().kind(),
+            &TreeErrorKind::IndexOutOfBounds
+        );
+    }
+
+    #[test]
+    fn test_verify_proof_at_reports_why_proofs_fail() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let proof = tree.get_proof_at(4).unwrap();
+        assert!(tree.verify_proof_at(4, &proof).is_ok());
+
+        let kind =
+            |index, proof: &[[u8; 32]]| *tree.verify_proof_at(index, proof).unwrap_err().kind();
+        assert_eq!(kind(5, &proof), TreeErrorKind::IndexOutOfBounds);
+        assert_eq!(kind(1, &proof), TreeErrorKind::ProofLengthMismatch);
+        assert_eq!(
+            kind(3, &tree.get_proof_at(2).unwrap()),
+            TreeErrorKind::ProofInvalid
+        );
+    }
+
+    #[test]
+    fn test_proofs_for_all_leaves_match_single_proofs() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let proofs = tree.proofs_for_all_leaves().unwrap();
+
+        assert_eq!(proofs.len(), leaves.len());
+        for (index, proof) in proofs.into_iter().enumerate() {
+            assert_eq!(proof, tree.get_proof_at(index).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_changed_and_extra_leaves() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+        let tree = Sha256Tree::from_leaves(&leaves);
+
+        assert!(tree
+            .diff(&Sha256Tree::from_leaves(&leaves))
+            .unwrap()
+            .is_empty());
+
+        let mut changed = leaves.clone();
+        changed[1] = Sha256::hash("changed".as_bytes());
+        assert_eq!(
+            tree.diff(&Sha256Tree::from_leaves(&changed)).unwrap(),
+            vec![1]
+        );
+        assert_eq!(
+            tree.diff(&Sha256Tree::from_leaves(&leaves[..4])).unwrap(),
+            vec![4]
+        );
+        assert_eq!(
+            Sha256Tree::new()
+                .diff(&Sha256Tree::from_leaves(&leaves[..2]))
+                .unwrap(),
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    fn test_odd_leaf_policies_match_reference_roots() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2"]);
+        let root = |odd_leaf| {
+            Sha256Tree::from_leaves(&leaves)
+                .with_odd_leaf_policy(odd_leaf)
+                .root_hash()
+                .unwrap()
+        };
+        let pair = Sha256::combine(leaves[0], leaves[1]);
+
+        assert_eq!(
+            root(OddLeafPolicy::Promote),
+            Sha256::combine(pair, leaves[2])
+        );
+        assert_eq!(
+            root(OddLeafPolicy::DuplicateLast),
+            Sha256::combine(pair, Sha256::combine(leaves[2], leaves[2]))
+        );
+        assert_eq!(
+            root(OddLeafPolicy::PadWithZeroHash),
+            Sha256::combine(pair, Sha256::combine(leaves[2], [0; 32]))
+        );
+
+        for odd_leaf in [OddLeafPolicy::DuplicateLast, OddLeafPolicy::PadWithZeroHash] {
+            let tree = Sha256Tree::from_leaves(&leaves).with_odd_leaf_policy(odd_leaf);
+            let proof = tree.get_proof_at(2).unwrap();
+            assert_eq!(proof.len(), 2);
+            assert!(Sha256Proof::new(proof).validate(root(odd_leaf), leaves[2]));
+        }
+    }
+
+    #[test]
+    fn test_promoted_positional_tree_matches_rfc6962() {
+        let data: [&[u8]; 8] = [
+            b"",
+            b"\x00",
+            b"\x10",
+            b"\x20\x21",
+            b"\x30\x31",
+            b"\x40\x41\x42\x43",
+            b"\x50\x51\x52\x53\x54\x55\x56\x57",
+            b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+        ];
+        let roots = [
+            (
+                1,
+                "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+            ),
+            (
+                3,
+                "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+            ),
+            (
+                5,
+                "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+            ),
+            (
+                7,
+                "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+            ),
+            (
+                8,
+                "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+            ),
+        ];
+
+        for (size, expected) in roots {
+            let mut builder = TreeBuilder::<Sha2_256>::new()
+                .pair_ordering(PairOrdering::Positional)
+                .odd_leaf_policy(OddLeafPolicy::Promote);
+            for leaf in &data[..size] {
+                builder = builder.leaf_data(leaf);
+            }
+            let root = builder.build().unwrap().root_hash().unwrap();
+
+            assert_eq!(hex::encode(&root), expected);
+        }
+    }
+
+    #[test]
+    fn test_proof_len_matches_generated_proofs() {
+        let leaves: Vec<_> = (0..17u8).map(|i| Sha256::hash(&[i])).collect();
+        for odd_leaf in [
+            OddLeafPolicy::Promote,
+            OddLeafPolicy::DuplicateLast,
+            OddLeafPolicy::PadWithZeroHash,
+        ] {
+            for size in 1..=leaves.len() {
+                let tree = Sha256Tree::from_leaves(&leaves[..size]).with_odd_leaf_policy(odd_leaf);
+                for index in 0..size {
+                    let proof_len = tree.get_proof_at(index).unwrap().len();
+                    assert_eq!(tree.proof_len_for(index).unwrap(), proof_len);
+                    assert!(proof_len <= Sha256Tree::expected_proof_len(size));
+                }
+                assert_eq!(
+                    tree.proof_len_for(size).unwrap_err().kind(),
+                    &TreeErrorKind::IndexOutOfBounds
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter_survives_round_trip_and_updates() {
+        let leaves: Vec<_> = (0..200u8).map(|i| Sha256::hash(&[i])).collect();
+        let tree = Sha256Tree::from_leaves(&leaves).with_bloom_filter(0.01);
+        let bytes = tree.bloom_filter().unwrap().to_bytes();
+
+        let mut restored = Sha256Tree::from_leaves(&leaves)
+            .attach_bloom_filter(BloomFilter::from_bytes(&bytes).unwrap());
+        restored.replace_leaf(3, Sha256::hash(b"replacement"));
+        assert!(restored.maybe_contains(&Sha256::hash(b"replacement")));
+        assert!(leaves.iter().all(|leaf| restored.maybe_contains(leaf)));
+
+        let absent = (0..1000u32)
+            .filter(|i| tree.maybe_contains(&Sha256::hash(&i.to_le_bytes()[..3])))
+            .count();
+        assert!(absent < 60, "{absent} false positives");
+        assert!(!Sha256Tree::from_leaves(&leaves).maybe_contains(&Sha256::hash(b"absent")));
+    }
+
+    #[test]
+    fn test_bloom_filter_survives_deduplication() {
+        let leaves: Vec<_> = [0u8, 1, 0, 2].iter().map(|i| Sha256::hash(&[*i])).collect();
+        let tree = Sha256Tree::from_leaves(&leaves)
+            .with_bloom_filter(0.01)
+            .with_duplicate_policy(DuplicatePolicy::Deduplicate)
+            .unwrap();
+
+        assert_eq!(tree.leaf_count(), 3);
+        assert!(tree.bloom_filter().is_some());
+        assert!(leaves.iter().all(|leaf| tree.maybe_contains(leaf)));
+    }
+
+    #[test]
+    fn test_sample_proofs_are_deterministic() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let samples = tree.sample_proofs("seed".as_bytes(), 8).unwrap();
+
+        assert_eq!(samples.len(), 8);
+        assert_eq!(samples, tree.sample_proofs("seed".as_bytes(), 8).unwrap());
+        for (index, proof) in samples {
+            assert_eq!(proof, tree.get_proof_at(index).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_append_from_reader_follows_duplicate_policy() {
+        let data = vec![7u8; 100_000];
+        let mut tree = Sha256Tree::new()
+            .with_duplicate_policy(DuplicatePolicy::Reject)
+            .unwrap();
+
+        let leaf = tree.append_from_reader(data.as_slice()).unwrap();
+        assert_eq!(leaf, Sha256::hash(&data));
+        assert_eq!(tree.leaves(), &[leaf]);
+        assert_eq!(
+            tree.append_from_reader(data.as_slice()).unwrap_err().kind(),
+            &TreeErrorKind::LeafDuplicated
+        );
+    }
+
+    #[test]
+    fn test_levels_are_cached_until_the_tree_changes() {
+        let leaves: Vec<_> = (0..5u8).map(|i| Sha256::hash(&[i])).collect();
+        let mut tree = Sha256Tree::from_leaves(&leaves);
+
+        let levels: *const TreeLevels<Sha256> = tree.levels().unwrap();
+        tree.get_proof_at(3).unwrap();
+        assert!(std::ptr::eq(levels, tree.levels().unwrap()));
+
+        tree.append(Sha256::hash(&[5]));
+        let proof = Sha256Proof::new(tree.get_proof_at(5).unwrap());
+        assert!(proof.validate(tree.root_hash().unwrap(), Sha256::hash(&[5])));
+        assert_eq!(tree.levels().unwrap()[0].len(), 6);
     }
 }
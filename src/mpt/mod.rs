@@ -0,0 +1,261 @@
+//! Verification of Ethereum Merkle Patricia Trie proofs, as returned by `eth_getProof`
+mod rlp;
+
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::hash::Keccak256;
+use crate::merkle_tree::TreeResult;
+use rlp::Rlp;
+
+const BRANCH_NODE_LEN: usize = 17;
+const SHORT_NODE_LEN: usize = 2;
+
+/// Account state committed to by the state trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub nonce: u64,
+    /// Big-endian balance in wei without leading zeros
+    pub balance: Vec<u8>,
+    pub storage_root: [u8; 32],
+    pub code_hash: [u8; 32],
+}
+
+enum NodeRef<'a> {
+    Hash(&'a [u8]),
+    Inline(Rlp<'a>),
+}
+
+/// Verifies a trie proof for `key` against `root`
+///
+/// Returns the stored value, or `None` when the proof shows that the key is absent. The
+/// key is used as-is; secure tries such as the state and storage tries expect it to be
+/// hashed first, see [`verify_account_proof`] and [`verify_storage_proof`].
+pub fn verify_proof(root: [u8; 32], key: &[u8], proof: &[Vec<u8>]) -> TreeResult<Option<Vec<u8>>> {
+    let nibbles: Vec<u8> = key
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect();
+    let mut remaining = &nibbles[..];
+    let mut proof_nodes = proof.iter();
+    let mut next = NodeRef::Hash(&root);
+
+    loop {
+        let node = match next {
+            NodeRef::Hash(hash) => {
+                let encoded = proof_nodes.next().ok_or_else(|| {
+                    TreeError::proof_malformed("proof ends before the key is resolved")
+                })?;
+                if Keccak256::hash(encoded).as_ref() != hash {
+                    return Err(TreeError::proof_malformed(
+                        "proof node does not match its hash",
+                    ));
+                }
+                rlp::decode(encoded)?
+            }
+            NodeRef::Inline(node) => node,
+        };
+
+        let items = node.as_list()?;
+        let child = match items.len() {
+            BRANCH_NODE_LEN => match remaining.split_first() {
+                None => return finish(value_of(&items[16])?, &mut proof_nodes),
+                Some((nibble, rest)) => {
+                    remaining = rest;
+                    &items[*nibble as usize]
+                }
+            },
+            SHORT_NODE_LEN => {
+                let (is_leaf, path) = decode_path(items[0].as_bytes()?)?;
+                if is_leaf {
+                    let value = match remaining == path.as_slice() {
+                        true => value_of(&items[1])?,
+                        false => None,
+                    };
+                    return finish(value, &mut proof_nodes);
+                }
+                match remaining.strip_prefix(path.as_slice()) {
+                    Some(rest) => remaining = rest,
+                    None => return finish(None, &mut proof_nodes),
+                }
+                &items[1]
+            }
+            _ => return Err(TreeError::invalid_encoding("unexpected trie node shape")),
+        };
+
+        next = match child {
+            Rlp::Bytes(hash) if hash.len() == 32 => NodeRef::Hash(hash),
+            Rlp::Bytes(&[]) => return finish(None, &mut proof_nodes),
+            Rlp::List(_) => NodeRef::Inline(child.clone()),
+            Rlp::Bytes(_) => {
+                return Err(TreeError::invalid_encoding("invalid trie node reference"))
+            }
+        };
+    }
+}
+
+/// Verifies an `accountProof` for `address` against a block's state root
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::mpt::verify_account_proof;
+///
+/// // a proof that cannot be resolved from the root is rejected
+/// assert!(verify_account_proof([0; 32], [0; 20], &[]).is_err());
+/// ```
+pub fn verify_account_proof(
+    state_root: [u8; 32],
+    address: [u8; 20],
+    proof: &[Vec<u8>],
+) -> TreeResult<Option<Account>> {
+    let encoded = match verify_proof(state_root, &Keccak256::hash(&address), proof)? {
+        Some(encoded) => encoded,
+        None => return Ok(None),
+    };
+
+    let account = rlp::decode(&encoded)?;
+    let fields = account.as_list()?;
+    if fields.len() != 4 {
+        return Err(TreeError::invalid_encoding("account must have four fields"));
+    }
+
+    let nonce = fields[0].as_bytes()?;
+    if nonce.len() > 8 {
+        return Err(TreeError::invalid_encoding("account nonce overflows u64"));
+    }
+
+    Ok(Some(Account {
+        nonce: nonce.iter().fold(0u64, |acc, byte| acc << 8 | *byte as u64),
+        balance: fields[1].as_bytes()?.to_vec(),
+        storage_root: hash_field(&fields[2])?,
+        code_hash: hash_field(&fields[3])?,
+    }))
+}
+
+/// Verifies a `storageProof` entry for `slot` against an account's storage root
+///
+/// Returns the big-endian slot value without leading zeros; absent slots read as zero.
+pub fn verify_storage_proof(
+    storage_root: [u8; 32],
+    slot: [u8; 32],
+    proof: &[Vec<u8>],
+) -> TreeResult<Vec<u8>> {
+    match verify_proof(storage_root, &Keccak256::hash(&slot), proof)? {
+        Some(encoded) => Ok(rlp::decode(&encoded)?.as_bytes()?.to_vec()),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn finish<'a>(
+    value: Option<Vec<u8>>,
+    proof_nodes: &mut impl Iterator<Item = &'a Vec<u8>>,
+) -> TreeResult<Option<Vec<u8>>> {
+    if proof_nodes.next().is_some() {
+        return Err(TreeError::proof_malformed("proof contains unused nodes"));
+    }
+
+    Ok(value)
+}
+
+fn value_of(item: &Rlp) -> TreeResult<Option<Vec<u8>>> {
+    let value = item.as_bytes()?;
+    Ok((!value.is_empty()).then(|| value.to_vec()))
+}
+
+fn hash_field(item: &Rlp) -> TreeResult<[u8; 32]> {
+    item.as_bytes()?
+        .try_into()
+        .map_err(|_| TreeError::hash_size_mismatch("account hashes must be 32 bytes"))
+}
+
+/// Decodes a hex-prefix encoded path into its leaf flag and nibbles
+fn decode_path(encoded: &[u8]) -> TreeResult<(bool, Vec<u8>)> {
+    let (first, rest) = encoded
+        .split_first()
+        .ok_or_else(|| TreeError::invalid_encoding("empty trie node path"))?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(TreeError::invalid_encoding("invalid trie node path flag"));
+    }
+
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+
+    Ok((flag & 2 == 2, nibbles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+        match bytes.len() {
+            1 if bytes[0] < 0x80 => bytes.to_vec(),
+            len if len < 56 => [&[0x80 + len as u8], bytes].concat(),
+            len => [&[0xb8, len as u8], bytes].concat(),
+        }
+    }
+
+    fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        match payload.len() {
+            len if len < 56 => [vec![0xc0 + len as u8], payload].concat(),
+            len if len < 256 => [vec![0xf8, len as u8], payload].concat(),
+            len => [vec![0xf9, (len >> 8) as u8, len as u8], payload].concat(),
+        }
+    }
+
+    fn leaf(path: &[u8], value: &[u8]) -> Vec<u8> {
+        encode_list(&[encode_bytes(path), encode_bytes(value)])
+    }
+
+    #[test]
+    fn test_single_leaf_trie() {
+        let key = Keccak256::hash("key".as_bytes());
+        let node = leaf(&[&[0x20], &key[..]].concat(), b"value");
+        let root = Keccak256::hash(&node);
+
+        assert_eq!(
+            verify_proof(root, &key, std::slice::from_ref(&node)).unwrap(),
+            Some(b"value".to_vec())
+        );
+        assert_eq!(
+            verify_proof(
+                root,
+                &Keccak256::hash("other".as_bytes()),
+                std::slice::from_ref(&node)
+            )
+            .unwrap(),
+            None
+        );
+        assert!(verify_proof([0; 32], &key, &[node]).is_err());
+    }
+
+    #[test]
+    fn test_branch_with_hashed_children() {
+        let key_a = [0x10u8; 32];
+        let key_b = [0x20u8; 32];
+        let value = [0xaa; 40];
+        // after the branch consumes the first nibble, 63 nibbles remain: odd leaf path
+        let leaf_a = leaf(&[&[0x30], &key_a[1..]].concat(), &value);
+        let leaf_b = leaf(&[&[0x30], &key_b[1..]].concat(), &value);
+
+        let mut children: Vec<Vec<u8>> = vec![encode_bytes(&[]); BRANCH_NODE_LEN];
+        children[1] = encode_bytes(&Keccak256::hash(&leaf_a));
+        children[2] = encode_bytes(&Keccak256::hash(&leaf_b));
+        let branch = encode_list(&children);
+        let root = Keccak256::hash(&branch);
+
+        assert_eq!(
+            verify_proof(root, &key_b, &[branch.clone(), leaf_b]).unwrap(),
+            Some(value.to_vec())
+        );
+        assert_eq!(
+            verify_proof(root, &[0x30; 32], std::slice::from_ref(&branch)).unwrap(),
+            None
+        );
+        assert!(verify_proof(root, &key_a, &[branch]).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::error::tree_error::TreeError;
+use crate::merkle_tree::TreeResult;
+
+/// Decoded RLP item borrowing from the encoded input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rlp<'a> {
+    Bytes(&'a [u8]),
+    List(Vec<Rlp<'a>>),
+}
+
+impl<'a> Rlp<'a> {
+    pub fn as_bytes(&self) -> TreeResult<&'a [u8]> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes),
+            Self::List(_) => Err(TreeError::invalid_encoding("expected rlp bytes")),
+        }
+    }
+
+    pub fn as_list(&self) -> TreeResult<&[Rlp<'a>]> {
+        match self {
+            Self::List(items) => Ok(items),
+            Self::Bytes(_) => Err(TreeError::invalid_encoding("expected rlp list")),
+        }
+    }
+}
+
+/// Decodes a single RLP item that must span the whole input
+pub fn decode(input: &[u8]) -> TreeResult<Rlp<'_>> {
+    let (item, rest) = decode_item(input)?;
+    if !rest.is_empty() {
+        return Err(TreeError::invalid_encoding("trailing bytes after rlp item"));
+    }
+
+    Ok(item)
+}
+
+fn decode_item(input: &[u8]) -> TreeResult<(Rlp<'_>, &[u8])> {
+    let prefix = *input
+        .first()
+        .ok_or_else(|| TreeError::invalid_encoding("unexpected end of rlp input"))?;
+
+    match prefix {
+        0x00..=0x7f => Ok((Rlp::Bytes(&input[..1]), &input[1..])),
+        0x80..=0xb7 => {
+            let (payload, rest) = split_payload(input, 1, (prefix - 0x80) as usize)?;
+            Ok((Rlp::Bytes(payload), rest))
+        }
+        0xb8..=0xbf => {
+            let (length, header_len) = long_length(input, (prefix - 0xb7) as usize)?;
+            let (payload, rest) = split_payload(input, header_len, length)?;
+            Ok((Rlp::Bytes(payload), rest))
+        }
+        0xc0..=0xf7 => {
+            let (payload, rest) = split_payload(input, 1, (prefix - 0xc0) as usize)?;
+            Ok((Rlp::List(decode_list(payload)?), rest))
+        }
+        0xf8..=0xff => {
+            let (length, header_len) = long_length(input, (prefix - 0xf7) as usize)?;
+            let (payload, rest) = split_payload(input, header_len, length)?;
+            Ok((Rlp::List(decode_list(payload)?), rest))
+        }
+    }
+}
+
+fn decode_list(mut payload: &[u8]) -> TreeResult<Vec<Rlp<'_>>> {
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let (item, rest) = decode_item(payload)?;
+        items.push(item);
+        payload = rest;
+    }
+
+    Ok(items)
+}
+
+fn long_length(input: &[u8], length_of_length: usize) -> TreeResult<(usize, usize)> {
+    let length_bytes = input
+        .get(1..1 + length_of_length)
+        .ok_or_else(|| TreeError::invalid_encoding("truncated rlp length"))?;
+    if length_of_length > std::mem::size_of::<usize>() || length_bytes[0] == 0 {
+        return Err(TreeError::invalid_encoding("invalid rlp length"));
+    }
+
+    let length = length_bytes
+        .iter()
+        .fold(0usize, |acc, byte| acc << 8 | *byte as usize);
+
+    Ok((length, 1 + length_of_length))
+}
+
+fn split_payload(input: &[u8], header_len: usize, length: usize) -> TreeResult<(&[u8], &[u8])> {
+    let end = header_len
+        .checked_add(length)
+        .filter(|end| *end <= input.len())
+        .ok_or_else(|| TreeError::invalid_encoding("truncated rlp payload"))?;
+
+    Ok((&input[header_len..end], &input[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_nested_list() {
+        // ["cat", ["dog"], ""]
+        let encoded = [
+            0xca, 0x83, b'c', b'a', b't', 0xc4, 0x83, b'd', b'o', b'g', 0x80,
+        ];
+        assert_eq!(
+            decode(&encoded).unwrap(),
+            Rlp::List(vec![
+                Rlp::Bytes(b"cat"),
+                Rlp::List(vec![Rlp::Bytes(b"dog")]),
+                Rlp::Bytes(b""),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_input() {
+        assert!(decode(&[0x83, b'c', b'a']).is_err());
+        assert!(decode(&[0x01, 0x02]).is_err());
+    }
+}
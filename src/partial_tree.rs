@@ -0,0 +1,197 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+use std::collections::BTreeMap;
+
+/// Level and index of a node, leaves being level 0
+type NodeKey = (usize, usize);
+
+/// Pruned view of a tree known only through its root, its size and the proofs seen so far
+///
+/// Every node on the path of an accepted proof, and every sibling along it, is authenticated
+/// by the root and remembered at its level and index. Later proofs only need to reach one of
+/// those nodes rather than the root, so a light client can verify leaves incrementally without
+/// holding the leaf set. Trees are expected to follow the default
+/// [`TreePolicy`](crate::TreePolicy).
+pub struct PartialTree<T: ToHash> {
+    root: T::Hash,
+    leaf_count: usize,
+    known: BTreeMap<NodeKey, T::Hash>,
+}
+
+impl<T: ToHash> PartialTree<T> {
+    pub fn new(root: T::Hash, leaf_count: usize) -> Self {
+        let mut height = 0;
+        let mut width = leaf_count;
+        while width > 1 {
+            width = width.div_ceil(2);
+            height += 1;
+        }
+
+        Self {
+            root,
+            leaf_count,
+            known: BTreeMap::from([((height, 0), root)]),
+        }
+    }
+
+    /// Creates a partial tree from a root and `(index, leaf, proof)` triples, failing on the
+    /// first proof that does not lead to the root or an already known node
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{PartialTree, Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..8).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     let partial = PartialTree::<Sha256>::from_proofs(
+    ///         tree.root_hash()?,
+    ///         leaves.len(),
+    ///         [(4, leaves[4], tree.get_proof_at(4)?)],
+    ///     )?;
+    ///
+    ///     // [67] is known from the proof of 4, so a single sibling proves 6
+    ///     assert!(partial.verify(6, leaves[6], &[leaves[7]]));
+    ///     assert!(!partial.verify(6, leaves[6], &[leaves[5]]));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_proofs(
+        root: T::Hash,
+        leaf_count: usize,
+        proofs: impl IntoIterator<Item = (usize, T::Hash, Vec<T::Hash>)>,
+    ) -> TreeResult<Self> {
+        let mut partial = Self::new(root, leaf_count);
+        for (index, leaf, proof) in proofs {
+            partial.insert(index, leaf, &proof)?;
+        }
+
+        Ok(partial)
+    }
+
+    pub fn root(&self) -> T::Hash {
+        self.root
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Node at `index` of `level`, leaves being level 0, if authenticated by a previously
+    /// accepted proof
+    pub fn node(&self, level: usize, index: usize) -> Option<T::Hash> {
+        self.known.get(&(level, index)).copied()
+    }
+
+    /// Validates that the proof folds the leaf at `index` into the root or a known node,
+    /// ending exactly there
+    pub fn verify(&self, index: usize, leaf: T::Hash, proof: &[T::Hash]) -> bool {
+        self.path(index, leaf, proof).is_ok()
+    }
+
+    /// Validates the proof like [`PartialTree::verify`] and learns every node along it
+    pub fn insert(&mut self, index: usize, leaf: T::Hash, proof: &[T::Hash]) -> TreeResult<()> {
+        let path = self.path(index, leaf, proof)?;
+        self.known.extend(path);
+
+        Ok(())
+    }
+
+    /// Nodes from the leaf up to the first known node and the siblings they were folded with,
+    /// keyed by level and index
+    fn path(
+        &self,
+        index: usize,
+        leaf: T::Hash,
+        proof: &[T::Hash],
+    ) -> TreeResult<Vec<(NodeKey, T::Hash)>> {
+        if index >= self.leaf_count {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let mut path = Vec::with_capacity(2 * proof.len() + 1);
+        let mut siblings = proof.iter();
+        let (mut level, mut index, mut width) = (0, index, self.leaf_count);
+        let mut current = leaf;
+        // The root is known at the top level, so every path ends at a known node
+        loop {
+            if let Some(known) = self.known.get(&(level, index)) {
+                return match *known == current && siblings.next().is_none() {
+                    true => Ok(path),
+                    false => Err(TreeError::proof_invalid()),
+                };
+            }
+
+            path.push(((level, index), current));
+            if index ^ 1 < width {
+                let sibling = *siblings.next().ok_or_else(TreeError::proof_invalid)?;
+                path.push(((level, index ^ 1), sibling));
+                current = T::try_combine(current, sibling)?;
+            }
+            index /= 2;
+            width = width.div_ceil(2);
+            level += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{PartialTree, Sha256Tree, ToHash};
+
+    #[test]
+    fn test_partial_tree_learns_nodes() {
+        let leaves_raw = ["0", "1", "2", "3", "4", "5", "6", "7"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let mut partial = PartialTree::<Sha256>::new(tree.root_hash().unwrap(), leaves.len());
+        let short_proof = [leaves[1]];
+        assert!(!partial.verify(0, leaves[0], &short_proof));
+
+        let proof = tree.get_proof_at(2).unwrap();
+        partial.insert(2, leaves[2], &proof).unwrap();
+        let node = Sha256::combine(leaves[0], leaves[1]);
+        assert_eq!(partial.node(0, 3), Some(leaves[3]));
+        assert_eq!(partial.node(1, 0), Some(node));
+        assert!(partial.verify(0, leaves[0], &short_proof));
+        assert!(partial.verify(3, leaves[3], &[]));
+
+        // Known nodes only stand for themselves, at their own level and index
+        assert!(!partial.verify(1, leaves[3], &[]));
+        assert!(!partial.verify(0, node, &[]));
+        assert!(!partial.verify(0, leaves[0], &[leaves[1], proof[1]]));
+
+        assert_eq!(
+            partial
+                .insert(5, leaves[5], &[leaves[5]])
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::ProofInvalid
+        );
+        assert_eq!(partial.node(0, 5), None);
+        assert_eq!(
+            partial.insert(8, leaves[7], &[]).unwrap_err().kind(),
+            &TreeErrorKind::IndexOutOfBounds
+        );
+    }
+
+    #[test]
+    fn test_odd_trees_promote_the_last_node() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+        let tree = Sha256Tree::from_leaves(&leaves);
+
+        for index in 0..leaves.len() {
+            let mut partial = PartialTree::<Sha256>::new(tree.root_hash().unwrap(), leaves.len());
+            let proof = tree.get_proof_at(index).unwrap();
+            partial.insert(index, leaves[index], &proof).unwrap();
+            assert_eq!(partial.node(0, index), Some(leaves[index]));
+        }
+    }
+}
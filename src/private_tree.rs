@@ -0,0 +1,146 @@
+use crate::error::hash_error::HashError;
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+
+/// Random value mixed into a leaf before hashing
+pub type Salt = [u8; 32];
+
+/// Tree of blinded leaves `hash(salt || data)`, each salted with fresh randomness
+///
+/// Without the salt, a leaf hash cannot be matched against guessed data (a salary, an
+/// address on an allow-list), so a proof reveals nothing about the siblings it contains.
+/// The tree keeps every salt so it can hand a leaf's salt out together with its proof.
+pub struct PrivateTree<T: ToHash> {
+    tree: MerkleTree<T>,
+    salts: Vec<Salt>,
+}
+
+/// Proof of a salted leaf: the salt to re-derive the leaf from its data, and the siblings
+pub struct SaltedProof<T: ToHash> {
+    pub salt: Salt,
+    pub proof: MerkleProof<T>,
+}
+
+impl<T: ToHash> PrivateTree<T> {
+    pub fn new() -> Self {
+        Self {
+            tree: MerkleTree::new(),
+            salts: Vec::new(),
+        }
+    }
+
+    /// Appends `data` under a fresh random salt, returning the salt
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{PrivateTree, Sha256};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut tree = PrivateTree::<Sha256>::new();
+    ///     tree.append_private(b"alice: 120k")?;
+    ///     tree.append_private(b"bob: 95k")?;
+    ///
+    ///     let root = tree.root_hash()?;
+    ///     let proof = tree.get_proof_at(1)?;
+    ///     assert!(proof.validate(root, b"bob: 95k"));
+    ///     assert!(!proof.validate(root, b"bob: 96k"));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn append_private(&mut self, data: &[u8]) -> TreeResult<Salt> {
+        let mut salt = [0u8; 32];
+        getrandom::fill(&mut salt).map_err(|error| {
+            TreeError::from(HashError::with_source("salt generation failed", error))
+        })?;
+        self.append_with_salt(data, salt)?;
+
+        Ok(salt)
+    }
+
+    /// Appends `data` under a caller-provided salt, e.g. when rebuilding a tree from storage
+    pub fn append_with_salt(&mut self, data: &[u8], salt: Salt) -> TreeResult<()> {
+        self.tree.try_append(salted_leaf::<T>(&salt, data)?)?;
+        self.salts.push(salt);
+
+        Ok(())
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.salts.len()
+    }
+
+    pub fn salt_at(&self, index: usize) -> Option<Salt> {
+        self.salts.get(index).copied()
+    }
+
+    pub fn root_hash(&self) -> TreeResult<T::Hash> {
+        self.tree.root_hash()
+    }
+
+    pub fn get_proof_at(&self, index: usize) -> TreeResult<SaltedProof<T>> {
+        Ok(SaltedProof {
+            proof: MerkleProof::new(self.tree.get_proof_at(index)?),
+            salt: self.salts[index],
+        })
+    }
+
+    /// The underlying tree of blinded leaves
+    pub fn tree(&self) -> &MerkleTree<T> {
+        &self.tree
+    }
+}
+
+impl<T: ToHash> Default for PrivateTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ToHash> SaltedProof<T> {
+    /// Validates that `data`, blinded with the proof's salt, is a leaf under `root_hash`
+    pub fn validate(&self, root_hash: T::Hash, data: &[u8]) -> bool {
+        match salted_leaf::<T>(&self.salt, data) {
+            Ok(leaf) => self.proof.validate(root_hash, leaf),
+            Err(_) => false,
+        }
+    }
+}
+
+fn salted_leaf<T: ToHash>(salt: &Salt, data: &[u8]) -> TreeResult<T::Hash> {
+    Ok(T::try_hash(&[salt.as_slice(), data].concat())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha256;
+
+    #[test]
+    fn test_salts_blind_leaves() {
+        let mut tree = PrivateTree::<Sha256>::new();
+        let first = tree.append_private(b"0").unwrap();
+        let second = tree.append_private(b"0").unwrap();
+
+        assert_ne!(first, second);
+        assert_ne!(tree.tree().leaves()[0], tree.tree().leaves()[1]);
+        assert_ne!(tree.tree().leaves()[0], Sha256::hash(b"0"));
+        assert_eq!(tree.salt_at(1), Some(second));
+    }
+
+    #[test]
+    fn test_salted_proof_needs_matching_salt() {
+        let mut tree = PrivateTree::<Sha256>::new();
+        for (index, data) in [b"0", b"1", b"2"].iter().enumerate() {
+            tree.append_with_salt(*data, [index as u8; 32]).unwrap();
+        }
+
+        let root = tree.root_hash().unwrap();
+        let mut proof = tree.get_proof_at(2).unwrap();
+        assert!(proof.validate(root, b"2"));
+
+        proof.salt = [0; 32];
+        assert!(!proof.validate(root, b"2"));
+    }
+}
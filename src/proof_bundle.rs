@@ -0,0 +1,257 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::TreeResult;
+use std::collections::HashMap;
+
+const BUNDLE_FORMAT_VERSION: u8 = 1;
+
+/// Proofs for many leaves of one tree, sharing every sibling hash they have in common
+///
+/// Proofs of neighbouring leaves repeat most of their upper siblings. The bundle keeps each
+/// distinct hash once in a dictionary and stores every proof as references into it.
+pub struct CompressedProofBundle<T: ToHash> {
+    nodes: Vec<T::Hash>,
+    proofs: Vec<(usize, Vec<u32>)>,
+}
+
+impl<T: ToHash> CompressedProofBundle<T> {
+    /// Compresses `(leaf index, proof)` pairs as returned by
+    /// [`MerkleTree::get_proof_at`](crate::MerkleTree::get_proof_at)
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{CompressedProofBundle, Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..8).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let proofs = (0..4)
+    ///         .map(|index| Ok((index, tree.get_proof_at(index)?)))
+    ///         .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    ///
+    ///     let bundle = CompressedProofBundle::<Sha256>::compress(&proofs);
+    ///     assert_eq!(bundle.node_count(), 7);
+    ///     assert_eq!(bundle.decompress(), proofs);
+    ///     assert!(bundle
+    ///         .validate_all(tree.root_hash()?, &leaves[..4])
+    ///         .into_iter()
+    ///         .all(|is_valid| is_valid));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn compress(proofs: &[(usize, Vec<T::Hash>)]) -> Self {
+        let mut nodes = Vec::new();
+        let mut references: HashMap<Vec<u8>, u32> = HashMap::new();
+
+        let proofs = proofs
+            .iter()
+            .map(|(index, proof)| {
+                let refs = proof
+                    .iter()
+                    .map(|hash| {
+                        *references.entry(hash.as_ref().to_vec()).or_insert_with(|| {
+                            nodes.push(*hash);
+                            (nodes.len() - 1) as u32
+                        })
+                    })
+                    .collect();
+                (*index, refs)
+            })
+            .collect();
+
+        Self { nodes, proofs }
+    }
+
+    /// Restores the `(leaf index, proof)` pairs in the order they were compressed
+    pub fn decompress(&self) -> Vec<(usize, Vec<T::Hash>)> {
+        self.proofs
+            .iter()
+            .map(|(index, refs)| {
+                let proof = refs.iter().map(|node| self.nodes[*node as usize]).collect();
+                (*index, proof)
+            })
+            .collect()
+    }
+
+    /// Number of distinct sibling hashes stored
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.proofs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.proofs.is_empty()
+    }
+
+    /// Validates every proof against `root_hash`, `leaves` holding the leaf of each proof in order
+    ///
+    /// Proofs without a matching leaf are reported as invalid.
+    pub fn validate_all(&self, root_hash: T::Hash, leaves: &[T::Hash]) -> Vec<bool> {
+        self.decompress()
+            .into_iter()
+            .enumerate()
+            .map(|(position, (_, proof))| match leaves.get(position) {
+                Some(leaf) => MerkleProof::<T>::new(proof).validate(root_hash, *leaf),
+                None => false,
+            })
+            .collect()
+    }
+
+    /// Serializes the bundle into a compact, versioned byte format
+    ///
+    /// Layout: `version (u8) | hash length (u8) | node count (u32, BE) | nodes |
+    /// proof count (u32, BE)`, then for every proof `leaf index (u64, BE) | sibling count
+    /// (u32, BE) | node references (u32, BE each)`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![BUNDLE_FORMAT_VERSION, T::hash_len() as u8];
+        bytes.extend_from_slice(&(self.nodes.len() as u32).to_be_bytes());
+        for node in &self.nodes {
+            bytes.extend_from_slice(node.as_ref());
+        }
+
+        bytes.extend_from_slice(&(self.proofs.len() as u32).to_be_bytes());
+        for (index, refs) in &self.proofs {
+            bytes.extend_from_slice(&(*index as u64).to_be_bytes());
+            bytes.extend_from_slice(&(refs.len() as u32).to_be_bytes());
+            for node in refs {
+                bytes.extend_from_slice(&node.to_be_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    /// Deserializes a bundle previously produced by [`CompressedProofBundle::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> TreeResult<Self> {
+        let mut reader = Reader { bytes };
+        if reader.take(1)?[0] != BUNDLE_FORMAT_VERSION {
+            return Err(TreeError::proof_malformed(
+                "unsupported bundle format version",
+            ));
+        }
+
+        let hash_len = T::hash_len();
+        if reader.take(1)?[0] as usize != hash_len {
+            return Err(TreeError::proof_malformed(
+                "hash length does not match the hasher",
+            ));
+        }
+
+        let node_count = reader.u32()? as usize;
+        let nodes = (0..node_count)
+            .map(|_| {
+                T::Hash::try_from(reader.take(hash_len)?)
+                    .map_err(|_| TreeError::proof_malformed("invalid hash in bundle"))
+            })
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        let proof_count = reader.u32()?;
+        let proofs = (0..proof_count)
+            .map(|_| {
+                let index = u64::from_be_bytes(reader.take(8)?.try_into().unwrap()) as usize;
+                let refs = (0..reader.u32()?)
+                    .map(|_| match reader.u32()? {
+                        node if (node as usize) < node_count => Ok(node),
+                        _ => Err(TreeError::proof_malformed(
+                            "bundle references an unknown node",
+                        )),
+                    })
+                    .collect::<TreeResult<Vec<_>>>()?;
+                Ok((index, refs))
+            })
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        if !reader.bytes.is_empty() {
+            return Err(TreeError::proof_malformed("bundle has trailing bytes"));
+        }
+
+        Ok(Self { nodes, proofs })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> TreeResult<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(TreeError::proof_malformed("bundle is truncated"));
+        }
+
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> TreeResult<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{CompressedProofBundle, Sha256Tree};
+
+    #[test]
+    fn test_bundle_round_trip_and_dedup() {
+        let leaves_raw = ["0", "1", "2", "3", "4", "5", "6", "7"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let proofs: Vec<_> = [0, 1, 5]
+            .into_iter()
+            .map(|index| (index, tree.get_proof_at(index).unwrap()))
+            .collect();
+        let bundle = CompressedProofBundle::<Sha256>::compress(&proofs);
+
+        // 0 and 1 share [23] and [4567], 5 needs 4, [67] and [0123]
+        assert_eq!(bundle.node_count(), 7);
+        let decoded = CompressedProofBundle::<Sha256>::from_bytes(&bundle.to_bytes()).unwrap();
+        assert_eq!(decoded.decompress(), proofs);
+
+        let root = tree.root_hash().unwrap();
+        let claimed = [leaves[0], leaves[1], leaves[4]];
+        assert_eq!(
+            decoded.validate_all(root, &claimed),
+            vec![true, true, false]
+        );
+        assert_eq!(
+            decoded.validate_all(root, &claimed[..2]),
+            vec![true, true, false]
+        );
+    }
+
+    #[test]
+    fn test_bundle_rejects_corrupted_bytes() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2"]);
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let bundle =
+            CompressedProofBundle::<Sha256>::compress(&[(0, tree.get_proof_at(0).unwrap())]);
+        let bytes = bundle.to_bytes();
+
+        let truncated = CompressedProofBundle::<Sha256>::from_bytes(&bytes[..bytes.len() - 1]);
+        assert_eq!(
+            truncated.err().unwrap().kind(),
+            &TreeErrorKind::ProofMalformed
+        );
+
+        let mut unknown = bytes.clone();
+        let last = unknown.len() - 1;
+        unknown[last] = 9;
+        assert_eq!(
+            CompressedProofBundle::<Sha256>::from_bytes(&unknown)
+                .err()
+                .unwrap()
+                .kind(),
+            &TreeErrorKind::ProofMalformed
+        );
+    }
+}
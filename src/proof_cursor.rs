@@ -0,0 +1,98 @@
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::proof_from_levels;
+use crate::tree_levels::TreeLevels;
+use crate::tree_policy::TreePolicy;
+use std::sync::Arc;
+
+/// Cursor over the leaves of a tree, lazily yielding `(index, leaf, proof)`
+///
+/// The cursor shares the levels cached by the tree, so each proof only costs a walk up the
+/// stored levels instead of a rebuild of the tree.
+pub struct ProofCursor<T: ToHash> {
+    levels: Arc<TreeLevels<T>>,
+    policy: TreePolicy,
+    position: usize,
+}
+
+impl<T: ToHash> ProofCursor<T> {
+    pub(crate) fn new(levels: Arc<TreeLevels<T>>, policy: TreePolicy) -> Self {
+        Self {
+            levels,
+            policy,
+            position: 0,
+        }
+    }
+
+    /// Index of the leaf the next call to `next` will yield
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves the cursor so that the next yielded leaf is the one at `index`
+    pub fn seek(&mut self, index: usize) {
+        self.position = index;
+    }
+
+    fn leaves(&self) -> &[T::Hash] {
+        &self.levels[0]
+    }
+}
+
+impl<T: ToHash> Iterator for ProofCursor<T> {
+    type Item = (usize, T::Hash, Vec<T::Hash>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.position;
+        let leaf = *self.leaves().get(index)?;
+        self.position += 1;
+
+        let proof = proof_from_levels::<T>(&self.levels, index, &self.policy)
+            .expect("odd siblings were already derived when the levels were built");
+
+        Some((index, leaf, proof))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.leaves().len().saturating_sub(self.position);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: ToHash> ExactSizeIterator for ProofCursor<T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_cursor_yields_every_proof_once() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let cursor = tree.cursor().unwrap();
+        assert_eq!(cursor.len(), leaves.len());
+
+        let items: Vec<_> = cursor.collect();
+        for (index, leaf, proof) in items {
+            assert_eq!(leaf, leaves[index]);
+            assert_eq!(proof, tree.get_proof(leaf).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_cursor_resumes_from_seek() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let mut cursor = tree.cursor().unwrap();
+        cursor.seek(3);
+
+        assert_eq!(cursor.next().map(|(index, _, _)| index), Some(3));
+        assert_eq!(cursor.position(), 4);
+        assert_eq!(cursor.count(), 1);
+    }
+}
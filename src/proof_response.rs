@@ -0,0 +1,267 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::{MerkleProof, Position};
+use crate::merkle_tree::TreeResult;
+use crate::utils::hex;
+use serde::{Deserialize, Serialize};
+
+pub const PROOF_RESPONSE_VERSION: u32 = 1;
+
+/// How the siblings of a [`ProofResponse`] are folded into the root
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProofFormat {
+    /// Pairs are ordered by value before hashing, see [`ToHash::combine`]
+    SortedPairs,
+    /// Pairs are ordered by the recorded sibling positions
+    Positional,
+}
+
+/// Versioned JSON document for exchanging a single inclusion proof between services
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "algorithm": "keccak256",
+///   "format": "sorted-pairs",
+///   "node_encoding": "rfc6962",
+///   "root": "0x…",
+///   "leaf": "0x…",
+///   "index": 3,
+///   "proof": ["0x…", "0x…"]
+/// }
+/// ```
+///
+/// Hashes are `0x`-prefixed lowercase hex. Positional proofs additionally carry a
+/// `positions` array of `"left"`/`"right"` entries, one per sibling. `node_encoding` is the
+/// [`ToHash::node_encoding`] of the hasher, left out for plain `concat` nodes, so that
+/// hashers sharing an algorithm are told apart. Unknown fields are rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProofResponse {
+    pub version: u32,
+    pub algorithm: String,
+    pub format: ProofFormat,
+    #[serde(
+        default = "concat_encoding",
+        skip_serializing_if = "is_concat_encoding"
+    )]
+    pub node_encoding: String,
+    pub root: String,
+    pub leaf: String,
+    pub index: usize,
+    pub proof: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub positions: Option<Vec<Position>>,
+}
+
+impl ProofResponse {
+    /// Creates a response for a proof of the leaf at `index`
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Keccak256, Keccak256Proof, Keccak256Tree, ProofResponse, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves = [
+    ///         Keccak256::hash("0".as_bytes()),
+    ///         Keccak256::hash("1".as_bytes()),
+    ///         Keccak256::hash("2".as_bytes()),
+    ///     ];
+    ///     let tree = Keccak256Tree::from_leaves(&leaves);
+    ///     let proof = Keccak256Proof::new(tree.get_proof_at(1)?);
+    ///     let response = ProofResponse::new(tree.root_hash()?, leaves[1], 1, &proof);
+    ///
+    ///     let received = ProofResponse::from_json(&response.to_json())?;
+    ///     assert!(received.validate::<Keccak256>()?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new<T: ToHash>(
+        root: T::Hash,
+        leaf: T::Hash,
+        index: usize,
+        proof: &MerkleProof<T>,
+    ) -> Self {
+        let format = match proof.positions() {
+            Some(_) => ProofFormat::Positional,
+            None => ProofFormat::SortedPairs,
+        };
+
+        Self {
+            version: PROOF_RESPONSE_VERSION,
+            algorithm: T::ALGORITHM.to_owned(),
+            format,
+            node_encoding: T::node_encoding(),
+            root: encode_hash(root.as_ref()),
+            leaf: encode_hash(leaf.as_ref()),
+            index,
+            proof: proof
+                .proof()
+                .iter()
+                .map(|sibling| encode_hash(sibling.as_ref()))
+                .collect(),
+            positions: proof.positions().map(<[Position]>::to_vec),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("proof responses always serialize")
+    }
+
+    pub fn from_json(json: &str) -> TreeResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|_| TreeError::proof_malformed("invalid proof response json"))
+    }
+
+    /// Strictly decodes the response for the hasher `T`, returning the root, leaf and proof
+    ///
+    /// Fails with `ProofMalformed` when the version, algorithm or node encoding do not match
+    /// or when the index needs more siblings than the proof has: every level at which the
+    /// leaf's ancestor is a right child has a sibling.
+    pub fn decode<T: ToHash>(&self) -> TreeResult<(T::Hash, T::Hash, MerkleProof<T>)> {
+        if self.version != PROOF_RESPONSE_VERSION {
+            return Err(TreeError::proof_malformed(
+                "unsupported proof response version",
+            ));
+        }
+
+        if self.algorithm != T::ALGORITHM {
+            return Err(TreeError::proof_malformed(
+                "proof response algorithm does not match the hasher",
+            ));
+        }
+
+        if self.node_encoding != T::node_encoding() {
+            return Err(TreeError::proof_malformed(
+                "proof response node encoding does not match the hasher",
+            ));
+        }
+
+        if self.index.count_ones() as usize > self.proof.len() {
+            return Err(TreeError::proof_malformed(
+                "proof response index does not fit the proof",
+            ));
+        }
+
+        let proof = self
+            .proof
+            .iter()
+            .map(|sibling| decode_hash::<T>(sibling))
+            .collect::<TreeResult<Vec<_>>>()?;
+        let proof = match (self.format, &self.positions) {
+            (ProofFormat::SortedPairs, None) => MerkleProof::new(proof),
+            (ProofFormat::Positional, Some(positions)) => {
+                MerkleProof::with_positions(proof, positions.clone())?
+            }
+            _ => {
+                return Err(TreeError::proof_malformed(
+                    "positions must be present exactly for positional proofs",
+                ))
+            }
+        };
+
+        Ok((
+            decode_hash::<T>(&self.root)?,
+            decode_hash::<T>(&self.leaf)?,
+            proof,
+        ))
+    }
+
+    /// Decodes the response for the hasher `T` and validates the proof it carries
+    pub fn validate<T: ToHash>(&self) -> TreeResult<bool> {
+        let (root, leaf, proof) = self.decode::<T>()?;
+        Ok(proof.validate(root, leaf))
+    }
+}
+
+fn encode_hash(hash: &[u8]) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+fn concat_encoding() -> String {
+    "concat".to_owned()
+}
+
+fn is_concat_encoding(encoding: &str) -> bool {
+    encoding == "concat"
+}
+
+fn decode_hash<T: ToHash>(hash: &str) -> TreeResult<T::Hash> {
+    let bytes = hex::decode_prefixed(hash)
+        .ok_or_else(|| TreeError::proof_malformed("hashes must be 0x-prefixed hex"))?;
+
+    T::Hash::try_from(bytes.as_slice())
+        .map_err(|_| TreeError::hash_size_mismatch("hash length does not match the hasher"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::{Combined, DomainSeparated, Keccak256, LengthPrefixed, Sha256};
+    use crate::merkle_tree::MerkleTree;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{Sha256Proof, Sha256Tree};
+
+    #[test]
+    fn test_proof_response_round_trip() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let proof = Sha256Proof::new(tree.get_proof_at(3).unwrap());
+        let response = ProofResponse::new(tree.root_hash().unwrap(), leaves[3], 3, &proof);
+        let json = response.to_json();
+
+        assert!(json.contains(r#""algorithm":"sha3-256","format":"sorted-pairs""#));
+        assert_eq!(ProofResponse::from_json(&json).unwrap(), response);
+        assert!(response.validate::<Sha256>().unwrap());
+    }
+
+    #[test]
+    fn test_proof_response_is_strict() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1"]);
+        let response =
+            ProofResponse::new(leaves[0], leaves[1], 1, &Sha256Proof::new(vec![leaves[0]]));
+
+        assert_eq!(
+            response.validate::<Keccak256>().unwrap_err().kind(),
+            &TreeErrorKind::ProofMalformed
+        );
+
+        let json = response.to_json().replacen('{', r#"{"extra":true,"#, 1);
+        assert!(ProofResponse::from_json(&json).is_err());
+
+        let mut doubled = response.clone();
+        doubled.leaf = doubled.leaf.replacen("0x", "0x0x", 1);
+        assert!(doubled.validate::<Sha256>().is_err());
+
+        let mut far = response.clone();
+        far.index = 999_999;
+        assert_eq!(
+            far.validate::<Sha256>().unwrap_err().kind(),
+            &TreeErrorKind::ProofMalformed
+        );
+    }
+
+    #[test]
+    fn test_node_encoding_tells_hashers_apart() {
+        type Separated = DomainSeparated<Sha256>;
+        let leaves = raw_leaves_to_hashed_leaves::<Separated>(&["0", "1"]);
+        let tree = MerkleTree::<Separated>::from_leaves(&leaves);
+        let proof = MerkleProof::<Separated>::new(tree.get_proof_at(1).unwrap());
+        let response = ProofResponse::new(tree.root_hash().unwrap(), leaves[1], 1, &proof);
+
+        assert!(response.to_json().contains(r#""node_encoding":"rfc6962""#));
+        assert!(response.validate::<Separated>().unwrap());
+        for error in [
+            response.validate::<Sha256>().unwrap_err(),
+            response
+                .validate::<Combined<Sha256, LengthPrefixed>>()
+                .unwrap_err(),
+        ] {
+            assert_eq!(error.kind(), &TreeErrorKind::ProofMalformed);
+        }
+    }
+}
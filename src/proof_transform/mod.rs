@@ -0,0 +1,109 @@
+#[cfg(feature = "zstd")]
+mod zstd;
+#[cfg(feature = "zstd")]
+pub use self::zstd::{Zstd, DEFAULT_MAX_DECODED_LEN};
+
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::TreeResult;
+
+/// Reversible transformation applied to serialized proofs, e.g. compression or encryption
+///
+/// `decode` must undo `encode`. Transforms can be stacked with tuples: `(A, B)` encodes
+/// with `A` then `B`, and decodes in the opposite order.
+pub trait ProofTransform {
+    fn encode(&self, bytes: Vec<u8>) -> TreeResult<Vec<u8>>;
+    fn decode(&self, bytes: Vec<u8>) -> TreeResult<Vec<u8>>;
+}
+
+impl<A: ProofTransform, B: ProofTransform> ProofTransform for (A, B) {
+    fn encode(&self, bytes: Vec<u8>) -> TreeResult<Vec<u8>> {
+        self.1.encode(self.0.encode(bytes)?)
+    }
+
+    fn decode(&self, bytes: Vec<u8>) -> TreeResult<Vec<u8>> {
+        self.0.decode(self.1.decode(bytes)?)
+    }
+}
+
+impl<T: ToHash> MerkleProof<T> {
+    /// Serializes the proof with [`MerkleProof::to_bytes`] and applies the transform
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::proof_transform::ProofTransform;
+    /// use merkle_tree::{Sha256, Sha256Proof, ToHash, TreeResult};
+    ///
+    /// struct Xor(u8);
+    ///
+    /// impl ProofTransform for Xor {
+    ///     fn encode(&self, bytes: Vec<u8>) -> TreeResult<Vec<u8>> {
+    ///         Ok(bytes.into_iter().map(|byte| byte ^ self.0).collect())
+    ///     }
+    ///
+    ///     fn decode(&self, bytes: Vec<u8>) -> TreeResult<Vec<u8>> {
+    ///         self.encode(bytes)
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let proof = Sha256Proof::new(vec![Sha256::hash("0".as_bytes())]);
+    ///     let bytes = proof.to_bytes_with(&Xor(0x5a))?;
+    ///
+    ///     assert_ne!(bytes, proof.to_bytes());
+    ///     assert_eq!(Sha256Proof::from_bytes_with(&bytes, &Xor(0x5a))?.proof(), proof.proof());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_bytes_with(&self, transform: &impl ProofTransform) -> TreeResult<Vec<u8>> {
+        transform.encode(self.to_bytes())
+    }
+
+    /// Reverses the transform and deserializes the proof with [`MerkleProof::from_bytes`]
+    pub fn from_bytes_with(bytes: &[u8], transform: &impl ProofTransform) -> TreeResult<Self> {
+        Self::from_bytes(&transform.decode(bytes.to_vec())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeError;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Proof;
+
+    struct Append(u8);
+
+    impl ProofTransform for Append {
+        fn encode(&self, mut bytes: Vec<u8>) -> TreeResult<Vec<u8>> {
+            bytes.push(self.0);
+            Ok(bytes)
+        }
+
+        fn decode(&self, mut bytes: Vec<u8>) -> TreeResult<Vec<u8>> {
+            match bytes.pop() {
+                Some(byte) if byte == self.0 => Ok(bytes),
+                _ => Err(TreeError::transform_failed("missing trailer")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_stacked_transforms_reverse_in_order() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1"]);
+        let proof = Sha256Proof::new(leaves);
+        let transform = (Append(1), Append(2));
+
+        let bytes = proof.to_bytes_with(&transform).unwrap();
+        assert_eq!(&bytes[bytes.len() - 2..], &[1, 2]);
+        assert_eq!(
+            Sha256Proof::from_bytes_with(&bytes, &transform)
+                .unwrap()
+                .proof(),
+            proof.proof()
+        );
+        assert!(Sha256Proof::from_bytes_with(&bytes, &(Append(2), Append(1))).is_err());
+    }
+}
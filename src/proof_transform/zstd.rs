@@ -0,0 +1,73 @@
+use crate::error::tree_error::TreeError;
+use crate::merkle_tree::TreeResult;
+use crate::proof_transform::ProofTransform;
+
+/// Default bound of [`Zstd::max_decoded_len`], far above any serialized proof
+pub const DEFAULT_MAX_DECODED_LEN: usize = 1 << 20;
+
+/// Compresses serialized proofs with zstd
+///
+/// Decompression stops at `max_decoded_len` bytes, so a small untrusted input cannot expand
+/// into an unbounded allocation.
+pub struct Zstd {
+    pub level: i32,
+    pub max_decoded_len: usize,
+}
+
+impl Default for Zstd {
+    fn default() -> Self {
+        Self {
+            level: zstd::DEFAULT_COMPRESSION_LEVEL,
+            max_decoded_len: DEFAULT_MAX_DECODED_LEN,
+        }
+    }
+}
+
+impl ProofTransform for Zstd {
+    fn encode(&self, bytes: Vec<u8>) -> TreeResult<Vec<u8>> {
+        zstd::encode_all(bytes.as_slice(), self.level).map_err(|error| {
+            TreeError::transform_failed("zstd compression failed").with_source(error)
+        })
+    }
+
+    fn decode(&self, bytes: Vec<u8>) -> TreeResult<Vec<u8>> {
+        zstd::bulk::decompress(&bytes, self.max_decoded_len).map_err(|error| {
+            TreeError::transform_failed("zstd decompression failed").with_source(error)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Proof;
+
+    #[test]
+    fn test_zstd_round_trip() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "0", "0", "0"]);
+        let proof = Sha256Proof::new(leaves);
+
+        let bytes = proof.to_bytes_with(&Zstd::default()).unwrap();
+        assert!(bytes.len() < proof.to_bytes().len());
+        assert_eq!(
+            Sha256Proof::from_bytes_with(&bytes, &Zstd::default())
+                .unwrap()
+                .proof(),
+            proof.proof()
+        );
+    }
+
+    #[test]
+    fn test_decompression_is_bounded() {
+        let zstd = Zstd::default();
+        let bomb = zstd.encode(vec![0; DEFAULT_MAX_DECODED_LEN + 1]).unwrap();
+        assert!(bomb.len() < 1024);
+
+        let error = zstd.decode(bomb).unwrap_err();
+        assert_eq!(error.kind(), &TreeErrorKind::TransformFailed);
+        assert!(std::error::Error::source(&error).is_some());
+    }
+}
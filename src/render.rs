@@ -0,0 +1,238 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::tree_levels::TreeLevels;
+use crate::utils::hex;
+use std::fmt::Write;
+
+/// Number of leading hash bytes shown in node labels
+const LABEL_BYTES: usize = 4;
+
+/// Role of a node relative to the highlighted proof
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    None,
+    Path,
+    Sibling,
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Renders the tree as a Graphviz DOT digraph, with edges pointing from parents to children
+    ///
+    /// When `highlight` is set, the nodes between that leaf and the root are filled blue and
+    /// the siblings making up its proof are filled yellow.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..3).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     let dot = tree.to_dot(Some(2))?;
+    ///     assert!(dot.starts_with("digraph merkle {"));
+    ///     assert!(dot.contains("n2_0 -> n1_1;"));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_dot(&self, highlight: Option<usize>) -> TreeResult<String> {
+        let levels = self.cached_levels()?;
+        let marks = marks(levels, highlight)?;
+
+        let mut dot =
+            String::from("digraph merkle {\n    node [shape=box, fontname=\"monospace\"];\n");
+        for (level, hashes) in levels.iter().enumerate() {
+            for (index, hash) in hashes.iter().enumerate() {
+                let style = match marks[level][index] {
+                    Mark::None => "",
+                    Mark::Path => ", style=filled, fillcolor=lightblue",
+                    Mark::Sibling => ", style=filled, fillcolor=lightyellow",
+                };
+                let _ = writeln!(
+                    dot,
+                    "    n{}_{} [label=\"{}\"{}];",
+                    level,
+                    index,
+                    label(hash.as_ref()),
+                    style
+                );
+            }
+        }
+
+        for level in 1..levels.len() {
+            for index in 0..levels[level - 1].len() {
+                let _ = writeln!(
+                    dot,
+                    "    n{}_{} -> n{}_{};",
+                    level,
+                    index / 2,
+                    level - 1,
+                    index
+                );
+            }
+        }
+        dot.push_str("}\n");
+
+        Ok(dot)
+    }
+
+    /// Renders the tree as indented ASCII art, root first and left children before right ones
+    ///
+    /// When `highlight` is set, nodes between that leaf and the root are suffixed with `*` and
+    /// the siblings making up its proof with `+`.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..3).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///
+    ///     let ascii = tree.to_ascii(Some(0))?;
+    ///     assert_eq!(ascii.lines().count(), 6);
+    ///     assert_eq!(ascii.lines().filter(|line| line.ends_with(" *")).count(), 3);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_ascii(&self, highlight: Option<usize>) -> TreeResult<String> {
+        let levels = self.cached_levels()?;
+        let marks = marks(levels, highlight)?;
+
+        let mut ascii = String::new();
+        if !levels[0].is_empty() {
+            write_ascii(&mut ascii, levels, &marks, levels.len() - 1, 0, "", None);
+        }
+
+        Ok(ascii)
+    }
+}
+
+fn label(hash: &[u8]) -> String {
+    match hash.len() > LABEL_BYTES {
+        true => format!("{}…", hex::encode(&hash[..LABEL_BYTES])),
+        false => hex::encode(hash),
+    }
+}
+
+/// Marks every node on the path from the highlighted leaf to the root, and its proof siblings
+fn marks<T: ToHash>(
+    levels: &TreeLevels<T>,
+    highlight: Option<usize>,
+) -> TreeResult<Vec<Vec<Mark>>> {
+    let mut marks: Vec<Vec<Mark>> = levels
+        .iter()
+        .map(|level| vec![Mark::None; level.len()])
+        .collect();
+
+    if let Some(leaf) = highlight {
+        if leaf >= levels[0].len() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let mut index = leaf;
+        for level in marks.iter_mut() {
+            level[index] = Mark::Path;
+            if let Some(sibling) = level.get_mut(index ^ 1) {
+                *sibling = Mark::Sibling;
+            }
+            index /= 2;
+        }
+    }
+
+    Ok(marks)
+}
+
+/// Writes the node and its subtree, `last` telling whether it is the last child of its parent
+fn write_ascii<T: ToHash>(
+    ascii: &mut String,
+    levels: &TreeLevels<T>,
+    marks: &[Vec<Mark>],
+    level: usize,
+    index: usize,
+    prefix: &str,
+    last: Option<bool>,
+) {
+    let (connector, child_prefix) = match last {
+        None => ("", String::new()),
+        Some(true) => ("└── ", format!("{}    ", prefix)),
+        Some(false) => ("├── ", format!("{}│   ", prefix)),
+    };
+    let suffix = match marks[level][index] {
+        Mark::None => "",
+        Mark::Path => " *",
+        Mark::Sibling => " +",
+    };
+    let _ = writeln!(
+        ascii,
+        "{}{}{}{}",
+        prefix,
+        connector,
+        label(levels[level][index].as_ref()),
+        suffix
+    );
+
+    if level == 0 {
+        return;
+    }
+
+    let children: Vec<usize> = (index * 2..index * 2 + 2)
+        .filter(|child| *child < levels[level - 1].len())
+        .collect();
+    for (position, child) in children.iter().enumerate() {
+        let last = position == children.len() - 1;
+        write_ascii(
+            ascii,
+            levels,
+            marks,
+            level - 1,
+            *child,
+            &child_prefix,
+            Some(last),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_ascii_highlights_proof() {
+        let leaves_raw = ["0", "1", "2"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let ascii = tree.to_ascii(Some(2)).unwrap();
+        let lines: Vec<_> = ascii.lines().collect();
+
+        // root, [01] +, 0, 1, 2 *, 2 *
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].ends_with(" *"));
+        assert!(lines[1].starts_with("├── ") && lines[1].ends_with(" +"));
+        assert!(lines[2].starts_with("│   ├── "));
+        assert!(lines[4].starts_with("└── ") && lines[4].ends_with(" *"));
+        assert!(lines[5].starts_with("    └── ") && lines[5].ends_with(" *"));
+    }
+
+    #[test]
+    fn test_dot_lists_every_node_and_edge() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let dot = tree.to_dot(None).unwrap();
+
+        assert_eq!(dot.matches("[label=").count(), 11);
+        assert_eq!(dot.matches(" -> ").count(), 10);
+        assert!(!dot.contains("fillcolor"));
+        assert_eq!(
+            tree.to_dot(Some(5)).unwrap_err().kind(),
+            &TreeErrorKind::IndexOutOfBounds
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use crate::checkpoint::{Checkpoint, CheckpointSigner, CheckpointVerifier, NamedKey};
+use crate::hash::to_hash::ToHash;
+use crate::hash::Sha2_256;
+use crate::merkle_tree::TreeResult;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+impl CheckpointSigner for SigningKey {
+    fn sign(&self, message: &[u8]) -> TreeResult<Vec<u8>> {
+        Ok(Signer::sign(self, message).to_bytes().to_vec())
+    }
+}
+
+impl CheckpointVerifier for VerifyingKey {
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        Signature::from_slice(signature)
+            .map(|signature| self.verify_strict(message, &signature).is_ok())
+            .unwrap_or(false)
+    }
+}
+
+/// Signature type byte of ed25519 keys in signed notes
+const NOTE_ED25519: u8 = 0x01;
+
+impl NamedKey<SigningKey> {
+    /// Names an ed25519 key for signed notes, deriving its key hash as verifiers expect it
+    pub fn ed25519(name: impl Into<String>, key: SigningKey) -> Self {
+        let name = name.into();
+        let key_hash = note_key_hash(&name, &key.verifying_key());
+        Self::new(name, key_hash, key)
+    }
+}
+
+impl NamedKey<VerifyingKey> {
+    /// Names an ed25519 public key for checking signed notes
+    ///
+    /// ##Examples
+    /// ```
+    /// use ed25519_dalek::SigningKey;
+    /// use merkle_tree::{Checkpoint, NamedKey, Sha2_256};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let key = SigningKey::from_bytes(&[7; 32]);
+    ///     let verifier = NamedKey::ed25519_verifier("example.com/log", key.verifying_key());
+    ///     let note = Checkpoint::<Sha2_256>::new(2, [0; 32])
+    ///         .with_origin("example.com/log")
+    ///         .sign_note(&NamedKey::ed25519("example.com/log", key))?;
+    ///
+    ///     assert!(Checkpoint::<Sha2_256>::verify_note(&note, &verifier).is_ok());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn ed25519_verifier(name: impl Into<String>, key: VerifyingKey) -> Self {
+        let name = name.into();
+        let key_hash = note_key_hash(&name, &key);
+        Self::new(name, key_hash, key)
+    }
+}
+
+/// First four bytes of `SHA-256(name || "\n" || 0x01 || public key)`
+fn note_key_hash(name: &str, key: &VerifyingKey) -> u32 {
+    let digest =
+        Sha2_256::hash(&[name.as_bytes(), b"\n", &[NOTE_ED25519], key.as_bytes()].concat());
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]])
+}
+
+/// Root and size of a tree signed with ed25519, ready to be published next to proofs
+///
+/// The signature covers the same canonical message as a [`Checkpoint`], so a signed root
+/// can be checked by anything that verifies ed25519 checkpoints.
+pub struct SignedRoot<T: ToHash> {
+    checkpoint: Checkpoint<T>,
+    signature: Signature,
+}
+
+impl<T: ToHash> SignedRoot<T> {
+    /// Signs the root of a tree holding `tree_size` leaves
+    ///
+    /// ##Examples
+    /// ```
+    /// use ed25519_dalek::SigningKey;
+    /// use merkle_tree::{Sha256Tree, Sha256, SignedRoot, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let key = SigningKey::from_bytes(&[7; 32]);
+    ///
+    ///     let signed = SignedRoot::<Sha256>::sign(tree.root_hash()?, 5, &key);
+    ///     assert!(signed.verify(&key.verifying_key()));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn sign(root: T::Hash, tree_size: u64, signing_key: &SigningKey) -> Self {
+        let checkpoint = Checkpoint::new(tree_size, root);
+        let signature = Signer::sign(signing_key, &checkpoint.to_bytes());
+
+        Self {
+            checkpoint,
+            signature,
+        }
+    }
+
+    pub fn root(&self) -> T::Hash {
+        self.checkpoint.root_hash
+    }
+
+    pub fn tree_size(&self) -> u64 {
+        self.checkpoint.tree_size
+    }
+
+    pub fn signature(&self) -> [u8; 64] {
+        self.signature.to_bytes()
+    }
+
+    pub fn verify(&self, public_key: &VerifyingKey) -> bool {
+        public_key
+            .verify_strict(&self.checkpoint.to_bytes(), &self.signature)
+            .is_ok()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod json {
+    use super::SignedRoot;
+    use crate::checkpoint::Checkpoint;
+    use crate::error::tree_error::TreeError;
+    use crate::hash::to_hash::ToHash;
+    use crate::merkle_tree::TreeResult;
+    use crate::utils::hex;
+    use ed25519_dalek::Signature;
+    use serde::{Deserialize, Serialize};
+
+    /// ```json
+    /// {
+    ///   "algorithm": "keccak256",
+    ///   "tree_size": 5,
+    ///   "root": "0x…",
+    ///   "signature": "0x…"
+    /// }
+    /// ```
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct SignedRootJson {
+        algorithm: String,
+        tree_size: u64,
+        root: String,
+        signature: String,
+    }
+
+    impl<T: ToHash> SignedRoot<T> {
+        /// Serializes the signed root with `0x`-prefixed hex root and signature
+        pub fn to_json(&self) -> String {
+            let json = SignedRootJson {
+                algorithm: T::ALGORITHM.to_owned(),
+                tree_size: self.tree_size(),
+                root: format!("0x{}", hex::encode(self.root().as_ref())),
+                signature: format!("0x{}", hex::encode(&self.signature())),
+            };
+
+            serde_json::to_string(&json).expect("signed roots always serialize")
+        }
+
+        /// Deserializes a signed root for the hasher `T`; the signature still has to be verified
+        ///
+        /// ##Examples
+        /// ```
+        /// use ed25519_dalek::SigningKey;
+        /// use merkle_tree::{Keccak256, SignedRoot, ToHash};
+        /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+        ///     let key = SigningKey::from_bytes(&[7; 32]);
+        ///     let signed = SignedRoot::<Keccak256>::sign(Keccak256::hash(b"root"), 3, &key);
+        ///
+        ///     let received = SignedRoot::<Keccak256>::from_json(&signed.to_json())?;
+        ///     assert!(received.verify(&key.verifying_key()));
+        ///
+        ///     Ok(())
+        /// }
+        /// ```
+        pub fn from_json(json: &str) -> TreeResult<Self> {
+            let json: SignedRootJson = serde_json::from_str(json)
+                .map_err(|_| TreeError::invalid_encoding("invalid signed root json"))?;
+            if json.algorithm != T::ALGORITHM {
+                return Err(TreeError::invalid_encoding(
+                    "signed root algorithm does not match the hasher",
+                ));
+            }
+
+            let root = hex::decode_prefixed(&json.root)
+                .ok_or_else(|| TreeError::invalid_encoding("root must be 0x-prefixed hex"))?;
+            let root = T::Hash::try_from(root.as_slice()).map_err(|_| {
+                TreeError::hash_size_mismatch("root length does not match the hasher")
+            })?;
+            let signature = hex::decode_prefixed(&json.signature)
+                .and_then(|bytes| Signature::from_slice(&bytes).ok())
+                .ok_or_else(|| {
+                    TreeError::invalid_encoding("signature must be 64 bytes of 0x-prefixed hex")
+                })?;
+
+            Ok(Self {
+                checkpoint: Checkpoint::new(json.tree_size, root),
+                signature,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::Checkpoint;
+    use crate::hash::Sha256;
+
+    #[test]
+    fn test_signed_root_rejects_other_keys_and_roots() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let other = SigningKey::from_bytes(&[8; 32]);
+        let root = Sha256::hash("root".as_bytes());
+
+        let signed = SignedRoot::<Sha256>::sign(root, 3, &key);
+        assert!(signed.verify(&key.verifying_key()));
+        assert!(!signed.verify(&other.verifying_key()));
+
+        let forged = SignedRoot::<Sha256> {
+            checkpoint: Checkpoint::new(4, root),
+            signature: signed.signature,
+        };
+        assert!(!forged.verify(&key.verifying_key()));
+    }
+
+    #[test]
+    fn test_ed25519_checkpoint_signer() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let checkpoint = Checkpoint::<Sha256>::new(3, Sha256::hash("root".as_bytes()));
+        let signed = checkpoint.sign(&key).unwrap();
+
+        assert!(signed.verify(&key.verifying_key()));
+        assert_eq!(
+            signed.signature(),
+            SignedRoot::<Sha256>::sign(signed.checkpoint().root_hash, 3, &key).signature()
+        );
+    }
+
+    #[test]
+    fn test_note_key_hash_matches_published_keys() {
+        // verifier key "PeterNeumann+c74f20a3+ARpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW"
+        let key = crate::utils::base64::decode("ARpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW");
+        let key = VerifyingKey::from_bytes(key.unwrap()[1..].try_into().unwrap()).unwrap();
+
+        assert_eq!(note_key_hash("PeterNeumann", &key), 0xc74f20a3);
+    }
+}
@@ -0,0 +1,135 @@
+//! SSZ-style merkleization as specified for the Ethereum consensus layer
+//!
+//! Values are packed into 32-byte chunks, padded with zero chunks to the next power of two
+//! (or to the type's limit) and hashed positionally as `hash(left || right)`. The spec
+//! uses SHA-256, i.e. [`Sha2_256`](crate::Sha2_256).
+mod ssz_tree;
+pub use ssz_tree::{verify_generalized_index_proof, SszTree};
+
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+
+pub const BYTES_PER_CHUNK: usize = 32;
+
+pub type Chunk = [u8; BYTES_PER_CHUNK];
+
+/// Splits serialized bytes into chunks, right-padding the last one with zeros
+pub fn pack(bytes: &[u8]) -> Vec<Chunk> {
+    bytes
+        .chunks(BYTES_PER_CHUNK)
+        .map(|part| {
+            let mut chunk = [0u8; BYTES_PER_CHUNK];
+            chunk[..part.len()].copy_from_slice(part);
+            chunk
+        })
+        .collect()
+}
+
+/// Merkleizes chunks, padding to `limit` chunks (or to the chunk count when `None`)
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::ssz::merkleize;
+/// use merkle_tree::Sha2_256;
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     assert_eq!(merkleize::<Sha2_256>(&[[1; 32]], None)?, [1; 32]);
+///
+///     Ok(())
+/// }
+/// ```
+pub fn merkleize<T: ToHash<Hash = Chunk>>(
+    chunks: &[Chunk],
+    limit: Option<usize>,
+) -> TreeResult<Chunk> {
+    SszTree::<T>::new(chunks, limit).map(|tree| tree.root())
+}
+
+/// Mixes the length of a list into its data root
+pub fn mix_in_length<T: ToHash<Hash = Chunk>>(root: Chunk, length: usize) -> Chunk {
+    hash_pair::<T>(root, length_chunk(length))
+}
+
+/// `hash_tree_root` of a vector of basic values, given their SSZ serialization
+pub fn hash_tree_root_basic_vector<T: ToHash<Hash = Chunk>>(
+    serialized: &[u8],
+) -> TreeResult<Chunk> {
+    merkleize::<T>(&pack(serialized), None)
+}
+
+/// `hash_tree_root` of a list of `length` basic values of `element_size` bytes, bounded by `limit`
+pub fn hash_tree_root_basic_list<T: ToHash<Hash = Chunk>>(
+    serialized: &[u8],
+    element_size: usize,
+    limit: usize,
+) -> TreeResult<Chunk> {
+    if element_size == 0 || !serialized.len().is_multiple_of(element_size) {
+        return Err(TreeError::invalid_encoding(
+            "serialized list must consist of whole elements",
+        ));
+    }
+
+    let limit_chunks = (limit * element_size).div_ceil(BYTES_PER_CHUNK);
+    let root = merkleize::<T>(&pack(serialized), Some(limit_chunks))?;
+
+    Ok(mix_in_length::<T>(root, serialized.len() / element_size))
+}
+
+/// `hash_tree_root` of a vector of composite values, given the roots of its elements
+pub fn hash_tree_root_composite_vector<T: ToHash<Hash = Chunk>>(
+    roots: &[Chunk],
+) -> TreeResult<Chunk> {
+    merkleize::<T>(roots, None)
+}
+
+/// `hash_tree_root` of a list of composite values bounded by `limit`, given their roots
+pub fn hash_tree_root_composite_list<T: ToHash<Hash = Chunk>>(
+    roots: &[Chunk],
+    limit: usize,
+) -> TreeResult<Chunk> {
+    let root = merkleize::<T>(roots, Some(limit))?;
+    Ok(mix_in_length::<T>(root, roots.len()))
+}
+
+pub(crate) fn hash_pair<T: ToHash<Hash = Chunk>>(left: Chunk, right: Chunk) -> Chunk {
+    T::hash(&[left, right].concat())
+}
+
+pub(crate) fn length_chunk(length: usize) -> Chunk {
+    let mut chunk = [0u8; BYTES_PER_CHUNK];
+    chunk[..8].copy_from_slice(&(length as u64).to_le_bytes());
+    chunk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha2_256;
+    use crate::utils::hex;
+
+    #[test]
+    fn test_merkleize_pads_with_zero_chunks() {
+        let root = merkleize::<Sha2_256>(&[[0; 32]], Some(2)).unwrap();
+        assert_eq!(
+            hex::encode(&root),
+            "f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b"
+        );
+        assert_eq!(
+            merkleize::<Sha2_256>(&[], Some(4)).unwrap(),
+            Sha2_256::hash(&[root, root].concat())
+        );
+    }
+
+    #[test]
+    fn test_basic_list_mixes_in_length() {
+        let serialized: Vec<u8> = [1u64, 2, 3]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let root = hash_tree_root_basic_list::<Sha2_256>(&serialized, 8, 8).unwrap();
+
+        let data_root = merkleize::<Sha2_256>(&pack(&serialized), Some(2)).unwrap();
+        assert_eq!(root, mix_in_length::<Sha2_256>(data_root, 3));
+        assert!(merkleize::<Sha2_256>(&pack(&serialized), Some(0)).is_err());
+    }
+}
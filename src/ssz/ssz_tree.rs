@@ -0,0 +1,226 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+use crate::ssz::{hash_pair, length_chunk, Chunk};
+use crate::utils::ct;
+use std::marker::PhantomData;
+
+/// SSZ merkle tree over chunks supporting generalized-index proofs
+///
+/// Only the chunks actually present are stored; padding subtrees are represented by
+/// precomputed zero hashes, so large limits cost `O(depth)` memory.
+pub struct SszTree<T: ToHash<Hash = Chunk>> {
+    levels: Vec<Vec<Chunk>>,
+    zero_hashes: Vec<Chunk>,
+    length: Option<usize>,
+    hasher: PhantomData<T>,
+}
+
+impl<T: ToHash<Hash = Chunk>> SszTree<T> {
+    /// Builds the tree over chunks padded to `limit` (or to the chunk count when `None`)
+    pub fn new(chunks: &[Chunk], limit: Option<usize>) -> TreeResult<Self> {
+        let limit = limit.unwrap_or(chunks.len());
+        if chunks.len() > limit {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let depth = limit.max(1).next_power_of_two().trailing_zeros() as usize;
+        let mut zero_hashes = vec![[0u8; 32]];
+        for height in 0..depth {
+            zero_hashes.push(hash_pair::<T>(zero_hashes[height], zero_hashes[height]));
+        }
+
+        let mut levels = vec![chunks.to_vec()];
+        for height in 0..depth {
+            let next_level = levels[height]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair::<T>(*left, *right),
+                    _ => hash_pair::<T>(pair[0], zero_hashes[height]),
+                })
+                .collect();
+            levels.push(next_level);
+        }
+
+        Ok(Self {
+            levels,
+            zero_hashes,
+            length: None,
+            hasher: PhantomData,
+        })
+    }
+
+    /// Builds the tree of an SSZ list, whose root mixes in the number of elements
+    pub fn new_list(chunks: &[Chunk], limit: usize, length: usize) -> TreeResult<Self> {
+        let mut tree = Self::new(chunks, Some(limit))?;
+        tree.length = Some(length);
+        Ok(tree)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Nodes actually present at `height` above the chunks, without the padding
+    pub(crate) fn level(&self, height: usize) -> &[Chunk] {
+        &self.levels[height]
+    }
+
+    pub fn root(&self) -> Chunk {
+        let data_root = self.node_in_data(1);
+        match self.length {
+            Some(length) => hash_pair::<T>(data_root, length_chunk(length)),
+            None => data_root,
+        }
+    }
+
+    /// Generalized index of the chunk at `index`, accounting for a mixed-in length
+    pub fn chunk_generalized_index(&self, index: usize) -> usize {
+        let gindex = (1 << self.depth()) + index;
+        match self.length {
+            Some(_) => (1 << (self.depth() + 1)) + index,
+            None => gindex,
+        }
+    }
+
+    /// Node at the given generalized index
+    pub fn node(&self, gindex: usize) -> TreeResult<Chunk> {
+        if gindex == 0 {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        match self.length {
+            None => self.checked_node_in_data(gindex),
+            Some(length) => match gindex {
+                1 => Ok(self.root()),
+                3 => Ok(length_chunk(length)),
+                _ => {
+                    // drop the leading "go left" step into the data subtree
+                    let depth = usize::BITS - 1 - gindex.leading_zeros();
+                    if gindex >> (depth - 1) != 0b10 {
+                        return Err(TreeError::index_out_of_bounds());
+                    }
+                    self.checked_node_in_data(gindex ^ (0b11 << (depth - 1)))
+                }
+            },
+        }
+    }
+
+    /// Sibling branch from the node at `gindex` up to the root
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::ssz::{verify_generalized_index_proof, SszTree};
+    /// use merkle_tree::Sha2_256;
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let chunks = [[1; 32], [2; 32], [3; 32]];
+    ///     let tree = SszTree::<Sha2_256>::new_list(&chunks, 16, 3)?;
+    ///     let gindex = tree.chunk_generalized_index(2);
+    ///
+    ///     let branch = tree.prove(gindex)?;
+    ///     assert!(verify_generalized_index_proof::<Sha2_256>(chunks[2], &branch, gindex, tree.root()));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn prove(&self, gindex: usize) -> TreeResult<Vec<Chunk>> {
+        self.node(gindex)?;
+
+        let mut branch = Vec::new();
+        let mut gindex = gindex;
+        while gindex > 1 {
+            branch.push(self.node(gindex ^ 1)?);
+            gindex >>= 1;
+        }
+
+        Ok(branch)
+    }
+
+    fn checked_node_in_data(&self, gindex: usize) -> TreeResult<Chunk> {
+        let depth = (usize::BITS - 1 - gindex.leading_zeros()) as usize;
+        if depth > self.depth() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        Ok(self.node_in_data(gindex))
+    }
+
+    fn node_in_data(&self, gindex: usize) -> Chunk {
+        let depth = (usize::BITS - 1 - gindex.leading_zeros()) as usize;
+        let height = self.depth() - depth;
+        let index = gindex - (1 << depth);
+
+        self.levels[height]
+            .get(index)
+            .copied()
+            .unwrap_or(self.zero_hashes[height])
+    }
+}
+
+/// Verifies a branch for `leaf` at the generalized index `gindex` against `root`
+pub fn verify_generalized_index_proof<T: ToHash<Hash = Chunk>>(
+    leaf: Chunk,
+    branch: &[Chunk],
+    gindex: usize,
+    root: Chunk,
+) -> bool {
+    let mut gindex = gindex;
+    let mut current = leaf;
+    for sibling in branch {
+        current = match gindex & 1 {
+            1 => hash_pair::<T>(*sibling, current),
+            _ => hash_pair::<T>(current, *sibling),
+        };
+        gindex >>= 1;
+    }
+
+    gindex == 1 && ct::eq(current.as_ref(), root.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha2_256;
+    use crate::ssz::{hash_tree_root_composite_list, merkleize};
+
+    #[test]
+    fn test_ssz_tree_root_matches_merkleize() {
+        let chunks = [[1; 32], [2; 32], [3; 32]];
+        let tree = SszTree::<Sha2_256>::new_list(&chunks, 16, 3).unwrap();
+
+        assert_eq!(tree.depth(), 4);
+        assert_eq!(
+            tree.root(),
+            hash_tree_root_composite_list::<Sha2_256>(&chunks, 16).unwrap()
+        );
+        assert_eq!(
+            tree.node(2).unwrap(),
+            merkleize::<Sha2_256>(&chunks, Some(16)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_generalized_index_proofs() {
+        let chunks = [[1; 32], [2; 32], [3; 32], [4; 32], [5; 32]];
+        let tree = SszTree::<Sha2_256>::new(&chunks, None).unwrap();
+
+        for (index, chunk) in chunks.iter().enumerate() {
+            let gindex = tree.chunk_generalized_index(index);
+            let branch = tree.prove(gindex).unwrap();
+            assert!(verify_generalized_index_proof::<Sha2_256>(
+                *chunk,
+                &branch,
+                gindex,
+                tree.root()
+            ));
+            assert!(!verify_generalized_index_proof::<Sha2_256>(
+                *chunk,
+                &branch,
+                gindex ^ 1,
+                tree.root()
+            ));
+        }
+
+        assert!(tree.prove(1 << 4).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+use crate::store::stored_tree::{require_duplicates_allowed, stored_hash};
+use crate::store::{MemoryStore, NodeStore, StoredTree};
+use crate::tree_policy::TreePolicy;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Asynchronous counterpart of [`NodeStore`] for remote stores (object storage, databases)
+pub trait AsyncNodeStore {
+    fn get(
+        &self,
+        level: usize,
+        index: usize,
+    ) -> impl Future<Output = TreeResult<Option<Vec<u8>>>> + Send;
+    fn put(
+        &mut self,
+        level: usize,
+        index: usize,
+        hash: &[u8],
+    ) -> impl Future<Output = TreeResult<()>> + Send;
+    fn leaf_count(&self) -> impl Future<Output = TreeResult<usize>> + Send;
+    fn set_leaf_count(&mut self, count: usize) -> impl Future<Output = TreeResult<()>> + Send;
+}
+
+impl AsyncNodeStore for MemoryStore {
+    async fn get(&self, level: usize, index: usize) -> TreeResult<Option<Vec<u8>>> {
+        NodeStore::get(self, level, index)
+    }
+
+    async fn put(&mut self, level: usize, index: usize, hash: &[u8]) -> TreeResult<()> {
+        NodeStore::put(self, level, index, hash)
+    }
+
+    async fn leaf_count(&self) -> TreeResult<usize> {
+        NodeStore::leaf_count(self)
+    }
+
+    async fn set_leaf_count(&mut self, count: usize) -> TreeResult<()> {
+        NodeStore::set_leaf_count(self, count)
+    }
+}
+
+/// Adapter running a blocking [`NodeStore`] (e.g. sled) on tokio's blocking thread pool
+pub struct BlockingStore<S> {
+    store: Arc<Mutex<S>>,
+}
+
+impl<S: NodeStore + Send + 'static> BlockingStore<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(store)),
+        }
+    }
+
+    async fn run<R: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&mut S) -> TreeResult<R> + Send + 'static,
+    ) -> TreeResult<R> {
+        let store = Arc::clone(&self.store);
+        tokio::task::spawn_blocking(move || {
+            let mut store = store
+                .lock()
+                .map_err(|_| TreeError::storage_failed("store lock is poisoned"))?;
+            operation(&mut store)
+        })
+        .await
+        .map_err(|error| {
+            TreeError::storage_failed("blocking store task failed").with_source(error)
+        })?
+    }
+}
+
+impl<S: NodeStore + Send + 'static> AsyncNodeStore for BlockingStore<S> {
+    async fn get(&self, level: usize, index: usize) -> TreeResult<Option<Vec<u8>>> {
+        self.run(move |store| store.get(level, index)).await
+    }
+
+    async fn put(&mut self, level: usize, index: usize, hash: &[u8]) -> TreeResult<()> {
+        let hash = hash.to_vec();
+        self.run(move |store| store.put(level, index, &hash)).await
+    }
+
+    async fn leaf_count(&self) -> TreeResult<usize> {
+        self.run(|store| store.leaf_count()).await
+    }
+
+    async fn set_leaf_count(&mut self, count: usize) -> TreeResult<()> {
+        self.run(move |store| store.set_leaf_count(count)).await
+    }
+}
+
+impl<T: ToHash, S: AsyncNodeStore> StoredTree<T, S> {
+    /// Opens the tree held by an asynchronous store, which may be empty
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::store::{MemoryStore, StoredTree};
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let mut stored = StoredTree::<Sha256, _>::open_async(MemoryStore::new()).await?;
+    ///     for leaf in &leaves {
+    ///         stored.append_async(*leaf).await?;
+    ///     }
+    ///
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     assert_eq!(stored.root_hash_async().await?, tree.root_hash()?);
+    ///     assert_eq!(stored.get_proof_async(3).await?, tree.get_proof_at(3)?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn open_async(store: S) -> TreeResult<Self> {
+        let leaf_count = store.leaf_count().await?;
+        Ok(Self::with_leaf_count(store, leaf_count))
+    }
+
+    /// Opens the tree held by the store like [`StoredTree::with_policy`], pairing its nodes by
+    /// `policy`
+    pub async fn open_async_with_policy(store: S, policy: TreePolicy) -> TreeResult<Self> {
+        require_duplicates_allowed(policy)?;
+        let mut tree = Self::open_async(store).await?;
+        tree.policy = policy;
+        Ok(tree)
+    }
+
+    /// Appends a leaf like [`StoredTree::append`] without blocking the executor
+    pub async fn append_async(&mut self, leaf: T::Hash) -> TreeResult<()> {
+        let mut index = self.leaf_count;
+        let mut width = self.leaf_count + 1;
+        let mut current = leaf;
+        self.store.put(0, index, leaf.as_ref()).await?;
+
+        let mut level = 0;
+        while width > 1 {
+            current = match index ^ 1 < width {
+                true => self.parent(index, current, self.node_async(level, index ^ 1).await?)?,
+                false => self.policy.lift_odd::<T>(current)?,
+            };
+            index /= 2;
+            width = width.div_ceil(2);
+            level += 1;
+            self.store.put(level, index, current.as_ref()).await?;
+        }
+
+        self.leaf_count += 1;
+        self.store.set_leaf_count(self.leaf_count).await
+    }
+
+    pub async fn root_hash_async(&self) -> TreeResult<T::Hash> {
+        if self.leaf_count == 0 {
+            return Err(TreeError::tree_empty());
+        }
+
+        self.node_async(self.height(), 0).await
+    }
+
+    pub async fn get_proof_async(&self, index: usize) -> TreeResult<Vec<T::Hash>> {
+        if index >= self.leaf_count {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let mut proof = Vec::with_capacity(self.height());
+        let mut index = index;
+        let mut width = self.leaf_count;
+        for level in 0..self.height() {
+            let sibling = match index ^ 1 < width {
+                true => Some(self.node_async(level, index ^ 1).await?),
+                false => {
+                    let node = self.node_async(level, index).await?;
+                    self.policy.odd_sibling::<T>(node)?
+                }
+            };
+            proof.extend(sibling);
+            index /= 2;
+            width = width.div_ceil(2);
+        }
+
+        Ok(proof)
+    }
+
+    async fn node_async(&self, level: usize, index: usize) -> TreeResult<T::Hash> {
+        stored_hash::<T>(self.store.get(level, index).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::to_hash::ToHash;
+    use crate::hash::Sha256;
+    use crate::store::{BlockingStore, MemoryStore, StoredTree};
+    use crate::tree_policy::{OddLeafPolicy, PairOrdering, TreePolicy};
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Tree;
+
+    #[tokio::test]
+    async fn test_blocking_store_matches_memory_tree() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let store = BlockingStore::new(MemoryStore::new());
+        let mut stored = StoredTree::<Sha256, _>::open_async(store).await.unwrap();
+        for leaf in &leaves {
+            stored.append_async(*leaf).await.unwrap();
+        }
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        assert_eq!(
+            stored.root_hash_async().await.unwrap(),
+            tree.root_hash().unwrap()
+        );
+        for index in 0..leaves.len() {
+            assert_eq!(
+                stored.get_proof_async(index).await.unwrap(),
+                tree.get_proof_at(index).unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_path_follows_the_policy() {
+        let policy = TreePolicy {
+            pair_ordering: PairOrdering::Positional,
+            odd_leaf: OddLeafPolicy::DuplicateLast,
+            ..TreePolicy::default()
+        };
+        let leaves: Vec<_> = (0..7u8).map(|i| Sha256::hash(&[i])).collect();
+        let mut sync = StoredTree::<Sha256, _>::with_policy(MemoryStore::new(), policy).unwrap();
+        sync.extend(&leaves).unwrap();
+
+        let store = BlockingStore::new(MemoryStore::new());
+        let mut stored = StoredTree::<Sha256, _>::open_async_with_policy(store, policy)
+            .await
+            .unwrap();
+        for leaf in &leaves {
+            stored.append_async(*leaf).await.unwrap();
+        }
+
+        assert_eq!(stored.policy(), policy);
+        assert_eq!(
+            stored.root_hash_async().await.unwrap(),
+            sync.root_hash().unwrap()
+        );
+        for index in 0..leaves.len() {
+            assert_eq!(
+                stored.get_proof_async(index).await.unwrap(),
+                sync.get_proof_at(index).unwrap()
+            );
+        }
+    }
+}
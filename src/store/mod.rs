@@ -0,0 +1,125 @@
+#[cfg(feature = "tokio")]
+mod async_store;
+#[cfg(feature = "sled")]
+mod sled;
+mod stored_tree;
+#[cfg(feature = "tokio")]
+pub use self::async_store::{AsyncNodeStore, BlockingStore};
+#[cfg(feature = "sled")]
+pub use self::sled::SledStore;
+pub use self::stored_tree::StoredTree;
+
+use self::stored_tree::stored_hash;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use std::collections::BTreeMap;
+
+/// Storage for the nodes of a tree, addressed by `(level, index)` with the leaves on level 0
+///
+/// Hashes are stored as raw bytes so a store does not depend on the hasher. A node that was
+/// never written reads as `None`.
+pub trait NodeStore {
+    fn get(&self, level: usize, index: usize) -> TreeResult<Option<Vec<u8>>>;
+    fn put(&mut self, level: usize, index: usize, hash: &[u8]) -> TreeResult<()>;
+    fn leaf_count(&self) -> TreeResult<usize>;
+    fn set_leaf_count(&mut self, count: usize) -> TreeResult<()>;
+}
+
+/// Storage for nodes addressed by opaque byte keys rather than by position
+///
+/// Used by trees whose nodes are not laid out by `(level, index)`, such as the
+/// [`JellyfishMerkleTree`](crate::jmt::JellyfishMerkleTree) keying nodes by version and path.
+pub trait KeyedNodeStore {
+    fn get_node(&self, key: &[u8]) -> TreeResult<Option<Vec<u8>>>;
+    fn put_node(&mut self, key: &[u8], node: &[u8]) -> TreeResult<()>;
+}
+
+/// [`NodeStore`] and [`KeyedNodeStore`] keeping every node in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    levels: Vec<Vec<Vec<u8>>>,
+    leaf_count: usize,
+    nodes: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl NodeStore for MemoryStore {
+    fn get(&self, level: usize, index: usize) -> TreeResult<Option<Vec<u8>>> {
+        Ok(self
+            .levels
+            .get(level)
+            .and_then(|hashes| hashes.get(index))
+            .filter(|hash| !hash.is_empty())
+            .cloned())
+    }
+
+    fn put(&mut self, level: usize, index: usize, hash: &[u8]) -> TreeResult<()> {
+        if self.levels.len() <= level {
+            self.levels.resize(level + 1, Vec::new());
+        }
+
+        let hashes = &mut self.levels[level];
+        if hashes.len() <= index {
+            hashes.resize(index + 1, Vec::new());
+        }
+        hashes[index] = hash.to_vec();
+
+        Ok(())
+    }
+
+    fn leaf_count(&self) -> TreeResult<usize> {
+        Ok(self.leaf_count)
+    }
+
+    fn set_leaf_count(&mut self, count: usize) -> TreeResult<()> {
+        self.leaf_count = count;
+        Ok(())
+    }
+}
+
+impl KeyedNodeStore for MemoryStore {
+    fn get_node(&self, key: &[u8]) -> TreeResult<Option<Vec<u8>>> {
+        Ok(self.nodes.get(key).cloned())
+    }
+
+    fn put_node(&mut self, key: &[u8], node: &[u8]) -> TreeResult<()> {
+        self.nodes.insert(key.to_vec(), node.to_vec());
+        Ok(())
+    }
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Loads the leaves held by a store into an in-memory tree
+    ///
+    /// Every leaf is read into memory and the tree has the default policy. Trees too large
+    /// for memory, or stored with another policy, are served straight from the store by a
+    /// [`StoredTree`] instead.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::store::{MemoryStore, StoredTree};
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let mut stored = StoredTree::<Sha256, _>::new(MemoryStore::new())?;
+    ///     stored.extend(&leaves)?;
+    ///
+    ///     let tree = Sha256Tree::from_store(stored.store())?;
+    ///     assert_eq!(tree.leaves(), &leaves[..]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_store(store: &impl NodeStore) -> TreeResult<Self> {
+        let leaves = (0..store.leaf_count()?)
+            .map(|index| stored_hash::<T>(store.get(0, index)?))
+            .collect::<TreeResult<Vec<_>>>()?;
+
+        Ok(Self::from_leaves(&leaves))
+    }
+}
@@ -0,0 +1,127 @@
+use crate::error::tree_error::TreeError;
+use crate::merkle_tree::TreeResult;
+use crate::store::{KeyedNodeStore, NodeStore};
+
+const LEAF_COUNT_KEY: &[u8] = b"leaf_count";
+/// Leading byte of keyed nodes, beyond any level a positional key can start with
+const KEYED_NODE_PREFIX: u8 = 0xff;
+
+/// [`NodeStore`] persisting nodes in a sled tree
+///
+/// Nodes are keyed by `level (u32, BE) | index (u64, BE)`, so the leaves of a level are
+/// stored contiguously and in order. Keyed nodes are stored under `0xff | key`.
+pub struct SledStore {
+    tree: sled::Tree,
+}
+
+impl SledStore {
+    pub fn new(tree: sled::Tree) -> Self {
+        Self { tree }
+    }
+
+    /// Opens (or creates) a database at `path` and stores the nodes in its `merkle_tree` tree
+    pub fn open(path: impl AsRef<std::path::Path>) -> TreeResult<Self> {
+        let db = sled::open(path).map_err(storage_error)?;
+        Ok(Self::new(
+            db.open_tree("merkle_tree").map_err(storage_error)?,
+        ))
+    }
+
+    /// Flushes pending writes to disk
+    pub fn flush(&self) -> TreeResult<()> {
+        self.tree.flush().map_err(storage_error)?;
+        Ok(())
+    }
+}
+
+impl NodeStore for SledStore {
+    fn get(&self, level: usize, index: usize) -> TreeResult<Option<Vec<u8>>> {
+        Ok(self
+            .tree
+            .get(node_key(level, index))
+            .map_err(storage_error)?
+            .map(|hash| hash.to_vec()))
+    }
+
+    fn put(&mut self, level: usize, index: usize, hash: &[u8]) -> TreeResult<()> {
+        self.tree
+            .insert(node_key(level, index), hash)
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    fn leaf_count(&self) -> TreeResult<usize> {
+        match self.tree.get(LEAF_COUNT_KEY).map_err(storage_error)? {
+            None => Ok(0),
+            Some(bytes) => {
+                let bytes: [u8; 8] = bytes
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_| TreeError::storage_failed("stored leaf count is not a u64"))?;
+                Ok(u64::from_be_bytes(bytes) as usize)
+            }
+        }
+    }
+
+    fn set_leaf_count(&mut self, count: usize) -> TreeResult<()> {
+        self.tree
+            .insert(LEAF_COUNT_KEY, &(count as u64).to_be_bytes())
+            .map_err(storage_error)?;
+        Ok(())
+    }
+}
+
+impl KeyedNodeStore for SledStore {
+    fn get_node(&self, key: &[u8]) -> TreeResult<Option<Vec<u8>>> {
+        Ok(self
+            .tree
+            .get([&[KEYED_NODE_PREFIX], key].concat())
+            .map_err(storage_error)?
+            .map(|node| node.to_vec()))
+    }
+
+    fn put_node(&mut self, key: &[u8], node: &[u8]) -> TreeResult<()> {
+        self.tree
+            .insert([&[KEYED_NODE_PREFIX], key].concat(), node)
+            .map_err(storage_error)?;
+        Ok(())
+    }
+}
+
+fn node_key(level: usize, index: usize) -> [u8; 12] {
+    let mut key = [0u8; 12];
+    key[..4].copy_from_slice(&(level as u32).to_be_bytes());
+    key[4..].copy_from_slice(&(index as u64).to_be_bytes());
+    key
+}
+
+fn storage_error(error: sled::Error) -> TreeError {
+    TreeError::storage_failed("sled operation failed").with_source(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::Sha256;
+    use crate::store::{SledStore, StoredTree};
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_sled_store_survives_reopen() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+        let db = sled::Config::new().temporary(true).open().unwrap();
+
+        let mut stored =
+            StoredTree::<Sha256, _>::new(SledStore::new(db.open_tree("tree").unwrap())).unwrap();
+        stored.extend(&leaves[..3]).unwrap();
+
+        let mut reopened =
+            StoredTree::<Sha256, _>::new(SledStore::new(db.open_tree("tree").unwrap())).unwrap();
+        reopened.extend(&leaves[3..]).unwrap();
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        assert_eq!(reopened.leaf_count(), leaves.len());
+        assert_eq!(reopened.root_hash().unwrap(), tree.root_hash().unwrap());
+    }
+}
@@ -0,0 +1,547 @@
+use crate::consistency_proof::largest_power_of_two_below;
+use crate::error::tree_error::{TreeError, TreeErrorKind};
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::TreeResult;
+use crate::store::NodeStore;
+use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering, TreePolicy};
+use std::marker::PhantomData;
+
+/// Tree whose nodes live in a [`NodeStore`] instead of memory
+///
+/// Every level is persisted, so appending a leaf rewrites only the O(log n) nodes above it
+/// and roots and proofs are read straight from the store. Reopening a store that already
+/// holds a tree continues where it left off.
+///
+/// Nodes are paired and odd nodes handled by the tree's [`TreePolicy`], which is not
+/// persisted: a store must be reopened with the policy it was written with. Stored trees keep
+/// no index of their leaves, so they always allow duplicates.
+pub struct StoredTree<T: ToHash, S> {
+    pub(super) store: S,
+    pub(super) leaf_count: usize,
+    pub(super) policy: TreePolicy,
+    hasher: PhantomData<T>,
+}
+
+impl<T: ToHash, S> StoredTree<T, S> {
+    pub(crate) fn with_leaf_count(store: S, leaf_count: usize) -> Self {
+        Self {
+            store,
+            leaf_count,
+            policy: TreePolicy::default(),
+            hasher: PhantomData,
+        }
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn policy(&self) -> TreePolicy {
+        self.policy
+    }
+
+    /// Parent of the node at `index` and its sibling, ordered by the side of the node
+    pub(super) fn parent(
+        &self,
+        index: usize,
+        node: T::Hash,
+        sibling: T::Hash,
+    ) -> TreeResult<T::Hash> {
+        match index % 2 {
+            0 => self.policy.combine::<T>(node, sibling),
+            _ => self.policy.combine::<T>(sibling, node),
+        }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// Number of levels above the leaves
+    pub(crate) fn height(&self) -> usize {
+        self.leaf_count.next_power_of_two().trailing_zeros() as usize
+    }
+}
+
+impl<T: ToHash, S: NodeStore> StoredTree<T, S> {
+    /// Opens the tree held by the store, which may be empty
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::store::{MemoryStore, StoredTree};
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let mut stored = StoredTree::<Sha256, _>::new(MemoryStore::new())?;
+    ///     stored.extend(&leaves)?;
+    ///
+    ///     let reopened = StoredTree::<Sha256, _>::new(stored.into_store())?;
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     assert_eq!(reopened.root_hash()?, tree.root_hash()?);
+    ///     assert_eq!(reopened.get_proof_at(3)?, tree.get_proof_at(3)?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new(store: S) -> TreeResult<Self> {
+        let leaf_count = store.leaf_count()?;
+        Ok(Self::with_leaf_count(store, leaf_count))
+    }
+
+    /// Opens the tree held by the store, pairing its nodes by `policy`
+    ///
+    /// Fails with `LeafDuplicated` unless the policy allows duplicate leaves, as the store
+    /// cannot tell whether a leaf was already appended without reading every leaf back.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::store::{MemoryStore, StoredTree};
+    /// use merkle_tree::{OddLeafPolicy, PairOrdering, Sha256, ToHash, TreeBuilder, TreePolicy};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let policy = TreePolicy {
+    ///         pair_ordering: PairOrdering::Positional,
+    ///         odd_leaf: OddLeafPolicy::DuplicateLast,
+    ///         ..TreePolicy::default()
+    ///     };
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let mut stored = StoredTree::<Sha256, _>::with_policy(MemoryStore::new(), policy)?;
+    ///     stored.extend(&leaves)?;
+    ///
+    ///     let tree = TreeBuilder::<Sha256>::new()
+    ///         .without_domain_separation()
+    ///         .leaf_hashes(leaves.iter().copied())
+    ///         .pair_ordering(PairOrdering::Positional)
+    ///         .odd_leaf_policy(OddLeafPolicy::DuplicateLast)
+    ///         .build()?;
+    ///     assert_eq!(stored.root_hash()?, tree.root_hash()?);
+    ///     assert_eq!(stored.get_proof_at(4)?, tree.get_proof_at(4)?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_policy(store: S, policy: TreePolicy) -> TreeResult<Self> {
+        require_duplicates_allowed(policy)?;
+        let mut tree = Self::new(store)?;
+        tree.policy = policy;
+        Ok(tree)
+    }
+
+    /// Appends a leaf, updating the nodes between it and the root
+    pub fn append(&mut self, leaf: T::Hash) -> TreeResult<()> {
+        let mut index = self.leaf_count;
+        let mut width = self.leaf_count + 1;
+        let mut current = leaf;
+        self.store.put(0, index, leaf.as_ref())?;
+
+        let mut level = 0;
+        while width > 1 {
+            current = match index ^ 1 < width {
+                true => self.parent(index, current, self.node(level, index ^ 1)?)?,
+                false => self.policy.lift_odd::<T>(current)?,
+            };
+            index /= 2;
+            width = width.div_ceil(2);
+            level += 1;
+            self.store.put(level, index, current.as_ref())?;
+        }
+
+        self.leaf_count += 1;
+        self.store.set_leaf_count(self.leaf_count)
+    }
+
+    pub fn extend(&mut self, leaves: &[T::Hash]) -> TreeResult<()> {
+        for leaf in leaves {
+            self.append(*leaf)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn root_hash(&self) -> TreeResult<T::Hash> {
+        if self.leaf_count == 0 {
+            return Err(TreeError::tree_empty());
+        }
+
+        self.node(self.height(), 0)
+    }
+
+    pub fn get_proof_at(&self, index: usize) -> TreeResult<Vec<T::Hash>> {
+        if index >= self.leaf_count {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let mut proof = Vec::with_capacity(self.height());
+        let mut index = index;
+        let mut width = self.leaf_count;
+        for level in 0..self.height() {
+            let sibling = match index ^ 1 < width {
+                true => Some(self.node(level, index ^ 1)?),
+                false => self.policy.odd_sibling::<T>(self.node(level, index)?)?,
+            };
+            proof.extend(sibling);
+            index /= 2;
+            width = width.div_ceil(2);
+        }
+
+        Ok(proof)
+    }
+
+    /// Inclusion path of `index` in the tree formed by the first `tree_size` leaves
+    pub(crate) fn inclusion_path(
+        &self,
+        index: usize,
+        tree_size: usize,
+    ) -> TreeResult<Vec<T::Hash>> {
+        self.require_rfc6962_shape()?;
+        if index >= tree_size || tree_size > self.leaf_count {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let mut path = Vec::new();
+        let (mut start, mut end) = (0, tree_size);
+        while end - start > 1 {
+            let split = start + largest_power_of_two_below(end - start);
+            match index < split {
+                true => {
+                    path.push(self.range_root(split, end)?);
+                    end = split;
+                }
+                false => {
+                    path.push(self.range_root(start, split)?);
+                    start = split;
+                }
+            }
+        }
+        path.reverse();
+
+        Ok(path)
+    }
+
+    /// RFC 6962 `SUBPROOF` between the first `old_size` and `new_size` leaves
+    pub(crate) fn consistency_path(
+        &self,
+        old_size: usize,
+        new_size: usize,
+    ) -> TreeResult<Vec<T::Hash>> {
+        self.require_rfc6962_shape()?;
+        if old_size == 0 || old_size > new_size || new_size > self.leaf_count {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        self.subproof(0, new_size, old_size, true)
+    }
+
+    fn subproof(
+        &self,
+        start: usize,
+        end: usize,
+        old_size: usize,
+        complete: bool,
+    ) -> TreeResult<Vec<T::Hash>> {
+        if old_size == end - start {
+            return match complete {
+                true => Ok(Vec::new()),
+                false => Ok(vec![self.range_root(start, end)?]),
+            };
+        }
+
+        let split = largest_power_of_two_below(end - start);
+        let mut path = match old_size <= split {
+            true => self.subproof(start, start + split, old_size, complete)?,
+            false => self.subproof(start + split, end, old_size - split, false)?,
+        };
+        path.push(match old_size <= split {
+            true => self.range_root(start + split, end)?,
+            false => self.range_root(start, start + split)?,
+        });
+
+        Ok(path)
+    }
+
+    /// Root of the leaves in `start..end`, read from the store when a stored node covers
+    /// exactly that range and combined from its halves otherwise
+    fn range_root(&self, start: usize, end: usize) -> TreeResult<T::Hash> {
+        let level = (end - start).next_power_of_two().trailing_zeros() as usize;
+        let aligned = start.is_multiple_of(1 << level);
+        if aligned && (end - start == 1 << level || end == self.leaf_count) {
+            return self.node(level, start >> level);
+        }
+
+        let split = start + largest_power_of_two_below(end - start);
+        Ok(T::try_combine(
+            self.range_root(start, split)?,
+            self.range_root(split, end)?,
+        )?)
+    }
+
+    /// Re-derives every stored interior node from its stored children, bottom-up, returning
+    /// the first `(level, index)` that does not match
+    ///
+    /// A node that is missing or does not have the hash length counts as corrupt. Leaves have
+    /// nothing to be checked against, so a corrupt leaf is reported through its parent.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::store::{MemoryStore, NodeStore, StoredTree};
+    /// use merkle_tree::{Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..6).map(|i| Sha256::hash(&[i])).collect();
+    ///     let mut stored = StoredTree::<Sha256, _>::new(MemoryStore::new())?;
+    ///     stored.extend(&leaves)?;
+    ///     assert_eq!(stored.verify_integrity()?, None);
+    ///
+    ///     let mut store = stored.into_store();
+    ///     store.put(1, 2, &[0; 32])?;
+    ///     let stored = StoredTree::<Sha256, _>::new(store)?;
+    ///     assert_eq!(stored.verify_integrity()?, Some((1, 2)));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn verify_integrity(&self) -> TreeResult<Option<(usize, usize)>> {
+        let mut width = self.leaf_count;
+        for level in 1..=self.height() {
+            width = width.div_ceil(2);
+            for index in 0..width {
+                if !self.node_is_intact(level, index)? {
+                    return Ok(Some((level, index)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Checks like [`StoredTree::verify_integrity`], spreading the nodes of each level over
+    /// the rayon thread pool
+    #[cfg(feature = "rayon")]
+    pub fn par_verify_integrity(&self) -> TreeResult<Option<(usize, usize)>>
+    where
+        S: Sync,
+        T: Sync,
+    {
+        use rayon::prelude::*;
+
+        let mut width = self.leaf_count;
+        for level in 1..=self.height() {
+            width = width.div_ceil(2);
+            let corrupt = (0..width)
+                .into_par_iter()
+                .map(|index| Ok((!self.node_is_intact(level, index)?).then_some(index)))
+                .find_first(|result: &TreeResult<Option<usize>>| !matches!(result, Ok(None)));
+            if let Some(index) = corrupt.transpose()?.flatten() {
+                return Ok(Some((level, index)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Checks only the nodes above `count` leaves picked from `seed`, lowest level first
+    ///
+    /// Leaves are picked like [`MerkleTree::sample_proofs`](crate::MerkleTree::sample_proofs),
+    /// so a verifier can spot-check a large store in O(count · log n) reads.
+    pub fn verify_integrity_sampled(
+        &self,
+        seed: &[u8],
+        count: usize,
+    ) -> TreeResult<Option<(usize, usize)>> {
+        if self.leaf_count == 0 {
+            return Err(TreeError::tree_empty());
+        }
+
+        let mut corrupt = None;
+        for sample in 0..count as u64 {
+            let digest = T::try_hash(&[seed, &sample.to_be_bytes()].concat())?;
+            let mut index = digest
+                .as_ref()
+                .iter()
+                .take(8)
+                .fold(0u64, |acc, byte| acc << 8 | *byte as u64)
+                % self.leaf_count as u64;
+
+            for level in 1..=self.height() {
+                index /= 2;
+                if corrupt.is_some_and(|found| found <= (level, index as usize)) {
+                    break;
+                }
+                if !self.node_is_intact(level, index as usize)? {
+                    corrupt = Some((level, index as usize));
+                    break;
+                }
+            }
+        }
+
+        Ok(corrupt)
+    }
+
+    /// Whether the stored node equals the parent of its stored children
+    fn node_is_intact(&self, level: usize, index: usize) -> TreeResult<bool> {
+        let width = (self.leaf_count - 1) / (1 << (level - 1)) + 1;
+        let (Ok(node), Ok(left)) = (
+            stored_hash::<T>(self.store.get(level, index)?),
+            stored_hash::<T>(self.store.get(level - 1, 2 * index)?),
+        ) else {
+            return Ok(false);
+        };
+        if 2 * index + 1 >= width {
+            return Ok(node == self.policy.lift_odd::<T>(left)?);
+        }
+
+        match stored_hash::<T>(self.store.get(level - 1, 2 * index + 1)?) {
+            Ok(right) => Ok(node == self.policy.combine::<T>(left, right)?),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Fails unless nodes are sorted and promoted, the shape of the RFC 6962 range proofs
+    fn require_rfc6962_shape(&self) -> TreeResult<()> {
+        match (self.policy.pair_ordering, self.policy.odd_leaf) {
+            (PairOrdering::Sorted, OddLeafPolicy::Promote) => Ok(()),
+            _ => Err(TreeError::proof_malformed(
+                "range proofs need a tree that sorts pairs and promotes odd nodes",
+            )),
+        }
+    }
+
+    fn node(&self, level: usize, index: usize) -> TreeResult<T::Hash> {
+        stored_hash::<T>(self.store.get(level, index)?)
+    }
+}
+
+/// Decodes a node read from a store, failing when it was never written
+pub(crate) fn stored_hash<T: ToHash>(bytes: Option<Vec<u8>>) -> TreeResult<T::Hash> {
+    let bytes = bytes.ok_or_else(|| TreeError::storage_failed("node is missing from the store"))?;
+
+    T::Hash::try_from(bytes.as_slice())
+        .map_err(|_| TreeError::hash_size_mismatch("stored node does not match the hash length"))
+}
+
+/// Rejects policies that would need a leaf index the store does not keep
+pub(super) fn require_duplicates_allowed(policy: TreePolicy) -> TreeResult<()> {
+    match policy.duplicates {
+        DuplicatePolicy::Allow => Ok(()),
+        _ => Err(TreeError::new(
+            TreeErrorKind::LeafDuplicated,
+            "Stored trees keep no leaf index and must allow duplicate leaves",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::to_hash::ToHash;
+    use crate::hash::Sha256;
+    use crate::store::{MemoryStore, NodeStore, StoredTree};
+    use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering, TreePolicy};
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_stored_tree_matches_memory_tree() {
+        let leaves_raw = ["0", "1", "2", "3", "4", "5", "6"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let mut stored = StoredTree::<Sha256, _>::new(MemoryStore::new()).unwrap();
+        for count in 1..=leaves.len() {
+            stored.append(leaves[count - 1]).unwrap();
+
+            let tree = Sha256Tree::from_leaves(&leaves[..count]);
+            assert_eq!(stored.root_hash().unwrap(), tree.root_hash().unwrap());
+            for index in 0..count {
+                assert_eq!(
+                    stored.get_proof_at(index).unwrap(),
+                    tree.get_proof_at(index).unwrap()
+                );
+            }
+        }
+
+        assert_eq!(stored.store().leaf_count().unwrap(), leaves.len());
+    }
+
+    #[test]
+    fn test_integrity_checks_find_the_lowest_corrupt_node() {
+        let leaves: Vec<_> = (0..11u8).map(|i| Sha256::hash(&[i])).collect();
+        let mut stored = StoredTree::<Sha256, _>::new(MemoryStore::new()).unwrap();
+        stored.extend(&leaves).unwrap();
+        assert_eq!(stored.verify_integrity().unwrap(), None);
+        assert_eq!(stored.verify_integrity_sampled(b"seed", 16).unwrap(), None);
+
+        let mut store = stored.into_store();
+        store.put(0, 10, &Sha256::hash(b"rot")).unwrap();
+        store.put(3, 0, &[1; 32]).unwrap();
+        let stored = StoredTree::<Sha256, _>::new(store).unwrap();
+
+        // leaf 10 has no sibling, so its promoted copy at (1, 5) no longer matches it
+        assert_eq!(stored.verify_integrity().unwrap(), Some((1, 5)));
+        assert_eq!(
+            stored.verify_integrity_sampled(b"seed", 64).unwrap(),
+            Some((1, 5))
+        );
+
+        let mut store = stored.into_store();
+        store.put(1, 3, &[2; 16]).unwrap();
+        let stored = StoredTree::<Sha256, _>::new(store).unwrap();
+        assert_eq!(stored.verify_integrity().unwrap(), Some((1, 3)));
+        #[cfg(feature = "rayon")]
+        assert_eq!(stored.par_verify_integrity().unwrap(), Some((1, 3)));
+    }
+
+    #[test]
+    fn test_stored_tree_follows_its_policy() {
+        let leaves: Vec<_> = (0..7u8).map(|i| Sha256::hash(&[i])).collect();
+        for pair_ordering in [
+            PairOrdering::Sorted,
+            PairOrdering::Positional,
+            PairOrdering::Ascending,
+        ] {
+            for odd_leaf in [
+                OddLeafPolicy::Promote,
+                OddLeafPolicy::DuplicateLast,
+                OddLeafPolicy::PadWithZeroHash,
+            ] {
+                let policy = TreePolicy {
+                    pair_ordering,
+                    odd_leaf,
+                    ..TreePolicy::default()
+                };
+                let mut stored =
+                    StoredTree::<Sha256, _>::with_policy(MemoryStore::new(), policy).unwrap();
+                for count in 1..=leaves.len() {
+                    stored.append(leaves[count - 1]).unwrap();
+
+                    let tree = Sha256Tree::from_leaves(&leaves[..count]).with_policy(policy);
+                    assert_eq!(stored.root_hash().unwrap(), tree.root_hash().unwrap());
+                    for index in 0..count {
+                        assert_eq!(
+                            stored.get_proof_at(index).unwrap(),
+                            tree.get_proof_at(index).unwrap()
+                        );
+                    }
+                }
+
+                assert_eq!(stored.verify_integrity().unwrap(), None);
+                assert_eq!(
+                    stored.inclusion_path(1, leaves.len()).is_ok(),
+                    policy == TreePolicy::default()
+                );
+            }
+        }
+
+        for duplicates in [DuplicatePolicy::Reject, DuplicatePolicy::Deduplicate] {
+            let policy = TreePolicy {
+                duplicates,
+                ..TreePolicy::default()
+            };
+            let error = StoredTree::<Sha256, _>::with_policy(MemoryStore::new(), policy)
+                .err()
+                .unwrap();
+            assert_eq!(error.kind(), &TreeErrorKind::LeafDuplicated);
+        }
+    }
+}
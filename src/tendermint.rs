@@ -0,0 +1,143 @@
+//! Simple merkle trees of Tendermint and CometBFT (`SimpleHashFromByteSlices`)
+//!
+//! Items are hashed as `SHA-256(0x00 || item)` and interior nodes as
+//! `SHA-256(0x01 || left || right)`, as in RFC 6962. A list of `n > 1` items is split after
+//! the largest power of two below `n` and both halves are hashed recursively, which is the
+//! shape of a positional tree promoting odd nodes. The root of an empty list is the SHA-256
+//! of no data. Validator set, block header and transaction list hashes are computed this way,
+//! and the aunts of Tendermint proofs are the siblings of [`IndexedProof`]s of these trees.
+
+use crate::hash::to_hash::ToHash;
+use crate::hash::{DomainSeparated, Sha2_256};
+use crate::indexed_proof::IndexedProof;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering, TreePolicy};
+
+/// SHA-256 with the Tendermint leaf and inner node prefixes
+pub type TendermintHasher = DomainSeparated<Sha2_256>;
+
+pub const TENDERMINT_POLICY: TreePolicy = TreePolicy {
+    pair_ordering: PairOrdering::Positional,
+    odd_leaf: OddLeafPolicy::Promote,
+    duplicates: DuplicatePolicy::Allow,
+};
+
+/// Builds the tree of the items, whose proofs carry sibling positions
+pub fn tree_from_items(items: &[impl AsRef<[u8]>]) -> TreeResult<MerkleTree<TendermintHasher>> {
+    let leaves = items
+        .iter()
+        .map(|item| TendermintHasher::try_hash(item.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(MerkleTree::from_leaves(&leaves).with_policy(TENDERMINT_POLICY))
+}
+
+/// Root of the items as computed by `merkle.HashFromByteSlices`
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::tendermint::hash_from_byte_slices;
+/// use merkle_tree::{ByteOrder, Sha2_256};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let root = hash_from_byte_slices(&[[1u8], [2u8]])?;
+///     assert_eq!(
+///         ByteOrder::Natural.to_hex::<Sha2_256>(root),
+///         "6bcf0e2e93e0a18e22789aee965e6553f4fbe93f0acfc4a705d691c8311c4965"
+///     );
+///
+///     Ok(())
+/// }
+/// ```
+pub fn hash_from_byte_slices(items: &[impl AsRef<[u8]>]) -> TreeResult<[u8; 32]> {
+    if items.is_empty() {
+        return Ok(Sha2_256::try_hash(&[])?);
+    }
+
+    tree_from_items(items)?.root_hash()
+}
+
+/// Tendermint proof of the item at `index`, its aunts ordered from the leaf up
+pub fn proof_at(
+    items: &[impl AsRef<[u8]>],
+    index: usize,
+) -> TreeResult<IndexedProof<TendermintHasher>> {
+    tree_from_items(items)?.indexed_proof_at(index)
+}
+
+/// Verifies the aunts of a Tendermint proof for `item` at `index` in a list of `total` items
+pub fn verify_item(
+    root: [u8; 32],
+    item: &[u8],
+    index: usize,
+    total: usize,
+    aunts: Vec<[u8; 32]>,
+) -> TreeResult<bool> {
+    let leaf = TendermintHasher::try_hash(item)?;
+
+    Ok(IndexedProof::<TendermintHasher>::new(aunts, index, total)
+        .validate_at(root, leaf, index, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hex;
+
+    fn reference_root(items: &[Vec<u8>]) -> [u8; 32] {
+        match items.len() {
+            0 => Sha2_256::hash(&[]),
+            1 => Sha2_256::hash(&[&[0x00], items[0].as_slice()].concat()),
+            len => {
+                let split = len.next_power_of_two() / 2;
+                let left = reference_root(&items[..split]);
+                let right = reference_root(&items[split..]);
+                Sha2_256::hash(&[&[0x01], left.as_slice(), right.as_slice()].concat())
+            }
+        }
+    }
+
+    #[test]
+    fn test_tendermint_reference_values() {
+        let empty: [&[u8]; 0] = [];
+        assert_eq!(
+            hex::encode(&hash_from_byte_slices(&empty).unwrap()),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex::encode(&hash_from_byte_slices(&[[0u8; 0]]).unwrap()),
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"
+        );
+        assert_eq!(
+            hex::encode(&hash_from_byte_slices(&[[1u8]]).unwrap()),
+            "b413f47d13ee2fe6c845b2ee141af81de858df4ec549a58b7970bb96645bc8d2"
+        );
+        assert_eq!(
+            hex::encode(&hash_from_byte_slices(&[[1u8], [2u8], [3u8]]).unwrap()),
+            "e2da0242936eb38ec996a543601b3a1da4226391ff92014ed1a7a248ace36347"
+        );
+    }
+
+    #[test]
+    fn test_roots_match_recursive_split() {
+        for count in 1..40u8 {
+            let items: Vec<Vec<u8>> = (0..count).map(|i| vec![i; i as usize]).collect();
+            assert_eq!(
+                hash_from_byte_slices(&items).unwrap(),
+                reference_root(&items),
+                "{count} items"
+            );
+        }
+    }
+
+    #[test]
+    fn test_aunts_verify_items() {
+        let items: Vec<Vec<u8>> = (0..7u8).map(|i| vec![i]).collect();
+        let root = hash_from_byte_slices(&items).unwrap();
+
+        for (index, item) in items.iter().enumerate() {
+            let aunts = proof_at(&items, index).unwrap().proof().to_vec();
+            assert!(verify_item(root, item, index, items.len(), aunts.clone()).unwrap());
+            assert!(!verify_item(root, item, (index + 1) % 7, items.len(), aunts).unwrap());
+        }
+    }
+}
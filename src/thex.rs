@@ -0,0 +1,100 @@
+//! Tiger Tree Hash as specified by THEX (Tree Hash EXchange format)
+//!
+//! A file is split into 1024-byte segments. Leaves are hashed as `Tiger(0x00 || segment)` and
+//! interior nodes as `Tiger(0x01 || left || right)`, left to right, with the last node of an
+//! odd level promoted unchanged. An empty file has the single leaf `Tiger(0x00)`. The root is
+//! usually shared base32 encoded, e.g. in `urn:tree:tiger:` URNs of DC++ and Gnutella.
+
+use crate::chunker::read_chunk;
+use crate::hash::to_hash::ToHash;
+use crate::hash::{DomainSeparated, Tiger};
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering, TreePolicy};
+use crate::utils::base32;
+use std::io::Read;
+
+/// Size of the segments forming the leaves of a THEX tree
+pub const SEGMENT_SIZE: usize = 1024;
+
+/// Tiger with the THEX leaf and node prefixes
+pub type ThexHasher = DomainSeparated<Tiger>;
+
+pub const THEX_POLICY: TreePolicy = TreePolicy {
+    pair_ordering: PairOrdering::Positional,
+    odd_leaf: OddLeafPolicy::Promote,
+    duplicates: DuplicatePolicy::Allow,
+};
+
+/// Reads the file to the end and builds its THEX tree, whose proofs carry sibling positions
+pub fn tree_from_reader(mut source: impl Read) -> TreeResult<MerkleTree<ThexHasher>> {
+    let mut leaves = Vec::new();
+    let mut segment = [0u8; SEGMENT_SIZE];
+    loop {
+        let read = read_chunk(&mut source, &mut segment)?;
+        if read > 0 || leaves.is_empty() {
+            leaves.push(ThexHasher::try_hash(&segment[..read])?);
+        }
+        if read < SEGMENT_SIZE {
+            break;
+        }
+    }
+
+    Ok(MerkleTree::from_leaves(&leaves).with_policy(THEX_POLICY))
+}
+
+/// Tiger Tree Hash of the file as its base32 form, e.g. for a `urn:tree:tiger:` URN
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::thex::tiger_tree_hash;
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     assert_eq!(
+///         tiger_tree_hash([].as_slice())?,
+///         "LWPNACQDBZRYXW3VHJVCJ64QBZNGHOHHHZWCLNQ"
+///     );
+///
+///     Ok(())
+/// }
+/// ```
+pub fn tiger_tree_hash(source: impl Read) -> TreeResult<String> {
+    Ok(encode_root(&tree_from_reader(source)?.root_hash()?))
+}
+
+/// Encodes a THEX root in the base32 form used by TTH values
+pub fn encode_root(root: &[u8; 24]) -> String {
+    base32::encode(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle_proof::MerkleProof;
+
+    #[test]
+    fn test_thex_reference_values() {
+        assert_eq!(
+            tiger_tree_hash([0u8; 0].as_slice()).unwrap(),
+            "LWPNACQDBZRYXW3VHJVCJ64QBZNGHOHHHZWCLNQ"
+        );
+        assert_eq!(
+            tiger_tree_hash([b'A'; 1024].as_slice()).unwrap(),
+            "L66Q4YVNAFWVS23X2HJIRA5ZJ7WXR3F26RSASFA"
+        );
+        assert_eq!(
+            tiger_tree_hash([b'A'; 1025].as_slice()).unwrap(),
+            "PZMRYHGY6LTBEH63ZWAHDORHSYTLO4LEFUIKHWY"
+        );
+    }
+
+    #[test]
+    fn test_segment_proofs_validate() {
+        let file: Vec<u8> = (0..5 * SEGMENT_SIZE + 1).map(|i| i as u8).collect();
+        let tree = tree_from_reader(file.as_slice()).unwrap();
+        assert_eq!(tree.leaf_count(), 6);
+
+        let root = tree.root_hash().unwrap();
+        let leaf = ThexHasher::hash(&file[4 * SEGMENT_SIZE..5 * SEGMENT_SIZE]);
+        let proof: MerkleProof<ThexHasher> = tree.merkle_proof_at(4).unwrap();
+        assert!(proof.validate(root, leaf));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::audit_log::EntryProof;
+use crate::checkpoint::{Checkpoint, CheckpointSigner, SignedCheckpoint};
+use crate::consistency_proof::ConsistencyProof;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::TreeResult;
+use crate::store::{NodeStore, StoredTree};
+
+/// Append-only transparency log persisted in a [`NodeStore`]
+///
+/// Entries are hashed into the leaves of a [`StoredTree`] and numbered in the order they were
+/// appended. The log issues signed tree heads, inclusion proofs against any earlier size and
+/// consistency proofs between two sizes, all read from the store, so reopening the store
+/// resumes the log with its history intact.
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::store::MemoryStore;
+/// use merkle_tree::{Sha256, TransparencyLog};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut log = TransparencyLog::<Sha256, _>::open(MemoryStore::new())?;
+///     let sequence = log.append(b"certificate 1")?;
+///     let old_head = log.tree_head()?;
+///     log.append(b"certificate 2")?;
+///     log.append(b"certificate 3")?;
+///     let head = log.tree_head()?;
+///
+///     let proof = log.prove_inclusion(sequence, old_head.tree_size as usize)?;
+///     assert!(TransparencyLog::<Sha256, MemoryStore>::verify_inclusion(&old_head, b"certificate 1", &proof));
+///     let proof = log.prove_consistency(1, 3)?;
+///     assert!(proof.validate(1, 3, old_head.root_hash, head.root_hash));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct TransparencyLog<T: ToHash, S> {
+    tree: StoredTree<T, S>,
+}
+
+impl<T: ToHash, S: NodeStore> TransparencyLog<T, S> {
+    /// Opens the log held by the store, which may be empty
+    pub fn open(store: S) -> TreeResult<Self> {
+        Ok(Self {
+            tree: StoredTree::new(store)?,
+        })
+    }
+
+    /// Leaf committed for an entry
+    pub fn entry_hash(entry: &[u8]) -> TreeResult<T::Hash> {
+        Ok(T::try_hash(entry)?)
+    }
+
+    /// Appends an entry, returning its sequence number
+    pub fn append(&mut self, entry: &[u8]) -> TreeResult<usize> {
+        self.append_leaf(Self::entry_hash(entry)?)
+    }
+
+    /// Appends an already hashed entry, returning its sequence number
+    pub fn append_leaf(&mut self, leaf: T::Hash) -> TreeResult<usize> {
+        let sequence = self.len();
+        self.tree.append(leaf)?;
+        Ok(sequence)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.leaf_count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn store(&self) -> &S {
+        self.tree.store()
+    }
+
+    pub fn into_store(self) -> S {
+        self.tree.into_store()
+    }
+
+    pub fn root_hash(&self) -> TreeResult<T::Hash> {
+        self.tree.root_hash()
+    }
+
+    /// Size and root of the log as it stands
+    pub fn tree_head(&self) -> TreeResult<Checkpoint<T>> {
+        Ok(Checkpoint::new(self.len() as u64, self.root_hash()?))
+    }
+
+    pub fn signed_tree_head(
+        &self,
+        signer: &impl CheckpointSigner,
+    ) -> TreeResult<SignedCheckpoint<T>> {
+        self.tree_head()?.sign(signer)
+    }
+
+    /// Inclusion proof for the entry `sequence` in the log as it was at `tree_size` entries
+    pub fn prove_inclusion(&self, sequence: usize, tree_size: usize) -> TreeResult<EntryProof<T>> {
+        Ok(EntryProof {
+            sequence,
+            tree_size,
+            proof: MerkleProof::new(self.tree.inclusion_path(sequence, tree_size)?),
+        })
+    }
+
+    /// Proof that the log at `old_size` entries is a prefix of the log at `new_size` entries
+    pub fn prove_consistency(
+        &self,
+        old_size: usize,
+        new_size: usize,
+    ) -> TreeResult<ConsistencyProof<T>> {
+        Ok(ConsistencyProof::new(
+            self.tree.consistency_path(old_size, new_size)?,
+        ))
+    }
+
+    /// Checks that the entry is included in the log described by the tree head
+    pub fn verify_inclusion(head: &Checkpoint<T>, entry: &[u8], proof: &EntryProof<T>) -> bool {
+        if proof.tree_size as u64 != head.tree_size || proof.sequence >= proof.tree_size {
+            return false;
+        }
+
+        match Self::entry_hash(entry) {
+            Ok(leaf) => proof.proof.validate(head.root_hash, leaf),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::tests::KeyedHashSigner;
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::store::MemoryStore;
+    use crate::Sha256Tree;
+
+    type Log = TransparencyLog<Sha256, MemoryStore>;
+
+    #[test]
+    fn test_proofs_match_memory_tree_at_every_size() {
+        let entries: Vec<_> = (0..11u8).map(|entry| vec![entry]).collect();
+        let leaves: Vec<_> = entries.iter().map(|entry| Sha256::hash(entry)).collect();
+        let mut log = Log::open(MemoryStore::new()).unwrap();
+        for entry in &entries {
+            log.append(entry).unwrap();
+        }
+
+        for new_size in 1..=leaves.len() {
+            let tree = Sha256Tree::from_leaves(&leaves[..new_size]);
+            let head = Checkpoint::<Sha256>::new(new_size as u64, tree.root_hash().unwrap());
+            for (sequence, entry) in entries[..new_size].iter().enumerate() {
+                let proof = log.prove_inclusion(sequence, new_size).unwrap();
+                assert_eq!(proof.proof.proof(), tree.get_proof_at(sequence).unwrap());
+                assert!(Log::verify_inclusion(&head, entry, &proof));
+            }
+            for old_size in 1..=new_size {
+                let proof = log.prove_consistency(old_size, new_size).unwrap();
+                assert_eq!(proof.proof(), tree.consistency_proof(old_size).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_reopened_log_continues_and_signs_heads() {
+        let mut log = Log::open(MemoryStore::new()).unwrap();
+        log.append(b"a").unwrap();
+        log.append(b"b").unwrap();
+        let old = log.signed_tree_head(&KeyedHashSigner([1; 4])).unwrap();
+
+        let mut log = Log::open(log.into_store()).unwrap();
+        assert_eq!(log.append(b"c").unwrap(), 2);
+        let new = log.signed_tree_head(&KeyedHashSigner([1; 4])).unwrap();
+
+        assert!(old.verify(&KeyedHashSigner([1; 4])));
+        assert!(new.verify(&KeyedHashSigner([1; 4])));
+        assert!(log.prove_consistency(2, 3).unwrap().validate(
+            2,
+            3,
+            old.checkpoint().root_hash,
+            new.checkpoint().root_hash
+        ));
+        assert!(!Log::verify_inclusion(
+            old.checkpoint(),
+            b"c",
+            &log.prove_inclusion(2, 3).unwrap()
+        ));
+        assert_eq!(
+            log.prove_inclusion(0, 4).err().unwrap().kind(),
+            &TreeErrorKind::IndexOutOfBounds
+        );
+    }
+}
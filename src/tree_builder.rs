@@ -0,0 +1,340 @@
+use crate::hash::to_hash::ToHash;
+use crate::hash::DomainSeparated;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering, TreePolicy};
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+enum Leaf<H> {
+    Data(Vec<u8>),
+    Hash(H),
+}
+
+/// Builds a [`MerkleTree`] over the hasher `H`, domain separated unless opted out of
+///
+/// By default the tree is a `MerkleTree<DomainSeparated<H>>`, whose leaves are hashed with a
+/// `0x00` prefix and interior nodes with a `0x01` prefix. Raw leaf data is only hashed in
+/// [`TreeBuilder::build`], so the convention can be changed at any point before building.
+///
+/// The pair ordering and odd-node handling are recorded in the tree's [`TreePolicy`], and
+/// [`MerkleTree::merkle_proof_at`] produces proofs that validate under the same rules.
+pub struct TreeBuilder<H: ToHash, T: ToHash = DomainSeparated<H>> {
+    leaves: Vec<Leaf<H::Hash>>,
+    policy: TreePolicy,
+    sort_leaves: bool,
+    dedup_leaves: bool,
+    bloom_false_positive_rate: Option<f64>,
+    hasher: PhantomData<T>,
+}
+
+impl<H: ToHash> TreeBuilder<H> {
+    pub fn new() -> Self {
+        Self {
+            leaves: Vec::new(),
+            policy: TreePolicy::default(),
+            sort_leaves: false,
+            dedup_leaves: false,
+            bloom_false_positive_rate: None,
+            hasher: PhantomData,
+        }
+    }
+
+    /// Builds a plain `MerkleTree<H>` without leaf/node prefixes, as produced by
+    /// [`MerkleTree::from_leaves`]
+    pub fn without_domain_separation(self) -> TreeBuilder<H, H> {
+        TreeBuilder {
+            leaves: self.leaves,
+            policy: self.policy,
+            sort_leaves: self.sort_leaves,
+            dedup_leaves: self.dedup_leaves,
+            bloom_false_positive_rate: self.bloom_false_positive_rate,
+            hasher: PhantomData,
+        }
+    }
+}
+
+impl<H: ToHash> Default for TreeBuilder<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: ToHash, T: ToHash<Hash = H::Hash>> TreeBuilder<H, T> {
+    /// Adds a leaf from raw data, hashed with the leaf convention of the tree
+    pub fn leaf_data(mut self, data: &[u8]) -> Self {
+        self.leaves.push(Leaf::Data(data.to_vec()));
+        self
+    }
+
+    /// Adds an already hashed leaf
+    pub fn leaf_hash(mut self, hash: H::Hash) -> Self {
+        self.leaves.push(Leaf::Hash(hash));
+        self
+    }
+
+    /// Adds already hashed leaves, in order
+    pub fn leaf_hashes(mut self, hashes: impl IntoIterator<Item = H::Hash>) -> Self {
+        self.leaves.extend(hashes.into_iter().map(Leaf::Hash));
+        self
+    }
+
+    /// Orders the two children of every node, sorted by default
+    pub fn pair_ordering(mut self, pair_ordering: PairOrdering) -> Self {
+        self.policy.pair_ordering = pair_ordering;
+        self
+    }
+
+    /// Handles the last node of odd levels, promoted by default
+    pub fn odd_leaf_policy(mut self, odd_leaf: OddLeafPolicy) -> Self {
+        self.policy.odd_leaf = odd_leaf;
+        self
+    }
+
+    /// Handles leaves equal to an earlier one, allowed by default
+    ///
+    /// Unlike [`TreeBuilder::dedup_leaves`], the policy stays with the tree and also governs
+    /// later appends.
+    pub fn duplicate_policy(mut self, duplicates: DuplicatePolicy) -> Self {
+        self.policy.duplicates = duplicates;
+        self
+    }
+
+    /// Sorts the hashed leaves before building, so the root does not depend on insertion order
+    pub fn sort_leaves(mut self, sort_leaves: bool) -> Self {
+        self.sort_leaves = sort_leaves;
+        self
+    }
+
+    /// Drops every hashed leaf equal to an earlier one before building
+    ///
+    /// Combined with [`TreeBuilder::sort_leaves`], trees built from the same set of leaves
+    /// share a root whatever the order or multiplicity in which the leaves were added.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::TreeBuilder;
+    /// use merkle_tree::Sha256;
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let build = |leaves: &[&[u8]]| {
+    ///         leaves
+    ///             .iter()
+    ///             .fold(TreeBuilder::<Sha256>::new(), |builder, leaf| builder.leaf_data(leaf))
+    ///             .sort_leaves(true)
+    ///             .dedup_leaves(true)
+    ///             .build()
+    ///     };
+    ///
+    ///     let ours = build(&[b"alice", b"bob", b"carol"])?;
+    ///     let theirs = build(&[b"carol", b"alice", b"bob", b"alice"])?;
+    ///     assert_eq!(ours.root_hash()?, theirs.root_hash()?);
+    ///     assert_eq!(theirs.leaf_count(), 3);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn dedup_leaves(mut self, dedup_leaves: bool) -> Self {
+        self.dedup_leaves = dedup_leaves;
+        self
+    }
+
+    /// Attaches a bloom filter over the built leaves for [`MerkleTree::maybe_contains`]
+    pub fn bloom_filter(mut self, false_positive_rate: f64) -> Self {
+        self.bloom_false_positive_rate = Some(false_positive_rate);
+        self
+    }
+
+    /// Hashes the raw leaves and builds the tree
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{DomainSeparated, MerkleProof, Sha256, ToHash, TreeBuilder};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let tree = TreeBuilder::<Sha256>::new()
+    ///         .leaf_data(b"alice")
+    ///         .leaf_data(b"bob")
+    ///         .leaf_data(b"carol")
+    ///         .build()?;
+    ///
+    ///     let proof = MerkleProof::<DomainSeparated<Sha256>>::new(tree.get_proof_at(1)?);
+    ///     assert!(proof.validate_data(tree.root_hash()?, b"bob"));
+    ///     assert!(!proof.validate(tree.root_hash()?, Sha256::hash(b"bob")));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build(self) -> TreeResult<MerkleTree<T>> {
+        let mut leaves = self
+            .leaves
+            .into_iter()
+            .map(|leaf| match leaf {
+                Leaf::Data(data) => Ok(T::try_hash(&data)?),
+                Leaf::Hash(hash) => Ok(hash),
+            })
+            .collect::<TreeResult<Vec<_>>>()?;
+        if self.sort_leaves {
+            leaves.sort_by(|left, right| left.as_ref().cmp(right.as_ref()));
+        }
+        if self.dedup_leaves {
+            let mut seen = HashSet::with_capacity(leaves.len());
+            leaves.retain(|leaf| seen.insert(*leaf));
+        }
+
+        let tree = MerkleTree::from_leaves(&leaves)
+            .with_policy(self.policy)
+            .enforce_duplicate_policy()?;
+
+        Ok(match self.bloom_false_positive_rate {
+            Some(rate) => tree.with_bloom_filter(rate),
+            None => tree,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::{DomainSeparated, Sha256};
+    use crate::{DuplicatePolicy, MerkleProof, OddLeafPolicy, PairOrdering, ToHash, TreeBuilder};
+
+    /// Data whose leaf hash equals the interior node of `left` and `right` in a plain tree
+    fn forged_leaf(left: [u8; 32], right: [u8; 32]) -> Vec<u8> {
+        match left <= right {
+            true => [right, left].concat(),
+            false => [left, right].concat(),
+        }
+    }
+
+    #[test]
+    fn test_interior_node_cannot_pose_as_leaf() {
+        let leaves: [&[u8]; 4] = [b"0", b"1", b"2", b"3"];
+
+        let mut plain = TreeBuilder::<Sha256>::new().without_domain_separation();
+        let mut separated = TreeBuilder::<Sha256>::new();
+        for leaf in leaves {
+            plain = plain.leaf_data(leaf);
+            separated = separated.leaf_data(leaf);
+        }
+        let plain = plain.build().unwrap();
+        let separated = separated.build().unwrap();
+
+        let forged = forged_leaf(plain.leaves()[2], plain.leaves()[3]);
+        let proof =
+            MerkleProof::<Sha256>::new(vec![Sha256::combine(plain.leaves()[0], plain.leaves()[1])]);
+        assert!(proof.validate_data(plain.root_hash().unwrap(), &forged));
+
+        let forged = forged_leaf(separated.leaves()[2], separated.leaves()[3]);
+        let proof =
+            MerkleProof::<DomainSeparated<Sha256>>::new(vec![DomainSeparated::<Sha256>::combine(
+                separated.leaves()[0],
+                separated.leaves()[1],
+            )]);
+        assert!(!proof.validate_data(separated.root_hash().unwrap(), &forged));
+    }
+
+    #[test]
+    fn test_policy_proofs_validate() {
+        for pair_ordering in [
+            PairOrdering::Sorted,
+            PairOrdering::Positional,
+            PairOrdering::Ascending,
+        ] {
+            for odd_leaf in [
+                OddLeafPolicy::Promote,
+                OddLeafPolicy::DuplicateLast,
+                OddLeafPolicy::PadWithZeroHash,
+            ] {
+                let mut builder = TreeBuilder::<Sha256>::new()
+                    .pair_ordering(pair_ordering)
+                    .odd_leaf_policy(odd_leaf);
+                for leaf in [b"0", b"1", b"2", b"3", b"4"] {
+                    builder = builder.leaf_data(leaf);
+                }
+                let tree = builder.build().unwrap();
+                let root = tree.root_hash().unwrap();
+
+                for index in 0..5 {
+                    let proof = tree.merkle_proof_at(index).unwrap();
+                    assert_eq!(proof.proof(), tree.get_proof_at(index).unwrap());
+                    assert!(proof.validate(root, tree.leaves()[index]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_positional_root_depends_on_order() {
+        let build = |leaves: [&[u8]; 2], pair_ordering| {
+            TreeBuilder::<Sha256>::new()
+                .without_domain_separation()
+                .pair_ordering(pair_ordering)
+                .leaf_data(leaves[0])
+                .leaf_data(leaves[1])
+                .build()
+                .unwrap()
+                .root_hash()
+                .unwrap()
+        };
+
+        assert_eq!(
+            build([b"0", b"1"], PairOrdering::Sorted),
+            build([b"1", b"0"], PairOrdering::Sorted)
+        );
+        assert_ne!(
+            build([b"0", b"1"], PairOrdering::Positional),
+            build([b"1", b"0"], PairOrdering::Positional)
+        );
+        assert_eq!(
+            build([b"0", b"1"], PairOrdering::Positional),
+            Sha256::hash_node(Sha256::hash(b"0"), Sha256::hash(b"1"))
+        );
+    }
+
+    #[test]
+    fn test_sorted_leaves_ignore_insertion_order() {
+        let build = |leaves: [&[u8]; 3]| {
+            let mut builder = TreeBuilder::<Sha256>::new()
+                .pair_ordering(PairOrdering::Positional)
+                .sort_leaves(true);
+            for leaf in leaves {
+                builder = builder.leaf_data(leaf);
+            }
+            builder.build().unwrap().root_hash().unwrap()
+        };
+
+        assert_eq!(build([b"0", b"1", b"2"]), build([b"2", b"0", b"1"]));
+    }
+
+    #[test]
+    fn test_duplicate_policy_is_enforced_on_build() {
+        let build = |duplicates| {
+            TreeBuilder::<Sha256>::new()
+                .duplicate_policy(duplicates)
+                .leaf_data(b"0")
+                .leaf_data(b"1")
+                .leaf_data(b"0")
+                .build()
+        };
+
+        let error = build(DuplicatePolicy::Reject).err().unwrap();
+        assert_eq!(error.kind(), &TreeErrorKind::LeafDuplicated);
+        assert_eq!(build(DuplicatePolicy::Allow).unwrap().leaf_count(), 3);
+
+        let mut tree = build(DuplicatePolicy::Deduplicate).unwrap();
+        assert_eq!(tree.leaf_count(), 2);
+        assert!(!tree.try_append(tree.leaves()[1]).unwrap());
+        assert_eq!(tree.policy().duplicates, DuplicatePolicy::Deduplicate);
+    }
+
+    #[test]
+    fn test_dedup_keeps_first_occurrences() {
+        let tree = TreeBuilder::<Sha256>::new()
+            .without_domain_separation()
+            .leaf_hashes([b"1", b"0", b"1", b"2", b"0"].map(|leaf| Sha256::hash(leaf)))
+            .dedup_leaves(true)
+            .build()
+            .unwrap();
+
+        let expected = [Sha256::hash(b"1"), Sha256::hash(b"0"), Sha256::hash(b"2")];
+        assert_eq!(tree.leaves(), expected);
+    }
+}
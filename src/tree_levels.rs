@@ -0,0 +1,217 @@
+use crate::hash::to_hash::ToHash;
+use std::ops::Index;
+
+/// Every level of a tree, from the leaves up to the root
+///
+/// The tree builds its levels on first use and keeps them until it changes, so walking
+/// them (or every node in them) never hashes again. All nodes live in one contiguous
+/// buffer, level after level, and are addressed by their position in it: the parent and
+/// sibling of a node are found with index arithmetic, and the whole tree can be exported
+/// without copying.
+pub struct TreeLevels<T: ToHash> {
+    nodes: Vec<T::Hash>,
+    offsets: Vec<usize>,
+}
+
+impl<T: ToHash> TreeLevels<T> {
+    /// Builds the levels above `leaves`, stored in one or more segments, deriving the parents
+    /// of each level's pairs in one batch with `combine_pairs` and lifting the last node of
+    /// odd levels with `lift_odd`
+    pub(crate) fn build<E>(
+        leaves: &[&[T::Hash]],
+        mut combine_pairs: impl FnMut(&[T::Hash], &mut Vec<T::Hash>) -> Result<(), E>,
+        mut lift_odd: impl FnMut(T::Hash) -> Result<T::Hash, E>,
+    ) -> Result<Self, E> {
+        let len = leaves.iter().map(|segment| segment.len()).sum::<usize>();
+        let mut nodes = Vec::with_capacity(len * 2);
+        for segment in leaves {
+            nodes.extend_from_slice(segment);
+        }
+        let mut offsets = vec![0, nodes.len()];
+        let mut parents = Vec::new();
+
+        while offsets[offsets.len() - 1] - offsets[offsets.len() - 2] > 1 {
+            let (start, end) = (offsets[offsets.len() - 2], offsets[offsets.len() - 1]);
+            let paired_end = end - (end - start) % 2;
+            combine_pairs(&nodes[start..paired_end], &mut parents)?;
+            nodes.append(&mut parents);
+            if paired_end < end {
+                let parent = lift_odd(nodes[end - 1])?;
+                nodes.push(parent);
+            }
+            offsets.push(nodes.len());
+        }
+
+        Ok(Self { nodes, offsets })
+    }
+
+    /// Number of levels, including the leaves and the root
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The nodes of one level, the leaves being level 0
+    pub fn get(&self, level: usize) -> Option<&[T::Hash]> {
+        Some(&self.nodes[*self.offsets.get(level)?..*self.offsets.get(level + 1)?])
+    }
+
+    pub fn root(&self) -> Option<T::Hash> {
+        self.get(self.len() - 1)?.first().copied()
+    }
+
+    /// Iterates the levels as slices, starting with the leaves and ending with the root
+    pub fn iter(&self) -> impl Iterator<Item = &[T::Hash]> + '_ {
+        self.offsets
+            .windows(2)
+            .map(|bounds| &self.nodes[bounds[0]..bounds[1]])
+    }
+
+    /// Iterates every node as `(level, index, hash)`, level by level from the leaves up
+    pub fn nodes(&self) -> impl Iterator<Item = (usize, usize, T::Hash)> + '_ {
+        self.iter().enumerate().flat_map(|(level, hashes)| {
+            hashes
+                .iter()
+                .enumerate()
+                .map(move |(index, hash)| (level, index, *hash))
+        })
+    }
+
+    /// Every node in one slice, level by level from the leaves up to the root
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let tree = Sha256Tree::from_leaves(&leaves);
+    ///     let levels = tree.levels()?;
+    ///
+    ///     let nodes = levels.as_slice();
+    ///     assert_eq!(nodes.len(), 5 + 3 + 2 + 1);
+    ///     assert_eq!(levels.position(1, 2), Some(7));
+    ///     assert_eq!(levels.parent(3), Some(6));
+    ///     assert_eq!(levels.sibling(3), Some(2));
+    ///     assert_eq!(nodes[10], tree.root_hash()?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn as_slice(&self) -> &[T::Hash] {
+        &self.nodes
+    }
+
+    /// Position in [`TreeLevels::as_slice`] of the node at `index` on `level`
+    pub fn position(&self, level: usize, index: usize) -> Option<usize> {
+        let position = self.offsets.get(level)? + index;
+        match position < *self.offsets.get(level + 1)? {
+            true => Some(position),
+            false => None,
+        }
+    }
+
+    /// Position of the parent of the node at `position`, `None` for the root
+    pub fn parent(&self, position: usize) -> Option<usize> {
+        let level = self.level_at(position)?;
+        self.position(level + 1, (position - self.offsets[level]) / 2)
+    }
+
+    /// Position of the node paired with the node at `position`, `None` when it has no sibling
+    pub fn sibling(&self, position: usize) -> Option<usize> {
+        let level = self.level_at(position)?;
+        self.position(level, (position - self.offsets[level]) ^ 1)
+    }
+
+    fn level_at(&self, position: usize) -> Option<usize> {
+        match position < self.nodes.len() {
+            true => Some(level_of(&self.offsets, position)),
+            false => None,
+        }
+    }
+}
+
+/// Level holding the node at `position`; the offsets of a tree hold at most 65 entries
+fn level_of(offsets: &[usize], position: usize) -> usize {
+    offsets.partition_point(|offset| *offset <= position) - 1
+}
+
+impl<T: ToHash> Index<usize> for TreeLevels<T> {
+    type Output = [T::Hash];
+
+    fn index(&self, level: usize) -> &Self::Output {
+        self.get(level).expect("level is outside of the tree")
+    }
+}
+
+impl<T: ToHash> Clone for TreeLevels<T> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            offsets: self.offsets.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{Sha256Tree, ToHash};
+
+    #[test]
+    fn test_levels_and_nodes() {
+        let leaves_raw = ["0", "1", "2"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let levels = tree.levels().unwrap();
+        let pair = Sha256::combine(leaves[0], leaves[1]);
+
+        let root = tree.root_hash().unwrap();
+        let expected = vec![leaves.clone(), vec![pair, leaves[2]], vec![root]];
+        assert_eq!(levels.iter().collect::<Vec<_>>(), expected);
+        assert_eq!(
+            levels.nodes().collect::<Vec<_>>(),
+            tree.nodes().unwrap().collect::<Vec<_>>()
+        );
+        assert_eq!(levels.nodes().nth(4), Some((1, 1, leaves[2])));
+        assert_eq!(levels.nodes().count(), 6);
+        assert_eq!(levels.root(), Some(root));
+    }
+
+    #[test]
+    fn test_flat_index_math() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let levels = tree.levels().unwrap();
+
+        // positions: leaves 0..5, level 1 5..8, level 2 8..10, root 10
+        assert_eq!(levels.position(2, 1), Some(9));
+        assert_eq!(levels.position(2, 2), None);
+        assert_eq!(levels.parent(4), Some(7));
+        assert_eq!(levels.parent(7), Some(9));
+        assert_eq!(levels.parent(10), None);
+        assert_eq!(levels.sibling(4), None);
+        assert_eq!(levels.sibling(8), Some(9));
+        assert_eq!(levels.sibling(11), None);
+
+        for position in 0..levels.as_slice().len() - 1 {
+            let parent = levels.parent(position).unwrap();
+            let children: Vec<_> = [Some(position), levels.sibling(position)]
+                .into_iter()
+                .flatten()
+                .collect();
+            if children.len() == 2 {
+                let (left, right) = (children[0].min(children[1]), children[0].max(children[1]));
+                assert_eq!(
+                    levels.as_slice()[parent],
+                    Sha256::combine(levels.as_slice()[left], levels.as_slice()[right])
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::Position;
+use crate::merkle_tree::TreeResult;
+
+/// How the two children of a node are ordered before hashing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PairOrdering {
+    /// The greater hash goes first, see [`ToHash::combine`]; proofs need no positions
+    #[default]
+    Sorted,
+    /// The left child goes first; proofs record the side of every sibling
+    Positional,
+    /// The lesser hash goes first, as OpenZeppelin's `MerkleProof` and merkletreejs'
+    /// `sortPairs` do; proofs record the side of every sibling
+    Ascending,
+}
+
+/// What happens to the last node of a level with an odd number of nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OddLeafPolicy {
+    /// The node moves up to the next level unchanged (RFC 6962 / certificate transparency)
+    #[default]
+    Promote,
+    /// The node is paired with itself (Bitcoin)
+    DuplicateLast,
+    /// The node is paired with an all-zero hash
+    PadWithZeroHash,
+}
+
+/// What happens to a leaf equal to one already in the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// The leaf is added again; proofs by value cover its first occurrence only
+    #[default]
+    Allow,
+    /// Adding the leaf fails with `LeafDuplicated`
+    Reject,
+    /// The leaf is skipped, keeping its first occurrence
+    Deduplicate,
+}
+
+/// Rules a [`MerkleTree`](crate::MerkleTree) is built with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TreePolicy {
+    pub pair_ordering: PairOrdering,
+    pub odd_leaf: OddLeafPolicy,
+    pub duplicates: DuplicatePolicy,
+}
+
+impl TreePolicy {
+    /// Hashes a node from its children according to the pair ordering
+    pub(crate) fn combine<T: ToHash>(&self, left: T::Hash, right: T::Hash) -> TreeResult<T::Hash> {
+        Ok(match self.pair_ordering {
+            PairOrdering::Sorted => T::try_combine(left, right)?,
+            PairOrdering::Positional => T::try_hash_node(left, right)?,
+            PairOrdering::Ascending if left > right => T::try_hash_node(right, left)?,
+            PairOrdering::Ascending => T::try_hash_node(left, right)?,
+        })
+    }
+
+    /// Appends the parents of consecutive pairs of `children`, an even number of nodes,
+    /// submitting them to the hasher as one batch
+    pub(crate) fn combine_pairs<T: ToHash>(
+        &self,
+        children: &[T::Hash],
+        parents: &mut Vec<T::Hash>,
+    ) -> TreeResult<()> {
+        let pairs: Vec<_> = children
+            .chunks_exact(2)
+            .map(|pair| match self.pair_ordering {
+                PairOrdering::Sorted if pair[0] <= pair[1] => (pair[1], pair[0]),
+                PairOrdering::Ascending if pair[0] > pair[1] => (pair[1], pair[0]),
+                _ => (pair[0], pair[1]),
+            })
+            .collect();
+
+        Ok(T::try_hash_nodes(&pairs, parents)?)
+    }
+
+    /// Sibling of the last node of an odd level, or `None` when the node is promoted
+    pub(crate) fn odd_sibling<T: ToHash>(&self, node: T::Hash) -> TreeResult<Option<T::Hash>> {
+        match self.odd_leaf {
+            OddLeafPolicy::Promote => Ok(None),
+            OddLeafPolicy::DuplicateLast => Ok(Some(node)),
+            OddLeafPolicy::PadWithZeroHash => T::Hash::try_from(&vec![0; T::hash_len()])
+                .map(Some)
+                .map_err(|_| TreeError::hash_size_mismatch("hasher cannot produce a zero hash")),
+        }
+    }
+
+    /// Parent of the last node of an odd level
+    pub(crate) fn lift_odd<T: ToHash>(&self, node: T::Hash) -> TreeResult<T::Hash> {
+        match self.odd_sibling::<T>(node)? {
+            Some(sibling) => self.combine::<T>(node, sibling),
+            None => Ok(node),
+        }
+    }
+
+    /// Side on which the sibling of the node at `index` sits
+    pub(crate) fn position(index: usize) -> Position {
+        match index % 2 {
+            0 => Position::Right,
+            _ => Position::Left,
+        }
+    }
+
+    /// Side on which `sibling` is hashed with the node at `index`, which ascending trees
+    /// decide by value rather than by index
+    pub(crate) fn sibling_position<H: PartialOrd>(
+        &self,
+        index: usize,
+        node: H,
+        sibling: H,
+    ) -> Position {
+        match self.pair_ordering {
+            PairOrdering::Ascending if sibling < node => Position::Left,
+            PairOrdering::Ascending if sibling > node => Position::Right,
+            _ => Self::position(index),
+        }
+    }
+}
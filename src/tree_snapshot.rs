@@ -0,0 +1,126 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::leaves::Leaves;
+use crate::merkle_tree::{levels_of, proof_from_levels, TreeResult};
+use crate::tree_levels::TreeLevels;
+use crate::tree_policy::TreePolicy;
+use std::sync::{Arc, OnceLock};
+
+/// Immutable view of a [`MerkleTree`](crate::MerkleTree) pinned at the time it was taken
+///
+/// The levels of the tree are taken from the tree when it had cached them, or computed on
+/// first use, and shared by every later root or proof request, so serving proofs never needs
+/// access to the (possibly growing) tree. Leaves are found by value through the tree's index.
+pub struct TreeSnapshot<T: ToHash> {
+    leaves: Arc<Leaves<T>>,
+    policy: TreePolicy,
+    levels: OnceLock<Arc<TreeLevels<T>>>,
+}
+
+impl<T: ToHash> TreeSnapshot<T> {
+    pub(crate) fn new(
+        leaves: Arc<Leaves<T>>,
+        policy: TreePolicy,
+        levels: Option<Arc<TreeLevels<T>>>,
+    ) -> Self {
+        Self {
+            leaves,
+            policy,
+            levels: levels.map(OnceLock::from).unwrap_or_default(),
+        }
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn root_hash(&self) -> TreeResult<T::Hash> {
+        if self.leaves.is_empty() {
+            return Err(TreeError::tree_empty());
+        }
+
+        let levels = self.levels()?;
+        Ok(levels[levels.len() - 1][0])
+    }
+
+    pub fn get_proof(&self, leaf: T::Hash) -> TreeResult<Vec<T::Hash>> {
+        match self.leaves.position(&leaf) {
+            Some(index) => self.get_proof_at(index),
+            None => Err(TreeError::leaf_not_found()),
+        }
+    }
+
+    pub fn get_proof_at(&self, index: usize) -> TreeResult<Vec<T::Hash>> {
+        if index >= self.leaves.len() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        proof_from_levels::<T>(self.levels()?, index, &self.policy)
+    }
+
+    fn levels(&self) -> TreeResult<&TreeLevels<T>> {
+        if let Some(levels) = self.levels.get() {
+            return Ok(levels);
+        }
+
+        let levels = levels_of::<T>(&self.leaves.segments(), &self.policy)?;
+        Ok(self.levels.get_or_init(|| Arc::new(levels)))
+    }
+}
+
+impl<T: ToHash> Clone for TreeSnapshot<T> {
+    fn clone(&self) -> Self {
+        Self {
+            leaves: Arc::clone(&self.leaves),
+            policy: self.policy,
+            levels: self.levels.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hash::to_hash::ToHash;
+    use crate::hash::Sha256;
+    use crate::utils::test::{full_root_hash, raw_leaves_to_hashed_leaves};
+    use crate::{Sha256Proof, Sha256Tree};
+    use std::thread;
+
+    #[test]
+    fn test_snapshot_is_unaffected_by_appends() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let mut tree = Sha256Tree::from_leaves(&leaves);
+        let snapshot = tree.read_snapshot();
+        let reader = thread::spawn(move || {
+            let root = snapshot.root_hash().unwrap();
+            let proof = Sha256Proof::new(snapshot.get_proof_at(3).unwrap());
+            (root, proof.validate(root, snapshot.leaves.get(3).unwrap()))
+        });
+
+        for leaf in raw_leaves_to_hashed_leaves::<Sha256>(&["5", "6", "7"]) {
+            tree.append(leaf);
+        }
+
+        let (root, is_valid) = reader.join().unwrap();
+        assert_eq!(root, full_root_hash::<Sha256>(&leaves));
+        assert!(is_valid);
+        assert_ne!(tree.root_hash().unwrap(), root);
+    }
+
+    #[test]
+    fn test_snapshot_shares_index_and_cached_levels() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "1"]);
+        let tree = Sha256Tree::from_leaves(&leaves);
+        let levels = tree.levels().unwrap();
+
+        let snapshot = tree.read_snapshot();
+        assert!(std::ptr::eq(snapshot.levels().unwrap(), levels));
+        assert_eq!(
+            snapshot.get_proof(leaves[1]).unwrap(),
+            tree.get_proof_at(1).unwrap()
+        );
+        assert!(snapshot.get_proof(Sha256::hash(b"5")).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use std::io::{self, Write};
+
+/// [`Write`] sink cutting the bytes written into fixed-size leaves of a tree
+///
+/// Every full chunk of `chunk_size` bytes is hashed and appended as soon as it is complete,
+/// so only one partial chunk is ever buffered. [`TreeWriter::finish`] appends what is left
+/// as a shorter last leaf, giving the same tree as
+/// [`ChunkedTree::from_reader`](crate::chunker::ChunkedTree::from_reader) over the same bytes.
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::{Sha256, TreeWriter};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let file: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+///
+///     let mut writer = TreeWriter::<Sha256>::new(1024)?;
+///     std::io::copy(&mut file.as_slice(), &mut writer)?;
+///     let tree = writer.finish()?;
+///     assert_eq!(tree.leaf_count(), 10);
+///
+///     Ok(())
+/// }
+/// ```
+pub struct TreeWriter<T: ToHash> {
+    tree: MerkleTree<T>,
+    chunk_size: usize,
+    buffer: Vec<u8>,
+    byte_len: u64,
+}
+
+impl<T: ToHash> TreeWriter<T> {
+    /// Writer filling a new tree with leaves of `chunk_size` bytes
+    pub fn new(chunk_size: usize) -> TreeResult<Self> {
+        Self::with_tree(MerkleTree::new(), chunk_size)
+    }
+
+    /// Writer appending leaves of `chunk_size` bytes to an existing tree, keeping its policy
+    pub fn with_tree(tree: MerkleTree<T>, chunk_size: usize) -> TreeResult<Self> {
+        if chunk_size == 0 {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        Ok(Self {
+            tree,
+            chunk_size,
+            buffer: Vec::with_capacity(chunk_size),
+            byte_len: 0,
+        })
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Total number of bytes written, including the buffered partial chunk
+    pub fn byte_len(&self) -> u64 {
+        self.byte_len
+    }
+
+    /// The tree so far, without the buffered partial chunk
+    pub fn tree(&self) -> &MerkleTree<T> {
+        &self.tree
+    }
+
+    /// Appends the buffered partial chunk, if any, and returns the tree
+    pub fn finish(mut self) -> TreeResult<MerkleTree<T>> {
+        if !self.buffer.is_empty() {
+            self.tree.append(T::try_hash(&self.buffer)?);
+        }
+
+        Ok(self.tree)
+    }
+
+    /// Appends the buffered partial chunk, if any, and returns the root
+    pub fn finalize(self) -> TreeResult<T::Hash> {
+        self.finish()?.root_hash()
+    }
+}
+
+impl<T: ToHash> Write for TreeWriter<T> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let mut rest = bytes;
+        if !self.buffer.is_empty() {
+            let taken = rest.len().min(self.chunk_size - self.buffer.len());
+            self.buffer.extend_from_slice(&rest[..taken]);
+            rest = &rest[taken..];
+            if self.buffer.len() < self.chunk_size {
+                self.byte_len += bytes.len() as u64;
+                return Ok(bytes.len());
+            }
+            append_chunk(&mut self.tree, &self.buffer)?;
+            self.buffer.clear();
+        }
+
+        let mut chunks = rest.chunks_exact(self.chunk_size);
+        for chunk in &mut chunks {
+            append_chunk(&mut self.tree, chunk)?;
+        }
+        self.buffer.extend_from_slice(chunks.remainder());
+        self.byte_len += bytes.len() as u64;
+
+        Ok(bytes.len())
+    }
+
+    /// Does nothing: a partial chunk stays buffered, as appending it early would move the
+    /// boundaries of every chunk after it
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hashes a chunk into a new leaf, reporting hasher failures as I/O errors
+fn append_chunk<T: ToHash>(tree: &mut MerkleTree<T>, chunk: &[u8]) -> io::Result<()> {
+    let leaf = T::try_hash(chunk).map_err(|error| io::Error::other(TreeError::from(error)))?;
+    tree.append(leaf);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunker::ChunkedTree;
+    use crate::hash::Sha256;
+    use crate::tree_policy::OddLeafPolicy;
+
+    #[test]
+    fn test_writes_of_any_size_match_the_chunker() {
+        let file: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+        let expected = ChunkedTree::<Sha256>::from_reader(file.as_slice(), 64)
+            .unwrap()
+            .root_hash()
+            .unwrap();
+
+        for write_size in [1, 7, 63, 64, 65, 200, 1000] {
+            let mut writer = TreeWriter::<Sha256>::new(64).unwrap();
+            for bytes in file.chunks(write_size) {
+                writer.write_all(bytes).unwrap();
+            }
+            assert_eq!(writer.byte_len(), 1000);
+            assert_eq!(writer.tree().leaf_count(), 15);
+            assert_eq!(writer.finalize().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_existing_trees_keep_their_leaves_and_policy() {
+        let tree = MerkleTree::<Sha256>::from_leaves(&[Sha256::hash(b"header")])
+            .with_odd_leaf_policy(OddLeafPolicy::DuplicateLast);
+        let mut writer = TreeWriter::with_tree(tree, 4).unwrap();
+        io::copy(&mut &b"0123456789"[..], &mut writer).unwrap();
+        let tree = writer.finish().unwrap();
+
+        let leaves = [
+            Sha256::hash(b"header"),
+            Sha256::hash(b"0123"),
+            Sha256::hash(b"4567"),
+            Sha256::hash(b"89"),
+        ];
+        assert_eq!(tree.leaves(), leaves);
+        assert_eq!(tree.policy().odd_leaf, OddLeafPolicy::DuplicateLast);
+
+        assert!(TreeWriter::<Sha256>::new(0).is_err());
+        assert!(TreeWriter::<Sha256>::new(4).unwrap().finalize().is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+use crate::tree_policy::{DuplicatePolicy, OddLeafPolicy, PairOrdering};
+use crate::utils::ct;
+
+/// Proof that replacing one leaf moves a tree from one root to another
+///
+/// Replacing a leaf leaves its siblings untouched, so a single path proves both the old leaf
+/// under the old root and the new leaf under the new root. A verifier holding only the two
+/// roots can check the transition without the tree.
+pub struct UpdateProof<T: ToHash> {
+    index: usize,
+    old_leaf: T::Hash,
+    new_leaf: T::Hash,
+    proof: MerkleProof<T>,
+}
+
+impl<T: ToHash> UpdateProof<T> {
+    pub fn new(index: usize, old_leaf: T::Hash, new_leaf: T::Hash, proof: MerkleProof<T>) -> Self {
+        Self {
+            index,
+            old_leaf,
+            new_leaf,
+            proof,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn old_leaf(&self) -> T::Hash {
+        self.old_leaf
+    }
+
+    pub fn new_leaf(&self) -> T::Hash {
+        self.new_leaf
+    }
+
+    pub fn proof(&self) -> &MerkleProof<T> {
+        &self.proof
+    }
+
+    /// Checks that the update turns `old_root` into `new_root`
+    pub fn verify(&self, old_root: T::Hash, new_root: T::Hash) -> bool {
+        let roots = self
+            .proof
+            .compute_root(self.old_leaf)
+            .and_then(|old| Ok((old, self.proof.compute_root(self.new_leaf)?)));
+
+        match roots {
+            Ok((old, new)) => {
+                ct::eq(old.as_ref(), old_root.as_ref()) & ct::eq(new.as_ref(), new_root.as_ref())
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Replaces the leaf at `index`, returning a proof of the root transition
+    ///
+    /// Fails with `ProofMalformed`, leaving the tree unchanged, when the tree duplicates odd
+    /// nodes and the leaf's path goes through a duplicated node: its copy would change along
+    /// with the leaf, so no single path covers both roots. Fails with `LeafDuplicated` when
+    /// the tree does not allow duplicates and holds the new leaf elsewhere. Trees with
+    /// [`PairOrdering::Ascending`] fail with `ProofMalformed` as well, since the sides of
+    /// their siblings depend on the leaf and differ between the two roots.
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let leaves: Vec<_> = (0..5).map(|i| Sha256::hash(&[i])).collect();
+    ///     let mut tree = Sha256Tree::from_leaves(&leaves);
+    ///     let old_root = tree.root_hash()?;
+    ///
+    ///     let update = tree.update_with_proof(3, Sha256::hash(b"new"))?;
+    ///     assert!(update.verify(old_root, tree.root_hash()?));
+    ///     assert!(!update.verify(old_root, old_root));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn update_with_proof(
+        &mut self,
+        index: usize,
+        new_leaf: T::Hash,
+    ) -> TreeResult<UpdateProof<T>> {
+        let old_leaf = self
+            .leaf_at(index)
+            .ok_or_else(TreeError::index_out_of_bounds)?;
+        if self.policy().duplicates != DuplicatePolicy::Allow
+            && self
+                .index_of(&new_leaf)
+                .is_some_and(|existing| existing != index)
+        {
+            return Err(TreeError::leaf_duplicated());
+        }
+        if self.policy().pair_ordering == PairOrdering::Ascending {
+            return Err(TreeError::proof_malformed(
+                "ascending trees order siblings by value, so one path cannot cover both roots",
+            ));
+        }
+        if self.policy().odd_leaf == OddLeafPolicy::DuplicateLast
+            && path_duplicates_itself(index, self.leaf_count())
+        {
+            return Err(TreeError::proof_malformed(
+                "the leaf's path duplicates its own nodes",
+            ));
+        }
+
+        let proof = self.merkle_proof_at(index)?;
+        self.replace_leaf(index, new_leaf);
+
+        Ok(UpdateProof::new(index, old_leaf, new_leaf, proof))
+    }
+}
+
+/// Whether the path of `index` passes through the last node of an odd level
+fn path_duplicates_itself(index: usize, leaf_count: usize) -> bool {
+    let (mut index, mut width) = (index, leaf_count);
+    while width > 1 {
+        if index == width - 1 && width % 2 == 1 {
+            return true;
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::{DuplicatePolicy, OddLeafPolicy, PairOrdering, Sha256Tree, TreeBuilder};
+
+    #[test]
+    fn test_update_proofs_match_rebuilt_trees() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2", "3", "4", "5", "6"]);
+        let replacement = raw_leaves_to_hashed_leaves::<Sha256>(&["new"])[0];
+
+        for ordering in [PairOrdering::Sorted, PairOrdering::Positional] {
+            for index in 0..leaves.len() {
+                let build = |leaves: &[[u8; 32]]| {
+                    TreeBuilder::<Sha256>::new()
+                        .without_domain_separation()
+                        .leaf_hashes(leaves.iter().copied())
+                        .pair_ordering(ordering)
+                        .build()
+                        .unwrap()
+                };
+                let mut tree = build(&leaves);
+                let old_root = tree.root_hash().unwrap();
+                let update = tree.update_with_proof(index, replacement).unwrap();
+
+                let mut expected = leaves.clone();
+                expected[index] = replacement;
+                let new_root = build(&expected).root_hash().unwrap();
+                assert_eq!(tree.root_hash().unwrap(), new_root);
+                assert!(update.verify(old_root, new_root));
+                assert!(!update.verify(new_root, old_root));
+                assert_eq!(tree.index_of(&replacement), Some(index));
+                assert_eq!(tree.index_of(&leaves[index]), None);
+            }
+        }
+
+        let mut ascending = TreeBuilder::<Sha256>::new()
+            .without_domain_separation()
+            .leaf_hashes(leaves.iter().copied())
+            .pair_ordering(PairOrdering::Ascending)
+            .build()
+            .unwrap();
+        let error = ascending.update_with_proof(0, replacement).err().unwrap();
+        assert_eq!(error.kind(), &TreeErrorKind::ProofMalformed);
+    }
+
+    #[test]
+    fn test_duplicated_paths_are_rejected() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2"]);
+        let mut tree =
+            Sha256Tree::from_leaves(&leaves).with_odd_leaf_policy(OddLeafPolicy::DuplicateLast);
+        let root = tree.root_hash().unwrap();
+
+        let error = tree.update_with_proof(2, leaves[0]).err().unwrap();
+        assert_eq!(error.kind(), &TreeErrorKind::ProofMalformed);
+        assert_eq!(tree.root_hash().unwrap(), root);
+
+        let update = tree.update_with_proof(1, leaves[2]).unwrap();
+        assert!(update.verify(root, tree.root_hash().unwrap()));
+        assert_eq!(tree.index_of(&leaves[2]), Some(1));
+    }
+
+    #[test]
+    fn test_duplicate_updates_follow_the_policy() {
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&["0", "1", "2"]);
+        let mut tree = Sha256Tree::from_leaves(&leaves)
+            .with_duplicate_policy(DuplicatePolicy::Reject)
+            .unwrap();
+
+        let error = tree.update_with_proof(0, leaves[2]).err().unwrap();
+        assert_eq!(error.kind(), &TreeErrorKind::LeafDuplicated);
+        assert!(tree.update_with_proof(1, leaves[1]).is_ok());
+    }
+}
@@ -0,0 +1,34 @@
+const BASE32_CHARS: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Encodes bytes as RFC 4648 base32 without padding, the form used for Tiger Tree Hash values
+pub fn encode(bytes: &[u8]) -> String {
+    let mut base32 = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            base32.push(BASE32_CHARS[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        base32.push(BASE32_CHARS[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    base32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc4648_vectors() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "MY");
+        assert_eq!(encode(b"foob"), "MZXW6YQ");
+        assert_eq!(encode(b"foobar"), "MZXW6YTBOI");
+    }
+}
@@ -0,0 +1,88 @@
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as padded RFC 4648 base64, the form used by signed notes
+pub fn encode(bytes: &[u8]) -> String {
+    let mut base64 = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let buffer = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |buffer, (index, byte)| {
+                buffer | (*byte as u32) << (16 - 8 * index)
+            });
+        for index in 0..4 {
+            match index <= chunk.len() {
+                true => {
+                    base64.push(BASE64_CHARS[(buffer >> (18 - 6 * index) & 0x3f) as usize] as char)
+                }
+                false => base64.push('='),
+            }
+        }
+    }
+
+    base64
+}
+
+/// Decodes padded RFC 4648 base64, rejecting anything that [`encode`] would not produce
+pub fn decode(base64: &str) -> Option<Vec<u8>> {
+    let base64 = base64.as_bytes();
+    if !base64.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(base64.len() / 4 * 3);
+    for (position, chunk) in base64.chunks_exact(4).enumerate() {
+        let last = position == base64.len() / 4 - 1;
+        let padding = match chunk {
+            [_, _, b'=', b'='] if last => 2,
+            [_, _, _, b'='] if last => 1,
+            _ => 0,
+        };
+
+        let mut buffer = 0u32;
+        for (index, char) in chunk[..4 - padding].iter().enumerate() {
+            buffer |= (sextet(*char)? as u32) << (18 - 6 * index);
+        }
+        let decoded = &buffer.to_be_bytes()[1..4 - padding];
+        if buffer.to_be_bytes()[4 - padding..]
+            .iter()
+            .any(|byte| *byte != 0)
+        {
+            return None;
+        }
+        bytes.extend_from_slice(decoded);
+    }
+
+    Some(bytes)
+}
+
+fn sextet(char: u8) -> Option<u8> {
+    BASE64_CHARS
+        .iter()
+        .position(|candidate| *candidate == char)
+        .map(|position| position as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc4648_vectors() {
+        for (bytes, base64) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode(bytes), base64);
+            assert_eq!(decode(base64).unwrap(), bytes);
+        }
+
+        assert_eq!(decode("Zg="), None);
+        assert_eq!(decode("Zh=="), None);
+        assert_eq!(decode("Zg==Zg=="), None);
+    }
+}
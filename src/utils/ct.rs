@@ -0,0 +1,21 @@
+use subtle::ConstantTimeEq;
+
+/// Compares two hashes in time independent of where they first differ
+///
+/// Roots gate authorization decisions, so an early-exit `==` could reveal how many leading
+/// bytes of a forged root were right.
+pub fn eq(left: &[u8], right: &[u8]) -> bool {
+    left.ct_eq(right).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(!eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!eq(&[1, 2, 3], &[1, 2]));
+    }
+}
@@ -0,0 +1,64 @@
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+/// Encodes bytes as lowercase hex without a `0x` prefix
+pub fn encode(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex.push(HEX_CHARS[(byte >> 4) as usize] as char);
+        hex.push(HEX_CHARS[(byte & 0x0f) as usize] as char);
+    }
+
+    hex
+}
+
+/// Decodes a hex string, accepting an optional `0x` prefix
+pub fn decode(hex: &str) -> Option<Vec<u8>> {
+    decode_digits(hex.strip_prefix("0x").unwrap_or(hex))
+}
+
+/// Decodes a hex string that must carry exactly one `0x` prefix
+#[cfg(any(feature = "serde", test))]
+pub fn decode_prefixed(hex: &str) -> Option<Vec<u8>> {
+    decode_digits(hex.strip_prefix("0x")?)
+}
+
+fn decode_digits(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.as_bytes();
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    hex.chunks_exact(2)
+        .map(|pair| Some(nibble(pair[0])? << 4 | nibble(pair[1])?))
+        .collect()
+}
+
+fn nibble(char: u8) -> Option<u8> {
+    match char {
+        b'0'..=b'9' => Some(char - b'0'),
+        b'a'..=b'f' => Some(char - b'a' + 10),
+        b'A'..=b'F' => Some(char - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes = [0x00, 0x1f, 0xab, 0xff];
+        assert_eq!(encode(&bytes), "001fabff");
+        assert_eq!(decode("0x001FABFF").unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_invalid_hex_is_rejected() {
+        assert!(decode("0x0").is_none());
+        assert!(decode("zz").is_none());
+        assert!(decode_prefixed("00").is_none());
+        assert!(decode_prefixed("0x0x00").is_none());
+        assert_eq!(decode_prefixed("0x00").unwrap(), [0]);
+    }
+}
@@ -1 +1,6 @@
+pub mod base32;
+pub mod base64;
+pub mod ct;
+pub mod hex;
+#[cfg(any(test, feature = "test-utils"))]
 pub mod test;
@@ -1,12 +1,17 @@
-#![allow(unused_imports)]
+//! Reference implementations and leaf generators for testing code built on this crate
+//!
+//! The oracles recompute roots and proofs straight from the RFC 6962 definitions, which give
+//! the same shape as the default [`TreePolicy`](crate::TreePolicy) (sorted pairs, odd nodes
+//! promoted), so they can check trees without sharing any of their code. Generators are
+//! seeded, so a failing case can be replayed from its seed.
 use crate::hash::to_hash::ToHash;
 
-#[cfg(test)]
+/// Hashes each string into a leaf
 pub fn raw_leaves_to_hashed_leaves<T: ToHash>(leaves: &[&str]) -> Vec<T::Hash> {
     leaves.iter().map(|leaf| T::hash(leaf.as_bytes())).collect()
 }
 
-#[cfg(test)]
+/// Root of the five leaf tree drawn below, spelled out by hand
 pub fn full_root_hash<T: ToHash>(leaves: &[T::Hash]) -> T::Hash {
     // root hash expectation
     //      [01234]
@@ -24,3 +29,127 @@ pub fn full_root_hash<T: ToHash>(leaves: &[T::Hash]) -> T::Hash {
         leaves[4],
     )
 }
+
+/// Root of a default tree over `leaves`, or `None` when there are none
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::test_utils::{random_leaves, reference_root};
+/// use merkle_tree::{Sha256, Sha256Tree};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let leaves = random_leaves::<Sha256>(7, 13);
+///     assert_eq!(reference_root::<Sha256>(&leaves), Some(Sha256Tree::from_leaves(&leaves).root_hash()?));
+///
+///     Ok(())
+/// }
+/// ```
+pub fn reference_root<T: ToHash>(leaves: &[T::Hash]) -> Option<T::Hash> {
+    match leaves.len() {
+        0 => None,
+        1 => Some(leaves[0]),
+        size => {
+            let (left, right) = leaves.split_at(split_point(size));
+            Some(T::combine(
+                reference_root::<T>(left)?,
+                reference_root::<T>(right)?,
+            ))
+        }
+    }
+}
+
+/// Proof of the leaf at `index` in a default tree over `leaves`, siblings from the leaf up
+pub fn reference_proof<T: ToHash>(leaves: &[T::Hash], index: usize) -> Option<Vec<T::Hash>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    if leaves.len() == 1 {
+        return Some(Vec::new());
+    }
+
+    let split = split_point(leaves.len());
+    let (left, right) = leaves.split_at(split);
+    let (mut proof, sibling) = match index < split {
+        true => (
+            reference_proof::<T>(left, index)?,
+            reference_root::<T>(right)?,
+        ),
+        false => (
+            reference_proof::<T>(right, index - split)?,
+            reference_root::<T>(left)?,
+        ),
+    };
+    proof.push(sibling);
+
+    Some(proof)
+}
+
+/// `count` leaves hashed from pseudo-random bytes, the same for the same seed
+pub fn random_leaves<T: ToHash>(seed: u64, count: usize) -> Vec<T::Hash> {
+    let mut state = seed;
+    (0..count)
+        .map(|_| T::hash(&next_random(&mut state).to_be_bytes()))
+        .collect()
+}
+
+/// `len` pseudo-random bytes, the same for the same seed
+pub fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed;
+    let mut bytes = Vec::with_capacity(len.next_multiple_of(8));
+    while bytes.len() < len {
+        bytes.extend_from_slice(&next_random(&mut state).to_be_bytes());
+    }
+    bytes.truncate(len);
+
+    bytes
+}
+
+/// SplitMix64 step
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut value = *state;
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+/// Largest power of two strictly below `size`, which must be at least 2
+fn split_point(size: usize) -> usize {
+    (size - 1).next_power_of_two() >> usize::from(!(size - 1).is_power_of_two())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha256;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_reference_implementations_match_the_tree() {
+        for count in 1..=17 {
+            let leaves = random_leaves::<Sha256>(count as u64, count);
+            let tree = Sha256Tree::from_leaves(&leaves);
+            assert_eq!(
+                reference_root::<Sha256>(&leaves),
+                Some(tree.root_hash().unwrap())
+            );
+            for index in 0..count {
+                assert_eq!(
+                    reference_proof::<Sha256>(&leaves, index),
+                    Some(tree.get_proof_at(index).unwrap())
+                );
+            }
+        }
+
+        assert_eq!(reference_root::<Sha256>(&[]), None);
+        assert_eq!(random_bytes(3, 13), random_bytes(3, 13));
+        assert_ne!(random_bytes(3, 13), random_bytes(4, 13));
+    }
+
+    #[test]
+    fn test_split_point() {
+        let expected = [(2, 1), (3, 2), (4, 2), (5, 4), (8, 4), (9, 8), (17, 16)];
+        for (size, split) in expected {
+            assert_eq!(split_point(size), split);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_proof::MerkleProof;
+use crate::merkle_tree::{MerkleTree, TreeResult};
+
+/// Merkle tree owning the values behind its leaves
+///
+/// Each value of type `L` is hashed with `T` into a leaf of the inner [`MerkleTree`], and the
+/// value itself is kept, so it can be read back by index and shipped inside proofs instead
+/// of being lost once hashed.
+///
+/// ##Examples
+/// ```
+/// use merkle_tree::{Sha256, ValueTree};
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let tree = ValueTree::<Sha256, String>::from_values(vec![
+///         "alice:100".to_owned(),
+///         "bob:250".to_owned(),
+///     ])?;
+///     assert_eq!(tree.value_at(1).map(String::as_str), Some("bob:250"));
+///
+///     let proof = tree.proof_at(1)?;
+///     assert_eq!(proof.value(), b"bob:250");
+///     assert!(proof.validate(tree.root_hash()?));
+///
+///     Ok(())
+/// }
+/// ```
+pub struct ValueTree<T: ToHash, L: AsRef<[u8]>> {
+    tree: MerkleTree<T>,
+    values: Vec<L>,
+}
+
+/// Inclusion proof carrying the value it proves
+pub struct ValueProof<T: ToHash> {
+    value: Vec<u8>,
+    proof: MerkleProof<T>,
+}
+
+impl<T: ToHash, L: AsRef<[u8]>> ValueTree<T, L> {
+    pub fn new() -> Self {
+        Self {
+            tree: MerkleTree::new(),
+            values: Vec::new(),
+        }
+    }
+
+    pub fn from_values(values: Vec<L>) -> TreeResult<Self> {
+        let mut tree = Self::new();
+        for value in values {
+            tree.append(value)?;
+        }
+
+        Ok(tree)
+    }
+
+    /// Hashes the value into a new leaf and keeps it
+    pub fn append(&mut self, value: L) -> TreeResult<()> {
+        self.tree.append(T::try_hash(value.as_ref())?);
+        self.values.push(value);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn value_at(&self, index: usize) -> Option<&L> {
+        self.values.get(index)
+    }
+
+    pub fn values(&self) -> &[L] {
+        &self.values
+    }
+
+    /// The tree over the hashed values
+    pub fn tree(&self) -> &MerkleTree<T> {
+        &self.tree
+    }
+
+    pub fn root_hash(&self) -> TreeResult<T::Hash> {
+        self.tree.root_hash()
+    }
+
+    /// Proof for the value at `index`, carrying a copy of the value
+    pub fn proof_at(&self, index: usize) -> TreeResult<ValueProof<T>> {
+        let value = self
+            .values
+            .get(index)
+            .ok_or_else(TreeError::index_out_of_bounds)?;
+
+        Ok(ValueProof::new(
+            value.as_ref().to_vec(),
+            self.tree.merkle_proof_at(index)?,
+        ))
+    }
+}
+
+impl<T: ToHash, L: AsRef<[u8]>> Default for ValueTree<T, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: ToHash> ValueProof<T> {
+    pub fn new(value: Vec<u8>, proof: MerkleProof<T>) -> Self {
+        Self { value, proof }
+    }
+
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+
+    pub fn proof(&self) -> &MerkleProof<T> {
+        &self.proof
+    }
+
+    /// Validates that the carried value is a leaf of the tree with the given root
+    pub fn validate(&self, root_hash: T::Hash) -> bool {
+        self.proof.validate_data(root_hash, &self.value)
+    }
+
+    /// Serializes the proof as `value length (u32, BE) | value | proof`, the proof in the
+    /// format of [`MerkleProof::to_bytes`]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [
+            &(self.value.len() as u32).to_be_bytes()[..],
+            &self.value,
+            &self.proof.to_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Deserializes a proof previously produced by [`ValueProof::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> TreeResult<Self> {
+        let (len, rest) = bytes
+            .split_first_chunk::<4>()
+            .ok_or_else(|| TreeError::proof_malformed("value length is truncated"))?;
+        let len = u32::from_be_bytes(*len) as usize;
+        if rest.len() < len {
+            return Err(TreeError::proof_malformed("value is truncated"));
+        }
+        let (value, proof) = rest.split_at(len);
+
+        Ok(Self::new(value.to_vec(), MerkleProof::from_bytes(proof)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Sha256;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_values_are_kept_and_proven() {
+        let values: Vec<Vec<u8>> = (0..5u8).map(|value| vec![value; value as usize]).collect();
+        let tree = ValueTree::<Sha256, Vec<u8>>::from_values(values.clone()).unwrap();
+        let leaves: Vec<_> = values.iter().map(|value| Sha256::hash(value)).collect();
+        let root = tree.root_hash().unwrap();
+        assert_eq!(root, Sha256Tree::from_leaves(&leaves).root_hash().unwrap());
+
+        for (index, value) in values.iter().enumerate() {
+            assert_eq!(tree.value_at(index), Some(value));
+            let proof = ValueProof::<Sha256>::from_bytes(&tree.proof_at(index).unwrap().to_bytes())
+                .unwrap();
+            assert_eq!(proof.value(), &value[..]);
+            assert!(proof.validate(root));
+        }
+
+        let forged = ValueProof::new(vec![9], tree.proof_at(1).unwrap().proof);
+        assert!(!forged.validate(root));
+        assert!(ValueProof::<Sha256>::from_bytes(&[0, 0, 0, 5, 1]).is_err());
+    }
+}
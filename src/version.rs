@@ -0,0 +1,123 @@
+use crate::error::tree_error::TreeError;
+use crate::hash::to_hash::ToHash;
+use crate::merkle_tree::{levels_of, proof_from_levels, root_of, MerkleTree, TreeResult};
+
+/// Point in the history of an append-only [`MerkleTree`], taken with [`MerkleTree::snapshot`]
+///
+/// A version is the number of leaves the tree held when it was taken, so it costs nothing to
+/// keep: roots and proofs at a version are recomputed from that prefix of the shared leaves.
+/// Rolling back past a version and appending again reuses its leaf positions, after which the
+/// version describes the new leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version(usize);
+
+impl Version {
+    pub fn leaf_count(&self) -> usize {
+        self.0
+    }
+}
+
+impl<T: ToHash> MerkleTree<T> {
+    /// Records the current state of the tree
+    ///
+    /// ##Examples
+    /// ```
+    /// use merkle_tree::{Sha256Tree, Sha256Proof, Sha256, ToHash};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let mut tree = Sha256Tree::new();
+    ///     let mut versions = Vec::new();
+    ///     for i in 0..5 {
+    ///         tree.append(Sha256::hash(&[i]));
+    ///         versions.push(tree.snapshot());
+    ///     }
+    ///
+    ///     // the root when leaf 2 was added, and the proof of leaf 1 against it
+    ///     let root = tree.root_at(versions[2])?;
+    ///     let proof = Sha256Proof::new(tree.proof_at(versions[2], 1)?);
+    ///     assert!(proof.validate(root, Sha256::hash(&[1])));
+    ///
+    ///     tree.rollback(versions[2])?;
+    ///     assert_eq!(tree.root_hash()?, root);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn snapshot(&self) -> Version {
+        Version(self.leaf_count())
+    }
+
+    pub fn root_at(&self, version: Version) -> TreeResult<T::Hash> {
+        root_of::<T>(&[self.leaves_at(version)?], &self.policy())
+    }
+
+    pub fn proof_at(&self, version: Version, index: usize) -> TreeResult<Vec<T::Hash>> {
+        let leaves = self.leaves_at(version)?;
+        if index >= leaves.len() {
+            return Err(TreeError::index_out_of_bounds());
+        }
+
+        let levels = levels_of::<T>(&[leaves], &self.policy())?;
+        proof_from_levels::<T>(&levels, index, &self.policy())
+    }
+
+    /// Drops every leaf appended after the version
+    ///
+    /// Snapshots taken with [`MerkleTree::read_snapshot`] keep the leaves they were taken with.
+    pub fn rollback(&mut self, version: Version) -> TreeResult<()> {
+        self.leaves_at(version)?;
+        self.truncate(version.0);
+
+        Ok(())
+    }
+
+    fn leaves_at(&self, version: Version) -> TreeResult<&[T::Hash]> {
+        self.leaves()
+            .get(..version.0)
+            .ok_or_else(TreeError::version_unknown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::tree_error::TreeErrorKind;
+    use crate::hash::Sha256;
+    use crate::utils::test::raw_leaves_to_hashed_leaves;
+    use crate::Sha256Tree;
+
+    #[test]
+    fn test_versions_survive_appends_and_rollback() {
+        let leaves_raw = ["0", "1", "2", "3", "4"];
+        let leaves = raw_leaves_to_hashed_leaves::<Sha256>(&leaves_raw);
+
+        let mut tree = Sha256Tree::from_leaves(&leaves[..3]);
+        let version = tree.snapshot();
+        let old = Sha256Tree::from_leaves(&leaves[..3]);
+
+        tree.append(leaves[3]);
+        tree.append(leaves[4]);
+        let latest = tree.snapshot();
+        assert_eq!(tree.root_at(version).unwrap(), old.root_hash().unwrap());
+        assert_eq!(
+            tree.proof_at(version, 2).unwrap(),
+            old.get_proof_at(2).unwrap()
+        );
+        assert_eq!(
+            tree.proof_at(version, 3).unwrap_err().kind(),
+            &TreeErrorKind::IndexOutOfBounds
+        );
+
+        tree.rollback(version).unwrap();
+        assert_eq!(tree.leaves(), &leaves[..3]);
+        assert_eq!(
+            tree.root_at(latest).unwrap_err().kind(),
+            &TreeErrorKind::VersionUnknown
+        );
+        assert_eq!(
+            Sha256Tree::new()
+                .root_at(Sha256Tree::new().snapshot())
+                .unwrap_err()
+                .kind(),
+            &TreeErrorKind::TreeEmpty
+        );
+    }
+}